@@ -168,6 +168,71 @@ pub enum LendingError {
     /// Lending instruction exceeds desired slippage limit
     #[error("Amount smaller than desired slippage limit")]
     ExceededSlippage,
+    /// Obligation still has collateral deposited
+    #[error("Obligation deposits are not empty")]
+    ObligationDepositsNotEmpty,
+    /// Expected a different pending market owner
+    #[error("Pending market owner is invalid")]
+    InvalidPendingMarketOwner,
+    /// Lending market role cannot make this change
+    #[error("Signer role is not permitted to make this change")]
+    RoleNotPermitted,
+    /// Reserve status does not allow the operation
+    #[error("Reserve status does not allow this operation")]
+    ReserveStatusRestricted,
+    /// Deposit would exceed the reserve deposit limit
+    #[error("Reserve deposit limit exceeded")]
+    DepositLimitExceeded,
+    /// Borrow would exceed the reserve borrow limit
+    #[error("Reserve borrow limit exceeded")]
+    BorrowLimitExceeded,
+    /// Reserve has no pending config to apply
+    #[error("Reserve has no pending config")]
+    NoPendingReserveConfig,
+    /// Pending reserve config activation time not reached
+    #[error("Pending reserve config is still timelocked")]
+    ReserveConfigTimelocked,
+    /// Reserve config change lowers the liquidation threshold too far at once
+    #[error("Liquidation threshold decrease exceeds the max per config change")]
+    LiquidationThresholdDecreaseTooLarge,
+    /// Collateral ramp timestamps are invalid
+    #[error("Invalid collateral ramp")]
+    InvalidCollateralRamp,
+    /// Obligation delegate does not match the obligation or authority
+    #[error("Invalid obligation delegate")]
+    InvalidObligationDelegate,
+    /// Obligation delegate lacks the permission for the action
+    #[error("Obligation delegate is not permitted to take this action")]
+    DelegateNotPermitted,
+    /// Borrow by an obligation delegate exceeds its max borrow value
+    #[error("Borrow would exceed the obligation delegate borrow limit")]
+    DelegateBorrowLimitExceeded,
+    /// Obligation owner transfer does not match the obligation or new owner
+    #[error("Invalid obligation owner transfer")]
+    InvalidObligationOwnerTransfer,
+    /// Referrer fee receiver is not derived from the reserve and referrer
+    #[error("Invalid referrer fee receiver")]
+    InvalidReferrerFeeReceiver,
+    /// Fixed rate borrow is not allowed by the reserve or obligation
+    #[error("Invalid fixed rate borrow")]
+    InvalidFixedRateBorrow,
+    /// Reserve rewards do not match the reserve or reward accounts provided
+    #[error("Invalid reserve rewards")]
+    InvalidReserveRewards,
+    /// Invalid event data logged by the program
+    #[error("Failed to unpack event data")]
+    EventUnpackError,
+    /// Return data was not set by the lending program, or has an unexpected
+    /// length
+    #[error("Invalid return data")]
+    InvalidReturnData,
+    /// Instruction is no longer supported by the lending program
+    #[error("Instruction is deprecated")]
+    DeprecatedInstruction,
+    /// Reserve was created before the collateral fee receiver and must be
+    /// migrated with MigrateAccount
+    #[error("Reserve has no collateral fee receiver")]
+    MissingCollateralFeeReceiver,
 }
 
 impl From<LendingError> for ProgramError {
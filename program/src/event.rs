@@ -0,0 +1,786 @@
+//! Events logged by the lending program
+//!
+//! Every processor logs a single event with `sol_log_data` once it succeeds,
+//! so indexers can follow lending activity without diffing accounts. An event
+//! is logged as a single field, starting with the event tag followed by its
+//! fields in little-endian order.
+
+use {
+    crate::{error::LendingError, math::Decimal},
+    solana_program::{
+        log::sol_log_data,
+        program_error::ProgramError,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+};
+
+/// Events logged by the lending program
+#[derive(Clone, Debug, PartialEq)]
+pub enum LendingEvent {
+    // 0
+    /// The lending market was initialized, or its owner or roles changed
+    LendingMarketChanged {
+        /// Lending market account
+        lending_market: Pubkey,
+    },
+    // 1
+    /// The reserve was initialized, or its config or status changed
+    ReserveConfigChanged {
+        /// Reserve account
+        reserve: Pubkey,
+    },
+    // 2
+    /// The reserve accrued interest when refreshed
+    InterestAccrued {
+        /// Reserve account
+        reserve: Pubkey,
+        /// Reserve cumulative borrow rate
+        cumulative_borrow_rate_wads: Decimal,
+        /// Reserve liquidity borrowed plus interest
+        borrowed_amount_wads: Decimal,
+        /// Reserve liquidity available
+        available_amount: u64,
+    },
+    // 3
+    /// Liquidity was deposited in the reserve in exchange for collateral
+    DepositReserveLiquidity {
+        /// Reserve account
+        reserve: Pubkey,
+        /// Amount of liquidity deposited
+        liquidity_amount: u64,
+        /// Amount of collateral minted
+        collateral_amount: u64,
+    },
+    // 4
+    /// Collateral was redeemed from the reserve in exchange for liquidity
+    RedeemReserveCollateral {
+        /// Reserve account
+        reserve: Pubkey,
+        /// Amount of collateral burned
+        collateral_amount: u64,
+        /// Amount of liquidity withdrawn
+        liquidity_amount: u64,
+    },
+    // 5
+    /// The obligation was initialized or resized, or its owner or delegates
+    /// changed
+    ObligationChanged {
+        /// Obligation account
+        obligation: Pubkey,
+    },
+    // 6
+    /// The obligation values were refreshed
+    ObligationRefreshed {
+        /// Obligation account
+        obligation: Pubkey,
+        /// Market value of the obligation deposits
+        deposited_value: Decimal,
+        /// Market value of the obligation borrows
+        borrowed_value: Decimal,
+        /// Market value the obligation can borrow up to
+        allowed_borrow_value: Decimal,
+        /// Market value of borrows at which the obligation is unhealthy
+        unhealthy_borrow_value: Decimal,
+    },
+    // 7
+    /// Collateral was deposited in the obligation
+    DepositObligationCollateral {
+        /// Obligation account
+        obligation: Pubkey,
+        /// Deposit reserve account
+        reserve: Pubkey,
+        /// Amount of collateral deposited
+        collateral_amount: u64,
+    },
+    // 8
+    /// Collateral was withdrawn from the obligation
+    WithdrawObligationCollateral {
+        /// Obligation account
+        obligation: Pubkey,
+        /// Withdraw reserve account
+        reserve: Pubkey,
+        /// Amount of collateral withdrawn
+        collateral_amount: u64,
+    },
+    // 9
+    /// Liquidity was borrowed by the obligation
+    Borrow {
+        /// Obligation account
+        obligation: Pubkey,
+        /// Borrow reserve account
+        reserve: Pubkey,
+        /// Amount of liquidity owed, including fees
+        borrow_amount_wads: Decimal,
+        /// Amount of liquidity received by the borrower
+        receive_amount: u64,
+        /// Borrow fee paid to the reserve owner and host
+        borrow_fee: u64,
+        /// Whether the liquidity was borrowed at a fixed rate
+        fixed_rate: bool,
+    },
+    // 10
+    /// Liquidity was repaid to the obligation
+    Repay {
+        /// Obligation account
+        obligation: Pubkey,
+        /// Repay reserve account
+        reserve: Pubkey,
+        /// Amount of liquidity repaid
+        repay_amount: u64,
+        /// Amount of the obligation borrow settled
+        settle_amount_wads: Decimal,
+    },
+    // 11
+    /// The obligation was liquidated
+    Liquidate {
+        /// Obligation account
+        obligation: Pubkey,
+        /// Repay reserve account
+        repay_reserve: Pubkey,
+        /// Withdraw reserve account
+        withdraw_reserve: Pubkey,
+        /// Amount of liquidity repaid
+        repay_amount: u64,
+        /// Amount of collateral withdrawn, including the protocol fee
+        withdraw_amount: u64,
+        /// Amount of collateral paid as liquidation protocol fee
+        protocol_fee: u64,
+    },
+    // 12
+    /// Bad debt of the obligation was forgiven
+    ForgiveDebt {
+        /// Obligation account
+        obligation: Pubkey,
+        /// Reserve account
+        reserve: Pubkey,
+        /// Amount of the obligation borrow forgiven
+        settle_amount_wads: Decimal,
+    },
+    // 13
+    /// The account was migrated to the latest layout
+    AccountMigrated {
+        /// Migrated account
+        account: Pubkey,
+        /// Layout version before the migration
+        version: u8,
+    },
+    // 14
+    /// A referrer fee receiver was created for the reserve
+    ReferrerFeeReceiverInitialized {
+        /// Reserve account
+        reserve: Pubkey,
+        /// Referrer
+        referrer: Pubkey,
+    },
+    // 15
+    /// A referrer claimed the fees of the reserve
+    ReferrerFeesClaimed {
+        /// Reserve account
+        reserve: Pubkey,
+        /// Referrer
+        referrer: Pubkey,
+        /// Amount of liquidity claimed
+        amount: u64,
+    },
+    // 16
+    /// Reserve rewards were initialized, or their emissions changed
+    ReserveRewardsChanged {
+        /// Reserve rewards account
+        reserve_rewards: Pubkey,
+    },
+    // 17
+    /// Rewards were claimed for the obligation
+    RewardsClaimed {
+        /// Reserve rewards account
+        reserve_rewards: Pubkey,
+        /// Obligation account
+        obligation: Pubkey,
+        /// Amount of reward tokens claimed
+        amount: u64,
+    },
+    // 18
+    /// Rewards left to claim by emptied deposits and borrows of the
+    /// obligation were forfeited
+    RewardsForfeited {
+        /// Obligation account
+        obligation: Pubkey,
+        /// Reserve account
+        reserve: Pubkey,
+        /// Amount of reward tokens forfeited
+        forfeit_amount_wads: Decimal,
+    },
+}
+
+impl LendingEvent {
+    /// Log the event
+    pub fn emit(&self) {
+        sol_log_data(&[&self.pack()]);
+    }
+
+    /// Unpack an event from the data it was logged with
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input.split_first().ok_or(LendingError::EventUnpackError)?;
+        let mut input = EventInput(rest);
+        let event = match tag {
+            0 => Self::LendingMarketChanged {
+                lending_market: input.pubkey()?,
+            },
+            1 => Self::ReserveConfigChanged {
+                reserve: input.pubkey()?,
+            },
+            2 => Self::InterestAccrued {
+                reserve: input.pubkey()?,
+                cumulative_borrow_rate_wads: input.decimal()?,
+                borrowed_amount_wads: input.decimal()?,
+                available_amount: input.u64()?,
+            },
+            3 => Self::DepositReserveLiquidity {
+                reserve: input.pubkey()?,
+                liquidity_amount: input.u64()?,
+                collateral_amount: input.u64()?,
+            },
+            4 => Self::RedeemReserveCollateral {
+                reserve: input.pubkey()?,
+                collateral_amount: input.u64()?,
+                liquidity_amount: input.u64()?,
+            },
+            5 => Self::ObligationChanged {
+                obligation: input.pubkey()?,
+            },
+            6 => Self::ObligationRefreshed {
+                obligation: input.pubkey()?,
+                deposited_value: input.decimal()?,
+                borrowed_value: input.decimal()?,
+                allowed_borrow_value: input.decimal()?,
+                unhealthy_borrow_value: input.decimal()?,
+            },
+            7 => Self::DepositObligationCollateral {
+                obligation: input.pubkey()?,
+                reserve: input.pubkey()?,
+                collateral_amount: input.u64()?,
+            },
+            8 => Self::WithdrawObligationCollateral {
+                obligation: input.pubkey()?,
+                reserve: input.pubkey()?,
+                collateral_amount: input.u64()?,
+            },
+            9 => Self::Borrow {
+                obligation: input.pubkey()?,
+                reserve: input.pubkey()?,
+                borrow_amount_wads: input.decimal()?,
+                receive_amount: input.u64()?,
+                borrow_fee: input.u64()?,
+                fixed_rate: input.bool()?,
+            },
+            10 => Self::Repay {
+                obligation: input.pubkey()?,
+                reserve: input.pubkey()?,
+                repay_amount: input.u64()?,
+                settle_amount_wads: input.decimal()?,
+            },
+            11 => Self::Liquidate {
+                obligation: input.pubkey()?,
+                repay_reserve: input.pubkey()?,
+                withdraw_reserve: input.pubkey()?,
+                repay_amount: input.u64()?,
+                withdraw_amount: input.u64()?,
+                protocol_fee: input.u64()?,
+            },
+            12 => Self::ForgiveDebt {
+                obligation: input.pubkey()?,
+                reserve: input.pubkey()?,
+                settle_amount_wads: input.decimal()?,
+            },
+            13 => Self::AccountMigrated {
+                account: input.pubkey()?,
+                version: input.u8()?,
+            },
+            14 => Self::ReferrerFeeReceiverInitialized {
+                reserve: input.pubkey()?,
+                referrer: input.pubkey()?,
+            },
+            15 => Self::ReferrerFeesClaimed {
+                reserve: input.pubkey()?,
+                referrer: input.pubkey()?,
+                amount: input.u64()?,
+            },
+            16 => Self::ReserveRewardsChanged {
+                reserve_rewards: input.pubkey()?,
+            },
+            17 => Self::RewardsClaimed {
+                reserve_rewards: input.pubkey()?,
+                obligation: input.pubkey()?,
+                amount: input.u64()?,
+            },
+            18 => Self::RewardsForfeited {
+                obligation: input.pubkey()?,
+                reserve: input.pubkey()?,
+                forfeit_amount_wads: input.decimal()?,
+            },
+            _ => return Err(LendingError::EventUnpackError.into()),
+        };
+        if !input.0.is_empty() {
+            return Err(LendingError::EventUnpackError.into());
+        }
+        Ok(event)
+    }
+
+    /// Pack the event into the data it is logged with
+    pub fn pack(&self) -> Vec<u8> {
+        let mut output = EventOutput(Vec::new());
+        match *self {
+            Self::LendingMarketChanged { lending_market } => {
+                output.u8(0);
+                output.pubkey(&lending_market);
+            }
+            Self::ReserveConfigChanged { reserve } => {
+                output.u8(1);
+                output.pubkey(&reserve);
+            }
+            Self::InterestAccrued {
+                reserve,
+                cumulative_borrow_rate_wads,
+                borrowed_amount_wads,
+                available_amount,
+            } => {
+                output.u8(2);
+                output.pubkey(&reserve);
+                output.decimal(cumulative_borrow_rate_wads);
+                output.decimal(borrowed_amount_wads);
+                output.u64(available_amount);
+            }
+            Self::DepositReserveLiquidity {
+                reserve,
+                liquidity_amount,
+                collateral_amount,
+            } => {
+                output.u8(3);
+                output.pubkey(&reserve);
+                output.u64(liquidity_amount);
+                output.u64(collateral_amount);
+            }
+            Self::RedeemReserveCollateral {
+                reserve,
+                collateral_amount,
+                liquidity_amount,
+            } => {
+                output.u8(4);
+                output.pubkey(&reserve);
+                output.u64(collateral_amount);
+                output.u64(liquidity_amount);
+            }
+            Self::ObligationChanged { obligation } => {
+                output.u8(5);
+                output.pubkey(&obligation);
+            }
+            Self::ObligationRefreshed {
+                obligation,
+                deposited_value,
+                borrowed_value,
+                allowed_borrow_value,
+                unhealthy_borrow_value,
+            } => {
+                output.u8(6);
+                output.pubkey(&obligation);
+                output.decimal(deposited_value);
+                output.decimal(borrowed_value);
+                output.decimal(allowed_borrow_value);
+                output.decimal(unhealthy_borrow_value);
+            }
+            Self::DepositObligationCollateral {
+                obligation,
+                reserve,
+                collateral_amount,
+            } => {
+                output.u8(7);
+                output.pubkey(&obligation);
+                output.pubkey(&reserve);
+                output.u64(collateral_amount);
+            }
+            Self::WithdrawObligationCollateral {
+                obligation,
+                reserve,
+                collateral_amount,
+            } => {
+                output.u8(8);
+                output.pubkey(&obligation);
+                output.pubkey(&reserve);
+                output.u64(collateral_amount);
+            }
+            Self::Borrow {
+                obligation,
+                reserve,
+                borrow_amount_wads,
+                receive_amount,
+                borrow_fee,
+                fixed_rate,
+            } => {
+                output.u8(9);
+                output.pubkey(&obligation);
+                output.pubkey(&reserve);
+                output.decimal(borrow_amount_wads);
+                output.u64(receive_amount);
+                output.u64(borrow_fee);
+                output.u8(fixed_rate as u8);
+            }
+            Self::Repay {
+                obligation,
+                reserve,
+                repay_amount,
+                settle_amount_wads,
+            } => {
+                output.u8(10);
+                output.pubkey(&obligation);
+                output.pubkey(&reserve);
+                output.u64(repay_amount);
+                output.decimal(settle_amount_wads);
+            }
+            Self::Liquidate {
+                obligation,
+                repay_reserve,
+                withdraw_reserve,
+                repay_amount,
+                withdraw_amount,
+                protocol_fee,
+            } => {
+                output.u8(11);
+                output.pubkey(&obligation);
+                output.pubkey(&repay_reserve);
+                output.pubkey(&withdraw_reserve);
+                output.u64(repay_amount);
+                output.u64(withdraw_amount);
+                output.u64(protocol_fee);
+            }
+            Self::ForgiveDebt {
+                obligation,
+                reserve,
+                settle_amount_wads,
+            } => {
+                output.u8(12);
+                output.pubkey(&obligation);
+                output.pubkey(&reserve);
+                output.decimal(settle_amount_wads);
+            }
+            Self::AccountMigrated { account, version } => {
+                output.u8(13);
+                output.pubkey(&account);
+                output.u8(version);
+            }
+            Self::ReferrerFeeReceiverInitialized { reserve, referrer } => {
+                output.u8(14);
+                output.pubkey(&reserve);
+                output.pubkey(&referrer);
+            }
+            Self::ReferrerFeesClaimed {
+                reserve,
+                referrer,
+                amount,
+            } => {
+                output.u8(15);
+                output.pubkey(&reserve);
+                output.pubkey(&referrer);
+                output.u64(amount);
+            }
+            Self::ReserveRewardsChanged { reserve_rewards } => {
+                output.u8(16);
+                output.pubkey(&reserve_rewards);
+            }
+            Self::RewardsClaimed {
+                reserve_rewards,
+                obligation,
+                amount,
+            } => {
+                output.u8(17);
+                output.pubkey(&reserve_rewards);
+                output.pubkey(&obligation);
+                output.u64(amount);
+            }
+            Self::RewardsForfeited {
+                obligation,
+                reserve,
+                forfeit_amount_wads,
+            } => {
+                output.u8(18);
+                output.pubkey(&obligation);
+                output.pubkey(&reserve);
+                output.decimal(forfeit_amount_wads);
+            }
+        }
+        output.0
+    }
+
+    /// Decode the events logged by a program in the log messages of a
+    /// transaction
+    ///
+    /// Only events logged while the program itself is executing are decoded,
+    /// so data logged by programs it invokes, or by other programs invoking
+    /// it, is skipped.
+    pub fn decode_log_messages(
+        program_id: &Pubkey,
+        log_messages: &[String],
+    ) -> Result<Vec<Self>, ProgramError> {
+        let program_id = program_id.to_string();
+        let mut invoked_programs = vec![];
+        let mut events = vec![];
+        for log_message in log_messages {
+            let Some(log_message) = log_message.strip_prefix("Program ") else {
+                continue;
+            };
+            if let Some(data) = log_message.strip_prefix("data: ") {
+                if invoked_programs.last() == Some(&program_id.as_str()) {
+                    events.push(Self::decode_log_data(data)?);
+                }
+            } else if let Some((invoked_program, _depth)) = log_message.split_once(" invoke [") {
+                invoked_programs.push(invoked_program);
+            } else if let Some((returned_program, _result)) = log_message.split_once(' ') {
+                if (log_message.ends_with(" success") || log_message.contains(" failed: "))
+                    && invoked_programs.last() == Some(&returned_program)
+                {
+                    invoked_programs.pop();
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Decode an event from the base64 data of a `Program data:` log message
+    pub fn decode_log_data(data: &str) -> Result<Self, ProgramError> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let data = STANDARD
+            .decode(data)
+            .map_err(|_| LendingError::EventUnpackError)?;
+        Self::unpack(&data)
+    }
+}
+
+struct EventInput<'a>(&'a [u8]);
+
+impl EventInput<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ProgramError> {
+        if self.0.len() < N {
+            return Err(LendingError::EventUnpackError.into());
+        }
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+        bytes
+            .try_into()
+            .map_err(|_| LendingError::EventUnpackError.into())
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey, ProgramError> {
+        self.take::<PUBKEY_BYTES>().map(Pubkey::new_from_array)
+    }
+
+    fn u8(&mut self) -> Result<u8, ProgramError> {
+        self.take::<1>().map(u8::from_le_bytes)
+    }
+
+    fn bool(&mut self) -> Result<bool, ProgramError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(LendingError::EventUnpackError.into()),
+        }
+    }
+
+    fn u64(&mut self) -> Result<u64, ProgramError> {
+        self.take::<8>().map(u64::from_le_bytes)
+    }
+
+    fn decimal(&mut self) -> Result<Decimal, ProgramError> {
+        self.take::<16>()
+            .map(|bytes| Decimal::from_scaled_val(u128::from_le_bytes(bytes)))
+    }
+}
+
+struct EventOutput(Vec<u8>);
+
+impl EventOutput {
+    fn pubkey(&mut self, pubkey: &Pubkey) {
+        self.0.extend_from_slice(pubkey.as_ref());
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn decimal(&mut self, value: Decimal) {
+        // events only log values that are also stored in accounts, which
+        // cannot exceed 128 bits either
+        let value = value.to_scaled_val().unwrap_or(u128::MAX);
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn events() -> Vec<LendingEvent> {
+        let pubkey = Pubkey::new_unique();
+        let decimal = Decimal::from_scaled_val(1_234_567);
+        vec![
+            LendingEvent::LendingMarketChanged {
+                lending_market: pubkey,
+            },
+            LendingEvent::ReserveConfigChanged { reserve: pubkey },
+            LendingEvent::InterestAccrued {
+                reserve: pubkey,
+                cumulative_borrow_rate_wads: decimal,
+                borrowed_amount_wads: Decimal::from(2u64),
+                available_amount: 3,
+            },
+            LendingEvent::DepositReserveLiquidity {
+                reserve: pubkey,
+                liquidity_amount: 1,
+                collateral_amount: 2,
+            },
+            LendingEvent::RedeemReserveCollateral {
+                reserve: pubkey,
+                collateral_amount: 1,
+                liquidity_amount: 2,
+            },
+            LendingEvent::ObligationChanged { obligation: pubkey },
+            LendingEvent::ObligationRefreshed {
+                obligation: pubkey,
+                deposited_value: decimal,
+                borrowed_value: Decimal::one(),
+                allowed_borrow_value: Decimal::zero(),
+                unhealthy_borrow_value: Decimal::from(4u64),
+            },
+            LendingEvent::DepositObligationCollateral {
+                obligation: pubkey,
+                reserve: Pubkey::new_unique(),
+                collateral_amount: 1,
+            },
+            LendingEvent::WithdrawObligationCollateral {
+                obligation: pubkey,
+                reserve: Pubkey::new_unique(),
+                collateral_amount: u64::MAX,
+            },
+            LendingEvent::Borrow {
+                obligation: pubkey,
+                reserve: Pubkey::new_unique(),
+                borrow_amount_wads: decimal,
+                receive_amount: 1,
+                borrow_fee: 2,
+                fixed_rate: true,
+            },
+            LendingEvent::Repay {
+                obligation: pubkey,
+                reserve: Pubkey::new_unique(),
+                repay_amount: 1,
+                settle_amount_wads: decimal,
+            },
+            LendingEvent::Liquidate {
+                obligation: pubkey,
+                repay_reserve: Pubkey::new_unique(),
+                withdraw_reserve: Pubkey::new_unique(),
+                repay_amount: 1,
+                withdraw_amount: 2,
+                protocol_fee: 3,
+            },
+            LendingEvent::ForgiveDebt {
+                obligation: pubkey,
+                reserve: Pubkey::new_unique(),
+                settle_amount_wads: decimal,
+            },
+            LendingEvent::AccountMigrated {
+                account: pubkey,
+                version: 1,
+            },
+            LendingEvent::ReferrerFeeReceiverInitialized {
+                reserve: pubkey,
+                referrer: Pubkey::new_unique(),
+            },
+            LendingEvent::ReferrerFeesClaimed {
+                reserve: pubkey,
+                referrer: Pubkey::new_unique(),
+                amount: 1,
+            },
+            LendingEvent::ReserveRewardsChanged {
+                reserve_rewards: pubkey,
+            },
+            LendingEvent::RewardsClaimed {
+                reserve_rewards: pubkey,
+                obligation: Pubkey::new_unique(),
+                amount: 1,
+            },
+            LendingEvent::RewardsForfeited {
+                obligation: pubkey,
+                reserve: Pubkey::new_unique(),
+                forfeit_amount_wads: decimal,
+            },
+        ]
+    }
+
+    #[test]
+    fn pack_events() {
+        for (tag, event) in events().into_iter().enumerate() {
+            let data = event.pack();
+            assert_eq!(data[0] as usize, tag);
+            assert_eq!(LendingEvent::unpack(&data).unwrap(), event);
+
+            assert_eq!(
+                LendingEvent::unpack(&data[..data.len() - 1]),
+                Err(LendingError::EventUnpackError.into())
+            );
+            let mut data = data;
+            data.push(0);
+            assert_eq!(
+                LendingEvent::unpack(&data),
+                Err(LendingError::EventUnpackError.into())
+            );
+        }
+        assert_eq!(
+            LendingEvent::unpack(&[u8::MAX]),
+            Err(LendingError::EventUnpackError.into())
+        );
+    }
+
+    #[test]
+    fn decode_log_messages() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        let event = LendingEvent::ObligationChanged {
+            obligation: Pubkey::new_unique(),
+        };
+        let data = STANDARD.encode(event.pack());
+        let log_messages = [
+            format!("Program {} invoke [1]", other_program_id),
+            format!("Program data: {}", data),
+            format!("Program {} invoke [2]", program_id),
+            "Program log: Instruction: Init Obligation".to_string(),
+            format!("Program data: {}", data),
+            format!(
+                "Program {} consumed 1000 of 200000 compute units",
+                program_id
+            ),
+            format!("Program {} success", program_id),
+            format!("Program data: {}", data),
+            format!("Program {} success", other_program_id),
+        ];
+        assert_eq!(
+            LendingEvent::decode_log_messages(&program_id, &log_messages).unwrap(),
+            vec![event]
+        );
+
+        assert_eq!(
+            LendingEvent::decode_log_messages(
+                &program_id,
+                &[
+                    format!("Program {} invoke [1]", program_id),
+                    "Program data: AA==".to_string(),
+                ]
+            ),
+            Err(LendingError::EventUnpackError.into())
+        );
+    }
+}
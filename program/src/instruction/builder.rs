@@ -1,10 +1,18 @@
 //a helper for rust clients to create instructions
+pub use crate::state::{find_obligation_address, find_reserve_address};
 use {
     super::LendingInstruction,
-    crate::state::ReserveConfig,
+    crate::state::{
+        find_referrer_fee_receiver_address, find_reserve_account_address, AccountType,
+        DelegatePermissions, ReserveConfig, ReserveStatus, RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+        RESERVE_COLLATERAL_MINT_SEED, RESERVE_COLLATERAL_SUPPLY_SEED,
+        RESERVE_LIQUIDITY_FEE_RECEIVER_SEED, RESERVE_LIQUIDITY_SUPPLY_SEED,
+    },
     solana_program::{
+        clock::UnixTimestamp,
         instruction::{AccountMeta, Instruction},
         pubkey::{Pubkey, PUBKEY_BYTES},
+        system_program,
     },
 };
 
@@ -48,29 +56,36 @@ pub fn liquidate_obligation(
     repay_reserve_liquidity_supply_pubkey: Pubkey,
     withdraw_reserve_pubkey: Pubkey,
     withdraw_reserve_collateral_supply_pubkey: Pubkey,
+    withdraw_reserve_collateral_fee_receiver_pubkey: Pubkey,
     obligation_pubkey: Pubkey,
     lending_market_pubkey: Pubkey,
     user_transfer_authority_pubkey: Pubkey,
+    host_fee_receiver_pubkey: Option<Pubkey>,
 ) -> Instruction {
     let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
         &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
         &program_id,
     );
+    let mut accounts = vec![
+        AccountMeta::new(source_liquidity_pubkey, false),
+        AccountMeta::new(destination_collateral_pubkey, false),
+        AccountMeta::new(repay_reserve_pubkey, false),
+        AccountMeta::new(repay_reserve_liquidity_supply_pubkey, false),
+        AccountMeta::new_readonly(withdraw_reserve_pubkey, false),
+        AccountMeta::new(withdraw_reserve_collateral_supply_pubkey, false),
+        AccountMeta::new(withdraw_reserve_collateral_fee_receiver_pubkey, false),
+        AccountMeta::new(obligation_pubkey, false),
+        AccountMeta::new_readonly(lending_market_pubkey, false),
+        AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    if let Some(host_fee_receiver_pubkey) = host_fee_receiver_pubkey {
+        accounts.push(AccountMeta::new(host_fee_receiver_pubkey, false));
+    }
     Instruction {
         program_id,
-        accounts: vec![
-            AccountMeta::new(source_liquidity_pubkey, false),
-            AccountMeta::new(destination_collateral_pubkey, false),
-            AccountMeta::new(repay_reserve_pubkey, false),
-            AccountMeta::new(repay_reserve_liquidity_supply_pubkey, false),
-            AccountMeta::new_readonly(withdraw_reserve_pubkey, false),
-            AccountMeta::new(withdraw_reserve_collateral_supply_pubkey, false),
-            AccountMeta::new(obligation_pubkey, false),
-            AccountMeta::new_readonly(lending_market_pubkey, false),
-            AccountMeta::new_readonly(lending_market_authority_pubkey, false),
-            AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
-            AccountMeta::new_readonly(spl_token::id(), false),
-        ],
+        accounts,
         data: LendingInstruction::LiquidateObligation { liquidity_amount }.pack(),
     }
 }
@@ -149,34 +164,16 @@ pub fn init_lending_market(
         .pack(),
     }
 }
-/// Creates a 'SetLendingMarketOwner' instruction.
-pub fn set_lending_market_owner(
-    program_id: Pubkey,
-    lending_market_pubkey: Pubkey,
-    lending_market_owner: Pubkey,
-    new_owner: Pubkey,
-) -> Instruction {
-    Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new(lending_market_pubkey, false),
-            AccountMeta::new_readonly(lending_market_owner, true),
-        ],
-        data: LendingInstruction::SetLendingMarketOwner { new_owner }.pack(),
-    }
-}
+
+/// Creates an 'InitReserve' instruction.
+#[allow(clippy::too_many_arguments)]
 pub fn init_reserve(
     program_id: Pubkey,
     liquidity_amount: u64,
     config: ReserveConfig,
     source_liquidity_pubkey: Pubkey,
     destination_collateral_pubkey: Pubkey,
-    reserve_pubkey: Pubkey,
     reserve_liquidity_mint_pubkey: Pubkey,
-    reserve_liquidity_supply_pubkey: Pubkey,
-    reserve_liquidity_fee_receiver_pubkey: Pubkey,
-    reserve_collateral_mint_pubkey: Pubkey,
-    reserve_collateral_supply_pubkey: Pubkey,
     pyth_product_pubkey: Pubkey,
     pyth_price_pubkey: Pubkey,
     lending_market_pubkey: Pubkey,
@@ -187,22 +184,38 @@ pub fn init_reserve(
         &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
         &program_id,
     );
+    let reserve_account = |seed| {
+        find_reserve_account_address(
+            &program_id,
+            seed,
+            &lending_market_pubkey,
+            &reserve_liquidity_mint_pubkey,
+        )
+        .0
+    };
+    let (reserve_pubkey, _bump_seed) = find_reserve_address(
+        &program_id,
+        &lending_market_pubkey,
+        &reserve_liquidity_mint_pubkey,
+    );
     let accounts = vec![
         AccountMeta::new(source_liquidity_pubkey, false),
         AccountMeta::new(destination_collateral_pubkey, false),
         AccountMeta::new(reserve_pubkey, false),
         AccountMeta::new_readonly(reserve_liquidity_mint_pubkey, false),
-        AccountMeta::new(reserve_liquidity_supply_pubkey, false),
-        AccountMeta::new(reserve_liquidity_fee_receiver_pubkey, false),
-        AccountMeta::new(reserve_collateral_mint_pubkey, false),
-        AccountMeta::new(reserve_collateral_supply_pubkey, false),
+        AccountMeta::new(reserve_account(RESERVE_LIQUIDITY_SUPPLY_SEED), false),
+        AccountMeta::new(reserve_account(RESERVE_LIQUIDITY_FEE_RECEIVER_SEED), false),
+        AccountMeta::new(reserve_account(RESERVE_COLLATERAL_MINT_SEED), false),
+        AccountMeta::new(reserve_account(RESERVE_COLLATERAL_SUPPLY_SEED), false),
+        AccountMeta::new(reserve_account(RESERVE_COLLATERAL_FEE_RECEIVER_SEED), false),
         AccountMeta::new_readonly(pyth_product_pubkey, false),
         AccountMeta::new_readonly(pyth_price_pubkey, false),
         AccountMeta::new_readonly(lending_market_pubkey, false),
         AccountMeta::new_readonly(lending_market_authority_pubkey, false),
-        AccountMeta::new_readonly(lending_market_owner_pubkey, true),
+        AccountMeta::new(lending_market_owner_pubkey, true),
         AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
         AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
     ];
     Instruction {
         program_id,
@@ -222,16 +235,56 @@ pub fn init_obligation(
     obligation_pubkey: Pubkey,
     lending_market_pubkey: Pubkey,
     obligation_owner_pubkey: Pubkey,
+    referrer_pubkey: Option<Pubkey>,
 ) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(obligation_pubkey, false),
+        AccountMeta::new_readonly(lending_market_pubkey, false),
+        AccountMeta::new_readonly(obligation_owner_pubkey, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    if let Some(referrer_pubkey) = referrer_pubkey {
+        accounts.push(AccountMeta::new_readonly(referrer_pubkey, false));
+    }
     Instruction {
         program_id,
-        accounts: vec![
-            AccountMeta::new(obligation_pubkey, false),
-            AccountMeta::new_readonly(lending_market_pubkey, false),
-            AccountMeta::new_readonly(obligation_owner_pubkey, true),
-            AccountMeta::new_readonly(spl_token::id(), false),
-        ],
-        data: LendingInstruction::InitObligation.pack(),
+        accounts,
+        data: LendingInstruction::InitObligation { seed_id: None }.pack(),
+    }
+}
+
+/// Creates an 'InitObligation' instruction creating the obligation at the
+/// address derived by `find_obligation_address` for the seed id.
+pub fn init_obligation_with_seed(
+    program_id: Pubkey,
+    seed_id: u64,
+    lending_market_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+    referrer_pubkey: Option<Pubkey>,
+) -> Instruction {
+    let (obligation_pubkey, _bump_seed) = find_obligation_address(
+        &program_id,
+        &lending_market_pubkey,
+        &obligation_owner_pubkey,
+        seed_id,
+    );
+    let mut accounts = vec![
+        AccountMeta::new(obligation_pubkey, false),
+        AccountMeta::new_readonly(lending_market_pubkey, false),
+        AccountMeta::new(obligation_owner_pubkey, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(referrer_pubkey) = referrer_pubkey {
+        accounts.push(AccountMeta::new_readonly(referrer_pubkey, false));
+    }
+    Instruction {
+        program_id,
+        accounts,
+        data: LendingInstruction::InitObligation {
+            seed_id: Some(seed_id),
+        }
+        .pack(),
     }
 }
 pub fn refresh_obligation(
@@ -408,439 +461,1681 @@ pub fn redeem_reserve_collateral(
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use {super::*, crate::state::ReserveFees};
-    #[test]
-    fn test_init_lending_market() {
-        let program_id = Pubkey::new_unique();
-        let owner = Pubkey::new_unique();
-        let lending_market_pubkey = Pubkey::new_unique();
-        let oracle_program_id = Pubkey::new_unique();
-        let currency = "USD";
-        let mut quote_currency = [0u8; 32];
-        quote_currency[0..currency.len()].clone_from_slice(currency.as_bytes());
-        let instruction = init_lending_market(
-            program_id,
-            owner,
-            quote_currency,
-            lending_market_pubkey,
-            oracle_program_id,
-        );
-        assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 3);
+/// Creates a 'ForgiveDebt' instruction.
+pub fn forgive_debt(
+    program_id: Pubkey,
+    obligation_pubkey: Pubkey,
+    reserve_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(obligation_pubkey, false),
+            AccountMeta::new(reserve_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+        ],
+        data: LendingInstruction::ForgiveDebt.pack(),
+    }
+}
 
-        assert_eq!(
-            instruction.data,
-            LendingInstruction::InitLendingMarket {
-                owner,
-                quote_currency,
-            }
-            .pack()
-        );
+/// Creates a 'ResizeObligation' instruction.
+pub fn resize_obligation(
+    program_id: Pubkey,
+    deposits_limit: u8,
+    borrows_limit: u8,
+    obligation_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(obligation_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new(obligation_owner_pubkey, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: LendingInstruction::ResizeObligation {
+            deposits_limit,
+            borrows_limit,
+        }
+        .pack(),
     }
+}
 
-    #[test]
-    fn test_set_lending_market_owner() {
-        let program_id = Pubkey::new_unique();
-        let lending_market_pubkey = Pubkey::new_unique();
-        let lending_market_owner = Pubkey::new_unique();
-        let new_owner = Pubkey::new_unique();
-        let instruction = set_lending_market_owner(
-            program_id,
-            lending_market_pubkey,
-            lending_market_owner,
-            new_owner,
-        );
-        assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 2);
-        assert_eq!(
-            instruction.data,
-            LendingInstruction::SetLendingMarketOwner { new_owner }.pack()
-        );
+/// Creates a 'MigrateAccount' instruction.
+pub fn migrate_account(
+    program_id: Pubkey,
+    account_type: AccountType,
+    account_pubkey: Pubkey,
+    payer: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(account_pubkey, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: LendingInstruction::MigrateAccount { account_type }.pack(),
     }
-    #[test]
-    fn test_refresh_reserve() {
-        let program_id = Pubkey::new_unique();
-        let reserve_pubkey = Pubkey::new_unique();
-        let reserve_liquidity_oracle_pubkey = Pubkey::new_unique();
-        let instruction =
-            refresh_reserve(program_id, reserve_pubkey, reserve_liquidity_oracle_pubkey);
-        assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 2);
-        assert_eq!(instruction.data, LendingInstruction::RefreshReserve.pack());
+}
+
+/// Creates a 'MigrateAccount' instruction for a reserve, creating its
+/// collateral fee receiver if the reserve has none.
+pub fn migrate_reserve(
+    program_id: Pubkey,
+    reserve_pubkey: Pubkey,
+    reserve_liquidity_mint_pubkey: Pubkey,
+    reserve_collateral_mint_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    payer: Pubkey,
+) -> Instruction {
+    let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+        &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+        &program_id,
+    );
+    let (reserve_collateral_fee_receiver_pubkey, _bump_seed) = find_reserve_account_address(
+        &program_id,
+        RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+        &lending_market_pubkey,
+        &reserve_liquidity_mint_pubkey,
+    );
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(reserve_pubkey, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(reserve_collateral_fee_receiver_pubkey, false),
+            AccountMeta::new_readonly(reserve_collateral_mint_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: LendingInstruction::MigrateAccount {
+            account_type: AccountType::Reserve,
+        }
+        .pack(),
     }
+}
 
-    #[test]
-    fn test_init_reserve() {
-        let program_id = Pubkey::new_unique();
-        let liquidity_amount = u64::MAX;
-        let config = ReserveConfig {
-            optimal_utilization_rate: 50,
-            loan_to_value_ratio: 1,
-            liquidation_bonus: 10,
-            liquidation_threshold: 5,
-            min_borrow_rate: 2,
-            optimal_borrow_rate: 4,
-            max_borrow_rate: 10,
-            fees: ReserveFees {
-                borrow_fee_wad: 1,
-                flash_loan_fee_wad: 3,
-                host_fee_percentage: 1,
-            },
-        };
-        let source_liquidity_pubkey = Pubkey::new_unique();
-        let destination_collateral_pubkey = Pubkey::new_unique();
-        let reserve_pubkey = Pubkey::new_unique();
-        let reserve_liquidity_mint_pubkey = Pubkey::new_unique();
-        let reserve_liquidity_supply_pubkey = Pubkey::new_unique();
-        let reserve_liquidity_fee_receiver_pubkey = Pubkey::new_unique();
-        let reserve_collateral_mint_pubkey = Pubkey::new_unique();
-        let reserve_collateral_supply_pubkey = Pubkey::new_unique();
-        let pyth_product_pubkey = Pubkey::new_unique();
-        let pyth_price_pubkey = Pubkey::new_unique();
-        let lending_market_pubkey = Pubkey::new_unique();
-        let lending_market_owner_pubkey = Pubkey::new_unique();
-        let user_transfer_authority_pubkey = Pubkey::new_unique();
-        let instruction = init_reserve(
-            program_id,
-            liquidity_amount,
-            config,
-            source_liquidity_pubkey,
-            destination_collateral_pubkey,
-            reserve_pubkey,
-            reserve_liquidity_mint_pubkey,
-            reserve_liquidity_supply_pubkey,
-            reserve_liquidity_fee_receiver_pubkey,
-            reserve_collateral_mint_pubkey,
-            reserve_collateral_supply_pubkey,
-            pyth_product_pubkey,
-            pyth_price_pubkey,
-            lending_market_pubkey,
-            lending_market_owner_pubkey,
-            user_transfer_authority_pubkey,
-        );
-        assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 15);
-        assert_eq!(
-            instruction.data,
-            LendingInstruction::InitReserve {
-                liquidity_amount,
-                config,
-            }
-            .pack()
-        );
+/// Creates a 'ProposeLendingMarketOwner' instruction.
+pub fn propose_lending_market_owner(
+    program_id: Pubkey,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner: Pubkey,
+    new_owner: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_owner, true),
+        ],
+        data: LendingInstruction::ProposeLendingMarketOwner { new_owner }.pack(),
     }
-    #[test]
-    fn test_deposit_reserve_liquidity() {
-        let program_id = Pubkey::new_unique();
-        let liquidity_amount = u64::MAX;
-        let source_liquidity_pubkey = Pubkey::new_unique();
-        let destination_collateral_pubkey = Pubkey::new_unique();
+}
+
+/// Creates an 'AcceptLendingMarketOwner' instruction.
+pub fn accept_lending_market_owner(
+    program_id: Pubkey,
+    lending_market_pubkey: Pubkey,
+    pending_owner: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(lending_market_pubkey, false),
+            AccountMeta::new_readonly(pending_owner, true),
+        ],
+        data: LendingInstruction::AcceptLendingMarketOwner.pack(),
+    }
+}
+
+/// Creates a 'CancelLendingMarketOwner' instruction.
+pub fn cancel_lending_market_owner(
+    program_id: Pubkey,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_owner, true),
+        ],
+        data: LendingInstruction::CancelLendingMarketOwner.pack(),
+    }
+}
+
+/// Creates a 'SetRiskManager' instruction.
+pub fn set_risk_manager(
+    program_id: Pubkey,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner: Pubkey,
+    risk_manager: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_owner, true),
+        ],
+        data: LendingInstruction::SetRiskManager { risk_manager }.pack(),
+    }
+}
+
+/// Creates a 'SetGuardian' instruction.
+pub fn set_guardian(
+    program_id: Pubkey,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner: Pubkey,
+    guardian: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_owner, true),
+        ],
+        data: LendingInstruction::SetGuardian { guardian }.pack(),
+    }
+}
+
+/// Creates a 'SetReserveStatus' instruction.
+pub fn set_reserve_status(
+    program_id: Pubkey,
+    status: ReserveStatus,
+    reserve_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(reserve_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(authority_pubkey, true),
+        ],
+        data: LendingInstruction::SetReserveStatus { status }.pack(),
+    }
+}
+
+/// Creates an 'ApplyPendingReserveConfig' instruction.
+pub fn apply_pending_reserve_config(program_id: Pubkey, reserve_pubkey: Pubkey) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new(reserve_pubkey, false)],
+        data: LendingInstruction::ApplyPendingReserveConfig.pack(),
+    }
+}
+
+/// Creates a 'SetReserveCollateralRamp' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn set_reserve_collateral_ramp(
+    program_id: Pubkey,
+    loan_to_value_ratio_bps: u16,
+    liquidation_threshold_bps: u16,
+    start_timestamp: UnixTimestamp,
+    end_timestamp: UnixTimestamp,
+    reserve_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(reserve_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_owner, true),
+        ],
+        data: LendingInstruction::SetReserveCollateralRamp {
+            loan_to_value_ratio_bps,
+            liquidation_threshold_bps,
+            start_timestamp,
+            end_timestamp,
+        }
+        .pack(),
+    }
+}
+
+/// Creates a 'SetObligationDelegate' instruction.
+pub fn set_obligation_delegate(
+    program_id: Pubkey,
+    delegate: Pubkey,
+    permissions: DelegatePermissions,
+    max_borrow_value: u64,
+    obligation_delegate_pubkey: Pubkey,
+    obligation_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(obligation_delegate_pubkey, false),
+            AccountMeta::new_readonly(obligation_pubkey, false),
+            AccountMeta::new_readonly(obligation_owner_pubkey, true),
+        ],
+        data: LendingInstruction::SetObligationDelegate {
+            delegate,
+            permissions,
+            max_borrow_value,
+        }
+        .pack(),
+    }
+}
+
+/// Adds the obligation delegate account to a 'DepositObligationCollateral',
+/// 'WithdrawObligationCollateral' or 'BorrowObligationLiquidity' instruction
+/// created with the delegate as obligation owner.
+pub fn with_obligation_delegate(
+    mut instruction: Instruction,
+    obligation_delegate_pubkey: Pubkey,
+) -> Instruction {
+    // the delegate account directly follows the token program id
+    let index = instruction
+        .accounts
+        .iter()
+        .position(|account| account.pubkey == spl_token::id())
+        .map_or(instruction.accounts.len(), |index| index + 1);
+    instruction.accounts.insert(
+        index,
+        AccountMeta::new_readonly(obligation_delegate_pubkey, false),
+    );
+    instruction
+}
+
+/// Creates a 'SetObligationOwner' instruction.
+pub fn set_obligation_owner(
+    program_id: Pubkey,
+    new_owner: Pubkey,
+    obligation_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+    obligation_owner_transfer_pubkey: Option<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(obligation_pubkey, false),
+        AccountMeta::new(obligation_owner_pubkey, true),
+    ];
+    if let Some(obligation_owner_transfer_pubkey) = obligation_owner_transfer_pubkey {
+        accounts.push(AccountMeta::new(obligation_owner_transfer_pubkey, false));
+    }
+    Instruction {
+        program_id,
+        accounts,
+        data: LendingInstruction::SetObligationOwner { new_owner }.pack(),
+    }
+}
+
+/// Creates an 'AcceptObligationOwner' instruction.
+pub fn accept_obligation_owner(
+    program_id: Pubkey,
+    obligation_owner_transfer_pubkey: Pubkey,
+    obligation_pubkey: Pubkey,
+    new_owner_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(obligation_owner_transfer_pubkey, false),
+            AccountMeta::new(obligation_pubkey, false),
+            AccountMeta::new_readonly(new_owner_pubkey, true),
+            AccountMeta::new(obligation_owner_pubkey, false),
+        ],
+        data: LendingInstruction::AcceptObligationOwner.pack(),
+    }
+}
+
+/// Creates an 'InitReferrerFeeReceiver' instruction.
+pub fn init_referrer_fee_receiver(
+    program_id: Pubkey,
+    funding_pubkey: Pubkey,
+    reserve_pubkey: Pubkey,
+    reserve_liquidity_mint_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    referrer_pubkey: Pubkey,
+) -> Instruction {
+    let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+        &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+        &program_id,
+    );
+    let (referrer_fee_receiver_pubkey, _bump_seed) =
+        find_referrer_fee_receiver_address(&program_id, &reserve_pubkey, &referrer_pubkey);
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(funding_pubkey, true),
+            AccountMeta::new(referrer_fee_receiver_pubkey, false),
+            AccountMeta::new_readonly(reserve_pubkey, false),
+            AccountMeta::new_readonly(reserve_liquidity_mint_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+            AccountMeta::new_readonly(referrer_pubkey, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: LendingInstruction::InitReferrerFeeReceiver.pack(),
+    }
+}
+
+/// Creates a 'ClaimReferrerFees' instruction.
+pub fn claim_referrer_fees(
+    program_id: Pubkey,
+    destination_liquidity_pubkey: Pubkey,
+    reserve_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    referrer_pubkey: Pubkey,
+) -> Instruction {
+    let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+        &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+        &program_id,
+    );
+    let (referrer_fee_receiver_pubkey, _bump_seed) =
+        find_referrer_fee_receiver_address(&program_id, &reserve_pubkey, &referrer_pubkey);
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(referrer_fee_receiver_pubkey, false),
+            AccountMeta::new(destination_liquidity_pubkey, false),
+            AccountMeta::new_readonly(reserve_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+            AccountMeta::new_readonly(referrer_pubkey, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: LendingInstruction::ClaimReferrerFees.pack(),
+    }
+}
+
+/// Creates a 'BorrowObligationLiquidityFixedRate' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn borrow_obligation_liquidity_fixed_rate(
+    program_id: Pubkey,
+    liquidity_amount: u64,
+    slippage_limit: Option<u64>,
+    term: u32,
+    max_borrow_rate_bps: u32,
+    source_liquidity_pubkey: Pubkey,
+    destination_liquidity_pubkey: Pubkey,
+    borrow_reserve_pubkey: Pubkey,
+    borrow_reserve_liquidity_fee_receiver_pubkey: Pubkey,
+    obligation_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+    host_fee_receiver_pubkey: Option<Pubkey>,
+) -> Instruction {
+    let mut instruction = borrow_obligation_liquidity(
+        program_id,
+        liquidity_amount,
+        slippage_limit,
+        source_liquidity_pubkey,
+        destination_liquidity_pubkey,
+        borrow_reserve_pubkey,
+        borrow_reserve_liquidity_fee_receiver_pubkey,
+        obligation_pubkey,
+        lending_market_pubkey,
+        obligation_owner_pubkey,
+        host_fee_receiver_pubkey,
+    );
+    instruction.data = LendingInstruction::BorrowObligationLiquidityFixedRate {
+        liquidity_amount,
+        slippage_limit: slippage_limit.unwrap_or(0),
+        term,
+        max_borrow_rate_bps,
+    }
+    .pack();
+    instruction
+}
+
+/// Creates an 'InitReserveRewards' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn init_reserve_rewards(
+    program_id: Pubkey,
+    supply_emission_per_second: u64,
+    borrow_emission_per_second: u64,
+    reserve_rewards_pubkey: Pubkey,
+    reward_supply_pubkey: Pubkey,
+    reward_mint_pubkey: Pubkey,
+    reserve_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner_pubkey: Pubkey,
+) -> Instruction {
+    let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+        &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+        &program_id,
+    );
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(reserve_rewards_pubkey, false),
+            AccountMeta::new(reward_supply_pubkey, false),
+            AccountMeta::new_readonly(reward_mint_pubkey, false),
+            AccountMeta::new(reserve_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+            AccountMeta::new_readonly(lending_market_owner_pubkey, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: LendingInstruction::InitReserveRewards {
+            supply_emission_per_second,
+            borrow_emission_per_second,
+        }
+        .pack(),
+    }
+}
+
+/// Creates a 'SetReserveRewardEmissions' instruction.
+pub fn set_reserve_reward_emissions(
+    program_id: Pubkey,
+    supply_emission_per_second: u64,
+    borrow_emission_per_second: u64,
+    reserve_rewards_pubkey: Pubkey,
+    reserve_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner_pubkey: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(reserve_rewards_pubkey, false),
+            AccountMeta::new(reserve_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_owner_pubkey, true),
+        ],
+        data: LendingInstruction::SetReserveRewardEmissions {
+            supply_emission_per_second,
+            borrow_emission_per_second,
+        }
+        .pack(),
+    }
+}
+
+/// Creates a 'ClaimRewards' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_rewards(
+    program_id: Pubkey,
+    reserve_rewards_pubkey: Pubkey,
+    reward_supply_pubkey: Pubkey,
+    destination_reward_pubkey: Pubkey,
+    obligation_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+) -> Instruction {
+    let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+        &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+        &program_id,
+    );
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(reserve_rewards_pubkey, false),
+            AccountMeta::new(reward_supply_pubkey, false),
+            AccountMeta::new(destination_reward_pubkey, false),
+            AccountMeta::new(obligation_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+            AccountMeta::new_readonly(obligation_owner_pubkey, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: LendingInstruction::ClaimRewards.pack(),
+    }
+}
+
+/// Creates a 'GetObligationHealth' instruction.
+pub fn get_obligation_health(
+    program_id: Pubkey,
+    obligation_pubkey: Pubkey,
+    reserve_pubkeys: Vec<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(obligation_pubkey, false)];
+    accounts.extend(
+        reserve_pubkeys
+            .into_iter()
+            .map(|pubkey| AccountMeta::new_readonly(pubkey, false)),
+    );
+
+    Instruction {
+        program_id,
+        accounts,
+        data: LendingInstruction::GetObligationHealth.pack(),
+    }
+}
+
+/// Creates a 'ForfeitRewards' instruction.
+pub fn forfeit_rewards(
+    program_id: Pubkey,
+    reserve_pubkey: Pubkey,
+    obligation_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(reserve_pubkey, false),
+            AccountMeta::new(obligation_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(obligation_owner_pubkey, true),
+        ],
+        data: LendingInstruction::ForfeitRewards.pack(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            error::LendingError,
+            state::{AdaptiveRateConfig, FixedRateConfig, RateCurve, RateCurvePoint, ReserveFees},
+        },
+    };
+    #[test]
+    fn test_init_lending_market() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let oracle_program_id = Pubkey::new_unique();
+        let currency = "USD";
+        let mut quote_currency = [0u8; 32];
+        quote_currency[0..currency.len()].clone_from_slice(currency.as_bytes());
+        let instruction = init_lending_market(
+            program_id,
+            owner,
+            quote_currency,
+            lending_market_pubkey,
+            oracle_program_id,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 3);
+
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::InitLendingMarket {
+                owner,
+                quote_currency,
+            }
+            .pack()
+        );
+    }
+
+    #[test]
+    fn test_refresh_reserve() {
+        let program_id = Pubkey::new_unique();
+        let reserve_pubkey = Pubkey::new_unique();
+        let reserve_liquidity_oracle_pubkey = Pubkey::new_unique();
+        let instruction =
+            refresh_reserve(program_id, reserve_pubkey, reserve_liquidity_oracle_pubkey);
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 2);
+        assert_eq!(instruction.data, LendingInstruction::RefreshReserve.pack());
+    }
+
+    #[test]
+    fn test_init_reserve() {
+        let program_id = Pubkey::new_unique();
+        let liquidity_amount = u64::MAX;
+        let config = ReserveConfig {
+            optimal_utilization_rate_bps: 5_000,
+            loan_to_value_ratio_bps: 100,
+            liquidation_bonus_bps: 1_000,
+            liquidation_threshold_bps: 500,
+            min_borrow_rate_bps: 200,
+            optimal_borrow_rate_bps: 400,
+            max_borrow_rate_bps: 1_000,
+            fees: ReserveFees {
+                borrow_fee_wad: 1,
+                flash_loan_fee_wad: 3,
+                host_fee_bps: 100,
+                protocol_liquidation_fee_bps: 3_000,
+            },
+            rate_curve: RateCurve::default(),
+            adaptive_rate: AdaptiveRateConfig::default(),
+            deposit_limit: 1_000_000,
+            borrow_limit: 500_000,
+            fixed_rate: FixedRateConfig::DISABLED,
+        };
+        let source_liquidity_pubkey = Pubkey::new_unique();
+        let destination_collateral_pubkey = Pubkey::new_unique();
+        let reserve_liquidity_mint_pubkey = Pubkey::new_unique();
+        let pyth_product_pubkey = Pubkey::new_unique();
+        let pyth_price_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let lending_market_owner_pubkey = Pubkey::new_unique();
+        let user_transfer_authority_pubkey = Pubkey::new_unique();
+        let instruction = init_reserve(
+            program_id,
+            liquidity_amount,
+            config,
+            source_liquidity_pubkey,
+            destination_collateral_pubkey,
+            reserve_liquidity_mint_pubkey,
+            pyth_product_pubkey,
+            pyth_price_pubkey,
+            lending_market_pubkey,
+            lending_market_owner_pubkey,
+            user_transfer_authority_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 17);
+        assert_eq!(
+            instruction.accounts[2].pubkey,
+            find_reserve_address(
+                &program_id,
+                &lending_market_pubkey,
+                &reserve_liquidity_mint_pubkey
+            )
+            .0
+        );
+        assert_ne!(
+            instruction.accounts[2].pubkey,
+            find_reserve_address(&program_id, &lending_market_pubkey, &Pubkey::new_unique()).0
+        );
+        assert_eq!(
+            instruction.accounts[6].pubkey,
+            find_reserve_account_address(
+                &program_id,
+                RESERVE_COLLATERAL_MINT_SEED,
+                &lending_market_pubkey,
+                &reserve_liquidity_mint_pubkey
+            )
+            .0
+        );
+        assert!(instruction.accounts[13].is_writable);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::InitReserve {
+                liquidity_amount,
+                config,
+            }
+            .pack()
+        );
+    }
+    #[test]
+    fn test_unpack_reserve_config() {
+        // legacy percentage encoding: rates, loan to value and thresholds in
+        // percent, then borrow fee, flash loan fee and host fee percentage
+        let mut data = vec![14, 80, 50, 5, 55, 0, 4, 30];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&3u64.to_le_bytes());
+        data.push(20);
+        let new_config = match LendingInstruction::unpack(&data).unwrap() {
+            LendingInstruction::ModifyReserveConfig { new_config } => new_config,
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        };
+        assert_eq!(new_config.loan_to_value_ratio_bps, 5_000);
+        assert_eq!(new_config.max_borrow_rate_bps, 3_000);
+        assert_eq!(new_config.fees.host_fee_bps, 2_000);
+        assert_eq!(new_config.fees.protocol_liquidation_fee_bps, 0);
+        assert_eq!(new_config.rate_curve, RateCurve::EMPTY);
+        assert_eq!(new_config.adaptive_rate, AdaptiveRateConfig::DISABLED);
+
+        // every field of the basis point encoding is required
+        let config = ReserveConfig {
+            fees: ReserveFees {
+                protocol_liquidation_fee_bps: 3_025,
+                ..ReserveFees::default()
+            },
+            ..ReserveConfig::default()
+        };
+        let data = LendingInstruction::ModifyReserveConfig { new_config: config }.pack();
+        assert_eq!(data[40..42], 3_025u16.to_le_bytes());
+        for len in 1..data.len() {
+            assert_eq!(
+                LendingInstruction::unpack(&data[..len]),
+                Err(LendingError::InstructionUnpackError.into())
+            );
+        }
+    }
+    #[test]
+    fn test_deposit_reserve_liquidity() {
+        let program_id = Pubkey::new_unique();
+        let liquidity_amount = u64::MAX;
+        let source_liquidity_pubkey = Pubkey::new_unique();
+        let destination_collateral_pubkey = Pubkey::new_unique();
         let reserve_pubkey = Pubkey::new_unique();
         let reserve_liquidity_supply_pubkey = Pubkey::new_unique();
         let reserve_collateral_mint_pubkey = Pubkey::new_unique();
         let lending_market_pubkey = Pubkey::new_unique();
         let user_transfer_authority_pubkey = Pubkey::new_unique();
-        let instruction = deposit_reserve_liquidity(
+        let instruction = deposit_reserve_liquidity(
+            program_id,
+            liquidity_amount,
+            source_liquidity_pubkey,
+            destination_collateral_pubkey,
+            reserve_pubkey,
+            reserve_liquidity_supply_pubkey,
+            reserve_collateral_mint_pubkey,
+            lending_market_pubkey,
+            user_transfer_authority_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::DepositReserveLiquidity { liquidity_amount }.pack()
+        );
+    }
+    #[test]
+    fn test_redeem_reserve_collateral() {
+        let program_id = Pubkey::new_unique();
+        let collateral_amount = u64::MAX;
+        let source_collateral_pubkey = Pubkey::new_unique();
+        let destination_liquidity_pubkey = Pubkey::new_unique();
+        let reserve_pubkey = Pubkey::new_unique();
+        let reserve_collateral_mint_pubkey = Pubkey::new_unique();
+        let reserve_liquidity_supply_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let user_transfer_authority_pubkey = Pubkey::new_unique();
+        let instruction = redeem_reserve_collateral(
+            program_id,
+            collateral_amount,
+            source_collateral_pubkey,
+            destination_liquidity_pubkey,
+            reserve_pubkey,
+            reserve_collateral_mint_pubkey,
+            reserve_liquidity_supply_pubkey,
+            lending_market_pubkey,
+            user_transfer_authority_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::RedeemReserveCollateral { collateral_amount }.pack()
+        );
+    }
+    #[test]
+    fn test_init_obligation() {
+        let program_id = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let obligation_owner_pubkey = Pubkey::new_unique();
+        let instruction = init_obligation(
+            program_id,
+            obligation_pubkey,
+            lending_market_pubkey,
+            obligation_owner_pubkey,
+            None,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 4);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::InitObligation { seed_id: None }
+        );
+
+        let instruction = init_obligation(
+            program_id,
+            obligation_pubkey,
+            lending_market_pubkey,
+            obligation_owner_pubkey,
+            Some(Pubkey::new_unique()),
+        );
+        assert_eq!(instruction.accounts.len(), 5);
+
+        let seed_id = 1;
+        let instruction = init_obligation_with_seed(
+            program_id,
+            seed_id,
+            lending_market_pubkey,
+            obligation_owner_pubkey,
+            Some(Pubkey::new_unique()),
+        );
+        assert_eq!(instruction.accounts.len(), 6);
+        assert_eq!(
+            instruction.accounts[0].pubkey,
+            find_obligation_address(
+                &program_id,
+                &lending_market_pubkey,
+                &obligation_owner_pubkey,
+                seed_id
+            )
+            .0
+        );
+        assert_ne!(
+            instruction.accounts[0].pubkey,
+            find_obligation_address(
+                &program_id,
+                &lending_market_pubkey,
+                &obligation_owner_pubkey,
+                seed_id + 1
+            )
+            .0
+        );
+        assert!(instruction.accounts[2].is_writable);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::InitObligation {
+                seed_id: Some(seed_id)
+            }
+        );
+    }
+    #[test]
+    fn test_refresh_obligation() {
+        let program_id = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let reserve_pubkeys = vec![Pubkey::new_unique()];
+        let instruction = refresh_obligation(program_id, obligation_pubkey, reserve_pubkeys);
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 2);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::RefreshObligation.pack()
+        );
+    }
+    #[test]
+    fn test_deposit_obligation_collateral() {
+        let program_id = Pubkey::new_unique();
+        let collateral_amount = u64::MAX;
+        let source_collateral_pubkey = Pubkey::new_unique();
+        let destination_collateral_pubkey = Pubkey::new_unique();
+        let deposit_reserve_pubkey = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let obligation_owner_pubkey = Pubkey::new_unique();
+        let user_transfer_authority_pubkey = Pubkey::new_unique();
+        let instruction = deposit_obligation_collateral(
+            program_id,
+            collateral_amount,
+            source_collateral_pubkey,
+            destination_collateral_pubkey,
+            deposit_reserve_pubkey,
+            obligation_pubkey,
+            lending_market_pubkey,
+            obligation_owner_pubkey,
+            user_transfer_authority_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 8);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::DepositObligationCollateral { collateral_amount }.pack()
+        );
+    }
+    #[test]
+    fn test_withdraw_obligation_collateral() {
+        let program_id = Pubkey::new_unique();
+        let collateral_amount = u64::MAX;
+        let source_collateral_pubkey = Pubkey::new_unique();
+        let destination_collateral_pubkey = Pubkey::new_unique();
+        let withdraw_reserve_pubkey = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let obligation_owner_pubkey = Pubkey::new_unique();
+        let instruction = withdraw_obligation_collateral(
+            program_id,
+            collateral_amount,
+            source_collateral_pubkey,
+            destination_collateral_pubkey,
+            withdraw_reserve_pubkey,
+            obligation_pubkey,
+            lending_market_pubkey,
+            obligation_owner_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 8);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::WithdrawObligationCollateral { collateral_amount }.pack()
+        );
+    }
+    #[test]
+    fn test_borrow_obligation_liquidity() {
+        let program_id = Pubkey::new_unique();
+        let liquidity_amount = u64::MAX;
+        let source_liquidity_pubkey = Pubkey::new_unique();
+        let destination_liquidity_pubkey = Pubkey::new_unique();
+        let borrow_reserve_pubkey = Pubkey::new_unique();
+        let borrow_reserve_liquidity_fee_receiver_pubkey = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let obligation_owner_pubkey = Pubkey::new_unique();
+        let host_fee_receiver_pubkey = Some(Pubkey::new_unique());
+        let instruction = borrow_obligation_liquidity(
+            program_id,
+            liquidity_amount,
+            None,
+            source_liquidity_pubkey,
+            destination_liquidity_pubkey,
+            borrow_reserve_pubkey,
+            borrow_reserve_liquidity_fee_receiver_pubkey,
+            obligation_pubkey,
+            lending_market_pubkey,
+            obligation_owner_pubkey,
+            host_fee_receiver_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 10);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::BorrowObligationLiquidity {
+                liquidity_amount,
+                slippage_limit: 0
+            }
+            .pack()
+        );
+    }
+    #[test]
+    fn test_repay_obligation_liquidity() {
+        let program_id = Pubkey::new_unique();
+        let liquidity_amount = u64::MAX;
+        let source_liquidity_pubkey = Pubkey::new_unique();
+        let destination_liquidity_pubkey = Pubkey::new_unique();
+        let repay_reserve_pubkey = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let user_transfer_authority_pubkey = Pubkey::new_unique();
+        let instruction = repay_obligation_liquidity(
+            program_id,
+            liquidity_amount,
+            source_liquidity_pubkey,
+            destination_liquidity_pubkey,
+            repay_reserve_pubkey,
+            obligation_pubkey,
+            lending_market_pubkey,
+            user_transfer_authority_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 7);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::RepayObligationLiquidity { liquidity_amount }.pack()
+        );
+    }
+
+    #[test]
+    fn test_liquidate_obligation() {
+        let program_id = Pubkey::new_unique();
+        let liquidity_amount = u64::MAX;
+        let source_liquidity_pubkey = Pubkey::new_unique();
+        let destination_collateral_pubkey = Pubkey::new_unique();
+        let repay_reserve_pubkey = Pubkey::new_unique();
+        let repay_reserve_liquidity_supply_pubkey = Pubkey::new_unique();
+        let withdraw_reserve_pubkey = Pubkey::new_unique();
+        let withdraw_reserve_collateral_supply_pubkey = Pubkey::new_unique();
+        let withdraw_reserve_collateral_fee_receiver_pubkey = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let user_transfer_authority_pubkey = Pubkey::new_unique();
+        let instruction = liquidate_obligation(
             program_id,
             liquidity_amount,
             source_liquidity_pubkey,
             destination_collateral_pubkey,
-            reserve_pubkey,
-            reserve_liquidity_supply_pubkey,
-            reserve_collateral_mint_pubkey,
+            repay_reserve_pubkey,
+            repay_reserve_liquidity_supply_pubkey,
+            withdraw_reserve_pubkey,
+            withdraw_reserve_collateral_supply_pubkey,
+            withdraw_reserve_collateral_fee_receiver_pubkey,
+            obligation_pubkey,
             lending_market_pubkey,
             user_transfer_authority_pubkey,
+            None,
         );
         assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(instruction.accounts.len(), 12);
         assert_eq!(
             instruction.data,
-            LendingInstruction::DepositReserveLiquidity { liquidity_amount }.pack()
+            LendingInstruction::LiquidateObligation { liquidity_amount }.pack()
+        );
+
+        let host_fee_receiver_pubkey = Pubkey::new_unique();
+        let instruction = liquidate_obligation(
+            program_id,
+            liquidity_amount,
+            source_liquidity_pubkey,
+            destination_collateral_pubkey,
+            repay_reserve_pubkey,
+            repay_reserve_liquidity_supply_pubkey,
+            withdraw_reserve_pubkey,
+            withdraw_reserve_collateral_supply_pubkey,
+            withdraw_reserve_collateral_fee_receiver_pubkey,
+            obligation_pubkey,
+            lending_market_pubkey,
+            user_transfer_authority_pubkey,
+            Some(host_fee_receiver_pubkey),
         );
+        assert_eq!(instruction.accounts.len(), 13);
+        assert_eq!(instruction.accounts[12].pubkey, host_fee_receiver_pubkey);
+        assert!(instruction.accounts[12].is_writable);
     }
+
+    // #[test]
+    // fn test_flash_loan() {
+    //     let program_id = Pubkey::new_unique();
+    //     let amount = u64::MAX;
+    //     let source_liquidity_pubkey = Pubkey::new_unique();
+    //     let destination_liquidity_pubkey = Pubkey::new_unique();
+    //     let repay_reserve_pubkey = Pubkey::new_unique();
+    //     let reserve_liquidity_fee_receiver_pubkey = Pubkey::new_unique();
+    //     let host_fee_receiver_pubkey = Pubkey::new_unique();
+    //     let lending_market_pubkey = Pubkey::new_unique();
+    //     let flash_loan_receiver_program_id = Pubkey::new_unique();
+    //     let account_meta = AccountMeta {
+    //         pubkey: Pubkey::new_unique(),
+    //         is_signer: true,
+    //         is_writable: false,
+    //     };
+    //     let flash_loan_receiver_program_accounts = vec![account_meta];
+    //     let instruction = flash_loan(
+    //         program_id,
+    //         amount,
+    //         source_liquidity_pubkey,
+    //         destination_liquidity_pubkey,
+    //         repay_reserve_pubkey,
+    //         reserve_liquidity_fee_receiver_pubkey,
+    //         host_fee_receiver_pubkey,
+    //         lending_market_pubkey,
+    //         flash_loan_receiver_program_id,
+    //         flash_loan_receiver_program_accounts,
+    //     );
+    //     assert_eq!(instruction.program_id, program_id);
+    //     assert_eq!(instruction.accounts.len(), 10);
+    //     assert_eq!(
+    //         instruction.data,
+    //         LendingInstruction::FlashLoan { amount }.pack()
+    //     );
+    // }
+
     #[test]
-    fn test_redeem_reserve_collateral() {
+    fn test_modify_reserve_config() {
         let program_id = Pubkey::new_unique();
-        let collateral_amount = u64::MAX;
-        let source_collateral_pubkey = Pubkey::new_unique();
-        let destination_liquidity_pubkey = Pubkey::new_unique();
+        let config = ReserveConfig {
+            optimal_utilization_rate_bps: 6_000,
+            loan_to_value_ratio_bps: 100,
+            liquidation_bonus_bps: 1_000,
+            liquidation_threshold_bps: 500,
+            min_borrow_rate_bps: 200,
+            optimal_borrow_rate_bps: 400,
+            max_borrow_rate_bps: 1_000,
+            fees: ReserveFees {
+                borrow_fee_wad: 1,
+                flash_loan_fee_wad: 3,
+                host_fee_bps: 100,
+                protocol_liquidation_fee_bps: 3_000,
+            },
+            rate_curve: RateCurve::new(&[
+                RateCurvePoint {
+                    utilization_rate_bps: 0,
+                    borrow_rate_bps: 100,
+                },
+                RateCurvePoint {
+                    utilization_rate_bps: 8_000,
+                    borrow_rate_bps: 800,
+                },
+                RateCurvePoint {
+                    utilization_rate_bps: 10_000,
+                    borrow_rate_bps: 10_000,
+                },
+            ])
+            .unwrap(),
+            adaptive_rate: AdaptiveRateConfig {
+                target_utilization_rate_bps: 8_000,
+                adjustment_speed_bps: 5_000,
+            },
+            deposit_limit: 0,
+            borrow_limit: 250_000,
+            fixed_rate: FixedRateConfig {
+                premium_bps: 150,
+                max_term: 30 * 24 * 60 * 60,
+            },
+        };
         let reserve_pubkey = Pubkey::new_unique();
-        let reserve_collateral_mint_pubkey = Pubkey::new_unique();
-        let reserve_liquidity_supply_pubkey = Pubkey::new_unique();
         let lending_market_pubkey = Pubkey::new_unique();
-        let user_transfer_authority_pubkey = Pubkey::new_unique();
-        let instruction = redeem_reserve_collateral(
+        let lending_market_owner_pubkey = Pubkey::new_unique();
+        let instruction = modify_reserve_config(
             program_id,
-            collateral_amount,
-            source_collateral_pubkey,
-            destination_liquidity_pubkey,
+            config,
             reserve_pubkey,
-            reserve_collateral_mint_pubkey,
-            reserve_liquidity_supply_pubkey,
             lending_market_pubkey,
-            user_transfer_authority_pubkey,
+            lending_market_owner_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 3);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::ModifyReserveConfig { new_config: config }.pack()
+        );
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::ModifyReserveConfig { new_config: config }
+        );
+    }
+
+    #[test]
+    fn test_unpack_percent_reserve_config() {
+        let mut data = vec![14, 80, 50, 5, 55, 0, 4, 30];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&3u64.to_le_bytes());
+        data.push(20);
+        assert_eq!(
+            LendingInstruction::unpack(&data).unwrap(),
+            LendingInstruction::ModifyReserveConfig {
+                new_config: ReserveConfig {
+                    optimal_utilization_rate_bps: 8_000,
+                    loan_to_value_ratio_bps: 5_000,
+                    liquidation_bonus_bps: 500,
+                    liquidation_threshold_bps: 5_500,
+                    min_borrow_rate_bps: 0,
+                    optimal_borrow_rate_bps: 400,
+                    max_borrow_rate_bps: 3_000,
+                    fees: ReserveFees {
+                        borrow_fee_wad: 1,
+                        flash_loan_fee_wad: 3,
+                        host_fee_bps: 2_000,
+                        protocol_liquidation_fee_bps: 0,
+                    },
+                    rate_curve: RateCurve::EMPTY,
+                    adaptive_rate: AdaptiveRateConfig::DISABLED,
+                    deposit_limit: 0,
+                    borrow_limit: 0,
+                    fixed_rate: FixedRateConfig::DISABLED,
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_forgive_debt() {
+        let program_id = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let reserve_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let instruction = forgive_debt(
+            program_id,
+            obligation_pubkey,
+            reserve_pubkey,
+            lending_market_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 3);
+        assert_eq!(instruction.data, LendingInstruction::ForgiveDebt.pack());
+    }
+
+    #[test]
+    fn test_resize_obligation() {
+        let program_id = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let obligation_owner_pubkey = Pubkey::new_unique();
+        let instruction = resize_obligation(
+            program_id,
+            12,
+            6,
+            obligation_pubkey,
+            lending_market_pubkey,
+            obligation_owner_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 4);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::ResizeObligation {
+                deposits_limit: 12,
+                borrows_limit: 6,
+            }
+            .pack()
+        );
+    }
+
+    #[test]
+    fn test_migrate_account() {
+        let program_id = Pubkey::new_unique();
+        let account_pubkey = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let instruction =
+            migrate_account(program_id, AccountType::Obligation, account_pubkey, payer);
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 3);
+        assert!(instruction.accounts[1].is_signer);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::MigrateAccount {
+                account_type: AccountType::Obligation,
+            }
+        );
+        assert_eq!(
+            LendingInstruction::unpack(&[17, 7]),
+            Err(LendingError::InstructionUnpackError.into())
+        );
+
+        let reserve_liquidity_mint_pubkey = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let instruction = migrate_reserve(
+            program_id,
+            account_pubkey,
+            reserve_liquidity_mint_pubkey,
+            Pubkey::new_unique(),
+            lending_market_pubkey,
+            payer,
+        );
+        assert_eq!(instruction.accounts.len(), 8);
+        assert_eq!(
+            instruction.accounts[3].pubkey,
+            find_reserve_account_address(
+                &program_id,
+                RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+                &lending_market_pubkey,
+                &reserve_liquidity_mint_pubkey,
+            )
+            .0
+        );
+        assert!(instruction.accounts[3].is_writable);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::MigrateAccount {
+                account_type: AccountType::Reserve,
+            }
+        );
+    }
+
+    #[test]
+    fn test_lending_market_owner_transfer() {
+        let program_id = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let lending_market_owner = Pubkey::new_unique();
+        let new_owner = Pubkey::new_unique();
+
+        let instruction = propose_lending_market_owner(
+            program_id,
+            lending_market_pubkey,
+            lending_market_owner,
+            new_owner,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 2);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::ProposeLendingMarketOwner { new_owner }
+        );
+
+        let instruction = accept_lending_market_owner(program_id, lending_market_pubkey, new_owner);
+        assert!(instruction.accounts[1].is_signer);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::AcceptLendingMarketOwner
+        );
+
+        let instruction =
+            cancel_lending_market_owner(program_id, lending_market_pubkey, lending_market_owner);
+        assert_eq!(instruction.accounts.len(), 2);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::CancelLendingMarketOwner
+        );
+    }
+
+    #[test]
+    fn test_lending_market_roles() {
+        let program_id = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let lending_market_owner = Pubkey::new_unique();
+        let risk_manager = Pubkey::new_unique();
+        let guardian = Pubkey::new_unique();
+
+        let instruction = set_risk_manager(
+            program_id,
+            lending_market_pubkey,
+            lending_market_owner,
+            risk_manager,
+        );
+        assert_eq!(instruction.accounts.len(), 2);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::SetRiskManager { risk_manager }
+        );
+
+        let instruction = set_guardian(
+            program_id,
+            lending_market_pubkey,
+            lending_market_owner,
+            guardian,
+        );
+        assert_eq!(instruction.accounts.len(), 2);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::SetGuardian { guardian }
+        );
+
+        let reserve_pubkey = Pubkey::new_unique();
+        let instruction = set_reserve_status(
+            program_id,
+            ReserveStatus::ReduceOnly,
+            reserve_pubkey,
+            lending_market_pubkey,
+            guardian,
+        );
+        assert_eq!(instruction.accounts.len(), 3);
+        assert!(instruction.accounts[2].is_signer);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::SetReserveStatus {
+                status: ReserveStatus::ReduceOnly
+            }
         );
+        assert!(LendingInstruction::unpack(&[23, 3]).is_err());
+    }
+
+    #[test]
+    fn test_apply_pending_reserve_config() {
+        let program_id = Pubkey::new_unique();
+        let reserve_pubkey = Pubkey::new_unique();
+        let instruction = apply_pending_reserve_config(program_id, reserve_pubkey);
         assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(instruction.accounts.len(), 1);
+        assert!(instruction.accounts[0].is_writable);
         assert_eq!(
             instruction.data,
-            LendingInstruction::RedeemReserveCollateral { collateral_amount }.pack()
+            LendingInstruction::ApplyPendingReserveConfig.pack()
         );
     }
+
     #[test]
-    fn test_init_obligation() {
+    fn test_set_reserve_collateral_ramp() {
         let program_id = Pubkey::new_unique();
-        let obligation_pubkey = Pubkey::new_unique();
+        let reserve_pubkey = Pubkey::new_unique();
         let lending_market_pubkey = Pubkey::new_unique();
-        let obligation_owner_pubkey = Pubkey::new_unique();
-        let instruction = init_obligation(
+        let lending_market_owner = Pubkey::new_unique();
+        let instruction = set_reserve_collateral_ramp(
             program_id,
-            obligation_pubkey,
+            2_000,
+            3_000,
+            1_000,
+            2_000,
+            reserve_pubkey,
             lending_market_pubkey,
-            obligation_owner_pubkey,
+            lending_market_owner,
         );
         assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 4);
-        assert_eq!(instruction.data, LendingInstruction::InitObligation.pack());
+        assert_eq!(instruction.accounts.len(), 3);
+        assert!(instruction.accounts[0].is_writable);
+        assert!(instruction.accounts[2].is_signer);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::SetReserveCollateralRamp {
+                loan_to_value_ratio_bps: 2_000,
+                liquidation_threshold_bps: 3_000,
+                start_timestamp: 1_000,
+                end_timestamp: 2_000,
+            }
+        );
     }
+
     #[test]
-    fn test_refresh_obligation() {
+    fn test_set_obligation_delegate() {
         let program_id = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let obligation_delegate_pubkey = Pubkey::new_unique();
         let obligation_pubkey = Pubkey::new_unique();
-        let reserve_pubkeys = vec![Pubkey::new_unique()];
-        let instruction = refresh_obligation(program_id, obligation_pubkey, reserve_pubkeys);
+        let obligation_owner = Pubkey::new_unique();
+        let instruction = set_obligation_delegate(
+            program_id,
+            delegate,
+            DelegatePermissions::BORROW,
+            1_000,
+            obligation_delegate_pubkey,
+            obligation_pubkey,
+            obligation_owner,
+        );
         assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 2);
+        assert_eq!(instruction.accounts.len(), 3);
+        assert!(instruction.accounts[0].is_writable);
+        assert!(instruction.accounts[2].is_signer);
         assert_eq!(
-            instruction.data,
-            LendingInstruction::RefreshObligation.pack()
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::SetObligationDelegate {
+                delegate,
+                permissions: DelegatePermissions::BORROW,
+                max_borrow_value: 1_000,
+            }
         );
     }
+
     #[test]
-    fn test_deposit_obligation_collateral() {
+    fn test_with_obligation_delegate() {
+        let obligation_delegate_pubkey = Pubkey::new_unique();
+        let instruction = with_obligation_delegate(
+            borrow_obligation_liquidity(
+                Pubkey::new_unique(),
+                1,
+                None,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Some(Pubkey::new_unique()),
+            ),
+            obligation_delegate_pubkey,
+        );
+        assert_eq!(instruction.accounts.len(), 11);
+        assert_eq!(instruction.accounts[8].pubkey, spl_token::id());
+        assert_eq!(instruction.accounts[9].pubkey, obligation_delegate_pubkey);
+        assert!(!instruction.accounts[9].is_writable);
+    }
+
+    #[test]
+    fn test_set_obligation_owner() {
         let program_id = Pubkey::new_unique();
-        let collateral_amount = u64::MAX;
-        let source_collateral_pubkey = Pubkey::new_unique();
-        let destination_collateral_pubkey = Pubkey::new_unique();
-        let deposit_reserve_pubkey = Pubkey::new_unique();
+        let new_owner = Pubkey::new_unique();
         let obligation_pubkey = Pubkey::new_unique();
-        let lending_market_pubkey = Pubkey::new_unique();
-        let obligation_owner_pubkey = Pubkey::new_unique();
-        let user_transfer_authority_pubkey = Pubkey::new_unique();
-        let instruction = deposit_obligation_collateral(
+        let obligation_owner = Pubkey::new_unique();
+        let obligation_owner_transfer_pubkey = Pubkey::new_unique();
+        let instruction = set_obligation_owner(
             program_id,
-            collateral_amount,
-            source_collateral_pubkey,
-            destination_collateral_pubkey,
-            deposit_reserve_pubkey,
+            new_owner,
             obligation_pubkey,
-            lending_market_pubkey,
-            obligation_owner_pubkey,
-            user_transfer_authority_pubkey,
+            obligation_owner,
+            None,
         );
         assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 8);
+        assert_eq!(instruction.accounts.len(), 2);
+        assert!(instruction.accounts[0].is_writable);
+        assert!(instruction.accounts[1].is_signer);
+        assert!(instruction.accounts[1].is_writable);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::SetObligationOwner { new_owner }
+        );
+
+        let instruction = set_obligation_owner(
+            program_id,
+            new_owner,
+            obligation_pubkey,
+            obligation_owner,
+            Some(obligation_owner_transfer_pubkey),
+        );
+        assert_eq!(instruction.accounts.len(), 3);
+        assert!(instruction.accounts[2].is_writable);
+
+        let instruction = accept_obligation_owner(
+            program_id,
+            obligation_owner_transfer_pubkey,
+            obligation_pubkey,
+            new_owner,
+            obligation_owner,
+        );
+        assert_eq!(instruction.accounts.len(), 4);
+        assert!(instruction.accounts[1].is_writable);
+        assert!(instruction.accounts[2].is_signer);
+        assert_eq!(instruction.accounts[3].pubkey, obligation_owner);
+        assert!(instruction.accounts[3].is_writable);
         assert_eq!(
             instruction.data,
-            LendingInstruction::DepositObligationCollateral { collateral_amount }.pack()
+            LendingInstruction::AcceptObligationOwner.pack()
         );
     }
     #[test]
-    fn test_withdraw_obligation_collateral() {
+    fn test_referrer_fees() {
         let program_id = Pubkey::new_unique();
-        let collateral_amount = u64::MAX;
-        let source_collateral_pubkey = Pubkey::new_unique();
-        let destination_collateral_pubkey = Pubkey::new_unique();
-        let withdraw_reserve_pubkey = Pubkey::new_unique();
-        let obligation_pubkey = Pubkey::new_unique();
+        let reserve_pubkey = Pubkey::new_unique();
         let lending_market_pubkey = Pubkey::new_unique();
-        let obligation_owner_pubkey = Pubkey::new_unique();
-        let instruction = withdraw_obligation_collateral(
+        let referrer_pubkey = Pubkey::new_unique();
+        let (referrer_fee_receiver_pubkey, _bump_seed) =
+            find_referrer_fee_receiver_address(&program_id, &reserve_pubkey, &referrer_pubkey);
+        let instruction = init_referrer_fee_receiver(
             program_id,
-            collateral_amount,
-            source_collateral_pubkey,
-            destination_collateral_pubkey,
-            withdraw_reserve_pubkey,
-            obligation_pubkey,
+            Pubkey::new_unique(),
+            reserve_pubkey,
+            Pubkey::new_unique(),
             lending_market_pubkey,
-            obligation_owner_pubkey,
+            referrer_pubkey,
         );
         assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 8);
+        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(instruction.accounts[1].pubkey, referrer_fee_receiver_pubkey);
         assert_eq!(
             instruction.data,
-            LendingInstruction::WithdrawObligationCollateral { collateral_amount }.pack()
+            LendingInstruction::InitReferrerFeeReceiver.pack()
+        );
+
+        let instruction = claim_referrer_fees(
+            program_id,
+            Pubkey::new_unique(),
+            reserve_pubkey,
+            lending_market_pubkey,
+            referrer_pubkey,
+        );
+        assert_eq!(instruction.accounts.len(), 7);
+        assert_eq!(instruction.accounts[0].pubkey, referrer_fee_receiver_pubkey);
+        assert!(instruction.accounts[5].is_signer);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::ClaimReferrerFees
         );
     }
+
     #[test]
-    fn test_borrow_obligation_liquidity() {
+    fn test_borrow_obligation_liquidity_fixed_rate() {
         let program_id = Pubkey::new_unique();
-        let liquidity_amount = u64::MAX;
-        let source_liquidity_pubkey = Pubkey::new_unique();
-        let destination_liquidity_pubkey = Pubkey::new_unique();
-        let borrow_reserve_pubkey = Pubkey::new_unique();
-        let borrow_reserve_liquidity_fee_receiver_pubkey = Pubkey::new_unique();
-        let obligation_pubkey = Pubkey::new_unique();
-        let lending_market_pubkey = Pubkey::new_unique();
-        let obligation_owner_pubkey = Pubkey::new_unique();
-        let host_fee_receiver_pubkey = Some(Pubkey::new_unique());
-        let instruction = borrow_obligation_liquidity(
+        let instruction = borrow_obligation_liquidity_fixed_rate(
             program_id,
-            liquidity_amount,
+            1_000,
+            None,
+            7 * 24 * 60 * 60,
+            1_200,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
             None,
-            source_liquidity_pubkey,
-            destination_liquidity_pubkey,
-            borrow_reserve_pubkey,
-            borrow_reserve_liquidity_fee_receiver_pubkey,
-            obligation_pubkey,
-            lending_market_pubkey,
-            obligation_owner_pubkey,
-            host_fee_receiver_pubkey,
         );
         assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 10);
+        assert_eq!(instruction.accounts.len(), 9);
+        assert!(instruction.accounts[7].is_signer);
         assert_eq!(
-            instruction.data,
-            LendingInstruction::BorrowObligationLiquidity {
-                liquidity_amount,
-                slippage_limit: 0
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::BorrowObligationLiquidityFixedRate {
+                liquidity_amount: 1_000,
+                slippage_limit: 0,
+                term: 7 * 24 * 60 * 60,
+                max_borrow_rate_bps: 1_200,
             }
-            .pack()
         );
     }
     #[test]
-    fn test_repay_obligation_liquidity() {
+    fn test_reserve_rewards() {
         let program_id = Pubkey::new_unique();
-        let liquidity_amount = u64::MAX;
-        let source_liquidity_pubkey = Pubkey::new_unique();
-        let destination_liquidity_pubkey = Pubkey::new_unique();
-        let repay_reserve_pubkey = Pubkey::new_unique();
-        let obligation_pubkey = Pubkey::new_unique();
+        let reserve_rewards_pubkey = Pubkey::new_unique();
+        let reserve_pubkey = Pubkey::new_unique();
         let lending_market_pubkey = Pubkey::new_unique();
-        let user_transfer_authority_pubkey = Pubkey::new_unique();
-        let instruction = repay_obligation_liquidity(
+        let instruction = init_reserve_rewards(
             program_id,
-            liquidity_amount,
-            source_liquidity_pubkey,
-            destination_liquidity_pubkey,
-            repay_reserve_pubkey,
-            obligation_pubkey,
+            100,
+            50,
+            reserve_rewards_pubkey,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            reserve_pubkey,
             lending_market_pubkey,
-            user_transfer_authority_pubkey,
+            Pubkey::new_unique(),
         );
         assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 7);
+        assert_eq!(instruction.accounts.len(), 8);
+        assert!(instruction.accounts[6].is_signer);
         assert_eq!(
-            instruction.data,
-            LendingInstruction::RepayObligationLiquidity { liquidity_amount }.pack()
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::InitReserveRewards {
+                supply_emission_per_second: 100,
+                borrow_emission_per_second: 50,
+            }
+        );
+
+        let instruction = set_reserve_reward_emissions(
+            program_id,
+            0,
+            200,
+            reserve_rewards_pubkey,
+            reserve_pubkey,
+            lending_market_pubkey,
+            Pubkey::new_unique(),
+        );
+        assert_eq!(instruction.accounts.len(), 4);
+        assert!(instruction.accounts[3].is_signer);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::SetReserveRewardEmissions {
+                supply_emission_per_second: 0,
+                borrow_emission_per_second: 200,
+            }
         );
-    }
 
-    #[test]
-    fn test_liquidate_obligation() {
-        let program_id = Pubkey::new_unique();
-        let liquidity_amount = u64::MAX;
-        let source_liquidity_pubkey = Pubkey::new_unique();
-        let destination_collateral_pubkey = Pubkey::new_unique();
-        let repay_reserve_pubkey = Pubkey::new_unique();
-        let repay_reserve_liquidity_supply_pubkey = Pubkey::new_unique();
-        let withdraw_reserve_pubkey = Pubkey::new_unique();
-        let withdraw_reserve_collateral_supply_pubkey = Pubkey::new_unique();
         let obligation_pubkey = Pubkey::new_unique();
-        let lending_market_pubkey = Pubkey::new_unique();
-        let user_transfer_authority_pubkey = Pubkey::new_unique();
-        let instruction = liquidate_obligation(
+        let instruction = claim_rewards(
             program_id,
-            liquidity_amount,
-            source_liquidity_pubkey,
-            destination_collateral_pubkey,
-            repay_reserve_pubkey,
-            repay_reserve_liquidity_supply_pubkey,
-            withdraw_reserve_pubkey,
-            withdraw_reserve_collateral_supply_pubkey,
+            reserve_rewards_pubkey,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
             obligation_pubkey,
             lending_market_pubkey,
-            user_transfer_authority_pubkey,
+            Pubkey::new_unique(),
         );
-        assert_eq!(instruction.program_id, program_id);
-        assert_eq!(instruction.accounts.len(), 11);
+        assert_eq!(instruction.accounts.len(), 8);
+        assert_eq!(instruction.accounts[3].pubkey, obligation_pubkey);
+        assert!(instruction.accounts[3].is_writable);
+        assert!(instruction.accounts[6].is_signer);
         assert_eq!(
-            instruction.data,
-            LendingInstruction::LiquidateObligation { liquidity_amount }.pack()
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::ClaimRewards
         );
-    }
-
-    // #[test]
-    // fn test_flash_loan() {
-    //     let program_id = Pubkey::new_unique();
-    //     let amount = u64::MAX;
-    //     let source_liquidity_pubkey = Pubkey::new_unique();
-    //     let destination_liquidity_pubkey = Pubkey::new_unique();
-    //     let repay_reserve_pubkey = Pubkey::new_unique();
-    //     let reserve_liquidity_fee_receiver_pubkey = Pubkey::new_unique();
-    //     let host_fee_receiver_pubkey = Pubkey::new_unique();
-    //     let lending_market_pubkey = Pubkey::new_unique();
-    //     let flash_loan_receiver_program_id = Pubkey::new_unique();
-    //     let account_meta = AccountMeta {
-    //         pubkey: Pubkey::new_unique(),
-    //         is_signer: true,
-    //         is_writable: false,
-    //     };
-    //     let flash_loan_receiver_program_accounts = vec![account_meta];
-    //     let instruction = flash_loan(
-    //         program_id,
-    //         amount,
-    //         source_liquidity_pubkey,
-    //         destination_liquidity_pubkey,
-    //         repay_reserve_pubkey,
-    //         reserve_liquidity_fee_receiver_pubkey,
-    //         host_fee_receiver_pubkey,
-    //         lending_market_pubkey,
-    //         flash_loan_receiver_program_id,
-    //         flash_loan_receiver_program_accounts,
-    //     );
-    //     assert_eq!(instruction.program_id, program_id);
-    //     assert_eq!(instruction.accounts.len(), 10);
-    //     assert_eq!(
-    //         instruction.data,
-    //         LendingInstruction::FlashLoan { amount }.pack()
-    //     );
-    // }
 
-    #[test]
-    fn test_modify_reserve_config() {
-        let program_id = Pubkey::new_unique();
-        let config = ReserveConfig {
-            optimal_utilization_rate: 60,
-            loan_to_value_ratio: 1,
-            liquidation_bonus: 10,
-            liquidation_threshold: 5,
-            min_borrow_rate: 2,
-            optimal_borrow_rate: 4,
-            max_borrow_rate: 10,
-            fees: ReserveFees {
-                borrow_fee_wad: 1,
-                flash_loan_fee_wad: 3,
-                host_fee_percentage: 1,
-            },
-        };
-        let reserve_pubkey = Pubkey::new_unique();
-        let lending_market_pubkey = Pubkey::new_unique();
-        let lending_market_owner_pubkey = Pubkey::new_unique();
-        let instruction = modify_reserve_config(
+        let instruction = forfeit_rewards(
             program_id,
-            config,
             reserve_pubkey,
+            obligation_pubkey,
             lending_market_pubkey,
-            lending_market_owner_pubkey,
+            Pubkey::new_unique(),
+        );
+        assert_eq!(instruction.accounts.len(), 4);
+        assert_eq!(instruction.accounts[1].pubkey, obligation_pubkey);
+        assert!(instruction.accounts[1].is_writable);
+        assert!(instruction.accounts[3].is_signer);
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::ForfeitRewards
         );
+    }
+    #[test]
+    fn test_get_obligation_health() {
+        let program_id = Pubkey::new_unique();
+        let obligation_pubkey = Pubkey::new_unique();
+        let reserve_pubkeys = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let instruction = get_obligation_health(program_id, obligation_pubkey, reserve_pubkeys);
         assert_eq!(instruction.program_id, program_id);
         assert_eq!(instruction.accounts.len(), 3);
+        assert!(instruction.accounts.iter().all(|meta| !meta.is_writable));
         assert_eq!(
-            instruction.data,
-            LendingInstruction::ModifyReserveConfig { new_config: config }.pack()
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::GetObligationHealth
         );
     }
 }
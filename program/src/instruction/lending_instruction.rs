@@ -1,4 +1,7 @@
-use {crate::state::ReserveConfig, solana_program::pubkey::Pubkey};
+use {
+    crate::state::{AccountType, DelegatePermissions, ReserveConfig, ReserveStatus},
+    solana_program::{clock::UnixTimestamp, pubkey::Pubkey},
+};
 
 /// Instructions supported by the lending program.
 #[derive(Debug, PartialEq)]
@@ -21,7 +24,9 @@ pub enum LendingInstruction {
         quote_currency: [u8; 32],
     },
     // 1
-    /// Sets the new owner of a lending market.
+    /// Deprecated, always fails. The lending market owner is transferred with
+    /// `ProposeLendingMarketOwner` and `AcceptLendingMarketOwner`, which
+    /// requires the new owner to accept.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -34,26 +39,39 @@ pub enum LendingInstruction {
     // 2
     /// Initializes a new lending market reserve.
     ///
+    /// The reserve, its supply and fee receiver token accounts and its
+    /// collateral mint are created by the program, funded by the lending
+    /// market owner, at the addresses derived by `find_reserve_address` and
+    /// `find_reserve_account_address` from the lending market and liquidity
+    /// mint. A lending market holds at most one reserve per liquidity mint.
+    ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[writable]` Source liquidity token account. $authority can
     ///      transfer $liquidity_amount.
     ///   1. `[writable]` Destination collateral token account - uninitialized.
-    ///   2. `[writable]` Reserve account - uninitialized.
+    ///   2. `[writable]` Reserve account - derived, not yet created.
     ///   3. `[]` Reserve liquidity SPL Token mint.
-    ///   4. `[writable]` Reserve liquidity supply SPL Token account -
-    ///      uninitialized.
-    ///   5. `[writable]` Reserve liquidity fee receiver - uninitialized.
-    ///   6. `[writable]` Reserve collateral SPL Token mint - uninitialized.
-    ///   7. `[writable]` Reserve collateral token supply - uninitialized.
-    ///   8. `[]` Pyth product account.
-    ///   9. `[]` Pyth price account. This will be used as the reserve liquidity
-    ///      oracle account.
-    ///   10. `[]` Lending market account.
-    ///   11. `[]` Derived lending market authority.
-    ///   12. `[signer]` Lending market owner.
-    ///   13. `[signer]` User transfer authority ($authority).
-    ///   14. `[]` Token program id.
+    ///   4. `[writable]` Reserve liquidity supply SPL Token account - derived,
+    ///      not yet created.
+    ///   5. `[writable]` Reserve liquidity fee receiver - derived, not yet
+    ///      created.
+    ///   6. `[writable]` Reserve collateral SPL Token mint - derived, not yet
+    ///      created.
+    ///   7. `[writable]` Reserve collateral token supply - derived, not yet
+    ///      created.
+    ///   8. `[writable]` Reserve collateral fee receiver - derived, not yet
+    ///      created.
+    ///   9. `[]` Pyth product account.
+    ///   10. `[]` Pyth price account. This will be used as the reserve
+    ///       liquidity oracle account.
+    ///   11. `[]` Lending market account.
+    ///   12. `[]` Derived lending market authority.
+    ///   13. `[signer, writable]` Lending market owner, funding the reserve
+    ///       accounts.
+    ///   14. `[signer]` User transfer authority ($authority).
+    ///   15. `[]` Token program id.
+    ///   16. `[]` System program id.
     InitReserve {
         /// Initial amount of liquidity to deposit into the new reserve
         liquidity_amount: u64,
@@ -71,7 +89,8 @@ pub enum LendingInstruction {
     RefreshReserve,
     // 4
     /// Deposit liquidity into a reserve in exchange for collateral. Collateral
-    /// represents a share of the reserve liquidity pool.
+    /// represents a share of the reserve liquidity pool. Returns a
+    /// `DepositReserveLiquidityResult` as return data.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -91,7 +110,8 @@ pub enum LendingInstruction {
     },
 
     // 5
-    /// Redeem collateral from a reserve in exchange for liquidity.
+    /// Redeem collateral from a reserve in exchange for liquidity. Returns a
+    /// `RedeemReserveCollateralResult` as return data.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -112,13 +132,34 @@ pub enum LendingInstruction {
     // 6
     /// Initializes a new lending market obligation.
     ///
+    /// When a referrer is provided, the obligation uses the resizable layout
+    /// and the host share of its borrow fees accrues to the referrer fee
+    /// receivers. The obligation account must then be sized for no deposits
+    /// or borrows, see `Obligation::resizable_len`, and grown with
+    /// ResizeObligation. A referrer provided with an account sized for the
+    /// fixed layout is rejected, and the referrer of an obligation cannot be
+    /// set after initialization.
+    ///
+    /// When a seed id is provided, the program creates the obligation account
+    /// at the address derived by `find_obligation_address`, funded by the
+    /// obligation owner and sized for a resizable obligation without reserves.
+    /// It must be grown with ResizeObligation before its first deposit or
+    /// borrow. Lamports sent to the address ahead of time do not block its
+    /// creation.
+    ///
     /// Accounts expected by this instruction:
     ///
-    ///   0. `[writable]` Obligation account - uninitialized.
+    ///   0. `[writable]` Obligation account - uninitialized, or not yet
+    ///      created when a seed id is provided.
     ///   1. `[]` Lending market account.
-    ///   2. `[signer]` Obligation owner.
+    ///   2. `[signer]` Obligation owner, writable when a seed id is provided.
     ///   3. `[]` Token program id.
-    InitObligation,
+    ///   4. `[]` System program id - only when a seed id is provided.
+    ///   5. `[optional]` Referrer.
+    InitObligation {
+        /// Seed id of the obligation address to create, if any
+        seed_id: Option<u64>,
+    },
     // 7
     /// Refresh an obligation's accrued interest and collateral and liquidity
     /// prices. Requires refreshed reserves, as all obligation collateral
@@ -146,9 +187,11 @@ pub enum LendingInstruction {
     ///   2. `[]` Deposit reserve account - refreshed.
     ///   3. `[writable]` Obligation account.
     ///   4. `[]` Lending market account.
-    ///   5. `[signer]` Obligation owner.
+    ///   5. `[signer]` Obligation owner, or obligation delegate with the
+    ///      deposit permission.
     ///   6. `[signer]` User transfer authority ($authority).
     ///   7. `[]` Token program id.
+    ///   8. `[optional]` Obligation delegate account, if signed by a delegate.
     DepositObligationCollateral {
         /// Amount of collateral tokens to deposit
         collateral_amount: u64,
@@ -167,8 +210,11 @@ pub enum LendingInstruction {
     ///   3. `[writable]` Obligation account - refreshed.
     ///   4. `[]` Lending market account.
     ///   5. `[]` Derived lending market authority.
-    ///   6. `[signer]` Obligation owner.
+    ///   6. `[signer]` Obligation owner, or obligation delegate with the
+    ///      withdraw permission withdrawing to an obligation owner token
+    ///      account.
     ///   7. `[]` Token program id.
+    ///   8. `[optional]` Obligation delegate account, if signed by a delegate.
     WithdrawObligationCollateral {
         /// Amount of collateral tokens to withdraw - u64::MAX for up to 100% of
         /// deposited amount
@@ -176,7 +222,8 @@ pub enum LendingInstruction {
     },
     // 10
     /// Borrow liquidity from a reserve by depositing collateral tokens.
-    /// Requires a refreshed obligation and reserve.
+    /// Requires a refreshed obligation and reserve. Returns a
+    /// `CalculateBorrowResult` as return data.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -190,9 +237,15 @@ pub enum LendingInstruction {
     ///   4. `[writable]` Obligation account - refreshed.
     ///   5. `[]` Lending market account.
     ///   6. `[]` Derived lending market authority.
-    ///   7. `[signer]` Obligation owner.
+    ///   7. `[signer]` Obligation owner, or obligation delegate with the borrow
+    ///      permission, borrowing within its max borrow value to an obligation
+    ///      owner token account.
     ///   8. `[]` Token program id.
-    ///   9. `[optional, writable]` Host fee receiver account.
+    ///   9. `[optional]` Obligation delegate account, if signed by a delegate.
+    ///   10. `[optional, writable]` Host fee receiver account. Required for
+    ///       obligations with a referrer, and must be the referrer fee
+    ///       receiver of the borrow reserve, initialized with
+    ///       InitReferrerFeeReceiver.
     BorrowObligationLiquidity {
         /// Amount of liquidity to borrow - u64::MAX for 100% of borrowing power
         liquidity_amount: u64,
@@ -202,7 +255,8 @@ pub enum LendingInstruction {
     },
     // 11
     /// Repay borrowed liquidity to a reserve. Requires a refreshed obligation
-    /// and reserve.
+    /// and reserve. Repayments are not charged a fee, so unlike borrow and
+    /// liquidate there is no host fee receiver.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -221,8 +275,9 @@ pub enum LendingInstruction {
     },
     // 12
     /// Repay borrowed liquidity to a reserve to receive collateral at a
-    /// discount from an unhealthy obligation. Requires a refreshed
-    /// obligation and reserves.
+    /// discount from an unhealthy obligation, or from any obligation with a
+    /// matured fixed rate borrow. Requires a refreshed obligation and
+    /// reserves. Returns a `CalculateLiquidationResult` as return data.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -234,18 +289,26 @@ pub enum LendingInstruction {
     ///   3. `[writable]` Repay reserve liquidity supply SPL Token account.
     ///   4. `[]` Withdraw reserve account - refreshed.
     ///   5. `[writable]` Withdraw reserve collateral supply SPL Token account.
-    ///   6. `[writable]` Obligation account - refreshed.
-    ///   7. `[]` Lending market account.
-    ///   8. `[]` Derived lending market authority.
-    ///   9. `[signer]` User transfer authority ($authority).
-    ///   10. `[]` Token program id.
+    ///   6. `[writable]` Withdraw reserve collateral fee receiver account. Must
+    ///      be the collateral fee account specified at InitReserve, or created
+    ///      by MigrateAccount for older reserves.
+    ///   7. `[writable]` Obligation account - refreshed.
+    ///   8. `[]` Lending market account.
+    ///   9. `[]` Derived lending market authority.
+    ///   10. `[signer]` User transfer authority ($authority).
+    ///   11. `[]` Token program id.
+    ///   12. `[writable]` Host fee receiver account - optional. Minted by
+    ///       withdraw reserve collateral mint, receives the host share of the
+    ///       protocol liquidation fee.
     LiquidateObligation {
         /// Amount of liquidity to repay - u64::MAX for up to 100% of borrowed
         /// amount
         liquidity_amount: u64,
     },
     // 13
-    /// Make a flash loan.
+    /// Make a flash loan. Not processed by this program: the instruction is
+    /// kept so that the tags of the following instructions are unchanged,
+    /// and it always fails.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -291,13 +354,368 @@ pub enum LendingInstruction {
     /// Modify the ReserveConfig parameters of an already initialized Reserve
     /// account
     ///
+    /// A config equal to the current one is rejected. Changes which only
+    /// lower the loan to value ratio and limits are applied immediately,
+    /// without cancelling a collateral ramp, whose loan to value ratios are
+    /// capped by the new one. When signed by the owner they drop the pending
+    /// config, unless a collateral ramp moves towards it. Other changes are
+    /// queued as the pending config of the reserve until the timelock
+    /// expires, see `ApplyPendingReserveConfig`. The liquidation threshold
+    /// can be lowered by at most `MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS` per
+    /// change. Raising the deposit or borrow limit is queued like any other
+    /// change, and is only permitted to the lending market owner.
+    ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[writable]` Reserve account
     ///   1. `[]` Lending market account
-    ///   2. `[signer]` Lending market owner
+    ///   2. `[signer]` Lending market owner, or risk manager which can only
+    ///      lower the loan to value ratio and limits
     ModifyReserveConfig {
         /// Reserve configuration updated values
         new_config: ReserveConfig,
     },
+    // 15
+    /// Write off the remaining borrowed liquidity of an obligation which has
+    /// no collateral left. The forgiven amount is removed from the reserve
+    /// total supply, so the loss is shared by all depositors. Requires a
+    /// refreshed obligation and reserve.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Obligation account - refreshed.
+    ///   1. `[writable]` Borrow reserve account - refreshed.
+    ///   2. `[]` Lending market account.
+    ForgiveDebt,
+    // 16
+    /// Resize an obligation account to hold up to the given number of deposits
+    /// and borrows, switching it to the resizable layout. The owner pays for
+    /// any additional rent and receives any excess lamports.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Obligation account.
+    ///   1. `[]` Lending market account.
+    ///   2. `[signer, writable]` Obligation owner.
+    ///   3. `[]` System program.
+    ResizeObligation {
+        /// Max number of deposits
+        deposits_limit: u8,
+        /// Max number of borrows
+        borrows_limit: u8,
+    },
+    // 17
+    /// Rewrite an account stored with an outdated layout into the latest
+    /// layout, growing the account if the latest layout needs more space.
+    /// Accounts already using the latest layout are left unchanged.
+    ///
+    /// Reserves created before the protocol liquidation fee also get their
+    /// collateral fee receiver, which liquidations require, created at the
+    /// address derived by `find_reserve_account_address`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Account to migrate.
+    ///   1. `[signer, writable]` Payer funding rent exemption of a grown or
+    ///      created account.
+    ///   2. `[]` System program.
+    ///
+    ///   Reserves without a collateral fee receiver also expect:
+    ///
+    ///   3. `[writable]` Reserve collateral fee receiver - derived, not yet
+    ///      created.
+    ///   4. `[]` Reserve collateral SPL Token mint.
+    ///   5. `[]` Lending market account.
+    ///   6. `[]` Derived lending market authority.
+    ///   7. `[]` Token program id.
+    MigrateAccount {
+        /// Kind of state stored in the account
+        account_type: AccountType,
+    },
+    // 18
+    /// Propose a new owner of a lending market. Ownership is only transferred
+    /// once the proposed owner accepts it. Replaces any pending proposal.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Lending market account.
+    ///   1. `[signer]` Current owner.
+    ProposeLendingMarketOwner {
+        /// The proposed owner
+        new_owner: Pubkey,
+    },
+    // 19
+    /// Accept ownership of a lending market as its pending owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Lending market account.
+    ///   1. `[signer]` Pending owner.
+    AcceptLendingMarketOwner,
+    // 20
+    /// Cancel the pending ownership transfer of a lending market.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Lending market account.
+    ///   1. `[signer]` Current owner.
+    CancelLendingMarketOwner,
+    // 21
+    /// Sets the risk manager of a lending market, which can lower reserve
+    /// loan to value ratios and limits. Default pubkey to unset.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Lending market account.
+    ///   1. `[signer]` Lending market owner.
+    SetRiskManager {
+        /// The new risk manager
+        risk_manager: Pubkey,
+    },
+    // 22
+    /// Sets the guardian of a lending market, which can pause reserves or
+    /// make them reduce-only. Default pubkey to unset.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Lending market account.
+    ///   1. `[signer]` Lending market owner.
+    SetGuardian {
+        /// The new guardian
+        guardian: Pubkey,
+    },
+    // 23
+    /// Sets the operations allowed on a reserve. The guardian can only make
+    /// the status more restrictive, the owner can set any status.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Reserve account.
+    ///   1. `[]` Lending market account.
+    ///   2. `[signer]` Lending market owner or guardian.
+    SetReserveStatus {
+        /// The new reserve status
+        status: ReserveStatus,
+    },
+    // 24
+    /// Apply the pending config of a reserve once its timelock expired.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Reserve account.
+    ApplyPendingReserveConfig,
+    // 25
+    /// Move the loan to value ratio and liquidation threshold of a reserve
+    /// linearly from their current values to new values between two
+    /// timestamps, instead of changing them at once. The new values are
+    /// checked like a `ModifyReserveConfig` change from the values in effect
+    /// and queued as the pending config of the reserve, replacing any pending
+    /// one. The ramp cannot start before the timelock of the pending config
+    /// expires, and applying a config with other values cancels the ramp.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Reserve account.
+    ///   1. `[]` Lending market account.
+    ///   2. `[signer]` Lending market owner.
+    SetReserveCollateralRamp {
+        /// Loan to value ratio reached at the end of the ramp, in basis points
+        loan_to_value_ratio_bps: u16,
+        /// Liquidation threshold reached at the end of the ramp, in basis
+        /// points
+        liquidation_threshold_bps: u16,
+        /// Unix timestamp when the ramp starts
+        start_timestamp: UnixTimestamp,
+        /// Unix timestamp when the ramp ends, at least the config timelock
+        /// after its start
+        end_timestamp: UnixTimestamp,
+    },
+    // 26
+    /// Register or update a delegate which can manage an obligation on behalf
+    /// of its owner. Setting no permissions revokes the delegate.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Obligation delegate account - uninitialized, or set
+    ///      previously for the obligation.
+    ///   1. `[]` Obligation account.
+    ///   2. `[signer]` Obligation owner.
+    SetObligationDelegate {
+        /// Delegate authority
+        delegate: Pubkey,
+        /// Actions the delegate can take
+        permissions: DelegatePermissions,
+        /// Max borrowed value of the obligation after a borrow by the
+        /// delegate, in the lending market quote currency
+        max_borrow_value: u64,
+    },
+    // 27
+    /// Transfer an obligation to a new owner. Delegates registered by the
+    /// previous owner can no longer act on the obligation.
+    ///
+    /// When an obligation owner transfer account is provided, the transfer is
+    /// only recorded and the new owner must accept it. Proposing the current
+    /// owner cancels a recorded transfer, closing the obligation owner
+    /// transfer account and returning its lamports to the owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Obligation account.
+    ///   1. `[signer, writable]` Obligation owner.
+    ///   2. `[writable]` Obligation owner transfer account - optional,
+    ///      uninitialized, or set previously for the obligation.
+    SetObligationOwner {
+        /// The new obligation owner
+        new_owner: Pubkey,
+    },
+    // 28
+    /// Accept a recorded obligation owner transfer. The obligation owner
+    /// transfer account is closed and its lamports are returned to the owner
+    /// which proposed the transfer.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Obligation owner transfer account.
+    ///   1. `[writable]` Obligation account.
+    ///   2. `[signer]` New obligation owner.
+    ///   3. `[writable]` Obligation owner which proposed the transfer.
+    AcceptObligationOwner,
+    // 29
+    /// Create the token account collecting the host share of a reserve's
+    /// borrow fees for a referrer, at the address derived by
+    /// `find_referrer_fee_receiver_address`
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer, writable]` Funding account.
+    ///   1. `[writable]` Referrer fee receiver - uninitialized.
+    ///   2. `[]` Reserve account.
+    ///   3. `[]` Reserve liquidity mint.
+    ///   4. `[]` Lending market account.
+    ///   5. `[]` Derived lending market authority.
+    ///   6. `[]` Referrer.
+    ///   7. `[]` System program id.
+    ///   8. `[]` Token program id.
+    InitReferrerFeeReceiver,
+    // 30
+    /// Claim the fees collected by a referrer fee receiver
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Referrer fee receiver.
+    ///   1. `[writable]` Destination liquidity token account.
+    ///   2. `[]` Reserve account.
+    ///   3. `[]` Lending market account.
+    ///   4. `[]` Derived lending market authority.
+    ///   5. `[signer]` Referrer.
+    ///   6. `[]` Token program id.
+    ClaimReferrerFees,
+    // 31
+    /// Borrow liquidity from a reserve at a fixed rate for a term. The rate is
+    /// the current borrow rate of the reserve plus its fixed rate premium, and
+    /// keeps accruing until repaid. Once the term ends the borrow can be
+    /// liquidated in full, even if the obligation is healthy. Requires a
+    /// refreshed obligation and reserve, and an obligation using the latest
+    /// resizable layout. Returns a `CalculateBorrowResult` as return data.
+    ///
+    /// Accounts expected by this instruction are the same as
+    /// BorrowObligationLiquidity.
+    BorrowObligationLiquidityFixedRate {
+        /// Amount of liquidity to borrow - u64::MAX for 100% of borrowing power
+        liquidity_amount: u64,
+        /// Minimum amount of liquidity to receive, if borrowing 100% of
+        /// borrowing power
+        slippage_limit: u64,
+        /// Term of the borrow in seconds, up to the reserve max term
+        term: u32,
+        /// Max fixed borrow rate accepted, in basis points
+        max_borrow_rate_bps: u32,
+    },
+    // 32
+    /// Emit reward tokens to the obligations depositing collateral in and
+    /// borrowing liquidity from a reserve. Rewards are funded by transferring
+    /// reward tokens to the reward supply.
+    ///
+    /// Emissions are accrued by `RefreshReserve` and settled on each
+    /// obligation position by `RefreshObligation`. A reserve emits a single
+    /// reward, and must be migrated to the extended layout first.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Reserve rewards account - uninitialized.
+    ///   1. `[writable]` Reward supply token account - uninitialized.
+    ///   2. `[]` Reward mint.
+    ///   3. `[writable]` Reserve account - refreshed.
+    ///   4. `[]` Lending market account.
+    ///   5. `[]` Derived lending market authority.
+    ///   6. `[signer]` Lending market owner.
+    ///   7. `[]` Token program id.
+    InitReserveRewards {
+        /// Reward tokens emitted per second to deposited reserve collateral
+        supply_emission_per_second: u64,
+        /// Reward tokens emitted per second to borrowed reserve liquidity
+        borrow_emission_per_second: u64,
+    },
+    // 33
+    /// Change the reward tokens emitted by reserve rewards. Rewards emitted
+    /// up to the reserve refresh use the previous emissions.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Reserve rewards account.
+    ///   1. `[writable]` Reserve account - refreshed.
+    ///   2. `[]` Lending market account.
+    ///   3. `[signer]` Lending market owner.
+    SetReserveRewardEmissions {
+        /// Reward tokens emitted per second to deposited reserve collateral
+        supply_emission_per_second: u64,
+        /// Reward tokens emitted per second to borrowed reserve liquidity
+        borrow_emission_per_second: u64,
+    },
+    // 34
+    /// Claim the whole reward tokens earned by the deposit and borrow of an
+    /// obligation in a reserve, up to the reward supply balance.
+    ///
+    /// Rewards are settled by `RefreshObligation`. Deposits and borrows
+    /// emptied while holding rewards are kept by the obligation until their
+    /// rewards are claimed, or forfeited with `ForfeitRewards`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Reserve rewards account.
+    ///   1. `[writable]` Reward supply token account.
+    ///   2. `[writable]` Destination reward token account.
+    ///   3. `[writable]` Obligation account - refreshed.
+    ///   4. `[]` Lending market account.
+    ///   5. `[]` Derived lending market authority.
+    ///   6. `[signer]` Obligation owner.
+    ///   7. `[]` Token program id.
+    ClaimRewards,
+    // 35
+    /// Value an obligation as `RefreshObligation` would, without writing any
+    /// account, and return an `ObligationHealthResult` as return data.
+    /// Requires refreshed reserves, as all obligation collateral deposit
+    /// reserves in order, followed by all liquidity borrow reserves in order.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Obligation account.
+    ///   1. .. `[]` Collateral deposit reserve accounts - refreshed, all, in
+    ///      order.
+    ///   2. .. `[]` Liquidity borrow reserve accounts - refreshed, all, in
+    ///      order.
+    GetObligationHealth,
+    // 36
+    /// Forfeit the rewards left to claim by the deposit and borrow of an
+    /// obligation in a reserve once they are emptied, removing them from the
+    /// obligation. Frees the obligation slots held by rewards which cannot be
+    /// claimed, such as those of a drained reward supply.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Reserve account.
+    ///   1. `[writable]` Obligation account.
+    ///   2. `[]` Lending market account.
+    ///   3. `[signer]` Obligation owner.
+    ForfeitRewards,
 }
@@ -4,8 +4,15 @@ use {
     super::LendingInstruction,
     crate::{
         error::LendingError,
-        state::reserve::{ReserveConfig, ReserveFees},
+        state::{
+            reserve::{
+                AdaptiveRateConfig, FixedRateConfig, RateCurve, RateCurvePoint, ReserveConfig,
+                ReserveFees, ReserveStatus,
+            },
+            AccountType, DelegatePermissions,
+        },
     },
+    num_traits::FromPrimitive,
     solana_program::{
         msg,
         program_error::ProgramError,
@@ -14,6 +21,14 @@ use {
     std::mem::size_of,
 };
 
+/// Leading byte of a reserve config encoded in basis points
+///
+/// The legacy percentage encoding starts with the optimal utilization rate,
+/// which is at most 100, so the two encodings cannot be confused. It only
+/// holds the rates and fees of the original config, every value added since
+/// is left at its default.
+pub const RESERVE_CONFIG_BPS_ENCODING: u8 = u8::MAX;
+
 impl LendingInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
         let (&tag, rest) = input
@@ -49,7 +64,10 @@ impl LendingInstruction {
                 let (collateral_amount, _rest) = Self::unpack_u64(rest)?;
                 Self::RedeemReserveCollateral { collateral_amount }
             }
-            6 => Self::InitObligation,
+            6 => {
+                let seed_id = Self::unpack_u64(rest).ok().map(|(seed_id, _rest)| seed_id);
+                Self::InitObligation { seed_id }
+            }
             7 => Self::RefreshObligation,
             8 => {
                 let (collateral_amount, _) = Self::unpack_u64(rest)?;
@@ -83,6 +101,111 @@ impl LendingInstruction {
                 let new_config = Self::unpack_reserve_config(rest)?;
                 Self::ModifyReserveConfig { new_config }
             }
+            15 => Self::ForgiveDebt,
+            16 => {
+                let (deposits_limit, rest) = Self::unpack_u8(rest)?;
+                let (borrows_limit, _rest) = Self::unpack_u8(rest)?;
+                Self::ResizeObligation {
+                    deposits_limit,
+                    borrows_limit,
+                }
+            }
+            17 => {
+                let (account_type, _rest) = Self::unpack_u8(rest)?;
+                let account_type = AccountType::from_u8(account_type).ok_or_else(|| {
+                    msg!("Account type cannot be unpacked");
+                    LendingError::InstructionUnpackError
+                })?;
+                Self::MigrateAccount { account_type }
+            }
+            18 => {
+                let (new_owner, _rest) = Self::unpack_pubkey(rest)?;
+                Self::ProposeLendingMarketOwner { new_owner }
+            }
+            19 => Self::AcceptLendingMarketOwner,
+            20 => Self::CancelLendingMarketOwner,
+            21 => {
+                let (risk_manager, _rest) = Self::unpack_pubkey(rest)?;
+                Self::SetRiskManager { risk_manager }
+            }
+            22 => {
+                let (guardian, _rest) = Self::unpack_pubkey(rest)?;
+                Self::SetGuardian { guardian }
+            }
+            23 => {
+                let (status, _rest) = Self::unpack_u8(rest)?;
+                let status = ReserveStatus::from_u8(status).ok_or_else(|| {
+                    msg!("Reserve status cannot be unpacked");
+                    LendingError::InstructionUnpackError
+                })?;
+                Self::SetReserveStatus { status }
+            }
+            24 => Self::ApplyPendingReserveConfig,
+            25 => {
+                let (loan_to_value_ratio_bps, rest) = Self::unpack_u16(rest)?;
+                let (liquidation_threshold_bps, rest) = Self::unpack_u16(rest)?;
+                let (start_timestamp, rest) = Self::unpack_i64(rest)?;
+                let (end_timestamp, _rest) = Self::unpack_i64(rest)?;
+                Self::SetReserveCollateralRamp {
+                    loan_to_value_ratio_bps,
+                    liquidation_threshold_bps,
+                    start_timestamp,
+                    end_timestamp,
+                }
+            }
+            26 => {
+                let (delegate, rest) = Self::unpack_pubkey(rest)?;
+                let (permissions, rest) = Self::unpack_u8(rest)?;
+                let permissions = DelegatePermissions(permissions);
+                if !permissions.is_valid() {
+                    msg!("Delegate permissions cannot be unpacked");
+                    return Err(LendingError::InstructionUnpackError.into());
+                }
+                let (max_borrow_value, _rest) = Self::unpack_u64(rest)?;
+                Self::SetObligationDelegate {
+                    delegate,
+                    permissions,
+                    max_borrow_value,
+                }
+            }
+            27 => {
+                let (new_owner, _rest) = Self::unpack_pubkey(rest)?;
+                Self::SetObligationOwner { new_owner }
+            }
+            28 => Self::AcceptObligationOwner,
+            29 => Self::InitReferrerFeeReceiver,
+            30 => Self::ClaimReferrerFees,
+            31 => {
+                let (liquidity_amount, rest) = Self::unpack_u64(rest)?;
+                let (slippage_limit, rest) = Self::unpack_u64(rest)?;
+                let (term, rest) = Self::unpack_u32(rest)?;
+                let (max_borrow_rate_bps, _rest) = Self::unpack_u32(rest)?;
+                Self::BorrowObligationLiquidityFixedRate {
+                    liquidity_amount,
+                    slippage_limit,
+                    term,
+                    max_borrow_rate_bps,
+                }
+            }
+            32 => {
+                let (supply_emission_per_second, rest) = Self::unpack_u64(rest)?;
+                let (borrow_emission_per_second, _rest) = Self::unpack_u64(rest)?;
+                Self::InitReserveRewards {
+                    supply_emission_per_second,
+                    borrow_emission_per_second,
+                }
+            }
+            33 => {
+                let (supply_emission_per_second, rest) = Self::unpack_u64(rest)?;
+                let (borrow_emission_per_second, _rest) = Self::unpack_u64(rest)?;
+                Self::SetReserveRewardEmissions {
+                    supply_emission_per_second,
+                    borrow_emission_per_second,
+                }
+            }
+            34 => Self::ClaimRewards,
+            35 => Self::GetObligationHealth,
+            36 => Self::ForfeitRewards,
 
             _ => {
                 msg!("Instruction cannot be unpacked");
@@ -126,8 +249,11 @@ impl LendingInstruction {
                 buf.extend_from_slice(&collateral_amount.to_le_bytes());
             }
 
-            Self::InitObligation => {
+            Self::InitObligation { seed_id } => {
                 buf.push(6);
+                if let Some(seed_id) = seed_id {
+                    buf.extend_from_slice(&seed_id.to_le_bytes());
+                }
             }
             Self::RefreshObligation => {
                 buf.push(7);
@@ -160,6 +286,118 @@ impl LendingInstruction {
                 buf.push(14);
                 Self::extend_buffer_from_reserve_config(&mut buf, &new_config);
             }
+            Self::ForgiveDebt => {
+                buf.push(15);
+            }
+            Self::ResizeObligation {
+                deposits_limit,
+                borrows_limit,
+            } => {
+                buf.push(16);
+                buf.extend_from_slice(&deposits_limit.to_le_bytes());
+                buf.extend_from_slice(&borrows_limit.to_le_bytes());
+            }
+            Self::MigrateAccount { account_type } => {
+                buf.push(17);
+                buf.push(account_type as u8);
+            }
+            Self::ProposeLendingMarketOwner { new_owner } => {
+                buf.push(18);
+                buf.extend_from_slice(new_owner.as_ref());
+            }
+            Self::AcceptLendingMarketOwner => {
+                buf.push(19);
+            }
+            Self::CancelLendingMarketOwner => {
+                buf.push(20);
+            }
+            Self::SetRiskManager { risk_manager } => {
+                buf.push(21);
+                buf.extend_from_slice(risk_manager.as_ref());
+            }
+            Self::SetGuardian { guardian } => {
+                buf.push(22);
+                buf.extend_from_slice(guardian.as_ref());
+            }
+            Self::SetReserveStatus { status } => {
+                buf.push(23);
+                buf.push(status as u8);
+            }
+            Self::ApplyPendingReserveConfig => {
+                buf.push(24);
+            }
+            Self::SetReserveCollateralRamp {
+                loan_to_value_ratio_bps,
+                liquidation_threshold_bps,
+                start_timestamp,
+                end_timestamp,
+            } => {
+                buf.push(25);
+                buf.extend_from_slice(&loan_to_value_ratio_bps.to_le_bytes());
+                buf.extend_from_slice(&liquidation_threshold_bps.to_le_bytes());
+                buf.extend_from_slice(&start_timestamp.to_le_bytes());
+                buf.extend_from_slice(&end_timestamp.to_le_bytes());
+            }
+            Self::SetObligationDelegate {
+                delegate,
+                permissions,
+                max_borrow_value,
+            } => {
+                buf.push(26);
+                buf.extend_from_slice(delegate.as_ref());
+                buf.push(permissions.0);
+                buf.extend_from_slice(&max_borrow_value.to_le_bytes());
+            }
+            Self::SetObligationOwner { new_owner } => {
+                buf.push(27);
+                buf.extend_from_slice(new_owner.as_ref());
+            }
+            Self::AcceptObligationOwner => {
+                buf.push(28);
+            }
+            Self::InitReferrerFeeReceiver => {
+                buf.push(29);
+            }
+            Self::ClaimReferrerFees => {
+                buf.push(30);
+            }
+            Self::BorrowObligationLiquidityFixedRate {
+                liquidity_amount,
+                slippage_limit,
+                term,
+                max_borrow_rate_bps,
+            } => {
+                buf.push(31);
+                buf.extend_from_slice(&liquidity_amount.to_le_bytes());
+                buf.extend_from_slice(&slippage_limit.to_le_bytes());
+                buf.extend_from_slice(&term.to_le_bytes());
+                buf.extend_from_slice(&max_borrow_rate_bps.to_le_bytes());
+            }
+            Self::InitReserveRewards {
+                supply_emission_per_second,
+                borrow_emission_per_second,
+            } => {
+                buf.push(32);
+                buf.extend_from_slice(&supply_emission_per_second.to_le_bytes());
+                buf.extend_from_slice(&borrow_emission_per_second.to_le_bytes());
+            }
+            Self::SetReserveRewardEmissions {
+                supply_emission_per_second,
+                borrow_emission_per_second,
+            } => {
+                buf.push(33);
+                buf.extend_from_slice(&supply_emission_per_second.to_le_bytes());
+                buf.extend_from_slice(&borrow_emission_per_second.to_le_bytes());
+            }
+            Self::ClaimRewards => {
+                buf.push(34);
+            }
+            Self::GetObligationHealth => {
+                buf.push(35);
+            }
+            Self::ForfeitRewards => {
+                buf.push(36);
+            }
             _ => {
                 unreachable!()
             }
@@ -217,7 +455,101 @@ impl LendingInstruction {
             .ok_or(LendingError::InstructionUnpackError)?;
         Ok((value, rest))
     }
+    fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
+        if input.len() < 8 {
+            msg!("i64 cannot be unpacked");
+            return Err(LendingError::InstructionUnpackError.into());
+        }
+        let (bytes, rest) = input.split_at(8);
+        let value = bytes
+            .get(..8)
+            .and_then(|slice| slice.try_into().ok())
+            .map(i64::from_le_bytes)
+            .ok_or(LendingError::InstructionUnpackError)?;
+        Ok((value, rest))
+    }
+    fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+        if input.len() < 2 {
+            msg!("u16 cannot be unpacked");
+            return Err(LendingError::InstructionUnpackError.into());
+        }
+        let (bytes, rest) = input.split_at(2);
+        let value = bytes
+            .get(..2)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u16::from_le_bytes)
+            .ok_or(LendingError::InstructionUnpackError)?;
+        Ok((value, rest))
+    }
+    fn unpack_u32(input: &[u8]) -> Result<(u32, &[u8]), ProgramError> {
+        if input.len() < 4 {
+            msg!("u32 cannot be unpacked");
+            return Err(LendingError::InstructionUnpackError.into());
+        }
+        let (bytes, rest) = input.split_at(4);
+        let value = bytes
+            .get(..4)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or(LendingError::InstructionUnpackError)?;
+        Ok((value, rest))
+    }
+    /// Unpack a reserve config, either in basis points when prefixed by
+    /// `RESERVE_CONFIG_BPS_ENCODING` or in the legacy percentage encoding
     fn unpack_reserve_config(input: &[u8]) -> Result<ReserveConfig, ProgramError> {
+        match input.split_first() {
+            Some((&RESERVE_CONFIG_BPS_ENCODING, rest)) => Self::unpack_reserve_config_bps(rest),
+            _ => Self::unpack_reserve_config_percent(input),
+        }
+    }
+    fn unpack_reserve_config_bps(input: &[u8]) -> Result<ReserveConfig, ProgramError> {
+        let (optimal_utilization_rate_bps, rest) = Self::unpack_u16(input)?;
+        let (loan_to_value_ratio_bps, rest) = Self::unpack_u16(rest)?;
+        let (liquidation_bonus_bps, rest) = Self::unpack_u16(rest)?;
+        let (liquidation_threshold_bps, rest) = Self::unpack_u16(rest)?;
+        let (min_borrow_rate_bps, rest) = Self::unpack_u32(rest)?;
+        let (optimal_borrow_rate_bps, rest) = Self::unpack_u32(rest)?;
+        let (max_borrow_rate_bps, rest) = Self::unpack_u32(rest)?;
+        let (borrow_fee_wad, rest) = Self::unpack_u64(rest)?;
+        let (flash_loan_fee_wad, rest) = Self::unpack_u64(rest)?;
+        let (host_fee_bps, rest) = Self::unpack_u16(rest)?;
+        let (protocol_liquidation_fee_bps, rest) = Self::unpack_u16(rest)?;
+        let (rate_curve, rest) = Self::unpack_rate_curve(rest)?;
+        let (target_utilization_rate_bps, rest) = Self::unpack_u16(rest)?;
+        let (adjustment_speed_bps, rest) = Self::unpack_u16(rest)?;
+        let (deposit_limit, rest) = Self::unpack_u64(rest)?;
+        let (borrow_limit, rest) = Self::unpack_u64(rest)?;
+        let (fixed_rate_premium_bps, rest) = Self::unpack_u32(rest)?;
+        let (fixed_rate_max_term, _rest) = Self::unpack_u32(rest)?;
+
+        Ok(ReserveConfig {
+            optimal_utilization_rate_bps,
+            loan_to_value_ratio_bps,
+            liquidation_bonus_bps,
+            liquidation_threshold_bps,
+            min_borrow_rate_bps,
+            optimal_borrow_rate_bps,
+            max_borrow_rate_bps,
+            fees: ReserveFees {
+                borrow_fee_wad,
+                flash_loan_fee_wad,
+                host_fee_bps,
+                protocol_liquidation_fee_bps,
+            },
+            rate_curve,
+            adaptive_rate: AdaptiveRateConfig {
+                target_utilization_rate_bps,
+                adjustment_speed_bps,
+            },
+            deposit_limit,
+            borrow_limit,
+            fixed_rate: FixedRateConfig {
+                premium_bps: fixed_rate_premium_bps,
+                max_term: fixed_rate_max_term,
+            },
+        })
+    }
+    fn unpack_reserve_config_percent(input: &[u8]) -> Result<ReserveConfig, ProgramError> {
         let (optimal_utilization_rate, rest) = Self::unpack_u8(input)?;
         let (loan_to_value_ratio, rest) = Self::unpack_u8(rest)?;
         let (liquidation_bonus, rest) = Self::unpack_u8(rest)?;
@@ -230,31 +562,71 @@ impl LendingInstruction {
         let (host_fee_percentage, _rest) = Self::unpack_u8(rest)?;
 
         Ok(ReserveConfig {
-            optimal_utilization_rate,
-            loan_to_value_ratio,
-            liquidation_bonus,
-            liquidation_threshold,
-            min_borrow_rate,
-            optimal_borrow_rate,
-            max_borrow_rate,
+            optimal_utilization_rate_bps: u16::from(optimal_utilization_rate) * 100,
+            loan_to_value_ratio_bps: u16::from(loan_to_value_ratio) * 100,
+            liquidation_bonus_bps: u16::from(liquidation_bonus) * 100,
+            liquidation_threshold_bps: u16::from(liquidation_threshold) * 100,
+            min_borrow_rate_bps: u32::from(min_borrow_rate) * 100,
+            optimal_borrow_rate_bps: u32::from(optimal_borrow_rate) * 100,
+            max_borrow_rate_bps: u32::from(max_borrow_rate) * 100,
             fees: ReserveFees {
                 borrow_fee_wad,
                 flash_loan_fee_wad,
-                host_fee_percentage,
+                host_fee_bps: u16::from(host_fee_percentage) * 100,
+                // only set through the basis point encoding
+                protocol_liquidation_fee_bps: 0,
             },
+            rate_curve: RateCurve::EMPTY,
+            adaptive_rate: AdaptiveRateConfig::DISABLED,
+            deposit_limit: 0,
+            borrow_limit: 0,
+            fixed_rate: FixedRateConfig::DISABLED,
         })
     }
+    fn unpack_rate_curve(input: &[u8]) -> Result<(RateCurve, &[u8]), ProgramError> {
+        let (len, mut rest) = Self::unpack_u8(input)?;
+        let mut points = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let (utilization_rate_bps, next) = Self::unpack_u16(rest)?;
+            let (borrow_rate_bps, next) = Self::unpack_u32(next)?;
+            points.push(RateCurvePoint {
+                utilization_rate_bps,
+                borrow_rate_bps,
+            });
+            rest = next;
+        }
+        Ok((RateCurve::new(&points)?, rest))
+    }
     // Helper function to pack a ReserveConfig into a Vec<u8> buffer
     fn extend_buffer_from_reserve_config(buf: &mut Vec<u8>, config: &ReserveConfig) {
-        buf.extend_from_slice(&config.optimal_utilization_rate.to_le_bytes());
-        buf.extend_from_slice(&config.loan_to_value_ratio.to_le_bytes());
-        buf.extend_from_slice(&config.liquidation_bonus.to_le_bytes());
-        buf.extend_from_slice(&config.liquidation_threshold.to_le_bytes());
-        buf.extend_from_slice(&config.min_borrow_rate.to_le_bytes());
-        buf.extend_from_slice(&config.optimal_borrow_rate.to_le_bytes());
-        buf.extend_from_slice(&config.max_borrow_rate.to_le_bytes());
+        buf.push(RESERVE_CONFIG_BPS_ENCODING);
+        buf.extend_from_slice(&config.optimal_utilization_rate_bps.to_le_bytes());
+        buf.extend_from_slice(&config.loan_to_value_ratio_bps.to_le_bytes());
+        buf.extend_from_slice(&config.liquidation_bonus_bps.to_le_bytes());
+        buf.extend_from_slice(&config.liquidation_threshold_bps.to_le_bytes());
+        buf.extend_from_slice(&config.min_borrow_rate_bps.to_le_bytes());
+        buf.extend_from_slice(&config.optimal_borrow_rate_bps.to_le_bytes());
+        buf.extend_from_slice(&config.max_borrow_rate_bps.to_le_bytes());
         buf.extend_from_slice(&config.fees.borrow_fee_wad.to_le_bytes());
         buf.extend_from_slice(&config.fees.flash_loan_fee_wad.to_le_bytes());
-        buf.extend_from_slice(&config.fees.host_fee_percentage.to_le_bytes());
+        buf.extend_from_slice(&config.fees.host_fee_bps.to_le_bytes());
+        buf.extend_from_slice(&config.fees.protocol_liquidation_fee_bps.to_le_bytes());
+        let rate_curve_points = config.rate_curve.points();
+        buf.push(rate_curve_points.len() as u8);
+        for point in rate_curve_points {
+            buf.extend_from_slice(&point.utilization_rate_bps.to_le_bytes());
+            buf.extend_from_slice(&point.borrow_rate_bps.to_le_bytes());
+        }
+        buf.extend_from_slice(
+            &config
+                .adaptive_rate
+                .target_utilization_rate_bps
+                .to_le_bytes(),
+        );
+        buf.extend_from_slice(&config.adaptive_rate.adjustment_speed_bps.to_le_bytes());
+        buf.extend_from_slice(&config.deposit_limit.to_le_bytes());
+        buf.extend_from_slice(&config.borrow_limit.to_le_bytes());
+        buf.extend_from_slice(&config.fixed_rate.premium_bps.to_le_bytes());
+        buf.extend_from_slice(&config.fixed_rate.max_term.to_le_bytes());
     }
 }
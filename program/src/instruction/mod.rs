@@ -2,4 +2,4 @@ pub mod builder;
 mod lending_instruction;
 mod methods;
 
-pub use lending_instruction::*;
+pub use {lending_instruction::*, methods::RESERVE_CONFIG_BPS_ENCODING};
@@ -1,10 +1,12 @@
 pub mod constants;
 pub mod entrypoint;
 pub mod error;
+pub mod event;
 pub mod instruction;
 pub mod math;
 pub mod processor;
 pub mod pyth;
+pub mod return_data;
 pub mod state;
 pub mod utils;
 solana_program::declare_id!("6TvznH3B2e3p2mbhufNBpgSrLx6UkgvxtVQvopEZ2kuH");
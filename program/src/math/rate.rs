@@ -59,6 +59,11 @@ impl Rate {
         Self(U128::from(percent as u64 * PERCENT_SCALER))
     }
 
+    /// Create scaled decimal from basis point value
+    pub fn from_bps(bps: u32) -> Self {
+        Self(U128::from(bps) * U128::from(BPS_SCALER))
+    }
+
     /// Return raw scaled value
     #[allow(clippy::wrong_self_convention)]
     pub fn to_scaled_val(&self) -> u128 {
@@ -184,4 +189,14 @@ mod test {
     fn checked_pow() {
         assert_eq!(Rate::one(), Rate::one().try_pow(u64::MAX).unwrap());
     }
+
+    #[test]
+    fn from_bps() {
+        assert_eq!(Rate::from_bps(10_000), Rate::one());
+        assert_eq!(
+            Rate::from_bps(7_250),
+            Rate::from_scaled_val(725_000_000_000_000_000)
+        );
+        assert_eq!(Rate::from_bps(100), Rate::from_percent(1));
+    }
 }
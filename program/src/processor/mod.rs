@@ -1,35 +1,77 @@
+mod process_accept_lending_market_owner;
+mod process_accept_obligation_owner;
+mod process_apply_pending_reserve_config;
 mod process_borrow_obligation_liquidity;
+mod process_cancel_lending_market_owner;
+mod process_claim_referrer_fees;
+mod process_claim_rewards;
 mod process_deposit_obligation_collateral;
 mod process_deposit_reserve_liquidity;
+mod process_forfeit_rewards;
+mod process_forgive_debt;
+mod process_get_obligation_health;
 mod process_init_lending_market;
 mod process_init_obligation;
+mod process_init_referrer_fee_receiver;
 mod process_init_reserve;
+mod process_init_reserve_rewards;
 mod process_liquidate_obligation;
+mod process_migrate_account;
 mod process_modify_reserve_config;
+mod process_propose_lending_market_owner;
 mod process_redeem_reserve_collateral;
 mod process_refresh_obligation;
 mod process_refresh_reserve;
 mod process_repay_obligation_liquidity;
+mod process_resize_obligation;
+mod process_set_guardian;
 mod process_set_lending_market_owner;
+mod process_set_obligation_delegate;
+mod process_set_obligation_owner;
+mod process_set_reserve_collateral_ramp;
+mod process_set_reserve_reward_emissions;
+mod process_set_reserve_status;
+mod process_set_risk_manager;
 mod process_withdraw_obligation_collateral;
 mod token_helper;
 mod utils;
 
 use {
     crate::{error::LendingError, instruction::LendingInstruction},
+    process_accept_lending_market_owner::*,
+    process_accept_obligation_owner::*,
+    process_apply_pending_reserve_config::*,
     process_borrow_obligation_liquidity::*,
+    process_cancel_lending_market_owner::*,
+    process_claim_referrer_fees::*,
+    process_claim_rewards::*,
     process_deposit_obligation_collateral::*,
     process_deposit_reserve_liquidity::*,
+    process_forfeit_rewards::*,
+    process_forgive_debt::*,
+    process_get_obligation_health::*,
     process_init_lending_market::*,
     process_init_obligation::*,
+    process_init_referrer_fee_receiver::*,
     process_init_reserve::*,
+    process_init_reserve_rewards::*,
     process_liquidate_obligation::*,
+    process_migrate_account::*,
     process_modify_reserve_config::*,
+    process_propose_lending_market_owner::*,
     process_redeem_reserve_collateral::*,
     process_refresh_obligation::*,
     process_refresh_reserve::*,
     process_repay_obligation_liquidity::*,
+    process_resize_obligation::*,
+    process_set_guardian::*,
     process_set_lending_market_owner::*,
+    process_set_obligation_delegate::*,
+    process_set_obligation_owner::*,
+    process_set_reserve_collateral_ramp::*,
+    process_set_reserve_reward_emissions::*,
+    process_set_reserve_status::*,
+    process_set_risk_manager::*,
     process_withdraw_obligation_collateral::*,
     solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg, pubkey::Pubkey},
     token_helper::*,
@@ -75,10 +117,10 @@ pub fn process_instruction(
             process_refresh_reserve(program_id, accounts)
         }
 
-        LendingInstruction::InitObligation => {
+        LendingInstruction::InitObligation { seed_id } => {
             #[cfg(debug_assertions)]
             msg!("Instruction: Init Obligation");
-            process_init_obligation(program_id, accounts)
+            process_init_obligation(program_id, seed_id, accounts)
         }
         LendingInstruction::DepositObligationCollateral { collateral_amount } => {
             #[cfg(debug_assertions)]
@@ -105,6 +147,7 @@ pub fn process_instruction(
                 program_id,
                 liquidity_amount,
                 slippage_limit,
+                None,
                 accounts,
             )
         }
@@ -128,6 +171,168 @@ pub fn process_instruction(
             msg!("Instruction: Modify Reserve Config");
             process_modify_reserve_config(program_id, new_config, accounts)
         }
+        LendingInstruction::ForgiveDebt => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Forgive Debt");
+            process_forgive_debt(program_id, accounts)
+        }
+        LendingInstruction::ResizeObligation {
+            deposits_limit,
+            borrows_limit,
+        } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Resize Obligation");
+            process_resize_obligation(program_id, deposits_limit, borrows_limit, accounts)
+        }
+        LendingInstruction::MigrateAccount { account_type } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Migrate Account");
+            process_migrate_account(program_id, account_type, accounts)
+        }
+        LendingInstruction::ProposeLendingMarketOwner { new_owner } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Propose Lending Market Owner");
+            process_propose_lending_market_owner(program_id, new_owner, accounts)
+        }
+        LendingInstruction::AcceptLendingMarketOwner => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Accept Lending Market Owner");
+            process_accept_lending_market_owner(program_id, accounts)
+        }
+        LendingInstruction::CancelLendingMarketOwner => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Cancel Lending Market Owner");
+            process_cancel_lending_market_owner(program_id, accounts)
+        }
+        LendingInstruction::SetRiskManager { risk_manager } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Set Risk Manager");
+            process_set_risk_manager(program_id, risk_manager, accounts)
+        }
+        LendingInstruction::SetGuardian { guardian } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Set Guardian");
+            process_set_guardian(program_id, guardian, accounts)
+        }
+        LendingInstruction::SetReserveStatus { status } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Set Reserve Status");
+            process_set_reserve_status(program_id, status, accounts)
+        }
+        LendingInstruction::ApplyPendingReserveConfig => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Apply Pending Reserve Config");
+            process_apply_pending_reserve_config(program_id, accounts)
+        }
+        LendingInstruction::SetReserveCollateralRamp {
+            loan_to_value_ratio_bps,
+            liquidation_threshold_bps,
+            start_timestamp,
+            end_timestamp,
+        } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Set Reserve Collateral Ramp");
+            process_set_reserve_collateral_ramp(
+                program_id,
+                loan_to_value_ratio_bps,
+                liquidation_threshold_bps,
+                start_timestamp,
+                end_timestamp,
+                accounts,
+            )
+        }
+        LendingInstruction::SetObligationDelegate {
+            delegate,
+            permissions,
+            max_borrow_value,
+        } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Set Obligation Delegate");
+            process_set_obligation_delegate(
+                program_id,
+                delegate,
+                permissions,
+                max_borrow_value,
+                accounts,
+            )
+        }
+        LendingInstruction::SetObligationOwner { new_owner } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Set Obligation Owner");
+            process_set_obligation_owner(program_id, new_owner, accounts)
+        }
+        LendingInstruction::AcceptObligationOwner => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Accept Obligation Owner");
+            process_accept_obligation_owner(program_id, accounts)
+        }
+        LendingInstruction::InitReferrerFeeReceiver => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Init Referrer Fee Receiver");
+            process_init_referrer_fee_receiver(program_id, accounts)
+        }
+        LendingInstruction::ClaimReferrerFees => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Claim Referrer Fees");
+            process_claim_referrer_fees(program_id, accounts)
+        }
+        LendingInstruction::BorrowObligationLiquidityFixedRate {
+            liquidity_amount,
+            slippage_limit,
+            term,
+            max_borrow_rate_bps,
+        } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Borrow Obligation Liquidity Fixed Rate");
+            process_borrow_obligation_liquidity(
+                program_id,
+                liquidity_amount,
+                slippage_limit,
+                Some((term, max_borrow_rate_bps)),
+                accounts,
+            )
+        }
+        LendingInstruction::InitReserveRewards {
+            supply_emission_per_second,
+            borrow_emission_per_second,
+        } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Init Reserve Rewards");
+            process_init_reserve_rewards(
+                program_id,
+                supply_emission_per_second,
+                borrow_emission_per_second,
+                accounts,
+            )
+        }
+        LendingInstruction::SetReserveRewardEmissions {
+            supply_emission_per_second,
+            borrow_emission_per_second,
+        } => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Set Reserve Reward Emissions");
+            process_set_reserve_reward_emissions(
+                program_id,
+                supply_emission_per_second,
+                borrow_emission_per_second,
+                accounts,
+            )
+        }
+        LendingInstruction::ClaimRewards => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Claim Rewards");
+            process_claim_rewards(program_id, accounts)
+        }
+        LendingInstruction::GetObligationHealth => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Get Obligation Health");
+            process_get_obligation_health(program_id, accounts)
+        }
+        LendingInstruction::ForfeitRewards => {
+            #[cfg(debug_assertions)]
+            msg!("Instruction: Forfeit Rewards");
+            process_forfeit_rewards(program_id, accounts)
+        }
         _ => {
             msg!("Unsupported instruction");
             Err(LendingError::NotRentExempt.into())
@@ -0,0 +1,71 @@
+use {
+    super::close_account,
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{ObligationHeader, ObligationOwnerTransfer},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+    },
+};
+
+pub(super) fn process_accept_obligation_owner(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let obligation_owner_transfer_info = next_account_info(account_info_iter)?;
+    let obligation_info = next_account_info(account_info_iter)?;
+    let new_owner_info = next_account_info(account_info_iter)?;
+    let obligation_owner_info = next_account_info(account_info_iter)?;
+
+    if obligation_owner_transfer_info.owner != program_id {
+        msg!("Obligation owner transfer provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let obligation_owner_transfer =
+        ObligationOwnerTransfer::unpack(&obligation_owner_transfer_info.data.borrow())?;
+
+    let mut obligation_data = obligation_info.data.borrow_mut();
+    let obligation = ObligationHeader::load_mut(&mut obligation_data)?;
+    if obligation_info.owner != program_id {
+        msg!("Obligation provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &obligation_owner_transfer.obligation != obligation_info.key {
+        msg!("Obligation owner transfer was set for a different obligation");
+        return Err(LendingError::InvalidObligationOwnerTransfer.into());
+    }
+    if obligation_owner_transfer.owner != obligation.owner() {
+        msg!("Obligation owner changed since the transfer was proposed");
+        return Err(LendingError::InvalidObligationOwnerTransfer.into());
+    }
+    if &obligation_owner_transfer.new_owner != new_owner_info.key {
+        msg!("New obligation owner does not match the new owner provided");
+        return Err(LendingError::InvalidObligationOwnerTransfer.into());
+    }
+    if !new_owner_info.is_signer {
+        msg!("New obligation owner provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+    if &obligation_owner_transfer.owner != obligation_owner_info.key {
+        msg!("Obligation owner which proposed the transfer does not match the owner provided");
+        return Err(LendingError::InvalidObligationOwner.into());
+    }
+
+    obligation.set_owner(&obligation_owner_transfer.new_owner);
+    drop(obligation_data);
+    // the proposing owner funded the transfer account
+    close_account(obligation_owner_transfer_info, obligation_owner_info)?;
+
+    LendingEvent::ObligationChanged {
+        obligation: *obligation_info.key,
+    }
+    .emit();
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use {
+    crate::{error::LendingError, event::LendingEvent, state::Reserve},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+pub(super) fn process_apply_pending_reserve_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let reserve_info = next_account_info(account_info_iter)?;
+
+    if reserve_info.owner != program_id {
+        msg!("Reserve provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+
+    let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+    let clock = Clock::get()?;
+    reserve.apply_pending_config(clock.unix_timestamp)?;
+    Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
+
+    LendingEvent::ReserveConfigChanged {
+        reserve: *reserve_info.key,
+    }
+    .emit();
+    Ok(())
+}
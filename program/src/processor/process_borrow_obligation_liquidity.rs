@@ -1,9 +1,16 @@
 use {
-    super::{spl_token_transfer, TokenTransferParams},
+    super::{spl_token_transfer, validate_obligation_authority, TokenTransferParams},
     crate::{
         error::LendingError,
-        math::Decimal,
-        state::{CalculateBorrowResult, LendingMarket, Obligation, Reserve},
+        event::LendingEvent,
+        math::{Decimal, Rate, TryAdd, TryDiv, TryMul},
+        return_data::LendingReturnData,
+        state::{
+            find_referrer_fee_receiver_address, CalculateBorrowResult, DelegatePermissions,
+            FixedRateBorrow, LendingMarket, Obligation, ReserveData, ReserveExtensionData,
+            ReserveStatus,
+        },
+        utils::get_pow,
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -21,6 +28,7 @@ pub(super) fn process_borrow_obligation_liquidity(
     program_id: &Pubkey,
     liquidity_amount: u64,
     slippage_limit: u64,
+    fixed_rate_terms: Option<(u32, u32)>,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     if liquidity_amount == 0 {
@@ -49,34 +57,48 @@ pub(super) fn process_borrow_obligation_liquidity(
         return Err(LendingError::InvalidTokenProgram.into());
     }
 
-    let mut borrow_reserve = Reserve::unpack(&borrow_reserve_info.data.borrow())?;
     if borrow_reserve_info.owner != program_id {
         msg!("Borrow reserve provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &borrow_reserve.lending_market != lending_market_info.key {
+    let borrow_reserve_data = borrow_reserve_info.data.borrow();
+    let borrow_reserve = ReserveData::load(&borrow_reserve_data)?;
+    if &borrow_reserve.lending_market() != lending_market_info.key {
         msg!("Borrow reserve lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &borrow_reserve.liquidity.supply_pubkey != source_liquidity_info.key {
+    if &borrow_reserve.liquidity_supply_pubkey() != source_liquidity_info.key {
         msg!("Borrow reserve liquidity supply must be used as the source liquidity provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &borrow_reserve.liquidity.supply_pubkey == destination_liquidity_info.key {
+    if &borrow_reserve.liquidity_supply_pubkey() == destination_liquidity_info.key {
         msg!(
             "Borrow reserve liquidity supply cannot be used as the destination liquidity provided"
         );
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &borrow_reserve.liquidity.fee_receiver != borrow_reserve_liquidity_fee_receiver_info.key {
+    if &borrow_reserve.liquidity_fee_receiver() != borrow_reserve_liquidity_fee_receiver_info.key {
         msg!("Borrow reserve liquidity fee receiver does not match the borrow reserve liquidity fee receiver provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
     let clock = Clock::get()?;
-    if borrow_reserve.last_update.is_stale(clock.slot)? {
+    if borrow_reserve.last_update()?.is_stale(clock.slot)? {
         msg!("Borrow reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    if borrow_reserve.status()? != ReserveStatus::Active {
+        msg!("Borrow reserve does not allow borrows while reduce-only or paused");
+        return Err(LendingError::ReserveStatusRestricted.into());
+    }
+    // only the current config is decoded for the borrow fees, limit and fixed
+    // rate, the borrow is written in place
+    let borrow_reserve_extension = ReserveExtensionData::load(&borrow_reserve_data)?;
+    let borrow_reserve_config = borrow_reserve.config(borrow_reserve_extension)?;
+    let current_reward_index = match borrow_reserve_extension {
+        Some(extension) => extension.borrow_reward_index()?,
+        None => Decimal::one(),
+    };
+    let mut borrow_reserve_liquidity = borrow_reserve.liquidity();
 
     let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
     if obligation_info.owner != program_id {
@@ -87,13 +109,21 @@ pub(super) fn process_borrow_obligation_liquidity(
         msg!("Obligation lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &obligation.owner != obligation_owner_info.key {
-        msg!("Obligation owner does not match the obligation owner provided");
-        return Err(LendingError::InvalidObligationOwner.into());
-    }
-    if !obligation_owner_info.is_signer {
-        msg!("Obligation owner provided must be a signer");
-        return Err(LendingError::InvalidSigner.into());
+    let obligation_delegate = validate_obligation_authority(
+        program_id,
+        obligation_info.key,
+        &obligation,
+        obligation_owner_info,
+        account_info_iter,
+        DelegatePermissions::BORROW,
+    )?;
+    if obligation_delegate.is_some() {
+        let destination_liquidity =
+            spl_token::state::Account::unpack(&destination_liquidity_info.data.borrow())?;
+        if destination_liquidity.owner != obligation.owner {
+            msg!("Obligation delegate can only borrow to token accounts of the obligation owner");
+            return Err(LendingError::DelegateNotPermitted.into());
+        }
     }
     if obligation.last_update.is_stale(clock.slot)? {
         msg!("Obligation is stale and must be refreshed in the current slot");
@@ -132,7 +162,11 @@ pub(super) fn process_borrow_obligation_liquidity(
         receive_amount,
         borrow_fee,
         host_fee,
-    } = borrow_reserve.calculate_borrow(liquidity_amount, remaining_borrow_value)?;
+    } = borrow_reserve.calculate_borrow(
+        &borrow_reserve_config,
+        liquidity_amount,
+        remaining_borrow_value,
+    )?;
 
     if receive_amount == 0 {
         msg!("Borrow amount is too small to receive liquidity after fees");
@@ -144,23 +178,103 @@ pub(super) fn process_borrow_obligation_liquidity(
         return Err(LendingError::ExceededSlippage.into());
     }
 
-    borrow_reserve.liquidity.borrow(borrow_amount)?;
-    borrow_reserve.last_update.mark_stale();
-    let current_cumulative_borrow_rate_wads = borrow_reserve.liquidity.cumulative_borrow_rate_wads;
-    Reserve::pack(borrow_reserve, &mut borrow_reserve_info.data.borrow_mut())?;
+    if borrow_reserve_config.borrow_limit > 0
+        && borrow_reserve_liquidity
+            .borrowed_amount_wads
+            .try_add(borrow_amount)?
+            > Decimal::from(borrow_reserve_config.borrow_limit)
+    {
+        msg!("Borrow would exceed the reserve borrow limit");
+        return Err(LendingError::BorrowLimitExceeded.into());
+    }
+
+    if let Some(obligation_delegate) = &obligation_delegate {
+        let decimals = get_pow(borrow_reserve_liquidity.mint_decimals as u32)?;
+        let borrowed_value = obligation.borrowed_value.try_add(
+            borrow_amount
+                .try_mul(borrow_reserve_liquidity.market_price)?
+                .try_div(decimals)?,
+        )?;
+        if borrowed_value > Decimal::from(obligation_delegate.max_borrow_value) {
+            msg!("Borrow would exceed the obligation delegate borrow limit");
+            return Err(LendingError::DelegateBorrowLimitExceeded.into());
+        }
+    }
+
+    let fixed_rate = match fixed_rate_terms {
+        Some((term, max_borrow_rate_bps)) => {
+            if !Obligation::stores_fixed_rate_borrows(obligation.version) {
+                msg!("Obligation must be resized before borrowing at a fixed rate");
+                return Err(LendingError::InvalidFixedRateBorrow.into());
+            }
+            let borrow_rate = borrow_reserve_config
+                .fixed_rate
+                .borrow_rate(borrow_reserve.current_borrow_rate(&borrow_reserve_config)?)?;
+            if borrow_rate > Rate::from_bps(max_borrow_rate_bps) {
+                msg!("Fixed borrow rate would be higher than the desired max borrow rate");
+                return Err(LendingError::ExceededSlippage.into());
+            }
+            Some(FixedRateBorrow {
+                borrow_rate,
+                maturity_timestamp: borrow_reserve_config
+                    .fixed_rate
+                    .maturity_timestamp(term, clock.unix_timestamp)?,
+                last_accrual_timestamp: clock.unix_timestamp,
+                reserve_borrowed_amount_wads: Decimal::zero(),
+            })
+        }
+        None => None,
+    };
+
+    drop(borrow_reserve_data);
+    borrow_reserve_liquidity.borrow(borrow_amount)?;
+    let current_cumulative_borrow_rate_wads = borrow_reserve_liquidity.cumulative_borrow_rate_wads;
+    let mut borrow_reserve_data = borrow_reserve_info.data.borrow_mut();
+    let borrow_reserve = ReserveData::load_mut(&mut borrow_reserve_data)?;
+    borrow_reserve.set_liquidity(&borrow_reserve_liquidity);
+    borrow_reserve.mark_stale();
+    drop(borrow_reserve_data);
 
-    obligation
-        .find_or_add_liquidity_to_borrows(
-            *borrow_reserve_info.key,
-            current_cumulative_borrow_rate_wads,
-        )?
-        .borrow(borrow_amount)?;
+    let liquidity = obligation.find_or_add_liquidity_to_borrows(
+        *borrow_reserve_info.key,
+        current_cumulative_borrow_rate_wads,
+        current_reward_index,
+    )?;
+    if liquidity.fixed_rate.is_some() {
+        msg!("Fixed rate borrows cannot be increased, repay before borrowing again");
+        return Err(LendingError::InvalidFixedRateBorrow.into());
+    }
+    if fixed_rate.is_some() {
+        if liquidity.borrowed_amount_wads > Decimal::zero() {
+            msg!("Obligation already has a variable rate borrow from the borrow reserve");
+            return Err(LendingError::InvalidFixedRateBorrow.into());
+        }
+        liquidity.fixed_rate = fixed_rate;
+    }
+    liquidity.borrow(borrow_amount)?;
 
+    let referrer = obligation.referrer();
     obligation.last_update.mark_stale();
     Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
 
+    let host_fee_receiver_info = next_account_info(account_info_iter).ok();
+    if let Some(referrer) = referrer {
+        let referrer_fee_receiver_pubkey =
+            find_referrer_fee_receiver_address(program_id, borrow_reserve_info.key, &referrer).0;
+        if host_fee_receiver_info.map(|info| info.key) != Some(&referrer_fee_receiver_pubkey) {
+            msg!("Host fee receiver must be the referrer fee receiver of the borrow reserve");
+            return Err(LendingError::InvalidReferrerFeeReceiver.into());
+        }
+        // anyone can create the referrer fee receiver, so the host fee is
+        // never diverted to the reserve owner
+        if host_fee_receiver_info.is_some_and(|info| info.data_is_empty()) {
+            msg!("Referrer fee receiver must be initialized with InitReferrerFeeReceiver");
+            return Err(LendingError::InvalidReferrerFeeReceiver.into());
+        }
+    }
+
     let mut owner_fee = borrow_fee;
-    if let Ok(host_fee_receiver_info) = next_account_info(account_info_iter) {
+    if let Some(host_fee_receiver_info) = host_fee_receiver_info {
         if host_fee > 0 {
             owner_fee = owner_fee
                 .checked_sub(host_fee)
@@ -196,5 +310,23 @@ pub(super) fn process_borrow_obligation_liquidity(
         token_program: token_program_id.clone(),
     })?;
 
+    LendingEvent::Borrow {
+        obligation: *obligation_info.key,
+        reserve: *borrow_reserve_info.key,
+        borrow_amount_wads: borrow_amount,
+        receive_amount,
+        borrow_fee,
+        fixed_rate: fixed_rate_terms.is_some(),
+    }
+    .emit();
+    CalculateBorrowResult {
+        borrow_amount,
+        receive_amount,
+        borrow_fee,
+        host_fee: borrow_fee
+            .checked_sub(owner_fee)
+            .ok_or(LendingError::MathOverflow)?,
+    }
+    .set();
     Ok(())
 }
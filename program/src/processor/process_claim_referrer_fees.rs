@@ -0,0 +1,96 @@
+use {
+    super::{spl_token_transfer, TokenTransferParams},
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{find_referrer_fee_receiver_address, LendingMarket, Reserve},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+    },
+};
+
+pub(super) fn process_claim_referrer_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let referrer_fee_receiver_info = next_account_info(account_info_iter)?;
+    let destination_liquidity_info = next_account_info(account_info_iter)?;
+    let reserve_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let lending_market_authority_info = next_account_info(account_info_iter)?;
+    let referrer_info = next_account_info(account_info_iter)?;
+    let token_program_id = next_account_info(account_info_iter)?;
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &lending_market.token_program_id != token_program_id.key {
+        msg!("Lending market token program does not match the token program provided");
+        return Err(LendingError::InvalidTokenProgram.into());
+    }
+
+    let reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+    if reserve_info.owner != program_id {
+        msg!("Reserve provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &reserve.lending_market != lending_market_info.key {
+        msg!("Reserve lending market does not match the lending market provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+
+    if !referrer_info.is_signer {
+        msg!("Referrer provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+    let referrer_fee_receiver_pubkey =
+        find_referrer_fee_receiver_address(program_id, reserve_info.key, referrer_info.key).0;
+    if &referrer_fee_receiver_pubkey != referrer_fee_receiver_info.key {
+        msg!("Referrer fee receiver provided is not derived from the reserve and referrer");
+        return Err(LendingError::InvalidReferrerFeeReceiver.into());
+    }
+
+    let authority_signer_seeds = &[
+        lending_market_info.key.as_ref(),
+        &[lending_market.bump_seed],
+    ];
+    let lending_market_authority_pubkey =
+        Pubkey::create_program_address(authority_signer_seeds, program_id)?;
+    if &lending_market_authority_pubkey != lending_market_authority_info.key {
+        msg!(
+            "Derived lending market authority does not match the lending market authority provided"
+        );
+        return Err(LendingError::InvalidMarketAuthority.into());
+    }
+
+    let amount =
+        spl_token::state::Account::unpack(&referrer_fee_receiver_info.data.borrow())?.amount;
+    if amount == 0 {
+        msg!("Referrer fee receiver has no fees to claim");
+        return Err(LendingError::InvalidAmount.into());
+    }
+
+    spl_token_transfer(TokenTransferParams {
+        source: referrer_fee_receiver_info.clone(),
+        destination: destination_liquidity_info.clone(),
+        amount,
+        authority: lending_market_authority_info.clone(),
+        authority_signer_seeds,
+        token_program: token_program_id.clone(),
+    })?;
+    LendingEvent::ReferrerFeesClaimed {
+        reserve: *reserve_info.key,
+        referrer: *referrer_info.key,
+        amount,
+    }
+    .emit();
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use {
+    super::{spl_token_transfer, TokenTransferParams},
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{LendingMarket, Obligation, ReserveRewards},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+pub(super) fn process_claim_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let reserve_rewards_info = next_account_info(account_info_iter)?;
+    let reward_supply_info = next_account_info(account_info_iter)?;
+    let destination_reward_info = next_account_info(account_info_iter)?;
+    let obligation_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let lending_market_authority_info = next_account_info(account_info_iter)?;
+    let obligation_owner_info = next_account_info(account_info_iter)?;
+    let token_program_id = next_account_info(account_info_iter)?;
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &lending_market.token_program_id != token_program_id.key {
+        msg!("Lending market token program does not match the token program provided");
+        return Err(LendingError::InvalidTokenProgram.into());
+    }
+
+    let reserve_rewards = ReserveRewards::unpack(&reserve_rewards_info.data.borrow())?;
+    if reserve_rewards_info.owner != program_id {
+        msg!("Reserve rewards provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &reserve_rewards.lending_market != lending_market_info.key {
+        msg!("Reserve rewards lending market does not match the lending market provided");
+        return Err(LendingError::InvalidReserveRewards.into());
+    }
+    if &reserve_rewards.reward_supply != reward_supply_info.key {
+        msg!("Reserve rewards supply does not match the reward supply provided");
+        return Err(LendingError::InvalidReserveRewards.into());
+    }
+
+    let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
+    if obligation_info.owner != program_id {
+        msg!("Obligation provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &obligation.lending_market != lending_market_info.key {
+        msg!("Obligation lending market does not match the lending market provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    if &obligation.owner != obligation_owner_info.key {
+        msg!("Obligation owner does not match the obligation owner provided");
+        return Err(LendingError::InvalidObligationOwner.into());
+    }
+    if !obligation_owner_info.is_signer {
+        msg!("Obligation owner provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+    // rewards are settled when the obligation is refreshed
+    if obligation.last_update.is_stale(Clock::get()?.slot)? {
+        msg!("Obligation is stale and must be refreshed in the current slot");
+        return Err(LendingError::ObligationStale.into());
+    }
+
+    let authority_signer_seeds = &[
+        lending_market_info.key.as_ref(),
+        &[lending_market.bump_seed],
+    ];
+    let lending_market_authority_pubkey =
+        Pubkey::create_program_address(authority_signer_seeds, program_id)?;
+    if &lending_market_authority_pubkey != lending_market_authority_info.key {
+        msg!(
+            "Derived lending market authority does not match the lending market authority provided"
+        );
+        return Err(LendingError::InvalidMarketAuthority.into());
+    }
+
+    let reward_supply_amount =
+        spl_token::state::Account::unpack(&reward_supply_info.data.borrow())?.amount;
+    let claim_amount = obligation.claim_rewards(reserve_rewards.reserve, reward_supply_amount)?;
+    Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
+
+    if claim_amount > 0 {
+        spl_token_transfer(TokenTransferParams {
+            source: reward_supply_info.clone(),
+            destination: destination_reward_info.clone(),
+            amount: claim_amount,
+            authority: lending_market_authority_info.clone(),
+            authority_signer_seeds,
+            token_program: token_program_id.clone(),
+        })?;
+    }
+    LendingEvent::RewardsClaimed {
+        reserve_rewards: *reserve_rewards_info.key,
+        obligation: *obligation_info.key,
+        amount: claim_amount,
+    }
+    .emit();
+    Ok(())
+}
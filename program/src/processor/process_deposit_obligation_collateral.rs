@@ -1,8 +1,13 @@
 use {
-    super::{spl_token_transfer, TokenTransferParams},
+    super::{spl_token_transfer, validate_obligation_authority, TokenTransferParams},
     crate::{
         error::LendingError,
-        state::{LendingMarket, Obligation, Reserve},
+        event::LendingEvent,
+        math::Decimal,
+        state::{
+            DelegatePermissions, LendingMarket, Obligation, ReserveData, ReserveExtensionData,
+            ReserveStatus,
+        },
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -45,31 +50,38 @@ pub(super) fn process_deposit_obligation_collateral(
         return Err(LendingError::InvalidTokenProgram.into());
     }
 
-    let deposit_reserve = Reserve::unpack(&deposit_reserve_info.data.borrow())?;
+    let deposit_reserve_data = deposit_reserve_info.data.borrow();
+    let deposit_reserve = ReserveData::load(&deposit_reserve_data)?;
     if deposit_reserve_info.owner != program_id {
         msg!("Deposit reserve provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &deposit_reserve.lending_market != lending_market_info.key {
+    if &deposit_reserve.lending_market() != lending_market_info.key {
         msg!("Deposit reserve lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &deposit_reserve.collateral.supply_pubkey == source_collateral_info.key {
+    if &deposit_reserve.collateral_supply_pubkey() == source_collateral_info.key {
         msg!("Deposit reserve collateral supply cannot be used as the source collateral provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &deposit_reserve.collateral.supply_pubkey != destination_collateral_info.key {
+    if &deposit_reserve.collateral_supply_pubkey() != destination_collateral_info.key {
         msg!(
             "Deposit reserve collateral supply must be used as the destination collateral provided"
         );
         return Err(LendingError::InvalidAccountInput.into());
     }
     let clock = Clock::get()?;
-    if deposit_reserve.last_update.is_stale(clock.slot)? {
+    if deposit_reserve.last_update()?.is_stale(clock.slot)? {
         msg!("Deposit reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
-    if deposit_reserve.config.loan_to_value_ratio == 0 {
+    // collateral deposits only make obligations safer, so are allowed while
+    // reduce-only
+    if deposit_reserve.status()? == ReserveStatus::Paused {
+        msg!("Deposit reserve does not allow collateral deposits while paused");
+        return Err(LendingError::ReserveStatusRestricted.into());
+    }
+    if deposit_reserve.config_loan_to_value_ratio_bps() == 0 {
         msg!("Deposit reserve has collateral disabled for borrowing");
         return Err(LendingError::ReserveCollateralDisabled.into());
     }
@@ -83,17 +95,24 @@ pub(super) fn process_deposit_obligation_collateral(
         msg!("Obligation lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &obligation.owner != obligation_owner_info.key {
-        msg!("Obligation owner does not match the obligation owner provided");
-        return Err(LendingError::InvalidObligationOwner.into());
-    }
-    if !obligation_owner_info.is_signer {
-        msg!("Obligation owner provided must be a signer");
-        return Err(LendingError::InvalidSigner.into());
-    }
-    obligation
-        .find_or_add_collateral_to_deposits(*deposit_reserve_info.key)?
-        .deposit(collateral_amount)?;
+    validate_obligation_authority(
+        program_id,
+        obligation_info.key,
+        &obligation,
+        obligation_owner_info,
+        account_info_iter,
+        DelegatePermissions::DEPOSIT,
+    )?;
+    let current_reward_index = match ReserveExtensionData::load(&deposit_reserve_data)? {
+        Some(extension) => extension.supply_reward_index()?,
+        None => Decimal::one(),
+    };
+    let collateral = obligation
+        .find_or_add_collateral_to_deposits(*deposit_reserve_info.key, current_reward_index)?;
+    // deposits do not require a refreshed obligation, so the rewards earned
+    // by the collateral deposited so far are settled before it grows
+    collateral.accrue_rewards(current_reward_index)?;
+    collateral.deposit(collateral_amount)?;
     obligation.last_update.mark_stale();
     Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
 
@@ -106,5 +125,11 @@ pub(super) fn process_deposit_obligation_collateral(
         token_program: token_program_id.clone(),
     })?;
 
+    LendingEvent::DepositObligationCollateral {
+        obligation: *obligation_info.key,
+        reserve: *deposit_reserve_info.key,
+        collateral_amount,
+    }
+    .emit();
     Ok(())
 }
@@ -2,7 +2,9 @@ use {
     super::{spl_token_mint_to, spl_token_transfer, TokenMintToParams, TokenTransferParams},
     crate::{
         error::LendingError,
-        state::{LendingMarket, Reserve},
+        event::LendingEvent,
+        return_data::{DepositReserveLiquidityResult, LendingReturnData},
+        state::{LendingMarket, ReserveData, ReserveStatus},
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -44,36 +46,41 @@ pub(super) fn process_deposit_reserve_liquidity(
         msg!("Lending market token program does not match the token program provided");
         return Err(LendingError::InvalidTokenProgram.into());
     }
-    let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
     if reserve_info.owner != program_id {
         msg!("Reserve provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &reserve.lending_market != lending_market_info.key {
+    let mut reserve_data = reserve_info.data.borrow_mut();
+    let reserve = ReserveData::load_mut(&mut reserve_data)?;
+    if &reserve.lending_market() != lending_market_info.key {
         msg!("Reserve lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &reserve.liquidity.supply_pubkey != reserve_liquidity_supply_info.key {
+    if &reserve.liquidity_supply_pubkey() != reserve_liquidity_supply_info.key {
         msg!("Reserve liquidity supply does not match the reserve liquidity supply provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &reserve.collateral.mint_pubkey != reserve_collateral_mint_info.key {
+    if &reserve.collateral_mint_pubkey() != reserve_collateral_mint_info.key {
         msg!("Reserve collateral mint does not match the reserve collateral mint provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &reserve.liquidity.supply_pubkey == source_liquidity_info.key {
+    if &reserve.liquidity_supply_pubkey() == source_liquidity_info.key {
         msg!("Reserve liquidity supply cannot be used as the source liquidity provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &reserve.collateral.supply_pubkey == destination_collateral_info.key {
+    if &reserve.collateral_supply_pubkey() == destination_collateral_info.key {
         msg!("Reserve collateral supply cannot be used as the destination collateral provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
     let clock = Clock::get()?;
-    if reserve.last_update.is_stale(clock.slot)? {
+    if reserve.last_update()?.is_stale(clock.slot)? {
         msg!("Reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    if reserve.status()? != ReserveStatus::Active {
+        msg!("Reserve does not allow deposits while reduce-only or paused");
+        return Err(LendingError::ReserveStatusRestricted.into());
+    }
     let authority_signer_seeds = &[
         lending_market_info.key.as_ref(),
         &[lending_market.bump_seed],
@@ -89,8 +96,8 @@ pub(super) fn process_deposit_reserve_liquidity(
         return Err(LendingError::InvalidMarketAuthority.into());
     }
     let collateral_amount = reserve.deposit_liquidity(liquidity_amount)?;
-    reserve.last_update.mark_stale();
-    Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
+    reserve.mark_stale();
+    drop(reserve_data);
 
     spl_token_transfer(TokenTransferParams {
         source: source_liquidity_info.clone(),
@@ -109,5 +116,12 @@ pub(super) fn process_deposit_reserve_liquidity(
         authority_signer_seeds,
         token_program: token_program_id.clone(),
     })?;
+    LendingEvent::DepositReserveLiquidity {
+        reserve: *reserve_info.key,
+        liquidity_amount,
+        collateral_amount,
+    }
+    .emit();
+    DepositReserveLiquidityResult { collateral_amount }.set();
     Ok(())
 }
@@ -0,0 +1,98 @@
+use {
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        math::Decimal,
+        state::{Obligation, Reserve},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+#[inline(never)] // avoid stack frame limit
+pub(super) fn process_forgive_debt(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let obligation_info = next_account_info(account_info_iter)?;
+    let reserve_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+
+    let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+    if reserve_info.owner != program_id {
+        msg!("Reserve provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &reserve.lending_market != lending_market_info.key {
+        msg!("Reserve lending market does not match the lending market provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    let clock = Clock::get()?;
+    if reserve.last_update.is_stale(clock.slot)? {
+        msg!("Reserve is stale and must be refreshed in the current slot");
+        return Err(LendingError::ReserveStale.into());
+    }
+
+    let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
+    if obligation_info.owner != program_id {
+        msg!("Obligation provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &obligation.lending_market != lending_market_info.key {
+        msg!("Obligation lending market does not match the lending market provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    if obligation.last_update.is_stale(clock.slot)? {
+        msg!("Obligation is stale and must be refreshed in the current slot");
+        return Err(LendingError::ObligationStale.into());
+    }
+    // emptied deposits kept for their rewards do not hold collateral
+    if !obligation
+        .deposits
+        .iter()
+        .all(|collateral| collateral.deposited_amount == 0)
+    {
+        msg!("Obligation with collateral deposits cannot have its debt forgiven");
+        return Err(LendingError::ObligationDepositsNotEmpty.into());
+    }
+
+    let (liquidity, liquidity_index) = obligation.find_liquidity_in_borrows(*reserve_info.key)?;
+    let settle_amount = liquidity.borrowed_amount_wads;
+    if settle_amount == Decimal::zero() {
+        msg!("Liquidity borrowed amount is zero");
+        return Err(LendingError::ObligationLiquidityEmpty.into());
+    }
+
+    reserve
+        .liquidity
+        .forgive_debt(liquidity.reserve_settle_amount(settle_amount)?)?;
+    reserve.last_update.mark_stale();
+    Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
+
+    obligation.repay(settle_amount, liquidity_index)?;
+    obligation.last_update.mark_stale();
+    Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
+
+    msg!(
+        "Forgave {} of bad debt from obligation {} in reserve {}",
+        settle_amount,
+        obligation_info.key,
+        reserve_info.key
+    );
+    LendingEvent::ForgiveDebt {
+        obligation: *obligation_info.key,
+        reserve: *reserve_info.key,
+        settle_amount_wads: settle_amount,
+    }
+    .emit();
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use {
+    super::value_obligation,
+    crate::{
+        error::LendingError,
+        return_data::{LendingReturnData, ObligationHealthResult},
+        state::Obligation,
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+#[inline(never)]
+pub(super) fn process_get_obligation_health(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter().peekable();
+    let obligation_info = next_account_info(account_info_iter)?;
+    let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
+    if obligation_info.owner != program_id {
+        msg!("Obligation provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let clock = Clock::get()?;
+    value_obligation(program_id, &mut obligation, account_info_iter, &clock)?;
+
+    ObligationHealthResult {
+        deposited_value: obligation.deposited_value,
+        borrowed_value: obligation.borrowed_value,
+        allowed_borrow_value: obligation.allowed_borrow_value,
+        unhealthy_borrow_value: obligation.unhealthy_borrow_value,
+        health_factor: obligation.health_factor()?,
+    }
+    .set();
+    Ok(())
+}
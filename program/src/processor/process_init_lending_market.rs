@@ -2,6 +2,7 @@ use {
     super::{assert_rent_exempt, assert_uninitialized},
     crate::{
         error::LendingError,
+        event::LendingEvent,
         state::{InitLendingMarketParams, LendingMarket},
     },
     solana_program::{
@@ -38,5 +39,9 @@ pub(super) fn process_init_lending_market(
         oracle_program_id: *oracle_program_id.key,
     });
     LendingMarket::pack(lending_market, &mut lending_market_info.data.borrow_mut())?;
+    LendingEvent::LendingMarketChanged {
+        lending_market: *lending_market_info.key,
+    }
+    .emit();
     Ok(())
 }
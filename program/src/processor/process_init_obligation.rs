@@ -1,8 +1,12 @@
 use {
-    super::{assert_rent_exempt, assert_uninitialized},
+    super::{assert_rent_exempt, assert_uninitialized, create_pda_account},
     crate::{
         error::LendingError,
-        state::{InitObligationParams, LendingMarket, Obligation},
+        event::LendingEvent,
+        state::{
+            find_obligation_address, InitObligationParams, LendingMarket, Obligation,
+            OBLIGATION_SEED, UNINITIALIZED_VERSION,
+        },
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -11,12 +15,14 @@ use {
         msg,
         program_pack::Pack,
         pubkey::Pubkey,
+        system_program,
         sysvar::Sysvar,
     },
 };
 #[inline(never)] // avoid stack frame limit
 pub(super) fn process_init_obligation(
     program_id: &Pubkey,
+    seed_id: Option<u64>,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -24,11 +30,72 @@ pub(super) fn process_init_obligation(
     let lending_market_info = next_account_info(account_info_iter)?;
     let obligation_owner_info = next_account_info(account_info_iter)?;
     let token_program_id = next_account_info(account_info_iter)?;
+    let system_program_info = match seed_id {
+        Some(_) => Some(next_account_info(account_info_iter)?),
+        None => None,
+    };
+    let referrer_info = next_account_info(account_info_iter).ok();
     let clock = Clock::get()?;
 
+    if let (Some(seed_id), Some(system_program_info)) = (seed_id, system_program_info) {
+        let (obligation_pubkey, bump_seed) = find_obligation_address(
+            program_id,
+            lending_market_info.key,
+            obligation_owner_info.key,
+            seed_id,
+        );
+        if &obligation_pubkey != obligation_info.key {
+            msg!("Obligation provided is not derived from the lending market, owner and seed id");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+        if !system_program::check_id(system_program_info.key) {
+            msg!("System program provided does not match the system program id");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+        if !obligation_owner_info.is_signer {
+            msg!("Obligation owner provided must be a signer");
+            return Err(LendingError::InvalidSigner.into());
+        }
+        // created without room for reserves, ResizeObligation grows it
+        // before its first deposit or borrow
+        create_pda_account(
+            obligation_owner_info,
+            obligation_info,
+            system_program_info,
+            Obligation::resizable_len(0, 0),
+            program_id,
+            &[
+                OBLIGATION_SEED,
+                lending_market_info.key.as_ref(),
+                obligation_owner_info.key.as_ref(),
+                &seed_id.to_le_bytes(),
+                &[bump_seed],
+            ],
+        )?;
+    }
+
     assert_rent_exempt(obligation_info)?;
 
-    let mut obligation = assert_uninitialized::<Obligation>(obligation_info)?;
+    // derived obligations always use the resizable layout
+    let resizable = referrer_info.is_some() || seed_id.is_some();
+    if referrer_info.is_some() && obligation_info.data_len() == Obligation::LEN {
+        // the fixed layout has no room for the referrer
+        msg!("Referrer cannot be recorded on a fixed size obligation, size it as a resizable obligation");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    let mut obligation = if resizable {
+        if obligation_info.data_len() != Obligation::resizable_len(0, 0) {
+            msg!("Obligation with a referrer or seed id must be sized as a resizable obligation without reserves");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+        if obligation_info.data.borrow()[0] != UNINITIALIZED_VERSION {
+            msg!("Account is already initialized");
+            return Err(LendingError::AlreadyInitialized.into());
+        }
+        Obligation::default()
+    } else {
+        assert_uninitialized::<Obligation>(obligation_info)?
+    };
     if obligation_info.owner != program_id {
         msg!("Obligation provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
@@ -55,6 +122,16 @@ pub(super) fn process_init_obligation(
         deposits: vec![],
         borrows: vec![],
     });
+    if resizable {
+        obligation.resize(0, 0)?;
+    }
+    if let Some(referrer_info) = referrer_info {
+        obligation.referrer = *referrer_info.key;
+    }
     Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
+    LendingEvent::ObligationChanged {
+        obligation: *obligation_info.key,
+    }
+    .emit();
     Ok(())
 }
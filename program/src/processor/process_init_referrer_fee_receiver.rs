@@ -0,0 +1,109 @@
+use {
+    super::{create_pda_account, spl_token_init_account, TokenInitializeAccountParams},
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{
+            find_referrer_fee_receiver_address, LendingMarket, Reserve, REFERRER_FEE_RECEIVER_SEED,
+        },
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+pub(super) fn process_init_referrer_fee_receiver(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let funding_info = next_account_info(account_info_iter)?;
+    let referrer_fee_receiver_info = next_account_info(account_info_iter)?;
+    let reserve_info = next_account_info(account_info_iter)?;
+    let reserve_liquidity_mint_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let lending_market_authority_info = next_account_info(account_info_iter)?;
+    let referrer_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_id = next_account_info(account_info_iter)?;
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &lending_market.token_program_id != token_program_id.key {
+        msg!("Lending market token program does not match the token program provided");
+        return Err(LendingError::InvalidTokenProgram.into());
+    }
+    if !system_program::check_id(system_program_info.key) {
+        msg!("System program provided does not match the system program id");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+
+    let reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+    if reserve_info.owner != program_id {
+        msg!("Reserve provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &reserve.lending_market != lending_market_info.key {
+        msg!("Reserve lending market does not match the lending market provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    if &reserve.liquidity.mint_pubkey != reserve_liquidity_mint_info.key {
+        msg!("Reserve liquidity mint does not match the reserve liquidity mint provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+
+    let authority_signer_seeds = &[
+        lending_market_info.key.as_ref(),
+        &[lending_market.bump_seed],
+    ];
+    let lending_market_authority_pubkey =
+        Pubkey::create_program_address(authority_signer_seeds, program_id)?;
+    if &lending_market_authority_pubkey != lending_market_authority_info.key {
+        msg!(
+            "Derived lending market authority does not match the lending market authority provided"
+        );
+        return Err(LendingError::InvalidMarketAuthority.into());
+    }
+
+    let (referrer_fee_receiver_pubkey, bump_seed) =
+        find_referrer_fee_receiver_address(program_id, reserve_info.key, referrer_info.key);
+    if &referrer_fee_receiver_pubkey != referrer_fee_receiver_info.key {
+        msg!("Referrer fee receiver provided is not derived from the reserve and referrer");
+        return Err(LendingError::InvalidReferrerFeeReceiver.into());
+    }
+
+    create_pda_account(
+        funding_info,
+        referrer_fee_receiver_info,
+        system_program_info,
+        spl_token::state::Account::LEN,
+        token_program_id.key,
+        &[
+            REFERRER_FEE_RECEIVER_SEED,
+            reserve_info.key.as_ref(),
+            referrer_info.key.as_ref(),
+            &[bump_seed],
+        ],
+    )?;
+
+    spl_token_init_account(TokenInitializeAccountParams {
+        account: referrer_fee_receiver_info.clone(),
+        mint: reserve_liquidity_mint_info.clone(),
+        owner: lending_market_authority_info.clone(),
+        token_program: token_program_id.clone(),
+    })?;
+    LendingEvent::ReferrerFeeReceiverInitialized {
+        reserve: *reserve_info.key,
+        referrer: *referrer_info.key,
+    }
+    .emit();
+    Ok(())
+}
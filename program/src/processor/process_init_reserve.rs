@@ -1,6 +1,6 @@
 use {
     super::{
-        assert_rent_exempt, assert_uninitialized, get_pyth_price, get_pyth_product_quote_currency,
+        assert_uninitialized, create_pda_account, get_pyth_price, get_pyth_product_quote_currency,
         spl_token_init_account, spl_token_init_mint, spl_token_mint_to, spl_token_transfer,
         unpack_mint, TokenInitializeAccountParams, TokenInitializeMintParams, TokenMintToParams,
         TokenTransferParams,
@@ -8,10 +8,14 @@ use {
     crate::{
         assert_equal, assert_is_signer, assert_key_equal, assert_key_not_equal,
         error::LendingError,
+        event::LendingEvent,
         pyth,
         state::{
-            InitReserveParams, LendingMarket, NewReserveCollateralParams,
-            NewReserveLiquidityParams, Reserve, ReserveCollateral, ReserveConfig, ReserveLiquidity,
+            find_reserve_account_address, InitReserveParams, LendingMarket,
+            NewReserveCollateralParams, NewReserveLiquidityParams, Reserve, ReserveCollateral,
+            ReserveConfig, ReserveLiquidity, RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+            RESERVE_COLLATERAL_MINT_SEED, RESERVE_COLLATERAL_SUPPLY_SEED,
+            RESERVE_LIQUIDITY_FEE_RECEIVER_SEED, RESERVE_LIQUIDITY_SUPPLY_SEED, RESERVE_SEED,
         },
     },
     solana_program::{
@@ -22,8 +26,10 @@ use {
         program_error::ProgramError,
         program_pack::Pack,
         pubkey::Pubkey,
+        system_program,
         sysvar::Sysvar,
     },
+    spl_token::state::{Account as Token, Mint},
 };
 
 pub(super) fn process_init_reserve(
@@ -46,6 +52,7 @@ pub(super) fn process_init_reserve(
     let reserve_liquidity_fee_receiver_info = next_account_info(account_info_iter)?;
     let reserve_collateral_mint_info = next_account_info(account_info_iter)?;
     let reserve_collateral_supply_info = next_account_info(account_info_iter)?;
+    let reserve_collateral_fee_receiver_info = next_account_info(account_info_iter)?;
     let pyth_product_info = next_account_info(account_info_iter)?;
     let pyth_price_info = next_account_info(account_info_iter)?;
     let lending_market_info = next_account_info(account_info_iter)?;
@@ -53,14 +60,7 @@ pub(super) fn process_init_reserve(
     let lending_market_owner_info = next_account_info(account_info_iter)?;
     let user_transfer_authority_info = next_account_info(account_info_iter)?;
     let token_program_id = next_account_info(account_info_iter)?;
-    assert_rent_exempt(reserve_info)?;
-    let mut reserve = assert_uninitialized::<Reserve>(reserve_info)?;
-    assert_key_equal!(
-        reserve_info.owner,
-        program_id,
-        "Reserve provided is not owned by the lending program",
-        LendingError::InvalidAccountOwner
-    );
+    let system_program_info = next_account_info(account_info_iter)?;
     assert_key_not_equal!(
         reserve_liquidity_supply_info.key,
         source_liquidity_info.key,
@@ -87,6 +87,12 @@ pub(super) fn process_init_reserve(
         LendingError::InvalidMarketOwner
     );
     assert_is_signer!(lending_market_owner_info, "Lending market owner provided");
+    assert_key_equal!(
+        &system_program::id(),
+        system_program_info.key,
+        "System program provided does not match the system program id",
+        LendingError::InvalidAccountInput
+    );
     assert_key_equal!(
         &lending_market.oracle_program_id,
         pyth_product_info.owner,
@@ -158,8 +164,72 @@ pub(super) fn process_init_reserve(
         msg!("Reserve liquidity mint is not owned by the token program provided");
         return Err(LendingError::InvalidTokenOwner.into());
     }
+
+    // the reserve and its accounts are created at addresses derived from the
+    // lending market and liquidity mint, so a mint can only be listed once
+    for (account_info, seed, space, owner) in [
+        (reserve_info, RESERVE_SEED, Reserve::LEN, program_id),
+        (
+            reserve_liquidity_supply_info,
+            RESERVE_LIQUIDITY_SUPPLY_SEED,
+            Token::LEN,
+            token_program_id.key,
+        ),
+        (
+            reserve_liquidity_fee_receiver_info,
+            RESERVE_LIQUIDITY_FEE_RECEIVER_SEED,
+            Token::LEN,
+            token_program_id.key,
+        ),
+        (
+            reserve_collateral_mint_info,
+            RESERVE_COLLATERAL_MINT_SEED,
+            Mint::LEN,
+            token_program_id.key,
+        ),
+        (
+            reserve_collateral_supply_info,
+            RESERVE_COLLATERAL_SUPPLY_SEED,
+            Token::LEN,
+            token_program_id.key,
+        ),
+        (
+            reserve_collateral_fee_receiver_info,
+            RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+            Token::LEN,
+            token_program_id.key,
+        ),
+    ] {
+        let (address, bump_seed) = find_reserve_account_address(
+            program_id,
+            seed,
+            lending_market_info.key,
+            reserve_liquidity_mint_info.key,
+        );
+        assert_key_equal!(
+            &address,
+            account_info.key,
+            "Reserve account provided is not derived from the lending market and liquidity mint",
+            LendingError::InvalidAccountInput
+        );
+        create_pda_account(
+            lending_market_owner_info,
+            account_info,
+            system_program_info,
+            space,
+            owner,
+            &[
+                seed,
+                lending_market_info.key.as_ref(),
+                reserve_liquidity_mint_info.key.as_ref(),
+                &[bump_seed],
+            ],
+        )?;
+    }
+    let mut reserve = assert_uninitialized::<Reserve>(reserve_info)?;
     reserve.init(InitReserveParams {
         current_slot: clock.slot,
+        current_unix_timestamp: clock.unix_timestamp,
         lending_market: *lending_market_info.key,
         liquidity: ReserveLiquidity::new(NewReserveLiquidityParams {
             mint_pubkey: *reserve_liquidity_mint_info.key,
@@ -172,6 +242,7 @@ pub(super) fn process_init_reserve(
         collateral: ReserveCollateral::new(NewReserveCollateralParams {
             mint_pubkey: *reserve_collateral_mint_info.key,
             supply_pubkey: *reserve_collateral_supply_info.key,
+            fee_receiver: *reserve_collateral_fee_receiver_info.key,
         }),
         config,
     });
@@ -205,6 +276,13 @@ pub(super) fn process_init_reserve(
         token_program: token_program_id.clone(),
     })?;
 
+    spl_token_init_account(TokenInitializeAccountParams {
+        account: reserve_collateral_fee_receiver_info.clone(),
+        mint: reserve_collateral_mint_info.clone(),
+        owner: lending_market_authority_info.clone(),
+        token_program: token_program_id.clone(),
+    })?;
+
     spl_token_init_account(TokenInitializeAccountParams {
         account: destination_collateral_info.clone(),
         mint: reserve_collateral_mint_info.clone(),
@@ -229,5 +307,15 @@ pub(super) fn process_init_reserve(
         authority_signer_seeds,
         token_program: token_program_id.clone(),
     })?;
+    LendingEvent::ReserveConfigChanged {
+        reserve: *reserve_info.key,
+    }
+    .emit();
+    LendingEvent::DepositReserveLiquidity {
+        reserve: *reserve_info.key,
+        liquidity_amount,
+        collateral_amount,
+    }
+    .emit();
     Ok(())
 }
@@ -0,0 +1,132 @@
+use {
+    super::{
+        assert_rent_exempt, assert_uninitialized, spl_token_init_account, unpack_mint,
+        TokenInitializeAccountParams,
+    },
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{
+            InitReserveRewardsParams, LendingMarket, Migrate, Reserve, ReserveRewardEmissions,
+            ReserveRewards,
+        },
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+pub(super) fn process_init_reserve_rewards(
+    program_id: &Pubkey,
+    supply_emission_per_second: u64,
+    borrow_emission_per_second: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let reserve_rewards_info = next_account_info(account_info_iter)?;
+    let reward_supply_info = next_account_info(account_info_iter)?;
+    let reward_mint_info = next_account_info(account_info_iter)?;
+    let reserve_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let lending_market_authority_info = next_account_info(account_info_iter)?;
+    let lending_market_owner_info = next_account_info(account_info_iter)?;
+    let token_program_id = next_account_info(account_info_iter)?;
+
+    assert_rent_exempt(reserve_rewards_info)?;
+    if reserve_rewards_info.owner != program_id {
+        msg!("Reserve rewards provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let mut reserve_rewards = assert_uninitialized::<ReserveRewards>(reserve_rewards_info)?;
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &lending_market.token_program_id != token_program_id.key {
+        msg!("Lending market token program does not match the token program provided");
+        return Err(LendingError::InvalidTokenProgram.into());
+    }
+    if &lending_market.owner != lending_market_owner_info.key {
+        msg!("Lending market owner does not match the lending market owner provided");
+        return Err(LendingError::InvalidMarketOwner.into());
+    }
+    if !lending_market_owner_info.is_signer {
+        msg!("Lending market owner provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+
+    let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+    if reserve_info.owner != program_id {
+        msg!("Reserve provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &reserve.lending_market != lending_market_info.key {
+        msg!("Reserve lending market does not match the lending market provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    if reserve.last_update.is_stale(Clock::get()?.slot)? {
+        msg!("Reserve is stale and must be refreshed in the current slot");
+        return Err(LendingError::ReserveStale.into());
+    }
+    if Reserve::is_outdated(reserve.version) {
+        msg!("Reserve must be migrated before emitting rewards");
+        return Err(LendingError::InvalidReserveRewards.into());
+    }
+    if reserve.reward_emissions.is_some() {
+        msg!("Reserve already emits rewards");
+        return Err(LendingError::InvalidReserveRewards.into());
+    }
+
+    let authority_signer_seeds = &[
+        lending_market_info.key.as_ref(),
+        &[lending_market.bump_seed],
+    ];
+    let lending_market_authority_pubkey =
+        Pubkey::create_program_address(authority_signer_seeds, program_id)?;
+    if &lending_market_authority_pubkey != lending_market_authority_info.key {
+        msg!(
+            "Derived lending market authority does not match the lending market authority provided"
+        );
+        return Err(LendingError::InvalidMarketAuthority.into());
+    }
+
+    unpack_mint(&reward_mint_info.data.borrow())?;
+    if reward_mint_info.owner != token_program_id.key {
+        msg!("Reward mint is not owned by the token program provided");
+        return Err(LendingError::InvalidTokenOwner.into());
+    }
+
+    reserve_rewards.init(InitReserveRewardsParams {
+        lending_market: *lending_market_info.key,
+        reserve: *reserve_info.key,
+        reward_mint: *reward_mint_info.key,
+        reward_supply: *reward_supply_info.key,
+    });
+    ReserveRewards::pack(reserve_rewards, &mut reserve_rewards_info.data.borrow_mut())?;
+
+    reserve.reward_emissions = Some(ReserveRewardEmissions::new(
+        supply_emission_per_second,
+        borrow_emission_per_second,
+    ));
+    Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
+
+    spl_token_init_account(TokenInitializeAccountParams {
+        account: reward_supply_info.clone(),
+        mint: reward_mint_info.clone(),
+        owner: lending_market_authority_info.clone(),
+        token_program: token_program_id.clone(),
+    })?;
+    LendingEvent::ReserveRewardsChanged {
+        reserve_rewards: *reserve_rewards_info.key,
+    }
+    .emit();
+    Ok(())
+}
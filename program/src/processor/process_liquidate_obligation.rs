@@ -2,8 +2,13 @@ use {
     super::{spl_token_transfer, TokenTransferParams},
     crate::{
         error::LendingError,
+        event::LendingEvent,
         math::Decimal,
-        state::{CalculateLiquidationResult, LendingMarket, Obligation, Reserve},
+        return_data::LendingReturnData,
+        state::{
+            CalculateLiquidationResult, LendingMarket, Obligation, ReserveData,
+            ReserveExtensionData,
+        },
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -33,6 +38,7 @@ pub(super) fn process_liquidate_obligation(
     let repay_reserve_liquidity_supply_info = next_account_info(account_info_iter)?;
     let withdraw_reserve_info = next_account_info(account_info_iter)?;
     let withdraw_reserve_collateral_supply_info = next_account_info(account_info_iter)?;
+    let withdraw_reserve_collateral_fee_receiver_info = next_account_info(account_info_iter)?;
     let obligation_info = next_account_info(account_info_iter)?;
     let lending_market_info = next_account_info(account_info_iter)?;
     let lending_market_authority_info = next_account_info(account_info_iter)?;
@@ -48,59 +54,76 @@ pub(super) fn process_liquidate_obligation(
         msg!("Lending market token program does not match the token program provided");
         return Err(LendingError::InvalidTokenProgram.into());
     }
-    let mut repay_reserve = Reserve::unpack(&repay_reserve_info.data.borrow())?;
     if repay_reserve_info.owner != program_id {
         msg!("Repay reserve provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &repay_reserve.lending_market != lending_market_info.key {
+    let repay_reserve_data = repay_reserve_info.data.borrow();
+    let repay_reserve = ReserveData::load(&repay_reserve_data)?;
+    if &repay_reserve.lending_market() != lending_market_info.key {
         msg!("Repay reserve lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &repay_reserve.liquidity.supply_pubkey != repay_reserve_liquidity_supply_info.key {
+    if &repay_reserve.liquidity_supply_pubkey() != repay_reserve_liquidity_supply_info.key {
         msg!("Repay reserve liquidity supply does not match the repay reserve liquidity supply provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &repay_reserve.liquidity.supply_pubkey == source_liquidity_info.key {
+    if &repay_reserve.liquidity_supply_pubkey() == source_liquidity_info.key {
         msg!("Repay reserve liquidity supply cannot be used as the source liquidity provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &repay_reserve.collateral.supply_pubkey == destination_collateral_info.key {
+    if &repay_reserve.collateral_supply_pubkey() == destination_collateral_info.key {
         msg!(
             "Repay reserve collateral supply cannot be used as the destination collateral provided"
         );
         return Err(LendingError::InvalidAccountInput.into());
     }
     let clock = Clock::get()?;
-    if repay_reserve.last_update.is_stale(clock.slot)? {
+    if repay_reserve.last_update()?.is_stale(clock.slot)? {
         msg!("Repay reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
-    let withdraw_reserve = Reserve::unpack(&withdraw_reserve_info.data.borrow())?;
+    // the withdraw reserve can be the repay reserve
+    drop(repay_reserve_data);
+    let withdraw_reserve_data = withdraw_reserve_info.data.borrow();
+    let withdraw_reserve = ReserveData::load(&withdraw_reserve_data)?;
     if withdraw_reserve_info.owner != program_id {
         msg!("Withdraw reserve provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &withdraw_reserve.lending_market != lending_market_info.key {
+    if &withdraw_reserve.lending_market() != lending_market_info.key {
         msg!("Withdraw reserve lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &withdraw_reserve.collateral.supply_pubkey != withdraw_reserve_collateral_supply_info.key {
+    if &withdraw_reserve.collateral_supply_pubkey() != withdraw_reserve_collateral_supply_info.key {
         msg!("Withdraw reserve collateral supply does not match the withdraw reserve collateral supply provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &withdraw_reserve.liquidity.supply_pubkey == source_liquidity_info.key {
+    if withdraw_reserve.collateral_fee_receiver() == Pubkey::default() {
+        msg!("Withdraw reserve has no collateral fee receiver and must be migrated with MigrateAccount");
+        return Err(LendingError::MissingCollateralFeeReceiver.into());
+    }
+    if &withdraw_reserve.collateral_fee_receiver()
+        != withdraw_reserve_collateral_fee_receiver_info.key
+    {
+        msg!("Withdraw reserve collateral fee receiver does not match the withdraw reserve collateral fee receiver provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    if &withdraw_reserve.liquidity_supply_pubkey() == source_liquidity_info.key {
         msg!("Withdraw reserve liquidity supply cannot be used as the source liquidity provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &withdraw_reserve.collateral.supply_pubkey == destination_collateral_info.key {
+    if &withdraw_reserve.collateral_supply_pubkey() == destination_collateral_info.key {
         msg!("Withdraw reserve collateral supply cannot be used as the destination collateral provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if withdraw_reserve.last_update.is_stale(clock.slot)? {
+    if withdraw_reserve.last_update()?.is_stale(clock.slot)? {
         msg!("Withdraw reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    // only the current config is decoded for the liquidation bonus and fees
+    let withdraw_reserve_config =
+        withdraw_reserve.config(ReserveExtensionData::load(&withdraw_reserve_data)?)?;
 
     let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
     if obligation_info.owner != program_id {
@@ -123,12 +146,15 @@ pub(super) fn process_liquidate_obligation(
         msg!("Obligation borrowed value is zero");
         return Err(LendingError::ObligationBorrowsZero.into());
     }
-    if obligation.borrowed_value < obligation.unhealthy_borrow_value {
+    let (liquidity, liquidity_index) =
+        obligation.find_liquidity_in_borrows(*repay_reserve_info.key)?;
+    // matured fixed rate borrows can be liquidated until repaid
+    if obligation.borrowed_value < obligation.unhealthy_borrow_value
+        && !liquidity.is_matured(clock.unix_timestamp)
+    {
         msg!("Obligation is healthy and cannot be liquidated");
         return Err(LendingError::ObligationHealthy.into());
     }
-    let (liquidity, liquidity_index) =
-        obligation.find_liquidity_in_borrows(*repay_reserve_info.key)?;
     if liquidity.market_value == Decimal::zero() {
         msg!("Obligation borrow value is zero");
         return Err(LendingError::ObligationLiquidityEmpty.into());
@@ -155,11 +181,14 @@ pub(super) fn process_liquidate_obligation(
         settle_amount,
         repay_amount,
         withdraw_amount,
+        protocol_fee,
     } = withdraw_reserve.calculate_liquidation(
+        &withdraw_reserve_config,
         liquidity_amount,
         &obligation,
         liquidity,
         collateral,
+        clock.unix_timestamp,
     )?;
     if repay_amount == 0 {
         msg!("Liquidation is too small to transfer liquidity");
@@ -169,9 +198,15 @@ pub(super) fn process_liquidate_obligation(
         msg!("Liquidation is too small to receive collateral");
         return Err(LendingError::LiquidationTooSmall.into());
     }
-    repay_reserve.liquidity.repay(repay_amount, settle_amount)?;
-    repay_reserve.last_update.mark_stale();
-    Reserve::pack(repay_reserve, &mut repay_reserve_info.data.borrow_mut())?;
+    let reserve_settle_amount = liquidity.reserve_settle_amount(settle_amount)?;
+    drop(withdraw_reserve_data);
+    let mut repay_reserve_data = repay_reserve_info.data.borrow_mut();
+    let repay_reserve = ReserveData::load_mut(&mut repay_reserve_data)?;
+    let mut repay_reserve_liquidity = repay_reserve.liquidity();
+    repay_reserve_liquidity.repay(repay_amount, reserve_settle_amount)?;
+    repay_reserve.set_liquidity(&repay_reserve_liquidity);
+    repay_reserve.mark_stale();
+    drop(repay_reserve_data);
 
     obligation.repay(settle_amount, liquidity_index)?;
     obligation.withdraw(withdraw_amount, collateral_index)?;
@@ -187,13 +222,63 @@ pub(super) fn process_liquidate_obligation(
         token_program: token_program_id.clone(),
     })?;
 
+    let liquidator_amount = withdraw_amount
+        .checked_sub(protocol_fee)
+        .ok_or(LendingError::MathOverflow)?;
+    let mut owner_fee = protocol_fee;
+    if let Ok(host_fee_receiver_info) = next_account_info(account_info_iter) {
+        let host_fee = withdraw_reserve_config
+            .fees
+            .calculate_liquidation_host_fee(protocol_fee)?;
+        if host_fee > 0 {
+            owner_fee = owner_fee
+                .checked_sub(host_fee)
+                .ok_or(LendingError::MathOverflow)?;
+
+            spl_token_transfer(TokenTransferParams {
+                source: withdraw_reserve_collateral_supply_info.clone(),
+                destination: host_fee_receiver_info.clone(),
+                amount: host_fee,
+                authority: lending_market_authority_info.clone(),
+                authority_signer_seeds,
+                token_program: token_program_id.clone(),
+            })?;
+        }
+    }
+    if owner_fee > 0 {
+        spl_token_transfer(TokenTransferParams {
+            source: withdraw_reserve_collateral_supply_info.clone(),
+            destination: withdraw_reserve_collateral_fee_receiver_info.clone(),
+            amount: owner_fee,
+            authority: lending_market_authority_info.clone(),
+            authority_signer_seeds,
+            token_program: token_program_id.clone(),
+        })?;
+    }
+
     spl_token_transfer(TokenTransferParams {
         source: withdraw_reserve_collateral_supply_info.clone(),
         destination: destination_collateral_info.clone(),
-        amount: withdraw_amount,
+        amount: liquidator_amount,
         authority: lending_market_authority_info.clone(),
         authority_signer_seeds,
         token_program: token_program_id.clone(),
     })?;
+    LendingEvent::Liquidate {
+        obligation: *obligation_info.key,
+        repay_reserve: *repay_reserve_info.key,
+        withdraw_reserve: *withdraw_reserve_info.key,
+        repay_amount,
+        withdraw_amount,
+        protocol_fee,
+    }
+    .emit();
+    CalculateLiquidationResult {
+        settle_amount,
+        repay_amount,
+        withdraw_amount,
+        protocol_fee,
+    }
+    .set();
     Ok(())
 }
@@ -0,0 +1,205 @@
+use {
+    super::{
+        create_pda_account, spl_token_init_account, utils::fund_rent_exemption,
+        TokenInitializeAccountParams,
+    },
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{
+            find_reserve_account_address, AccountType, LendingMarket, Migrate, Obligation,
+            ObligationDelegate, ObligationOwnerTransfer, Reserve, ReserveRewards,
+            RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+        },
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        system_program,
+    },
+    spl_token::state::Account as Token,
+};
+
+pub(super) fn process_migrate_account(
+    program_id: &Pubkey,
+    account_type: AccountType,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let account_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    if account_info.owner != program_id {
+        msg!("Account provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if !system_program::check_id(system_program_info.key) {
+        msg!("System program provided does not match the system program id");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+
+    match account_type {
+        AccountType::LendingMarket => {
+            migrate_account::<LendingMarket>(account_info, payer_info, system_program_info)
+        }
+        AccountType::Reserve => {
+            migrate_account::<Reserve>(account_info, payer_info, system_program_info)?;
+            init_collateral_fee_receiver(
+                program_id,
+                account_info,
+                payer_info,
+                system_program_info,
+                account_info_iter,
+            )
+        }
+        AccountType::Obligation => {
+            migrate_account::<Obligation>(account_info, payer_info, system_program_info)
+        }
+        AccountType::ObligationDelegate => {
+            migrate_account::<ObligationDelegate>(account_info, payer_info, system_program_info)
+        }
+        AccountType::ObligationOwnerTransfer => migrate_account::<ObligationOwnerTransfer>(
+            account_info,
+            payer_info,
+            system_program_info,
+        ),
+        AccountType::ReserveRewards => {
+            migrate_account::<ReserveRewards>(account_info, payer_info, system_program_info)
+        }
+    }
+}
+
+fn migrate_account<'a, T: Migrate>(
+    account_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let version = *account_info
+        .data
+        .borrow()
+        .first()
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let mut state = T::unpack(&account_info.data.borrow())?;
+    if !T::is_outdated(version) {
+        msg!("Account already uses the latest layout");
+        return Ok(());
+    }
+
+    let new_len = state.migrate();
+    if new_len != account_info.data_len() {
+        fund_rent_exemption(payer_info, account_info, system_program_info, new_len)?;
+        account_info.realloc(new_len, true)?;
+    }
+    T::pack(state, &mut account_info.data.borrow_mut())?;
+    LendingEvent::AccountMigrated {
+        account: *account_info.key,
+        version,
+    }
+    .emit();
+    msg!(
+        "Migrated account {} from version {}",
+        account_info.key,
+        version
+    );
+
+    Ok(())
+}
+
+/// Reserves created before the protocol liquidation fee have no collateral
+/// fee receiver. It is created at its derived address, as for new reserves.
+fn init_collateral_fee_receiver<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    reserve_info: &AccountInfo<'b>,
+    payer_info: &AccountInfo<'b>,
+    system_program_info: &AccountInfo<'b>,
+    account_info_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+) -> ProgramResult {
+    let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+    if reserve.collateral.fee_receiver != Pubkey::default() {
+        return Ok(());
+    }
+
+    let reserve_collateral_fee_receiver_info = next_account_info(account_info_iter)?;
+    let reserve_collateral_mint_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let lending_market_authority_info = next_account_info(account_info_iter)?;
+    let token_program_id = next_account_info(account_info_iter)?;
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &reserve.lending_market != lending_market_info.key {
+        msg!("Reserve lending market does not match the lending market provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    if &lending_market.token_program_id != token_program_id.key {
+        msg!("Lending market token program does not match the token program provided");
+        return Err(LendingError::InvalidTokenProgram.into());
+    }
+    if &reserve.collateral.mint_pubkey != reserve_collateral_mint_info.key {
+        msg!("Reserve collateral mint does not match the reserve collateral mint provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    let lending_market_authority_pubkey = Pubkey::create_program_address(
+        &[
+            lending_market_info.key.as_ref(),
+            &[lending_market.bump_seed],
+        ],
+        program_id,
+    )?;
+    if &lending_market_authority_pubkey != lending_market_authority_info.key {
+        msg!(
+            "Derived lending market authority does not match the lending market authority provided"
+        );
+        return Err(LendingError::InvalidMarketAuthority.into());
+    }
+    let (address, bump_seed) = find_reserve_account_address(
+        program_id,
+        RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+        lending_market_info.key,
+        &reserve.liquidity.mint_pubkey,
+    );
+    if &address != reserve_collateral_fee_receiver_info.key {
+        msg!("Reserve collateral fee receiver provided is not derived from the lending market and liquidity mint");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+
+    create_pda_account(
+        payer_info,
+        reserve_collateral_fee_receiver_info,
+        system_program_info,
+        Token::LEN,
+        token_program_id.key,
+        &[
+            RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+            lending_market_info.key.as_ref(),
+            reserve.liquidity.mint_pubkey.as_ref(),
+            &[bump_seed],
+        ],
+    )?;
+    spl_token_init_account(TokenInitializeAccountParams {
+        account: reserve_collateral_fee_receiver_info.clone(),
+        mint: reserve_collateral_mint_info.clone(),
+        owner: lending_market_authority_info.clone(),
+        token_program: token_program_id.clone(),
+    })?;
+
+    reserve.collateral.fee_receiver = *reserve_collateral_fee_receiver_info.key;
+    Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
+    LendingEvent::ReserveConfigChanged {
+        reserve: *reserve_info.key,
+    }
+    .emit();
+    msg!(
+        "Created collateral fee receiver of reserve {}",
+        reserve_info.key
+    );
+
+    Ok(())
+}
@@ -1,14 +1,17 @@
 use {
     crate::{
         error::LendingError,
+        event::LendingEvent,
         state::{LendingMarket, Reserve, ReserveConfig},
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Clock,
         entrypoint::ProgramResult,
         msg,
         program_pack::Pack,
         pubkey::Pubkey,
+        sysvar::Sysvar,
     },
 };
 
@@ -22,7 +25,7 @@ pub(super) fn process_modify_reserve_config(
     let account_info_iter = &mut accounts.iter().peekable();
     let reserve_info = next_account_info(account_info_iter)?;
     let lending_market_info = next_account_info(account_info_iter)?;
-    let lending_market_owner_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
 
     if reserve_info.owner != program_id {
         msg!("Reserve provided is not owned by the lending program");
@@ -34,12 +37,13 @@ pub(super) fn process_modify_reserve_config(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.owner != lending_market_owner_info.key {
-        msg!("Lending market owner does not match the lending market owner provided");
+    let is_owner = &lending_market.owner == authority_info.key;
+    if !is_owner && !lending_market.is_risk_manager(authority_info.key) {
+        msg!("Lending market owner or risk manager does not match the authority provided");
         return Err(LendingError::InvalidMarketOwner.into());
     }
-    if !lending_market_owner_info.is_signer {
-        msg!("Lending market owner provided must be a signer");
+    if !authority_info.is_signer {
+        msg!("Lending market authority provided must be a signer");
         return Err(LendingError::InvalidSigner.into());
     }
 
@@ -52,10 +56,41 @@ pub(super) fn process_modify_reserve_config(
         msg!("Reserve account does not match the lending market");
         return Err(LendingError::InvalidAccountInput.into());
     }
+    if new_config == reserve.config {
+        msg!("Reserve config provided does not change the reserve config");
+        return Err(LendingError::InvalidConfig.into());
+    }
+    let clock = Clock::get()?;
+    // the liquidation threshold in effect differs from the config during a
+    // collateral ramp
+    ReserveConfig {
+        liquidation_threshold_bps: reserve.liquidation_threshold_bps(clock.unix_timestamp),
+        ..reserve.config
+    }
+    .validate_transition(&new_config)?;
+    // the risk manager can only make the reserve safer
+    if !is_owner {
+        reserve.config.validate_risk_reduction(&new_config)?;
+    }
 
-    reserve.config = new_config;
+    if reserve.config.is_risk_reduction(&new_config) {
+        // the owner drops its pending config, which would undo the risk
+        // reduction once applied, while the risk manager cannot cancel
+        // changes queued by the owner
+        reserve.reduce_risk(new_config, is_owner);
+    } else {
+        let activation_timestamp = reserve.queue_config(new_config, clock.unix_timestamp)?;
+        msg!(
+            "Reserve config change can be applied from {}",
+            activation_timestamp
+        );
+    }
 
     Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
 
+    LendingEvent::ReserveConfigChanged {
+        reserve: *reserve_info.key,
+    }
+    .emit();
     Ok(())
 }
@@ -2,7 +2,9 @@ use {
     super::{spl_token_burn, spl_token_transfer, TokenBurnParams, TokenTransferParams},
     crate::{
         error::LendingError,
-        state::{LendingMarket, Reserve},
+        event::LendingEvent,
+        return_data::{LendingReturnData, RedeemReserveCollateralResult},
+        state::{LendingMarket, Reserve, ReserveStatus},
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -77,6 +79,10 @@ pub(super) fn process_redeem_reserve_collateral(
         msg!("Reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    if reserve.status == ReserveStatus::Paused {
+        msg!("Reserve does not allow redemptions while paused");
+        return Err(LendingError::ReserveStatusRestricted.into());
+    }
     let authority_signer_seeds = &[
         lending_market_info.key.as_ref(),
         &[lending_market.bump_seed],
@@ -109,5 +115,12 @@ pub(super) fn process_redeem_reserve_collateral(
         authority_signer_seeds,
         token_program: token_program_id.clone(),
     })?;
+    LendingEvent::RedeemReserveCollateral {
+        reserve: *reserve_info.key,
+        collateral_amount,
+        liquidity_amount,
+    }
+    .emit();
+    RedeemReserveCollateralResult { liquidity_amount }.set();
     Ok(())
 }
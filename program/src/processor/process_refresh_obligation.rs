@@ -1,8 +1,9 @@
 use {
     crate::{
         error::LendingError,
+        event::LendingEvent,
         math::{Decimal, Rate, TryAdd, TryDiv, TryMul},
-        state::{Obligation, Reserve},
+        state::{Obligation, ReserveData, ReserveExtensionData},
         utils::get_pow,
         // utils::get_pow,
     },
@@ -15,6 +16,7 @@ use {
         pubkey::Pubkey,
         sysvar::Sysvar,
     },
+    std::{iter::Peekable, slice::Iter},
 };
 
 #[inline(never)]
@@ -29,11 +31,35 @@ pub(super) fn process_refresh_obligation(
         msg!("Obligation provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
+    let clock = Clock::get()?;
+    value_obligation(program_id, &mut obligation, account_info_iter, &clock)?;
+
+    obligation.last_update.update_slot(clock.slot);
+    let event = LendingEvent::ObligationRefreshed {
+        obligation: *obligation_info.key,
+        deposited_value: obligation.deposited_value,
+        borrowed_value: obligation.borrowed_value,
+        allowed_borrow_value: obligation.allowed_borrow_value,
+        unhealthy_borrow_value: obligation.unhealthy_borrow_value,
+    };
+    Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
+    event.emit();
+    Ok(())
+}
+
+/// Accrue interest and settle rewards on an obligation and value its
+/// collateral and liquidity from the reserves provided, without writing any
+/// account
+pub(super) fn value_obligation(
+    program_id: &Pubkey,
+    obligation: &mut Obligation,
+    account_info_iter: &mut Peekable<Iter<AccountInfo>>,
+    clock: &Clock,
+) -> ProgramResult {
     let mut deposited_value = Decimal::zero();
     let mut borrowed_value = Decimal::zero();
     let mut allowed_borrow_value = Decimal::zero();
     let mut unhealthy_borrow_value = Decimal::zero();
-    let clock = Clock::get()?;
     for (index, collateral) in obligation.deposits.iter_mut().enumerate() {
         let deposit_reserve_info = next_account_info(account_info_iter)?;
         if deposit_reserve_info.owner != program_id {
@@ -50,8 +76,9 @@ pub(super) fn process_refresh_obligation(
             );
             return Err(LendingError::InvalidAccountInput.into());
         }
-        let deposit_reserve = Reserve::unpack(&deposit_reserve_info.data.borrow())?;
-        if deposit_reserve.last_update.is_stale(clock.slot)? {
+        let deposit_reserve_data = deposit_reserve_info.data.borrow();
+        let deposit_reserve = ReserveData::load(&deposit_reserve_data)?;
+        if deposit_reserve.last_update()?.is_stale(clock.slot)? {
             msg!(
               "Deposit reserve provided for collateral {} is stale and must be refreshed in the current slot",
               index
@@ -59,17 +86,30 @@ pub(super) fn process_refresh_obligation(
             return Err(LendingError::ReserveStale.into());
         }
 
-        let decimals = get_pow(deposit_reserve.liquidity.mint_decimals as u32)?;
+        let supply_reward_index = match ReserveExtensionData::load(&deposit_reserve_data)? {
+            Some(extension) => extension.supply_reward_index()?,
+            None => Decimal::one(),
+        };
+        collateral.accrue_rewards(supply_reward_index)?;
+
+        let decimals = get_pow(deposit_reserve.liquidity_mint_decimals() as u32)?;
         let market_value = deposit_reserve
             .collateral_exchange_rate()?
             .decimal_collateral_to_liquidity(collateral.deposited_amount.into())?
-            .try_mul(deposit_reserve.liquidity.market_price)?
+            .try_mul(deposit_reserve.liquidity_market_price())?
             .try_div(decimals)?;
         collateral.market_value = market_value;
 
-        let loan_to_value_rate = Rate::from_percent(deposit_reserve.config.loan_to_value_ratio);
-        let liquidation_threshold_rate =
-            Rate::from_percent(deposit_reserve.config.liquidation_threshold);
+        let loan_to_value_rate = Rate::from_bps(
+            deposit_reserve
+                .loan_to_value_ratio_bps(clock.unix_timestamp)?
+                .into(),
+        );
+        let liquidation_threshold_rate = Rate::from_bps(
+            deposit_reserve
+                .liquidation_threshold_bps(clock.unix_timestamp)?
+                .into(),
+        );
         deposited_value = deposited_value.try_add(market_value)?;
         allowed_borrow_value =
             allowed_borrow_value.try_add(market_value.try_mul(loan_to_value_rate)?)?;
@@ -93,8 +133,9 @@ pub(super) fn process_refresh_obligation(
             return Err(LendingError::InvalidAccountInput.into());
         }
 
-        let borrow_reserve = Reserve::unpack(&borrow_reserve_info.data.borrow())?;
-        if borrow_reserve.last_update.is_stale(clock.slot)? {
+        let borrow_reserve_data = borrow_reserve_info.data.borrow();
+        let borrow_reserve = ReserveData::load(&borrow_reserve_data)?;
+        if borrow_reserve.last_update()?.is_stale(clock.slot)? {
             msg!(
               "Borrow reserve provided for liquidity {} is stale and must be refreshed in the current slot",
               index
@@ -102,12 +143,20 @@ pub(super) fn process_refresh_obligation(
             return Err(LendingError::ReserveStale.into());
         }
 
-        liquidity.accrue_interest(borrow_reserve.liquidity.cumulative_borrow_rate_wads)?;
+        // rewards are settled on the liquidity borrowed before interest
+        // accrues, as the reserve emits them
+        let borrow_reward_index = match ReserveExtensionData::load(&borrow_reserve_data)? {
+            Some(extension) => extension.borrow_reward_index()?,
+            None => Decimal::one(),
+        };
+        liquidity.accrue_rewards(borrow_reward_index)?;
+        liquidity.accrue_interest(borrow_reserve.liquidity_cumulative_borrow_rate_wads())?;
+        liquidity.accrue_fixed_interest(clock.unix_timestamp)?;
 
-        let decimals = get_pow(borrow_reserve.liquidity.mint_decimals as u32)?;
+        let decimals = get_pow(borrow_reserve.liquidity_mint_decimals() as u32)?;
         let market_value = liquidity
             .borrowed_amount_wads
-            .try_mul(borrow_reserve.liquidity.market_price)?
+            .try_mul(borrow_reserve.liquidity_market_price())?
             .try_div(decimals)?;
         liquidity.market_value = market_value;
 
@@ -123,8 +172,5 @@ pub(super) fn process_refresh_obligation(
     obligation.borrowed_value = borrowed_value;
     obligation.allowed_borrow_value = allowed_borrow_value;
     obligation.unhealthy_borrow_value = unhealthy_borrow_value;
-
-    obligation.last_update.update_slot(clock.slot);
-    Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
     Ok(())
 }
@@ -1,12 +1,11 @@
 use {
     super::get_pyth_price,
-    crate::{error::LendingError, state::Reserve},
+    crate::{error::LendingError, event::LendingEvent, state::ReserveData},
     solana_program::{
         account_info::{next_account_info, AccountInfo},
         clock::Clock,
         entrypoint::ProgramResult,
         msg,
-        program_pack::Pack,
         pubkey::Pubkey,
         sysvar::Sysvar,
     },
@@ -18,20 +17,36 @@ pub(super) fn process_refresh_reserve(
     let account_info_iter = &mut accounts.iter().peekable();
     let reserve_info = next_account_info(account_info_iter)?;
     let reserve_liquidity_oracle_info = next_account_info(account_info_iter)?;
-    let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
     if reserve_info.owner != program_id {
         msg!("Reserve provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &reserve.liquidity.oracle_pubkey != reserve_liquidity_oracle_info.key {
+    let mut reserve_data = reserve_info.data.borrow_mut();
+    let (reserve, extension) = ReserveData::load_mut_with_extension(&mut reserve_data)?;
+    if &reserve.liquidity_oracle_pubkey() != reserve_liquidity_oracle_info.key {
         msg!("Reserve liquidity oracle does not match the reserve liquidity oracle provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
+    // only the current config is decoded, the rest of the refresh is
+    // written in place
+    let config = reserve.config(extension.as_deref())?;
     let clock = &Clock::get()?;
-    reserve.liquidity.market_price = get_pyth_price(reserve_liquidity_oracle_info, clock)?;
-    reserve.accrue_interest(clock.slot)?;
-    reserve.last_update.update_slot(clock.slot);
-    Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
+    let mut liquidity = reserve.liquidity();
+    liquidity.market_price = get_pyth_price(reserve_liquidity_oracle_info, clock)?;
+    reserve.set_liquidity(&liquidity);
+    reserve.accrue_interest(extension, &config, clock.slot, clock.unix_timestamp)?;
+    let mut last_update = reserve.last_update()?;
+    last_update.update(clock.slot, clock.unix_timestamp);
+    reserve.set_last_update(&last_update);
+
+    let liquidity = reserve.liquidity();
+    LendingEvent::InterestAccrued {
+        reserve: *reserve_info.key,
+        cumulative_borrow_rate_wads: liquidity.cumulative_borrow_rate_wads,
+        borrowed_amount_wads: liquidity.borrowed_amount_wads,
+        available_amount: liquidity.available_amount,
+    }
+    .emit();
 
     Ok(())
 }
@@ -2,8 +2,9 @@ use {
     super::{spl_token_transfer, TokenTransferParams},
     crate::{
         error::LendingError,
+        event::LendingEvent,
         math::Decimal,
-        state::{CalculateRepayResult, LendingMarket, Obligation, Reserve},
+        state::{CalculateRepayResult, LendingMarket, Obligation, ReserveData},
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -43,25 +44,26 @@ pub(super) fn process_repay_obligation_liquidity(
         return Err(LendingError::InvalidTokenProgram.into());
     }
 
-    let mut repay_reserve = Reserve::unpack(&repay_reserve_info.data.borrow())?;
     if repay_reserve_info.owner != program_id {
         msg!("Repay reserve provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &repay_reserve.lending_market != lending_market_info.key {
+    let mut repay_reserve_data = repay_reserve_info.data.borrow_mut();
+    let repay_reserve = ReserveData::load_mut(&mut repay_reserve_data)?;
+    if &repay_reserve.lending_market() != lending_market_info.key {
         msg!("Repay reserve lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &repay_reserve.liquidity.supply_pubkey == source_liquidity_info.key {
+    if &repay_reserve.liquidity_supply_pubkey() == source_liquidity_info.key {
         msg!("Repay reserve liquidity supply cannot be used as the source liquidity provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &repay_reserve.liquidity.supply_pubkey != destination_liquidity_info.key {
+    if &repay_reserve.liquidity_supply_pubkey() != destination_liquidity_info.key {
         msg!("Repay reserve liquidity supply must be used as the destination liquidity provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
     let clock = Clock::get()?;
-    if repay_reserve.last_update.is_stale(clock.slot)? {
+    if repay_reserve.last_update()?.is_stale(clock.slot)? {
         msg!("Repay reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
@@ -93,9 +95,12 @@ pub(super) fn process_repay_obligation_liquidity(
         msg!("Repay amount is zero");
         return Err(LendingError::RepayTooSmall.into());
     }
-    repay_reserve.liquidity.repay(repay_amount, settle_amount)?;
-    repay_reserve.last_update.mark_stale();
-    Reserve::pack(repay_reserve, &mut repay_reserve_info.data.borrow_mut())?;
+    let reserve_settle_amount = liquidity.reserve_settle_amount(settle_amount)?;
+    let mut repay_reserve_liquidity = repay_reserve.liquidity();
+    repay_reserve_liquidity.repay(repay_amount, reserve_settle_amount)?;
+    repay_reserve.set_liquidity(&repay_reserve_liquidity);
+    repay_reserve.mark_stale();
+    drop(repay_reserve_data);
 
     obligation.repay(settle_amount, liquidity_index)?;
     obligation.last_update.mark_stale();
@@ -109,5 +114,12 @@ pub(super) fn process_repay_obligation_liquidity(
         authority_signer_seeds: &[],
         token_program: token_program_id.clone(),
     })?;
+    LendingEvent::Repay {
+        obligation: *obligation_info.key,
+        reserve: *repay_reserve_info.key,
+        repay_amount,
+        settle_amount_wads: settle_amount,
+    }
+    .emit();
     Ok(())
 }
@@ -0,0 +1,98 @@
+use {
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{LendingMarket, Obligation},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program::invoke,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction, system_program,
+        sysvar::Sysvar,
+    },
+    std::cmp::Ordering,
+};
+
+#[inline(never)] // avoid stack frame limit
+pub(super) fn process_resize_obligation(
+    program_id: &Pubkey,
+    deposits_limit: u8,
+    borrows_limit: u8,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let obligation_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let obligation_owner_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    let _lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if !system_program::check_id(system_program_info.key) {
+        msg!("System program provided does not match the system program id");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+
+    let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
+    if obligation_info.owner != program_id {
+        msg!("Obligation provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &obligation.lending_market != lending_market_info.key {
+        msg!("Obligation lending market does not match the lending market provided");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    if &obligation.owner != obligation_owner_info.key {
+        msg!("Obligation owner does not match the obligation owner provided");
+        return Err(LendingError::InvalidObligationOwner.into());
+    }
+    if !obligation_owner_info.is_signer {
+        msg!("Obligation owner provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+
+    let new_len = obligation.resize(deposits_limit, borrows_limit)?;
+    let required_lamports = Rent::get()?.minimum_balance(new_len);
+    let current_lamports = obligation_info.lamports();
+    match required_lamports.cmp(&current_lamports) {
+        Ordering::Greater => {
+            invoke(
+                &system_instruction::transfer(
+                    obligation_owner_info.key,
+                    obligation_info.key,
+                    required_lamports - current_lamports,
+                ),
+                &[
+                    obligation_owner_info.clone(),
+                    obligation_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        Ordering::Less => {
+            let excess_lamports = current_lamports - required_lamports;
+            **obligation_info.try_borrow_mut_lamports()? = required_lamports;
+            let owner_lamports = obligation_owner_info.lamports();
+            **obligation_owner_info.try_borrow_mut_lamports()? = owner_lamports
+                .checked_add(excess_lamports)
+                .ok_or(LendingError::MathOverflow)?;
+        }
+        Ordering::Equal => {}
+    }
+
+    obligation_info.realloc(new_len, true)?;
+    Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
+    LendingEvent::ObligationChanged {
+        obligation: *obligation_info.key,
+    }
+    .emit();
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use {
+    super::assert_rent_exempt,
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{
+            DelegatePermissions, InitObligationDelegateParams, ObligationDelegate, ObligationHeader,
+        },
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::{IsInitialized, Pack},
+        pubkey::Pubkey,
+    },
+};
+
+pub(super) fn process_set_obligation_delegate(
+    program_id: &Pubkey,
+    delegate: Pubkey,
+    permissions: DelegatePermissions,
+    max_borrow_value: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let obligation_delegate_info = next_account_info(account_info_iter)?;
+    let obligation_info = next_account_info(account_info_iter)?;
+    let obligation_owner_info = next_account_info(account_info_iter)?;
+
+    assert_rent_exempt(obligation_delegate_info)?;
+    if obligation_delegate_info.owner != program_id {
+        msg!("Obligation delegate provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let mut obligation_delegate =
+        ObligationDelegate::unpack_unchecked(&obligation_delegate_info.data.borrow())?;
+    if obligation_delegate.is_initialized()
+        && &obligation_delegate.obligation != obligation_info.key
+    {
+        msg!("Obligation delegate was set for a different obligation");
+        return Err(LendingError::InvalidObligationDelegate.into());
+    }
+
+    let obligation_data = obligation_info.data.borrow();
+    let obligation = ObligationHeader::load(&obligation_data)?;
+    if obligation_info.owner != program_id {
+        msg!("Obligation provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &obligation.owner() != obligation_owner_info.key {
+        msg!("Obligation owner does not match the obligation owner provided");
+        return Err(LendingError::InvalidObligationOwner.into());
+    }
+    if !obligation_owner_info.is_signer {
+        msg!("Obligation owner provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+
+    obligation_delegate.init(InitObligationDelegateParams {
+        obligation: *obligation_info.key,
+        owner: obligation.owner(),
+        delegate,
+        permissions,
+        max_borrow_value,
+    });
+    ObligationDelegate::pack(
+        obligation_delegate,
+        &mut obligation_delegate_info.data.borrow_mut(),
+    )?;
+
+    LendingEvent::ObligationChanged {
+        obligation: *obligation_info.key,
+    }
+    .emit();
+    Ok(())
+}
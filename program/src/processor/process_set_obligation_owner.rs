@@ -0,0 +1,96 @@
+use {
+    super::{assert_rent_exempt, close_account},
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{InitObligationOwnerTransferParams, ObligationHeader, ObligationOwnerTransfer},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::{IsInitialized, Pack},
+        pubkey::Pubkey,
+    },
+};
+
+pub(super) fn process_set_obligation_owner(
+    program_id: &Pubkey,
+    new_owner: Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if new_owner == Pubkey::default() {
+        msg!("New obligation owner cannot be the default pubkey");
+        return Err(LendingError::InvalidObligationOwner.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+    let obligation_info = next_account_info(account_info_iter)?;
+    let obligation_owner_info = next_account_info(account_info_iter)?;
+    let obligation_owner_transfer_info = next_account_info(account_info_iter).ok();
+
+    let mut obligation_data = obligation_info.data.borrow_mut();
+    let obligation = ObligationHeader::load_mut(&mut obligation_data)?;
+    if obligation_info.owner != program_id {
+        msg!("Obligation provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let obligation_owner = obligation.owner();
+    if &obligation_owner != obligation_owner_info.key {
+        msg!("Obligation owner does not match the obligation owner provided");
+        return Err(LendingError::InvalidObligationOwner.into());
+    }
+    if !obligation_owner_info.is_signer {
+        msg!("Obligation owner provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+
+    let obligation_owner_transfer_info = match obligation_owner_transfer_info {
+        Some(obligation_owner_transfer_info) => obligation_owner_transfer_info,
+        None => {
+            obligation.set_owner(&new_owner);
+            drop(obligation_data);
+            LendingEvent::ObligationChanged {
+                obligation: *obligation_info.key,
+            }
+            .emit();
+            return Ok(());
+        }
+    };
+
+    drop(obligation_data);
+    assert_rent_exempt(obligation_owner_transfer_info)?;
+    if obligation_owner_transfer_info.owner != program_id {
+        msg!("Obligation owner transfer provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let mut obligation_owner_transfer =
+        ObligationOwnerTransfer::unpack_unchecked(&obligation_owner_transfer_info.data.borrow())?;
+    if obligation_owner_transfer.is_initialized()
+        && &obligation_owner_transfer.obligation != obligation_info.key
+    {
+        msg!("Obligation owner transfer was set for a different obligation");
+        return Err(LendingError::InvalidObligationOwnerTransfer.into());
+    }
+
+    if new_owner == obligation_owner {
+        // cancelling returns the rent to the owner, as accepting does
+        close_account(obligation_owner_transfer_info, obligation_owner_info)?;
+    } else {
+        obligation_owner_transfer.init(InitObligationOwnerTransferParams {
+            obligation: *obligation_info.key,
+            owner: obligation_owner,
+            new_owner,
+        });
+        ObligationOwnerTransfer::pack(
+            obligation_owner_transfer,
+            &mut obligation_owner_transfer_info.data.borrow_mut(),
+        )?;
+    }
+
+    LendingEvent::ObligationChanged {
+        obligation: *obligation_info.key,
+    }
+    .emit();
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use {
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{LendingMarket, Reserve},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::{Clock, UnixTimestamp},
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+pub(super) fn process_set_reserve_collateral_ramp(
+    program_id: &Pubkey,
+    loan_to_value_ratio_bps: u16,
+    liquidation_threshold_bps: u16,
+    start_timestamp: UnixTimestamp,
+    end_timestamp: UnixTimestamp,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let reserve_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let lending_market_owner_info = next_account_info(account_info_iter)?;
+
+    if reserve_info.owner != program_id {
+        msg!("Reserve provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &lending_market.owner != lending_market_owner_info.key {
+        msg!("Lending market owner does not match the lending market owner provided");
+        return Err(LendingError::InvalidMarketOwner.into());
+    }
+    if !lending_market_owner_info.is_signer {
+        msg!("Lending market owner provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+
+    let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+    if reserve.lending_market != *lending_market_info.key {
+        msg!("Reserve account does not match the lending market");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+
+    let clock = Clock::get()?;
+    let activation_timestamp = reserve.start_collateral_ramp(
+        loan_to_value_ratio_bps,
+        liquidation_threshold_bps,
+        start_timestamp,
+        end_timestamp,
+        clock.unix_timestamp,
+    )?;
+    msg!(
+        "Reserve config change can be applied from {}",
+        activation_timestamp
+    );
+    Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
+
+    LendingEvent::ReserveConfigChanged {
+        reserve: *reserve_info.key,
+    }
+    .emit();
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use {
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{LendingMarket, Reserve, ReserveRewards},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+pub(super) fn process_set_reserve_reward_emissions(
+    program_id: &Pubkey,
+    supply_emission_per_second: u64,
+    borrow_emission_per_second: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let reserve_rewards_info = next_account_info(account_info_iter)?;
+    let reserve_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let lending_market_owner_info = next_account_info(account_info_iter)?;
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &lending_market.owner != lending_market_owner_info.key {
+        msg!("Lending market owner does not match the lending market owner provided");
+        return Err(LendingError::InvalidMarketOwner.into());
+    }
+    if !lending_market_owner_info.is_signer {
+        msg!("Lending market owner provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+
+    let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+    if reserve_info.owner != program_id {
+        msg!("Reserve provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    // rewards emitted up to the last refresh use the previous emissions
+    if reserve.last_update.is_stale(Clock::get()?.slot)? {
+        msg!("Reserve is stale and must be refreshed in the current slot");
+        return Err(LendingError::ReserveStale.into());
+    }
+
+    let reserve_rewards = ReserveRewards::unpack(&reserve_rewards_info.data.borrow())?;
+    if reserve_rewards_info.owner != program_id {
+        msg!("Reserve rewards provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    if &reserve_rewards.lending_market != lending_market_info.key
+        || &reserve_rewards.reserve != reserve_info.key
+    {
+        msg!("Reserve rewards do not match the reserve and lending market provided");
+        return Err(LendingError::InvalidReserveRewards.into());
+    }
+
+    let Some(reward_emissions) = &mut reserve.reward_emissions else {
+        msg!("Reserve does not emit rewards");
+        return Err(LendingError::InvalidReserveRewards.into());
+    };
+    reward_emissions.supply_emission_per_second = supply_emission_per_second;
+    reward_emissions.borrow_emission_per_second = borrow_emission_per_second;
+    Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
+
+    LendingEvent::ReserveRewardsChanged {
+        reserve_rewards: *reserve_rewards_info.key,
+    }
+    .emit();
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use {
+    crate::{
+        error::LendingError,
+        event::LendingEvent,
+        state::{LendingMarket, ReserveData, ReserveStatus},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_pack::Pack,
+        pubkey::Pubkey,
+    },
+};
+
+pub(super) fn process_set_reserve_status(
+    program_id: &Pubkey,
+    status: ReserveStatus,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let reserve_info = next_account_info(account_info_iter)?;
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+
+    if reserve_info.owner != program_id {
+        msg!("Reserve provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        msg!("Lending market provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let is_owner = &lending_market.owner == authority_info.key;
+    if !is_owner && !lending_market.is_guardian(authority_info.key) {
+        msg!("Lending market owner or guardian does not match the authority provided");
+        return Err(LendingError::InvalidMarketOwner.into());
+    }
+    if !authority_info.is_signer {
+        msg!("Lending market authority provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+
+    let mut reserve_data = reserve_info.data.borrow_mut();
+    let reserve = ReserveData::load_mut(&mut reserve_data)?;
+    if reserve.lending_market() != *lending_market_info.key {
+        msg!("Reserve account does not match the lending market");
+        return Err(LendingError::InvalidAccountInput.into());
+    }
+    if !is_owner && status < reserve.status()? {
+        msg!("Guardian can only make the reserve status more restrictive");
+        return Err(LendingError::RoleNotPermitted.into());
+    }
+
+    reserve.set_status(status);
+
+    LendingEvent::ReserveConfigChanged {
+        reserve: *reserve_info.key,
+    }
+    .emit();
+    Ok(())
+}
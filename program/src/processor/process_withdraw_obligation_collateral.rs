@@ -1,9 +1,10 @@
 use {
-    super::{spl_token_transfer, TokenTransferParams},
+    super::{spl_token_transfer, validate_obligation_authority, TokenTransferParams},
     crate::{
         error::LendingError,
+        event::LendingEvent,
         math::{Decimal, Rate, TryDiv, TryMul},
-        state::{LendingMarket, Obligation, Reserve},
+        state::{DelegatePermissions, LendingMarket, Obligation, Reserve, ReserveStatus},
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -69,6 +70,10 @@ pub(super) fn process_withdraw_obligation_collateral(
         msg!("Withdraw reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    if withdraw_reserve.status == ReserveStatus::Paused {
+        msg!("Withdraw reserve does not allow withdrawals while paused");
+        return Err(LendingError::ReserveStatusRestricted.into());
+    }
 
     let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
     if obligation_info.owner != program_id {
@@ -79,13 +84,21 @@ pub(super) fn process_withdraw_obligation_collateral(
         msg!("Obligation lending market does not match the lending market provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
-    if &obligation.owner != obligation_owner_info.key {
-        msg!("Obligation owner does not match the obligation owner provided");
-        return Err(LendingError::InvalidObligationOwner.into());
-    }
-    if !obligation_owner_info.is_signer {
-        msg!("Obligation owner provided must be a signer");
-        return Err(LendingError::InvalidSigner.into());
+    let obligation_delegate = validate_obligation_authority(
+        program_id,
+        obligation_info.key,
+        &obligation,
+        obligation_owner_info,
+        account_info_iter,
+        DelegatePermissions::WITHDRAW,
+    )?;
+    if obligation_delegate.is_some() {
+        let destination_collateral =
+            spl_token::state::Account::unpack(&destination_collateral_info.data.borrow())?;
+        if destination_collateral.owner != obligation.owner {
+            msg!("Obligation delegate can only withdraw to token accounts of the obligation owner");
+            return Err(LendingError::DelegateNotPermitted.into());
+        }
     }
     if obligation.last_update.is_stale(clock.slot)? {
         msg!("Obligation is stale and must be refreshed in the current slot");
@@ -122,8 +135,10 @@ pub(super) fn process_withdraw_obligation_collateral(
         msg!("Obligation deposited value is zero");
         return Err(LendingError::ObligationDepositsZero.into());
     } else {
-        let max_withdraw_value = obligation.max_withdraw_value(Rate::from_percent(
-            withdraw_reserve.config.loan_to_value_ratio,
+        let max_withdraw_value = obligation.max_withdraw_value(Rate::from_bps(
+            withdraw_reserve
+                .loan_to_value_ratio_bps(clock.unix_timestamp)
+                .into(),
         ))?;
         if max_withdraw_value == Decimal::zero() {
             msg!("Maximum withdraw value is zero");
@@ -168,5 +183,11 @@ pub(super) fn process_withdraw_obligation_collateral(
         token_program: token_program_id.clone(),
     })?;
 
+    LendingEvent::WithdrawObligationCollateral {
+        obligation: *obligation_info.key,
+        reserve: *withdraw_reserve_info.key,
+        collateral_amount: withdraw_amount,
+    }
+    .emit();
     Ok(())
 }
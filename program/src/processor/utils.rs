@@ -1,12 +1,18 @@
 use {
-    crate::error::LendingError,
+    crate::{
+        error::LendingError,
+        state::{DelegatePermissions, Obligation, ObligationDelegate},
+    },
     solana_program::{
-        account_info::AccountInfo,
+        account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
         msg,
+        program::{invoke, invoke_signed},
         program_error::ProgramError,
         program_pack::{IsInitialized, Pack},
+        pubkey::Pubkey,
         rent::Rent,
+        system_instruction, system_program,
         sysvar::Sysvar,
     },
 };
@@ -33,3 +39,123 @@ pub(super) fn assert_rent_exempt(account_info: &AccountInfo) -> ProgramResult {
         Ok(())
     }
 }
+
+/// Transfer the lamports an account is missing to be rent exempt with the
+/// given data length from the payer
+pub(super) fn fund_rent_exemption<'a>(
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+) -> ProgramResult {
+    let required_lamports = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account_info.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, required_lamports),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Close an account owned by the program, sending its lamports to the
+/// destination and handing the emptied account back to the system program
+pub(super) fn close_account(
+    account_info: &AccountInfo,
+    destination_info: &AccountInfo,
+) -> ProgramResult {
+    let destination_lamports = destination_info
+        .lamports()
+        .checked_add(account_info.lamports())
+        .ok_or(LendingError::MathOverflow)?;
+    **destination_info.try_borrow_mut_lamports()? = destination_lamports;
+    **account_info.try_borrow_mut_lamports()? = 0;
+    account_info.realloc(0, false)?;
+    account_info.assign(&system_program::id());
+    Ok(())
+}
+
+/// Create an account at a program derived address, funded by the payer. The
+/// address may already hold lamports, so the account is funded up to rent
+/// exemption, allocated and assigned instead of created, and lamports sent
+/// ahead of time cannot block its creation.
+pub(super) fn create_pda_account<'a>(
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    if !account_info.data_is_empty() {
+        msg!("Account is already initialized");
+        return Err(LendingError::AlreadyInitialized.into());
+    }
+    fund_rent_exemption(payer_info, account_info, system_program_info, space)?;
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, space as u64),
+        &[account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account_info.key, owner),
+        &[account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Check the authority is the signing obligation owner, or a signing delegate
+/// of the obligation with the given permissions. The obligation delegate
+/// account is read from the accounts iterator only if the authority is not
+/// the owner, and returned.
+pub(super) fn validate_obligation_authority<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    obligation_pubkey: &Pubkey,
+    obligation: &Obligation,
+    authority_info: &AccountInfo,
+    account_info_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+    permissions: DelegatePermissions,
+) -> Result<Option<ObligationDelegate>, ProgramError> {
+    if &obligation.owner == authority_info.key {
+        if !authority_info.is_signer {
+            msg!("Obligation owner provided must be a signer");
+            return Err(LendingError::InvalidSigner.into());
+        }
+        return Ok(None);
+    }
+
+    let obligation_delegate_info = next_account_info(account_info_iter).map_err(|_| {
+        msg!("Obligation owner does not match the obligation owner provided");
+        LendingError::InvalidObligationOwner
+    })?;
+    if obligation_delegate_info.owner != program_id {
+        msg!("Obligation delegate provided is not owned by the lending program");
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+    let obligation_delegate = ObligationDelegate::unpack(&obligation_delegate_info.data.borrow())?;
+    if &obligation_delegate.obligation != obligation_pubkey
+        || &obligation_delegate.delegate != authority_info.key
+    {
+        msg!("Obligation delegate does not match the obligation and authority provided");
+        return Err(LendingError::InvalidObligationDelegate.into());
+    }
+    if obligation_delegate.owner != obligation.owner {
+        msg!("Obligation delegate was registered by a previous obligation owner");
+        return Err(LendingError::InvalidObligationDelegate.into());
+    }
+    if !authority_info.is_signer {
+        msg!("Obligation delegate provided must be a signer");
+        return Err(LendingError::InvalidSigner.into());
+    }
+    if !obligation_delegate.permissions.contains(permissions) {
+        msg!("Obligation delegate does not have the permission for this action");
+        return Err(LendingError::DelegateNotPermitted.into());
+    }
+    Ok(Some(obligation_delegate))
+}
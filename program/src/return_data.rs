@@ -0,0 +1,294 @@
+//! Data returned by lending instructions
+//!
+//! Instructions moving tokens set the amounts they computed as return data,
+//! so programs invoking them can read the result without reloading accounts.
+//! `GetObligationHealth` only sets return data.
+
+use {
+    crate::{
+        error::LendingError,
+        math::Decimal,
+        state::{CalculateBorrowResult, CalculateLiquidationResult},
+    },
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    solana_program::{
+        msg,
+        program::{get_return_data, set_return_data},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+/// Data returned by a lending instruction
+pub trait LendingReturnData: Sized {
+    /// Length of the packed return data
+    const LEN: usize;
+
+    /// Pack into the returned bytes
+    fn pack_into_slice(&self, output: &mut [u8]);
+
+    /// Unpack from the returned bytes
+    fn unpack_from_slice(input: &[u8]) -> Self;
+
+    /// Set as the return data of the current instruction
+    fn set(&self) {
+        let mut output = vec![0; Self::LEN];
+        self.pack_into_slice(&mut output);
+        set_return_data(&output);
+    }
+
+    /// Unpack return data, checking its length
+    fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != Self::LEN {
+            msg!("Return data length does not match the expected length");
+            return Err(LendingError::InvalidReturnData.into());
+        }
+        Ok(Self::unpack_from_slice(input))
+    }
+
+    /// Get the data returned by the lending program, after invoking one of
+    /// its instructions
+    fn get(program_id: &Pubkey) -> Result<Self, ProgramError> {
+        match get_return_data() {
+            Some((returning_program_id, data)) if &returning_program_id == program_id => {
+                Self::unpack(&data)
+            }
+            _ => {
+                msg!("Lending program did not set return data");
+                Err(LendingError::InvalidReturnData.into())
+            }
+        }
+    }
+}
+
+/// Data returned by `DepositReserveLiquidity`
+#[derive(Debug, PartialEq)]
+pub struct DepositReserveLiquidityResult {
+    /// Amount of collateral minted in exchange for the liquidity deposited
+    pub collateral_amount: u64,
+}
+
+impl LendingReturnData for DepositReserveLiquidityResult {
+    const LEN: usize = 8;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        *array_mut_ref![output, 0, 8] = self.collateral_amount.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Self {
+        Self {
+            collateral_amount: u64::from_le_bytes(*array_ref![input, 0, 8]),
+        }
+    }
+}
+
+/// Data returned by `RedeemReserveCollateral`
+#[derive(Debug, PartialEq)]
+pub struct RedeemReserveCollateralResult {
+    /// Amount of liquidity withdrawn in exchange for the collateral redeemed
+    pub liquidity_amount: u64,
+}
+
+impl LendingReturnData for RedeemReserveCollateralResult {
+    const LEN: usize = 8;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        *array_mut_ref![output, 0, 8] = self.liquidity_amount.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Self {
+        Self {
+            liquidity_amount: u64::from_le_bytes(*array_ref![input, 0, 8]),
+        }
+    }
+}
+
+/// Returned by `BorrowObligationLiquidity` and
+/// `BorrowObligationLiquidityFixedRate`, with the host fee actually paid to
+/// the host fee receiver
+impl LendingReturnData for CalculateBorrowResult {
+    const LEN: usize = 40; // 16 + 8 + 8 + 8
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 40];
+        let (borrow_amount, receive_amount, borrow_fee, host_fee) =
+            mut_array_refs![output, 16, 8, 8, 8];
+        pack_decimal(self.borrow_amount, borrow_amount);
+        *receive_amount = self.receive_amount.to_le_bytes();
+        *borrow_fee = self.borrow_fee.to_le_bytes();
+        *host_fee = self.host_fee.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Self {
+        let input = array_ref![input, 0, 40];
+        let (borrow_amount, receive_amount, borrow_fee, host_fee) = array_refs![input, 16, 8, 8, 8];
+        Self {
+            borrow_amount: unpack_decimal(borrow_amount),
+            receive_amount: u64::from_le_bytes(*receive_amount),
+            borrow_fee: u64::from_le_bytes(*borrow_fee),
+            host_fee: u64::from_le_bytes(*host_fee),
+        }
+    }
+}
+
+/// Returned by `LiquidateObligation`
+impl LendingReturnData for CalculateLiquidationResult {
+    const LEN: usize = 40; // 16 + 8 + 8 + 8
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 40];
+        let (settle_amount, repay_amount, withdraw_amount, protocol_fee) =
+            mut_array_refs![output, 16, 8, 8, 8];
+        pack_decimal(self.settle_amount, settle_amount);
+        *repay_amount = self.repay_amount.to_le_bytes();
+        *withdraw_amount = self.withdraw_amount.to_le_bytes();
+        *protocol_fee = self.protocol_fee.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Self {
+        let input = array_ref![input, 0, 40];
+        let (settle_amount, repay_amount, withdraw_amount, protocol_fee) =
+            array_refs![input, 16, 8, 8, 8];
+        Self {
+            settle_amount: unpack_decimal(settle_amount),
+            repay_amount: u64::from_le_bytes(*repay_amount),
+            withdraw_amount: u64::from_le_bytes(*withdraw_amount),
+            protocol_fee: u64::from_le_bytes(*protocol_fee),
+        }
+    }
+}
+
+/// Data returned by `GetObligationHealth`, valued as `RefreshObligation`
+/// would value the obligation in the current slot
+#[derive(Debug, PartialEq)]
+pub struct ObligationHealthResult {
+    /// Market value of deposits
+    pub deposited_value: Decimal,
+    /// Market value of borrows
+    pub borrowed_value: Decimal,
+    /// The maximum borrow value at the weighted average loan to value ratio
+    pub allowed_borrow_value: Decimal,
+    /// The dangerous borrow value at the weighted average liquidation
+    /// threshold
+    pub unhealthy_borrow_value: Decimal,
+    /// Unhealthy borrow value over borrowed value, see
+    /// `Obligation::health_factor`
+    pub health_factor: Decimal,
+}
+
+impl LendingReturnData for ObligationHealthResult {
+    const LEN: usize = 80; // 16 + 16 + 16 + 16 + 16
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 80];
+        let (
+            deposited_value,
+            borrowed_value,
+            allowed_borrow_value,
+            unhealthy_borrow_value,
+            health_factor,
+        ) = mut_array_refs![output, 16, 16, 16, 16, 16];
+        pack_decimal(self.deposited_value, deposited_value);
+        pack_decimal(self.borrowed_value, borrowed_value);
+        pack_decimal(self.allowed_borrow_value, allowed_borrow_value);
+        pack_decimal(self.unhealthy_borrow_value, unhealthy_borrow_value);
+        pack_decimal(self.health_factor, health_factor);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Self {
+        let input = array_ref![input, 0, 80];
+        let (
+            deposited_value,
+            borrowed_value,
+            allowed_borrow_value,
+            unhealthy_borrow_value,
+            health_factor,
+        ) = array_refs![input, 16, 16, 16, 16, 16];
+        Self {
+            deposited_value: unpack_decimal(deposited_value),
+            borrowed_value: unpack_decimal(borrowed_value),
+            allowed_borrow_value: unpack_decimal(allowed_borrow_value),
+            unhealthy_borrow_value: unpack_decimal(unhealthy_borrow_value),
+            health_factor: unpack_decimal(health_factor),
+        }
+    }
+}
+
+fn pack_decimal(decimal: Decimal, dst: &mut [u8; 16]) {
+    // returned amounts are settled against account balances, which cannot
+    // exceed 128 bits either
+    *dst = decimal.to_scaled_val().unwrap_or(u128::MAX).to_le_bytes();
+}
+
+fn unpack_decimal(src: &[u8; 16]) -> Decimal {
+    Decimal::from_scaled_val(u128::from_le_bytes(*src))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pack<T: LendingReturnData>(data: &T) -> Vec<u8> {
+        let mut output = vec![0; T::LEN];
+        data.pack_into_slice(&mut output);
+        output
+    }
+
+    #[test]
+    fn pack_return_data() {
+        let deposit = DepositReserveLiquidityResult {
+            collateral_amount: 1,
+        };
+        assert_eq!(
+            DepositReserveLiquidityResult::unpack(&pack(&deposit)).unwrap(),
+            deposit
+        );
+
+        let redeem = RedeemReserveCollateralResult {
+            liquidity_amount: u64::MAX,
+        };
+        assert_eq!(
+            RedeemReserveCollateralResult::unpack(&pack(&redeem)).unwrap(),
+            redeem
+        );
+
+        let borrow = CalculateBorrowResult {
+            borrow_amount: Decimal::from_scaled_val(1_234_567),
+            receive_amount: 1,
+            borrow_fee: 2,
+            host_fee: 3,
+        };
+        assert_eq!(
+            CalculateBorrowResult::unpack(&pack(&borrow)).unwrap(),
+            borrow
+        );
+
+        let liquidation = CalculateLiquidationResult {
+            settle_amount: Decimal::from(5u64),
+            repay_amount: 5,
+            withdraw_amount: 6,
+            protocol_fee: 1,
+        };
+        assert_eq!(
+            CalculateLiquidationResult::unpack(&pack(&liquidation)).unwrap(),
+            liquidation
+        );
+
+        let health = ObligationHealthResult {
+            deposited_value: Decimal::from(100u64),
+            borrowed_value: Decimal::from(50u64),
+            allowed_borrow_value: Decimal::from(55u64),
+            unhealthy_borrow_value: Decimal::from(60u64),
+            health_factor: Decimal::from_scaled_val(u128::MAX),
+        };
+        assert_eq!(
+            ObligationHealthResult::unpack(&pack(&health)).unwrap(),
+            health
+        );
+
+        assert_eq!(
+            CalculateLiquidationResult::unpack(&pack(&deposit)),
+            Err(LendingError::InvalidReturnData.into())
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use {
     crate::error::LendingError,
-    solana_program::{clock::Slot, program_error::ProgramError},
+    solana_program::{
+        clock::{Slot, UnixTimestamp},
+        program_error::ProgramError,
+    },
 };
 
 /// Number of slots to consider stale after
@@ -10,11 +13,18 @@ pub const STALE_AFTER_SLOTS_ELAPSED: u64 = 1;
 pub struct LastUpdate {
     pub slot: Slot,
     pub stale: bool,
+    /// Unix timestamp of the last update, 0 if never recorded
+    /// Only persisted on reserves, which accrue interest over time
+    pub unix_timestamp: UnixTimestamp,
 }
 
 impl LastUpdate {
     pub fn new(slot: Slot) -> Self {
-        Self { slot, stale: true }
+        Self {
+            slot,
+            stale: true,
+            unix_timestamp: 0,
+        }
     }
     pub fn slots_elapsed(&self, slot: Slot) -> Result<u64, ProgramError> {
         let elapsed = slot
@@ -23,12 +33,27 @@ impl LastUpdate {
         Ok(elapsed)
     }
 
+    /// Seconds elapsed since the last update, or `None` if no timestamp was
+    /// recorded. A clock going backwards counts as no time elapsed.
+    pub fn seconds_elapsed(&self, unix_timestamp: UnixTimestamp) -> Option<u64> {
+        if self.unix_timestamp == 0 {
+            return None;
+        }
+        Some(u64::try_from(unix_timestamp.saturating_sub(self.unix_timestamp)).unwrap_or(0))
+    }
+
     /// Set last update slot
     pub fn update_slot(&mut self, slot: Slot) {
         self.slot = slot;
         self.stale = false;
     }
 
+    /// Set last update slot and unix timestamp
+    pub fn update(&mut self, slot: Slot, unix_timestamp: UnixTimestamp) {
+        self.update_slot(slot);
+        self.unix_timestamp = unix_timestamp;
+    }
+
     /// Set stale to true
     pub fn mark_stale(&mut self) {
         self.stale = true;
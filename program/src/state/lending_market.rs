@@ -1,5 +1,5 @@
 use {
-    super::{PROGRAM_VERSION, UNINITIALIZED_VERSION},
+    super::{Migrate, PROGRAM_VERSION, UNINITIALIZED_VERSION},
     arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
     solana_program::{
         msg,
@@ -44,6 +44,15 @@ pub struct LendingMarket {
     pub token_program_id: Pubkey,
     /// Oracle (Pyth) program id
     pub oracle_program_id: Pubkey,
+    /// Proposed owner which must accept ownership before it is transferred
+    /// Default pubkey if no transfer is pending
+    pub pending_owner: Pubkey,
+    /// Risk manager authority which can lower reserve loan to value ratios
+    /// and limits, default pubkey if unset
+    pub risk_manager: Pubkey,
+    /// Guardian authority which can pause reserves or make them reduce-only,
+    /// default pubkey if unset
+    pub guardian: Pubkey,
 }
 
 impl Sealed for LendingMarket {}
@@ -69,9 +78,39 @@ impl LendingMarket {
         self.quote_currency = params.quote_currency;
         self.token_program_id = params.token_program_id;
         self.oracle_program_id = params.oracle_program_id;
+        self.pending_owner = Pubkey::default();
+        self.risk_manager = Pubkey::default();
+        self.guardian = Pubkey::default();
+    }
+
+    /// Pending owner of the lending market, if an ownership transfer was
+    /// proposed
+    pub fn pending_owner(&self) -> Option<Pubkey> {
+        if self.pending_owner == Pubkey::default() {
+            None
+        } else {
+            Some(self.pending_owner)
+        }
+    }
+
+    /// Whether the key is the risk manager of the lending market
+    pub fn is_risk_manager(&self, key: &Pubkey) -> bool {
+        self.risk_manager != Pubkey::default() && &self.risk_manager == key
+    }
+
+    /// Whether the key is the guardian of the lending market
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        self.guardian != Pubkey::default() && &self.guardian == key
+    }
+}
+const LENDING_MARKET_LEN: usize = 258; // 1 + 1 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 32
+impl Migrate for LendingMarket {
+    /// Lending markets have a single layout
+    fn is_outdated(_version: u8) -> bool {
+        false
     }
 }
-const LENDING_MARKET_LEN: usize = 258; // 1 + 1 + 32 + 32 + 32 + 32 + 128
+
 impl Pack for LendingMarket {
     const LEN: usize = LENDING_MARKET_LEN;
     fn pack_into_slice(&self, output: &mut [u8]) {
@@ -84,6 +123,9 @@ impl Pack for LendingMarket {
             quote_currency,
             token_program_id,
             oracle_program_id,
+            pending_owner,
+            risk_manager,
+            guardian,
             _padding,
         ) = mut_array_refs![
             output,
@@ -93,7 +135,10 @@ impl Pack for LendingMarket {
             32,
             PUBKEY_BYTES,
             PUBKEY_BYTES,
-            128
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            32
         ];
         *version = self.version.to_le_bytes();
         *bump_seed = self.bump_seed.to_le_bytes();
@@ -101,6 +146,9 @@ impl Pack for LendingMarket {
         quote_currency.copy_from_slice(self.quote_currency.as_ref());
         token_program_id.copy_from_slice(self.token_program_id.as_ref());
         oracle_program_id.copy_from_slice(self.oracle_program_id.as_ref());
+        pending_owner.copy_from_slice(self.pending_owner.as_ref());
+        risk_manager.copy_from_slice(self.risk_manager.as_ref());
+        guardian.copy_from_slice(self.guardian.as_ref());
     }
     /// Unpacks a byte buffer into a
     /// [LendingMarketInfo](struct.LendingMarketInfo.html)
@@ -113,6 +161,9 @@ impl Pack for LendingMarket {
             quote_currency,
             token_program_id,
             oracle_program_id,
+            pending_owner,
+            risk_manager,
+            guardian,
             _padding,
         ) = array_refs![
             input,
@@ -122,12 +173,18 @@ impl Pack for LendingMarket {
             32,
             PUBKEY_BYTES,
             PUBKEY_BYTES,
-            128
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            32
         ];
         let version = u8::from_le_bytes(*version);
-        if version > PROGRAM_VERSION {
-            msg!("Lending market version does not match lending program version");
-            return Err(ProgramError::InvalidAccountData);
+        match version {
+            UNINITIALIZED_VERSION | PROGRAM_VERSION => {}
+            _ => {
+                msg!("Lending market version does not match lending program version");
+                return Err(ProgramError::InvalidAccountData);
+            }
         }
 
         Ok(Self {
@@ -137,6 +194,48 @@ impl Pack for LendingMarket {
             quote_currency: *quote_currency,
             token_program_id: Pubkey::new_from_array(*token_program_id),
             oracle_program_id: Pubkey::new_from_array(*oracle_program_id),
+            pending_owner: Pubkey::new_from_array(*pending_owner),
+            risk_manager: Pubkey::new_from_array(*risk_manager),
+            guardian: Pubkey::new_from_array(*guardian),
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lending_market_versions() {
+        assert!(!LendingMarket::is_outdated(PROGRAM_VERSION));
+
+        let lending_market = LendingMarket::new(InitLendingMarketParams {
+            bump_seed: 255,
+            owner: Pubkey::new_unique(),
+            quote_currency: [1; 32],
+            token_program_id: Pubkey::new_unique(),
+            oracle_program_id: Pubkey::new_unique(),
+        });
+        assert_eq!(lending_market.pending_owner(), None);
+        let mut data = [0; LendingMarket::LEN];
+        LendingMarket::pack(lending_market.clone(), &mut data).unwrap();
+        assert_eq!(LendingMarket::unpack(&data).unwrap(), lending_market);
+
+        let lending_market = LendingMarket {
+            pending_owner: Pubkey::new_unique(),
+            risk_manager: Pubkey::new_unique(),
+            guardian: Pubkey::new_unique(),
+            ..lending_market
+        };
+        LendingMarket::pack(lending_market.clone(), &mut data).unwrap();
+        let unpacked = LendingMarket::unpack(&data).unwrap();
+        assert_eq!(unpacked.pending_owner(), Some(lending_market.pending_owner));
+        assert_eq!(unpacked, lending_market);
+
+        data[0] = PROGRAM_VERSION + 1;
+        assert_eq!(
+            LendingMarket::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
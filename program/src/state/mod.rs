@@ -1,16 +1,24 @@
 mod last_update;
 mod lending_market;
 pub mod obligation;
+mod obligation_delegate;
+mod obligation_owner_transfer;
 pub mod reserve;
+mod reserve_rewards;
 use {
     crate::math::Decimal,
+    num_derive::FromPrimitive,
     solana_program::{
         clock::{DEFAULT_TICKS_PER_SECOND, DEFAULT_TICKS_PER_SLOT, SECONDS_PER_DAY},
         msg,
         program_error::ProgramError,
+        program_pack::{IsInitialized, Pack},
     },
 };
-pub use {crate::math::WAD, lending_market::*, obligation::*, reserve::*};
+pub use {
+    crate::math::WAD, lending_market::*, obligation::*, obligation_delegate::*,
+    obligation_owner_transfer::*, reserve::*, reserve_rewards::*,
+};
 
 pub const PROGRAM_VERSION: u8 = 1;
 
@@ -24,6 +32,40 @@ const INITIAL_COLLATERAL_RATE: u64 = INITIAL_COLLATERAL_RATIO * WAD;
 /// will have the version set to 0.
 pub const UNINITIALIZED_VERSION: u8 = 0;
 
+/// Account state stored with a versioned layout
+///
+/// Unpacking dispatches on the version byte stored first in the account.
+/// Older layouts fitting in the same account are upgraded in memory, so
+/// packing writes the latest of them. Layouts needing a larger account are
+/// kept until the account is rewritten by `MigrateAccount`.
+pub trait Migrate: Pack + IsInitialized {
+    /// Whether an account stored with a version uses an outdated layout
+    fn is_outdated(version: u8) -> bool;
+
+    /// Move the state to the latest layout, returning the account data length
+    /// the layout needs
+    fn migrate(&mut self) -> usize {
+        Self::LEN
+    }
+}
+
+/// Kind of state stored in an account owned by the lending program
+#[derive(Clone, Copy, Debug, PartialEq, FromPrimitive)]
+pub enum AccountType {
+    /// Lending market
+    LendingMarket,
+    /// Reserve
+    Reserve,
+    /// Obligation
+    Obligation,
+    /// Obligation delegate
+    ObligationDelegate,
+    /// Obligation owner transfer
+    ObligationOwnerTransfer,
+    /// Reserve rewards
+    ReserveRewards,
+}
+
 fn pack_bool(bool: bool, dst: &mut [u8; 1]) {
     *dst = (bool as u8).to_le_bytes();
 }
@@ -50,6 +92,8 @@ fn pack_decimal(decimal: Decimal, dst: &mut [u8; 16]) {
         .expect("Decimal cannot be packed")
         .to_le_bytes();
 }
+/// Number of seconds per year
+pub const SECONDS_PER_YEAR: u64 = SECONDS_PER_DAY * 365;
 /// Number of slots per year
 pub const SLOTS_PER_YEAR: u64 =
     DEFAULT_TICKS_PER_SECOND / DEFAULT_TICKS_PER_SLOT * SECONDS_PER_DAY * 365;
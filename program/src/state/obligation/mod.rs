@@ -2,23 +2,75 @@ mod obligation_collateral;
 mod obligation_liquidity;
 mod pack;
 mod test;
+mod zero_copy;
 
 use {
     super::{last_update::LastUpdate, LIQUIDATION_CLOSE_FACTOR, PROGRAM_VERSION},
     crate::{
         error::LendingError,
-        math::{Decimal, Rate, TryDiv, TryMul, TrySub},
+        math::{Decimal, Rate, TryAdd, TryDiv, TryMul, TrySub},
     },
     solana_program::{
-        clock::Slot, entrypoint::ProgramResult, msg, program_error::ProgramError, pubkey::Pubkey,
+        clock::{Slot, UnixTimestamp},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
     },
 };
-pub use {obligation_collateral::*, obligation_liquidity::*};
+pub use {obligation_collateral::*, obligation_liquidity::*, zero_copy::*};
 
-/// Max number of collateral and liquidity reserve accounts combined for an
-/// obligation
+/// Seed prefix of obligation addresses derived by `find_obligation_address`
+pub const OBLIGATION_SEED: &[u8] = b"obligation";
+
+/// Find the obligation address an owner can initialize in a lending market for
+/// a seed id, letting one owner hold several obligations per market
+pub fn find_obligation_address(
+    program_id: &Pubkey,
+    lending_market: &Pubkey,
+    owner: &Pubkey,
+    seed_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            OBLIGATION_SEED,
+            lending_market.as_ref(),
+            owner.as_ref(),
+            &seed_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Max number of collateral and liquidity reserve accounts combined for a
+/// fixed size obligation
 pub const MAX_OBLIGATION_RESERVES: usize = 10;
 
+/// Max number of collateral or liquidity reserve accounts for a resized
+/// obligation, each limit is counted separately
+pub const MAX_RESIZED_OBLIGATION_RESERVES: u8 = 32;
+
+/// Obligation version using the fixed size layout, holding up to
+/// `MAX_OBLIGATION_RESERVES` deposits and borrows combined
+pub const OBLIGATION_VERSION_FIXED: u8 = PROGRAM_VERSION;
+
+/// Obligation version using the resizable layout, with separate deposit and
+/// borrow limits
+pub const OBLIGATION_VERSION_RESIZABLE: u8 = 2;
+
+/// Obligation version storing the referrer after the limits of the resizable
+/// layout
+pub const OBLIGATION_VERSION_REFERRER: u8 = 3;
+
+/// Obligation version storing the fixed rate terms of each borrow, on top of
+/// the referrer and the resizable layout
+pub const OBLIGATION_VERSION_FIXED_RATE_BORROWS: u8 = 4;
+
+/// Obligation version storing the rewards earned by each deposit and borrow,
+/// on top of the fixed rate terms. Latest obligation version, set by
+/// `ResizeObligation` and `MigrateAccount`
+pub const OBLIGATION_VERSION_REWARDS: u8 = 5;
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Obligation {
     /// Version of the struct
@@ -42,6 +94,13 @@ pub struct Obligation {
     pub allowed_borrow_value: Decimal,
     /// The dangerous borrow value at the weighted average liquidation threshold
     pub unhealthy_borrow_value: Decimal,
+    /// Max number of deposits, only used by resizable obligations
+    pub deposits_limit: u8,
+    /// Max number of borrows, only used by resizable obligations
+    pub borrows_limit: u8,
+    /// Referrer collecting the host share of borrow fees, only stored by
+    /// resizable obligations and only recorded at initialization
+    pub referrer: Pubkey,
 }
 impl Obligation {
     pub fn new(params: InitObligationParams) -> Self {
@@ -59,22 +118,51 @@ impl Obligation {
         self.borrows = params.borrows;
     }
 
+    /// Whether the obligation uses a resizable layout
+    pub fn is_resizable(&self) -> bool {
+        self.version == OBLIGATION_VERSION_REWARDS
+            || self.version == OBLIGATION_VERSION_FIXED_RATE_BORROWS
+            || self.version == OBLIGATION_VERSION_REFERRER
+            || self.version == OBLIGATION_VERSION_RESIZABLE
+    }
+
+    /// Referrer of the obligation, if one was recorded at initialization.
+    /// Always None for obligations initialized with the fixed layout, which
+    /// keep no referrer after being upgraded by `MigrateAccount` or
+    /// `ResizeObligation`.
+    pub fn referrer(&self) -> Option<Pubkey> {
+        if self.referrer == Pubkey::default() {
+            None
+        } else {
+            Some(self.referrer)
+        }
+    }
+
     pub fn load_to_value(&self) -> Result<Decimal, ProgramError> {
         self.borrowed_value.try_div(self.deposited_value)
     }
 
+    /// Repay borrowed liquidity, removing the borrow once fully repaid unless
+    /// it holds rewards to claim
     pub fn repay(&mut self, settle_amount: Decimal, liquidity_index: usize) -> ProgramResult {
         let liquidity = &mut self.borrows[liquidity_index];
         if settle_amount == liquidity.borrowed_amount_wads {
-            self.borrows.remove(liquidity_index);
+            if liquidity.has_unclaimed_rewards() {
+                liquidity.borrowed_amount_wads = Decimal::zero();
+                liquidity.fixed_rate = None;
+            } else {
+                self.borrows.remove(liquidity_index);
+            }
         } else {
             liquidity.repay(settle_amount)?;
         }
         Ok(())
     }
+    /// Withdraw deposited collateral, removing the deposit once fully
+    /// withdrawn unless it holds rewards to claim
     pub fn withdraw(&mut self, withdraw_amount: u64, collateral_index: usize) -> ProgramResult {
         let collateral = &mut self.deposits.get_mut(collateral_index).unwrap();
-        if withdraw_amount == collateral.deposited_amount {
+        if withdraw_amount == collateral.deposited_amount && !collateral.has_unclaimed_rewards() {
             self.deposits.remove(collateral_index);
         } else {
             collateral.withdraw(withdraw_amount)?;
@@ -82,27 +170,112 @@ impl Obligation {
         Ok(())
     }
 
+    /// Claim the whole reward tokens earned by the deposit and borrow of a
+    /// reserve, up to a max amount, removing them if they were emptied and
+    /// hold no more rewards to claim
+    pub fn claim_rewards(&mut self, reserve: Pubkey, max_amount: u64) -> Result<u64, ProgramError> {
+        let mut claim_amount = 0;
+        let unclaimed_rewards = self
+            .deposits
+            .iter_mut()
+            .filter(|collateral| collateral.deposit_reserve == reserve)
+            .map(|collateral| &mut collateral.unclaimed_rewards_wads)
+            .chain(
+                self.borrows
+                    .iter_mut()
+                    .filter(|liquidity| liquidity.borrow_reserve == reserve)
+                    .map(|liquidity| &mut liquidity.unclaimed_rewards_wads),
+            );
+        for unclaimed_rewards_wads in unclaimed_rewards {
+            let amount = unclaimed_rewards_wads
+                .try_floor_u64()?
+                .min(max_amount - claim_amount);
+            *unclaimed_rewards_wads = unclaimed_rewards_wads.try_sub(Decimal::from(amount))?;
+            claim_amount += amount;
+        }
+        self.deposits.retain(|collateral| {
+            collateral.deposit_reserve != reserve
+                || collateral.deposited_amount > 0
+                || collateral.has_unclaimed_rewards()
+        });
+        self.borrows.retain(|liquidity| {
+            liquidity.borrow_reserve != reserve
+                || liquidity.borrowed_amount_wads > Decimal::zero()
+                || liquidity.has_unclaimed_rewards()
+        });
+        Ok(claim_amount)
+    }
+
+    /// Forfeit the rewards left to claim by the emptied deposit and borrow of
+    /// a reserve, removing them to free their obligation slots
+    pub fn forfeit_rewards(&mut self, reserve: Pubkey) -> Result<Decimal, ProgramError> {
+        let mut forfeit_amount_wads = Decimal::zero();
+        let mut forfeited = false;
+        for collateral in self.deposits.iter().filter(|collateral| {
+            collateral.deposit_reserve == reserve && collateral.deposited_amount == 0
+        }) {
+            forfeit_amount_wads = forfeit_amount_wads.try_add(collateral.unclaimed_rewards_wads)?;
+            forfeited = true;
+        }
+        for liquidity in self.borrows.iter().filter(|liquidity| {
+            liquidity.borrow_reserve == reserve && liquidity.borrowed_amount_wads == Decimal::zero()
+        }) {
+            forfeit_amount_wads = forfeit_amount_wads.try_add(liquidity.unclaimed_rewards_wads)?;
+            forfeited = true;
+        }
+        if !forfeited {
+            msg!("Obligation has no emptied deposit or borrow for the reserve");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+
+        self.deposits.retain(|collateral| {
+            collateral.deposit_reserve != reserve || collateral.deposited_amount > 0
+        });
+        self.borrows.retain(|liquidity| {
+            liquidity.borrow_reserve != reserve || liquidity.borrowed_amount_wads > Decimal::zero()
+        });
+        Ok(forfeit_amount_wads)
+    }
+
+    /// Ratio of the unhealthy borrow value to the borrowed value, which makes
+    /// the obligation liquidatable at or below one. Saturates when nothing
+    /// is borrowed.
+    pub fn health_factor(&self) -> Result<Decimal, ProgramError> {
+        if self.borrowed_value == Decimal::zero() {
+            return Ok(Decimal::from_scaled_val(u128::MAX));
+        }
+        self.unhealthy_borrow_value.try_div(self.borrowed_value)
+    }
+
     /// Calculate the maximum liquidity value that can be borrowed
     pub fn remaining_borrow_value(&self) -> Result<Decimal, ProgramError> {
         self.allowed_borrow_value.try_sub(self.borrowed_value)
     }
+    /// Find or add liquidity by borrow reserve, new liquidity starts at the
+    /// current cumulative borrow rate and borrow reward index of the reserve
     pub fn find_or_add_liquidity_to_borrows(
         &mut self,
         borrow_reserve: Pubkey,
         current_cumulative_borrow_rate_wads: Decimal,
+        current_reward_index: Decimal,
     ) -> Result<&mut ObligationLiquidity, ProgramError> {
         if let Some(liquidity_index) = self._find_liquidity_index_in_borrows(borrow_reserve) {
             return Ok(&mut self.borrows[liquidity_index]);
         }
-        if self.deposits.len() + self.borrows.len() >= MAX_OBLIGATION_RESERVES {
-            msg!(
-                "Obligation cannot have more than {} deposits and borrows combined",
-                MAX_OBLIGATION_RESERVES
-            );
-            return Err(LendingError::ObligationReserveLimit.into());
+        if self.is_resizable() {
+            if self.borrows.len() >= self.borrows_limit as usize {
+                msg!(
+                    "Obligation cannot have more than {} borrows",
+                    self.borrows_limit
+                );
+                return Err(LendingError::ObligationReserveLimit.into());
+            }
+        } else {
+            self.check_combined_reserve_limit()?;
         }
-        let liquidity =
+        let mut liquidity =
             ObligationLiquidity::new(borrow_reserve, current_cumulative_borrow_rate_wads);
+        liquidity.reward_index = current_reward_index;
         self.borrows.push(liquidity);
         Ok(self.borrows.last_mut().unwrap())
     }
@@ -154,14 +327,34 @@ impl Obligation {
             .try_sub(self.borrowed_value)?
             .try_div(withdraw_collateral_ltv)
     }
-    /// Find or add collateral by deposit reserve
+    /// Find or add collateral by deposit reserve, new collateral starts at the
+    /// current supply reward index of the reserve
     pub fn find_or_add_collateral_to_deposits(
         &mut self,
         deposit_reserve: Pubkey,
+        current_reward_index: Decimal,
     ) -> Result<&mut ObligationCollateral, ProgramError> {
         if let Some(collateral_index) = self._find_collateral_index_in_deposits(deposit_reserve) {
             return Ok(&mut self.deposits[collateral_index]);
         }
+        if self.is_resizable() {
+            if self.deposits.len() >= self.deposits_limit as usize {
+                msg!(
+                    "Obligation cannot have more than {} deposits",
+                    self.deposits_limit
+                );
+                return Err(LendingError::ObligationReserveLimit.into());
+            }
+        } else {
+            self.check_combined_reserve_limit()?;
+        }
+        let mut collateral = ObligationCollateral::new(deposit_reserve);
+        collateral.reward_index = current_reward_index;
+        self.deposits.push(collateral);
+        Ok(self.deposits.last_mut().unwrap())
+    }
+
+    fn check_combined_reserve_limit(&self) -> ProgramResult {
         if self.deposits.len() + self.borrows.len() >= MAX_OBLIGATION_RESERVES {
             msg!(
                 "Obligation cannot have more than {} deposits and borrows combined",
@@ -169,16 +362,44 @@ impl Obligation {
             );
             return Err(LendingError::ObligationReserveLimit.into());
         }
-        let collateral = ObligationCollateral::new(deposit_reserve);
-        self.deposits.push(collateral);
-        Ok(self.deposits.last_mut().unwrap())
+        Ok(())
+    }
+
+    /// Switch to the resizable layout with the given deposit and borrow limits,
+    /// returning the account data length required to hold them
+    pub fn resize(&mut self, deposits_limit: u8, borrows_limit: u8) -> Result<usize, ProgramError> {
+        if deposits_limit > MAX_RESIZED_OBLIGATION_RESERVES
+            || borrows_limit > MAX_RESIZED_OBLIGATION_RESERVES
+        {
+            msg!(
+                "Obligation deposit and borrow limits cannot exceed {}",
+                MAX_RESIZED_OBLIGATION_RESERVES
+            );
+            return Err(LendingError::ObligationReserveLimit.into());
+        }
+        if (deposits_limit as usize) < self.deposits.len()
+            || (borrows_limit as usize) < self.borrows.len()
+        {
+            msg!("Obligation limits cannot be lower than its current deposits and borrows");
+            return Err(LendingError::ObligationReserveLimit.into());
+        }
+        self.version = OBLIGATION_VERSION_REWARDS;
+        self.deposits_limit = deposits_limit;
+        self.borrows_limit = borrows_limit;
+        Ok(Self::resizable_len(deposits_limit, borrows_limit))
     }
 
     /// Calculate the maximum liquidation amount for a given liquidity
+    ///
+    /// Matured fixed rate borrows can be liquidated in full
     pub fn max_liquidation_amount(
         &self,
         liquidity: &ObligationLiquidity,
+        current_unix_timestamp: UnixTimestamp,
     ) -> Result<Decimal, ProgramError> {
+        if liquidity.is_matured(current_unix_timestamp) {
+            return Ok(liquidity.borrowed_amount_wads);
+        }
         let max_liquidation_value = self
             .borrowed_value
             .try_mul(Rate::from_percent(LIQUIDATION_CLOSE_FACTOR))?
@@ -1,5 +1,8 @@
 use {
-    crate::{error::LendingError, math::Decimal},
+    crate::{
+        error::LendingError,
+        math::{Decimal, TryAdd, TryMul, TrySub},
+    },
     solana_program::{entrypoint::ProgramResult, pubkey::Pubkey},
 };
 
@@ -12,6 +15,12 @@ pub struct ObligationCollateral {
     pub deposited_amount: u64,
     /// Collateral market value in quote currency
     pub market_value: Decimal,
+    /// Supply reward index of the deposit reserve the rewards of the
+    /// collateral were settled at, zero until the first settlement for
+    /// collateral deposited before obligations tracked rewards
+    pub reward_index: Decimal,
+    /// Reward tokens earned by the collateral and not claimed yet
+    pub unclaimed_rewards_wads: Decimal,
 }
 
 impl ObligationCollateral {
@@ -21,6 +30,8 @@ impl ObligationCollateral {
             deposit_reserve,
             deposited_amount: 0,
             market_value: Decimal::zero(),
+            reward_index: Decimal::zero(),
+            unclaimed_rewards_wads: Decimal::zero(),
         }
     }
     pub fn deposit(&mut self, collateral_amount: u64) -> ProgramResult {
@@ -38,4 +49,22 @@ impl ObligationCollateral {
             .ok_or(LendingError::MathOverflow)?;
         Ok(())
     }
+    /// Settle the rewards earned since the last settlement at the current
+    /// supply reward index of the deposit reserve
+    pub fn accrue_rewards(&mut self, supply_reward_index: Decimal) -> ProgramResult {
+        if self.reward_index != Decimal::zero() {
+            self.unclaimed_rewards_wads = self.unclaimed_rewards_wads.try_add(
+                supply_reward_index
+                    .try_sub(self.reward_index)?
+                    .try_mul(self.deposited_amount)?,
+            )?;
+        }
+        self.reward_index = supply_reward_index;
+        Ok(())
+    }
+    /// Whether the collateral earned at least one whole reward token which
+    /// was not claimed yet
+    pub fn has_unclaimed_rewards(&self) -> bool {
+        self.unclaimed_rewards_wads >= Decimal::one()
+    }
 }
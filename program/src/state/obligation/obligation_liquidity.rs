@@ -2,8 +2,12 @@ use {
     crate::{
         error::LendingError,
         math::{Decimal, Rate, TryAdd, TryDiv, TryMul, TrySub},
+        state::SECONDS_PER_YEAR,
+    },
+    solana_program::{
+        clock::UnixTimestamp, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
     },
-    solana_program::{entrypoint::ProgramResult, msg, pubkey::Pubkey},
     std::cmp::Ordering,
 };
 /// Obligation liquidity state
@@ -17,6 +21,31 @@ pub struct ObligationLiquidity {
     pub borrowed_amount_wads: Decimal,
     /// Liquidity market value in quote currency
     pub market_value: Decimal,
+    /// Fixed rate terms, if the liquidity was borrowed at a fixed rate
+    pub fixed_rate: Option<FixedRateBorrow>,
+    /// Borrow reward index of the borrow reserve the rewards of the liquidity
+    /// were settled at, zero until the first settlement for liquidity
+    /// borrowed before obligations tracked rewards
+    pub reward_index: Decimal,
+    /// Reward tokens earned by the liquidity and not claimed yet
+    pub unclaimed_rewards_wads: Decimal,
+}
+
+/// Fixed rate terms of borrowed liquidity
+///
+/// The reserve keeps accounting for fixed rate borrows at its variable rate,
+/// the share of the reserve debt settled by the borrow is tracked separately
+/// so the reserve books stay exact while the borrower owes the fixed rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FixedRateBorrow {
+    /// Annual borrow rate locked when borrowing
+    pub borrow_rate: Rate,
+    /// Unix timestamp after which the borrow can be liquidated until repaid
+    pub maturity_timestamp: UnixTimestamp,
+    /// Unix timestamp up to which fixed interest was accrued
+    pub last_accrual_timestamp: UnixTimestamp,
+    /// Amount of reserve liquidity borrowed plus variable interest
+    pub reserve_borrowed_amount_wads: Decimal,
 }
 
 impl ObligationLiquidity {
@@ -29,13 +58,63 @@ impl ObligationLiquidity {
     }
     /// Decrease borrowed liquidity
     pub fn repay(&mut self, settle_amount: Decimal) -> ProgramResult {
+        let reserve_settle_amount = self.reserve_settle_amount(settle_amount)?;
+        if let Some(fixed_rate) = &mut self.fixed_rate {
+            fixed_rate.reserve_borrowed_amount_wads = fixed_rate
+                .reserve_borrowed_amount_wads
+                .try_sub(reserve_settle_amount)?;
+        }
         self.borrowed_amount_wads = self.borrowed_amount_wads.try_sub(settle_amount)?;
         Ok(())
     }
     pub fn borrow(&mut self, borrow_amount: Decimal) -> ProgramResult {
+        if let Some(fixed_rate) = &mut self.fixed_rate {
+            fixed_rate.reserve_borrowed_amount_wads = fixed_rate
+                .reserve_borrowed_amount_wads
+                .try_add(borrow_amount)?;
+        }
         self.borrowed_amount_wads = self.borrowed_amount_wads.try_add(borrow_amount)?;
         Ok(())
     }
+    /// Amount of reserve liquidity settled when settling an amount of the
+    /// borrowed liquidity
+    pub fn reserve_settle_amount(&self, settle_amount: Decimal) -> Result<Decimal, ProgramError> {
+        match &self.fixed_rate {
+            None => Ok(settle_amount),
+            Some(fixed_rate) if settle_amount >= self.borrowed_amount_wads => {
+                Ok(fixed_rate.reserve_borrowed_amount_wads)
+            }
+            Some(fixed_rate) => fixed_rate
+                .reserve_borrowed_amount_wads
+                .try_mul(settle_amount)?
+                .try_div(self.borrowed_amount_wads),
+        }
+    }
+    /// Settle the rewards earned since the last settlement at the current
+    /// borrow reward index of the borrow reserve, on the reserve liquidity
+    /// the borrow accounts for
+    pub fn accrue_rewards(&mut self, borrow_reward_index: Decimal) -> ProgramResult {
+        if self.reward_index != Decimal::zero() {
+            self.unclaimed_rewards_wads = self.unclaimed_rewards_wads.try_add(
+                borrow_reward_index
+                    .try_sub(self.reward_index)?
+                    .try_mul(self.reserve_settle_amount(self.borrowed_amount_wads)?)?,
+            )?;
+        }
+        self.reward_index = borrow_reward_index;
+        Ok(())
+    }
+    /// Whether the liquidity earned at least one whole reward token which
+    /// was not claimed yet
+    pub fn has_unclaimed_rewards(&self) -> bool {
+        self.unclaimed_rewards_wads >= Decimal::one()
+    }
+    /// Whether the liquidity was borrowed at a fixed rate which reached
+    /// maturity
+    pub fn is_matured(&self, current_unix_timestamp: UnixTimestamp) -> bool {
+        self.fixed_rate
+            .is_some_and(|fixed_rate| current_unix_timestamp >= fixed_rate.maturity_timestamp)
+    }
     pub fn accrue_interest(&mut self, cumulative_borrow_rate_wads: Decimal) -> ProgramResult {
         match cumulative_borrow_rate_wads.cmp(&self.cumulative_borrow_rate_wads) {
             Ordering::Less => {
@@ -47,12 +126,47 @@ impl ObligationLiquidity {
                 let compounded_interest_rate: Rate = cumulative_borrow_rate_wads
                     .try_div(self.cumulative_borrow_rate_wads)?
                     .try_into()?;
-                self.borrowed_amount_wads = self
-                    .borrowed_amount_wads
-                    .try_mul(compounded_interest_rate)?;
+                // fixed rate borrows owe their own rate, only the reserve
+                // share follows the variable rate
+                match &mut self.fixed_rate {
+                    Some(fixed_rate) => {
+                        fixed_rate.reserve_borrowed_amount_wads = fixed_rate
+                            .reserve_borrowed_amount_wads
+                            .try_mul(compounded_interest_rate)?;
+                    }
+                    None => {
+                        self.borrowed_amount_wads = self
+                            .borrowed_amount_wads
+                            .try_mul(compounded_interest_rate)?;
+                    }
+                }
                 self.cumulative_borrow_rate_wads = cumulative_borrow_rate_wads;
                 Ok(())
             }
         }
     }
+    /// Accrue interest at the fixed rate over the seconds elapsed since the
+    /// last accrual, interest keeps accruing after maturity
+    pub fn accrue_fixed_interest(
+        &mut self,
+        current_unix_timestamp: UnixTimestamp,
+    ) -> ProgramResult {
+        let Some(fixed_rate) = &mut self.fixed_rate else {
+            return Ok(());
+        };
+        let seconds_elapsed = current_unix_timestamp
+            .checked_sub(fixed_rate.last_accrual_timestamp)
+            .ok_or(LendingError::MathOverflow)?;
+        if seconds_elapsed <= 0 {
+            return Ok(());
+        }
+        let compounded_interest_rate = Rate::one()
+            .try_add(fixed_rate.borrow_rate.try_div(SECONDS_PER_YEAR)?)?
+            .try_pow(seconds_elapsed as u64)?;
+        self.borrowed_amount_wads = self
+            .borrowed_amount_wads
+            .try_mul(compounded_interest_rate)?;
+        fixed_rate.last_accrual_timestamp = current_unix_timestamp;
+        Ok(())
+    }
 }
@@ -1,8 +1,13 @@
 use {
-    super::{Obligation, ObligationCollateral, ObligationLiquidity},
-    crate::state::{
-        last_update::LastUpdate, pack_bool, pack_decimal, unpack_bool, unpack_decimal,
-        MAX_OBLIGATION_RESERVES, PROGRAM_VERSION, UNINITIALIZED_VERSION,
+    super::{FixedRateBorrow, Obligation, ObligationCollateral, ObligationLiquidity},
+    crate::{
+        math::{Decimal, Rate},
+        state::{
+            last_update::LastUpdate, pack_bool, pack_decimal, unpack_bool, unpack_decimal, Migrate,
+            MAX_OBLIGATION_RESERVES, OBLIGATION_VERSION_FIXED,
+            OBLIGATION_VERSION_FIXED_RATE_BORROWS, OBLIGATION_VERSION_REFERRER,
+            OBLIGATION_VERSION_RESIZABLE, OBLIGATION_VERSION_REWARDS, UNINITIALIZED_VERSION,
+        },
     },
     arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
     solana_program::{
@@ -22,12 +27,126 @@ impl IsInitialized for Obligation {
 
 const OBLIGATION_COLLATERAL_LEN: usize = 56; // 32 + 8 + 16
 const OBLIGATION_LIQUIDITY_LEN: usize = 80; // 32 + 16 + 16 + 16
+pub(super) const OBLIGATION_HEADER_LEN: usize = 140; // 1 + 8 + 1 + 32 + 32 + 16 + 16 + 16 + 16 + 1 + 1
+const OBLIGATION_LIMITS_LEN: usize = 2; // 1 + 1
+const OBLIGATION_REFERRER_LEN: usize = PUBKEY_BYTES;
+const OBLIGATION_FIXED_RATE_BORROW_LEN: usize = 49; // 1 + 16 + 8 + 8 + 16
+const OBLIGATION_REWARDS_LEN: usize = 32; // 16 + 16
 const OBLIGATION_LEN: usize = 916; // 1 + 8 + 1 + 32 + 32 + 16 + 16 + 16 + 16 + 1 + 1 + (56 * 1) + (80 * 9)
                                    // @TODO: break this up by obligation / collateral / liquidity https://git.io/JOCca
+
+impl Obligation {
+    /// Account data length of a resizable obligation with the given limits
+    pub fn resizable_len(deposits_limit: u8, borrows_limit: u8) -> usize {
+        Self::layout_len(OBLIGATION_VERSION_REWARDS, deposits_limit, borrows_limit)
+    }
+
+    /// Account data length required by the obligation layout version
+    pub fn packed_len(&self) -> usize {
+        Self::layout_len(self.version, self.deposits_limit, self.borrows_limit)
+    }
+
+    pub(super) fn layout_len(version: u8, deposits_limit: u8, borrows_limit: u8) -> usize {
+        let (limits_len, collateral_len, liquidity_len) = match version {
+            OBLIGATION_VERSION_REWARDS => (
+                OBLIGATION_LIMITS_LEN + OBLIGATION_REFERRER_LEN,
+                OBLIGATION_COLLATERAL_LEN + OBLIGATION_REWARDS_LEN,
+                OBLIGATION_LIQUIDITY_LEN
+                    + OBLIGATION_FIXED_RATE_BORROW_LEN
+                    + OBLIGATION_REWARDS_LEN,
+            ),
+            OBLIGATION_VERSION_FIXED_RATE_BORROWS => (
+                OBLIGATION_LIMITS_LEN + OBLIGATION_REFERRER_LEN,
+                OBLIGATION_COLLATERAL_LEN,
+                OBLIGATION_LIQUIDITY_LEN + OBLIGATION_FIXED_RATE_BORROW_LEN,
+            ),
+            OBLIGATION_VERSION_REFERRER => (
+                OBLIGATION_LIMITS_LEN + OBLIGATION_REFERRER_LEN,
+                OBLIGATION_COLLATERAL_LEN,
+                OBLIGATION_LIQUIDITY_LEN,
+            ),
+            OBLIGATION_VERSION_RESIZABLE => (
+                OBLIGATION_LIMITS_LEN,
+                OBLIGATION_COLLATERAL_LEN,
+                OBLIGATION_LIQUIDITY_LEN,
+            ),
+            _ => return OBLIGATION_LEN,
+        };
+        OBLIGATION_HEADER_LEN
+            + limits_len
+            + collateral_len * deposits_limit as usize
+            + liquidity_len * borrows_limit as usize
+    }
+
+    /// Whether the layout version stores the referrer
+    fn stores_referrer(version: u8) -> bool {
+        version == OBLIGATION_VERSION_REWARDS
+            || version == OBLIGATION_VERSION_FIXED_RATE_BORROWS
+            || version == OBLIGATION_VERSION_REFERRER
+    }
+
+    /// Whether the layout version stores the fixed rate terms of borrows
+    pub fn stores_fixed_rate_borrows(version: u8) -> bool {
+        version == OBLIGATION_VERSION_REWARDS || version == OBLIGATION_VERSION_FIXED_RATE_BORROWS
+    }
+
+    /// Whether the layout version stores the rewards earned by deposits and
+    /// borrows
+    fn stores_rewards(version: u8) -> bool {
+        version == OBLIGATION_VERSION_REWARDS
+    }
+}
+
+impl Migrate for Obligation {
+    fn is_outdated(version: u8) -> bool {
+        version != UNINITIALIZED_VERSION && version != OBLIGATION_VERSION_REWARDS
+    }
+
+    /// Fixed size obligations get limits allowing every deposit and borrow
+    /// they could hold, resizable ones keep their limits
+    fn migrate(&mut self) -> usize {
+        if !self.is_resizable() {
+            self.deposits_limit = MAX_OBLIGATION_RESERVES as u8;
+            self.borrows_limit = MAX_OBLIGATION_RESERVES as u8;
+        }
+        self.version = OBLIGATION_VERSION_REWARDS;
+        self.packed_len()
+    }
+}
+
 impl Pack for Obligation {
+    /// Length of a fixed size obligation, resizable obligations use
+    /// [Obligation::resizable_len]
     const LEN: usize = OBLIGATION_LEN;
+
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        let expected_len = match input.first() {
+            Some(&version) if input.len() >= OBLIGATION_HEADER_LEN + OBLIGATION_LIMITS_LEN => {
+                Self::layout_len(
+                    version,
+                    input[OBLIGATION_HEADER_LEN],
+                    input[OBLIGATION_HEADER_LEN + 1],
+                )
+            }
+            _ => OBLIGATION_LEN,
+        };
+        if input.len() != expected_len {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::unpack_from_slice(input)
+    }
+
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != src.packed_len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let output = array_mut_ref![dst, 0, OBLIGATION_LEN];
+        let (output, data_flat) = dst.split_at_mut(OBLIGATION_HEADER_LEN);
+        let output = array_mut_ref![output, 0, OBLIGATION_HEADER_LEN];
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             version,
@@ -41,7 +160,6 @@ impl Pack for Obligation {
             unhealthy_borrow_value,
             deposits_len,
             borrows_len,
-            data_flat,
         ) = mut_array_refs![
             output,
             1,
@@ -54,8 +172,7 @@ impl Pack for Obligation {
             16,
             16,
             1,
-            1,
-            OBLIGATION_COLLATERAL_LEN + (OBLIGATION_LIQUIDITY_LEN * (MAX_OBLIGATION_RESERVES - 1))
+            1
         ];
 
         // obligation
@@ -71,6 +188,27 @@ impl Pack for Obligation {
         *deposits_len = u8::try_from(self.deposits.len()).unwrap().to_le_bytes();
         *borrows_len = u8::try_from(self.borrows.len()).unwrap().to_le_bytes();
 
+        // limits
+        let data_flat = if self.is_resizable() {
+            let (limits, data_flat) = data_flat.split_at_mut(OBLIGATION_LIMITS_LEN);
+            let (deposits_limit, borrows_limit) =
+                mut_array_refs![array_mut_ref![limits, 0, OBLIGATION_LIMITS_LEN], 1, 1];
+            *deposits_limit = self.deposits_limit.to_le_bytes();
+            *borrows_limit = self.borrows_limit.to_le_bytes();
+            data_flat
+        } else {
+            data_flat
+        };
+
+        // referrer
+        let data_flat = if Self::stores_referrer(self.version) {
+            let (referrer, data_flat) = data_flat.split_at_mut(OBLIGATION_REFERRER_LEN);
+            referrer.copy_from_slice(self.referrer.as_ref());
+            data_flat
+        } else {
+            data_flat
+        };
+
         let mut offset = 0;
 
         // deposits
@@ -83,6 +221,15 @@ impl Pack for Obligation {
             *deposited_amount = collateral.deposited_amount.to_le_bytes();
             pack_decimal(collateral.market_value, market_value);
             offset += OBLIGATION_COLLATERAL_LEN;
+
+            // rewards
+            if Self::stores_rewards(self.version) {
+                let rewards_flat = array_mut_ref![data_flat, offset, OBLIGATION_REWARDS_LEN];
+                let (reward_index, unclaimed_rewards_wads) = mut_array_refs![rewards_flat, 16, 16];
+                pack_decimal(collateral.reward_index, reward_index);
+                pack_decimal(collateral.unclaimed_rewards_wads, unclaimed_rewards_wads);
+                offset += OBLIGATION_REWARDS_LEN;
+            }
         }
 
         // borrows
@@ -99,13 +246,47 @@ impl Pack for Obligation {
             pack_decimal(liquidity.borrowed_amount_wads, borrowed_amount_wads);
             pack_decimal(liquidity.market_value, market_value);
             offset += OBLIGATION_LIQUIDITY_LEN;
+
+            // fixed rate terms
+            if Self::stores_fixed_rate_borrows(self.version) {
+                let fixed_rate_flat =
+                    array_mut_ref![data_flat, offset, OBLIGATION_FIXED_RATE_BORROW_LEN];
+                #[allow(clippy::ptr_offset_with_cast)]
+                let (
+                    fixed_rate_flag,
+                    borrow_rate,
+                    maturity_timestamp,
+                    last_accrual_timestamp,
+                    reserve_borrowed_amount_wads,
+                ) = mut_array_refs![fixed_rate_flat, 1, 16, 8, 8, 16];
+                let fixed_rate = liquidity.fixed_rate.unwrap_or_default();
+                pack_bool(liquidity.fixed_rate.is_some(), fixed_rate_flag);
+                pack_decimal(Decimal::from(fixed_rate.borrow_rate), borrow_rate);
+                *maturity_timestamp = fixed_rate.maturity_timestamp.to_le_bytes();
+                *last_accrual_timestamp = fixed_rate.last_accrual_timestamp.to_le_bytes();
+                pack_decimal(
+                    fixed_rate.reserve_borrowed_amount_wads,
+                    reserve_borrowed_amount_wads,
+                );
+                offset += OBLIGATION_FIXED_RATE_BORROW_LEN;
+            }
+
+            // rewards
+            if Self::stores_rewards(self.version) {
+                let rewards_flat = array_mut_ref![data_flat, offset, OBLIGATION_REWARDS_LEN];
+                let (reward_index, unclaimed_rewards_wads) = mut_array_refs![rewards_flat, 16, 16];
+                pack_decimal(liquidity.reward_index, reward_index);
+                pack_decimal(liquidity.unclaimed_rewards_wads, unclaimed_rewards_wads);
+                offset += OBLIGATION_REWARDS_LEN;
+            }
         }
     }
 
     /// Unpacks a byte buffer into an
     /// [ObligationInfo](struct.ObligationInfo.html).
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let input = array_ref![src, 0, OBLIGATION_LEN];
+        let (input, data_flat) = src.split_at(OBLIGATION_HEADER_LEN);
+        let input = array_ref![input, 0, OBLIGATION_HEADER_LEN];
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             version,
@@ -119,7 +300,6 @@ impl Pack for Obligation {
             unhealthy_borrow_value,
             deposits_len,
             borrows_len,
-            data_flat,
         ) = array_refs![
             input,
             1,
@@ -132,18 +312,52 @@ impl Pack for Obligation {
             16,
             16,
             1,
-            1,
-            OBLIGATION_COLLATERAL_LEN + (OBLIGATION_LIQUIDITY_LEN * (MAX_OBLIGATION_RESERVES - 1))
+            1
         ];
 
         let version = u8::from_le_bytes(*version);
-        if version > PROGRAM_VERSION {
-            msg!("Obligation version does not match lending program version");
-            return Err(ProgramError::InvalidAccountData);
-        }
-
         let deposits_len = u8::from_le_bytes(*deposits_len);
         let borrows_len = u8::from_le_bytes(*borrows_len);
+
+        let (deposits_limit, borrows_limit, data_flat) = match version {
+            OBLIGATION_VERSION_REWARDS
+            | OBLIGATION_VERSION_FIXED_RATE_BORROWS
+            | OBLIGATION_VERSION_REFERRER
+            | OBLIGATION_VERSION_RESIZABLE => {
+                let (limits, data_flat) = data_flat.split_at(OBLIGATION_LIMITS_LEN);
+                let (deposits_limit, borrows_limit) =
+                    array_refs![array_ref![limits, 0, OBLIGATION_LIMITS_LEN], 1, 1];
+                let deposits_limit = u8::from_le_bytes(*deposits_limit);
+                let borrows_limit = u8::from_le_bytes(*borrows_limit);
+                if deposits_len > deposits_limit || borrows_len > borrows_limit {
+                    msg!("Obligation deposits and borrows cannot exceed its limits");
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                (deposits_limit, borrows_limit, data_flat)
+            }
+            UNINITIALIZED_VERSION | OBLIGATION_VERSION_FIXED => {
+                if deposits_len as usize + borrows_len as usize > MAX_OBLIGATION_RESERVES {
+                    msg!("Obligation deposits and borrows cannot exceed its limits");
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                (0, 0, data_flat)
+            }
+            _ => {
+                msg!("Obligation version does not match lending program version");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        };
+
+        let (referrer, data_flat) = if Self::stores_referrer(version) {
+            let (referrer, data_flat) = data_flat.split_at(OBLIGATION_REFERRER_LEN);
+            (
+                Pubkey::new_from_array(*array_ref![referrer, 0, OBLIGATION_REFERRER_LEN]),
+                data_flat,
+            )
+        } else {
+            (Pubkey::default(), data_flat)
+        };
+
         let mut deposits = Vec::with_capacity(deposits_len as usize + 1);
         let mut borrows = Vec::with_capacity(borrows_len as usize + 1);
 
@@ -153,25 +367,82 @@ impl Pack for Obligation {
             #[allow(clippy::ptr_offset_with_cast)]
             let (deposit_reserve, deposited_amount, market_value) =
                 array_refs![deposits_flat, PUBKEY_BYTES, 8, 16];
+            offset += OBLIGATION_COLLATERAL_LEN;
+
+            let (reward_index, unclaimed_rewards_wads) = if Self::stores_rewards(version) {
+                let rewards_flat = array_ref![data_flat, offset, OBLIGATION_REWARDS_LEN];
+                let (reward_index, unclaimed_rewards_wads) = array_refs![rewards_flat, 16, 16];
+                offset += OBLIGATION_REWARDS_LEN;
+                (
+                    unpack_decimal(reward_index),
+                    unpack_decimal(unclaimed_rewards_wads),
+                )
+            } else {
+                (Decimal::zero(), Decimal::zero())
+            };
+
             deposits.push(ObligationCollateral {
                 deposit_reserve: Pubkey::new_from_array(*deposit_reserve),
                 deposited_amount: u64::from_le_bytes(*deposited_amount),
                 market_value: unpack_decimal(market_value),
+                reward_index,
+                unclaimed_rewards_wads,
             });
-            offset += OBLIGATION_COLLATERAL_LEN;
         }
         for _ in 0..borrows_len {
             let borrows_flat = array_ref![data_flat, offset, OBLIGATION_LIQUIDITY_LEN];
             #[allow(clippy::ptr_offset_with_cast)]
             let (borrow_reserve, cumulative_borrow_rate_wads, borrowed_amount_wads, market_value) =
                 array_refs![borrows_flat, PUBKEY_BYTES, 16, 16, 16];
+            offset += OBLIGATION_LIQUIDITY_LEN;
+
+            let fixed_rate = if Self::stores_fixed_rate_borrows(version) {
+                let fixed_rate_flat =
+                    array_ref![data_flat, offset, OBLIGATION_FIXED_RATE_BORROW_LEN];
+                #[allow(clippy::ptr_offset_with_cast)]
+                let (
+                    fixed_rate_flag,
+                    borrow_rate,
+                    maturity_timestamp,
+                    last_accrual_timestamp,
+                    reserve_borrowed_amount_wads,
+                ) = array_refs![fixed_rate_flat, 1, 16, 8, 8, 16];
+                offset += OBLIGATION_FIXED_RATE_BORROW_LEN;
+                if unpack_bool(fixed_rate_flag)? {
+                    Some(FixedRateBorrow {
+                        borrow_rate: Rate::try_from(unpack_decimal(borrow_rate))?,
+                        maturity_timestamp: i64::from_le_bytes(*maturity_timestamp),
+                        last_accrual_timestamp: i64::from_le_bytes(*last_accrual_timestamp),
+                        reserve_borrowed_amount_wads: unpack_decimal(reserve_borrowed_amount_wads),
+                    })
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+
+            let (reward_index, unclaimed_rewards_wads) = if Self::stores_rewards(version) {
+                let rewards_flat = array_ref![data_flat, offset, OBLIGATION_REWARDS_LEN];
+                let (reward_index, unclaimed_rewards_wads) = array_refs![rewards_flat, 16, 16];
+                offset += OBLIGATION_REWARDS_LEN;
+                (
+                    unpack_decimal(reward_index),
+                    unpack_decimal(unclaimed_rewards_wads),
+                )
+            } else {
+                (Decimal::zero(), Decimal::zero())
+            };
+
             borrows.push(ObligationLiquidity {
                 borrow_reserve: Pubkey::new_from_array(*borrow_reserve),
                 cumulative_borrow_rate_wads: unpack_decimal(cumulative_borrow_rate_wads),
                 borrowed_amount_wads: unpack_decimal(borrowed_amount_wads),
                 market_value: unpack_decimal(market_value),
+                fixed_rate,
+                reward_index,
+                unclaimed_rewards_wads,
             });
-            offset += OBLIGATION_LIQUIDITY_LEN;
         }
 
         Ok(Self {
@@ -179,6 +450,7 @@ impl Pack for Obligation {
             last_update: LastUpdate {
                 slot: u64::from_le_bytes(*last_update_slot),
                 stale: unpack_bool(last_update_stale)?,
+                unix_timestamp: 0,
             },
             lending_market: Pubkey::new_from_array(*lending_market),
             owner: Pubkey::new_from_array(*owner),
@@ -188,6 +460,9 @@ impl Pack for Obligation {
             borrowed_value: unpack_decimal(borrowed_value),
             allowed_borrow_value: unpack_decimal(allowed_borrow_value),
             unhealthy_borrow_value: unpack_decimal(unhealthy_borrow_value),
+            deposits_limit,
+            borrows_limit,
+            referrer,
         })
     }
 }
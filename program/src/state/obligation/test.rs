@@ -5,10 +5,21 @@ mod tests {
     use {
         crate::{
             error::LendingError,
-            math::{Decimal, TryAdd, WAD},
-            state::{Obligation, ObligationLiquidity},
+            math::{Decimal, Rate, TryAdd, WAD},
+            state::{
+                last_update::LastUpdate, FixedRateBorrow, Migrate, Obligation,
+                ObligationCollateral, ObligationHeader, ObligationLiquidity,
+                OBLIGATION_VERSION_FIXED, OBLIGATION_VERSION_FIXED_RATE_BORROWS,
+                OBLIGATION_VERSION_REFERRER, OBLIGATION_VERSION_RESIZABLE,
+                OBLIGATION_VERSION_REWARDS, SECONDS_PER_YEAR,
+            },
         },
         proptest::prelude::*,
+        solana_program::{
+            program_error::ProgramError,
+            program_pack::Pack,
+            pubkey::{Pubkey, PUBKEY_BYTES},
+        },
     };
     const MAX_COMPOUNDED_INTEREST: u64 = 100; // 10,000%
     #[test]
@@ -123,4 +134,569 @@ mod tests {
             }
         }
     }
+
+    fn obligation_with_reserves(deposits: usize, borrows: usize) -> Obligation {
+        Obligation {
+            version: OBLIGATION_VERSION_FIXED,
+            last_update: LastUpdate {
+                slot: 42,
+                stale: true,
+                unix_timestamp: 0,
+            },
+            lending_market: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            deposits: (0..deposits)
+                .map(|i| ObligationCollateral {
+                    deposit_reserve: Pubkey::new_unique(),
+                    deposited_amount: i as u64 + 1,
+                    market_value: Decimal::from(i as u64),
+                    ..ObligationCollateral::default()
+                })
+                .collect(),
+            borrows: (0..borrows)
+                .map(|i| ObligationLiquidity {
+                    borrow_reserve: Pubkey::new_unique(),
+                    cumulative_borrow_rate_wads: Decimal::one(),
+                    borrowed_amount_wads: Decimal::from(i as u64 + 1),
+                    market_value: Decimal::from(i as u64),
+                    fixed_rate: None,
+                    ..ObligationLiquidity::default()
+                })
+                .collect(),
+            deposited_value: Decimal::from(100u64),
+            borrowed_value: Decimal::from(50u64),
+            allowed_borrow_value: Decimal::from(80u64),
+            unhealthy_borrow_value: Decimal::from(90u64),
+            ..Obligation::default()
+        }
+    }
+
+    #[test]
+    fn pack_fixed_obligation() {
+        let obligation = obligation_with_reserves(4, 6);
+        let mut data = vec![0; Obligation::LEN];
+        Obligation::pack(obligation.clone(), &mut data).unwrap();
+        assert_eq!(Obligation::unpack(&data).unwrap(), obligation);
+
+        // a fixed obligation cannot be unpacked from a resized buffer
+        let mut data = vec![0; Obligation::LEN + 1];
+        assert_eq!(
+            Obligation::pack(obligation, &mut data),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            Obligation::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn pack_resizable_obligation() {
+        let mut obligation = obligation_with_reserves(12, 3);
+        let len = obligation.resize(16, 4).unwrap();
+        assert_eq!(len, Obligation::resizable_len(16, 4));
+        assert_eq!(obligation.version, OBLIGATION_VERSION_REWARDS);
+
+        let mut data = vec![0; len];
+        Obligation::pack(obligation.clone(), &mut data).unwrap();
+        assert_eq!(Obligation::unpack(&data).unwrap(), obligation);
+
+        let mut data = vec![0; Obligation::LEN];
+        assert_eq!(
+            Obligation::pack(obligation, &mut data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn pack_obligation_referrer() {
+        let mut obligation = obligation_with_reserves(2, 2);
+        obligation.resize(2, 2).unwrap();
+        obligation.referrer = Pubkey::new_unique();
+        let mut data = vec![0; Obligation::resizable_len(2, 2)];
+        Obligation::pack(obligation.clone(), &mut data).unwrap();
+        let unpacked = Obligation::unpack(&data).unwrap();
+        assert_eq!(unpacked.referrer(), Some(obligation.referrer));
+        assert_eq!(unpacked, obligation);
+
+        // resizable obligations without a referrer keep their layout until
+        // they are resized
+        let mut obligation = obligation_with_reserves(2, 2);
+        obligation.resize(2, 2).unwrap();
+        obligation.version = OBLIGATION_VERSION_RESIZABLE;
+        let len = obligation.packed_len();
+        assert_eq!(
+            len,
+            Obligation::resizable_len(2, 2) - PUBKEY_BYTES - 2 * 49 - (2 + 2) * 32
+        );
+        let mut data = vec![0; len];
+        Obligation::pack(obligation.clone(), &mut data).unwrap();
+        let mut unpacked = Obligation::unpack(&data).unwrap();
+        assert_eq!(unpacked, obligation);
+        assert_eq!(unpacked.referrer(), None);
+        assert_eq!(
+            unpacked.resize(2, 2).unwrap(),
+            Obligation::resizable_len(2, 2)
+        );
+        assert_eq!(unpacked.version, OBLIGATION_VERSION_REWARDS);
+    }
+
+    #[test]
+    fn pack_obligation_fixed_rate_borrows() {
+        let mut obligation = obligation_with_reserves(1, 2);
+        obligation.resize(1, 3).unwrap();
+        obligation.borrows[1].fixed_rate = Some(FixedRateBorrow {
+            borrow_rate: Rate::from_percent(12),
+            maturity_timestamp: 1_700_000_000,
+            last_accrual_timestamp: 1_699_000_000,
+            reserve_borrowed_amount_wads: Decimal::from(2u64),
+        });
+        let mut data = vec![0; Obligation::resizable_len(1, 3)];
+        Obligation::pack(obligation.clone(), &mut data).unwrap();
+        assert_eq!(Obligation::unpack(&data).unwrap(), obligation);
+
+        // obligations without fixed rate borrows keep their layout until they
+        // are resized
+        let mut obligation = obligation_with_reserves(1, 2);
+        obligation.resize(1, 3).unwrap();
+        obligation.version = OBLIGATION_VERSION_REFERRER;
+        let len = obligation.packed_len();
+        assert_eq!(len, Obligation::resizable_len(1, 3) - 3 * 49 - (1 + 3) * 32);
+        let mut data = vec![0; len];
+        Obligation::pack(obligation.clone(), &mut data).unwrap();
+        let mut unpacked = Obligation::unpack(&data).unwrap();
+        assert_eq!(unpacked, obligation);
+        assert_eq!(
+            unpacked.resize(1, 3).unwrap(),
+            Obligation::resizable_len(1, 3)
+        );
+        assert_eq!(unpacked.version, OBLIGATION_VERSION_REWARDS);
+    }
+
+    fn fixed_rate_liquidity(
+        borrowed_amount: u64,
+        reserve_borrowed_amount: u64,
+    ) -> ObligationLiquidity {
+        ObligationLiquidity {
+            cumulative_borrow_rate_wads: Decimal::one(),
+            borrowed_amount_wads: Decimal::from(borrowed_amount),
+            fixed_rate: Some(FixedRateBorrow {
+                borrow_rate: Rate::from_percent(10),
+                maturity_timestamp: 1_000,
+                last_accrual_timestamp: 0,
+                reserve_borrowed_amount_wads: Decimal::from(reserve_borrowed_amount),
+            }),
+            ..ObligationLiquidity::default()
+        }
+    }
+
+    #[test]
+    fn fixed_rate_borrow_accrues_interest() {
+        let mut liquidity = fixed_rate_liquidity(1_000, 1_000);
+
+        // the variable rate only compounds the reserve share
+        liquidity.accrue_interest(Decimal::from(2u64)).unwrap();
+        assert_eq!(liquidity.borrowed_amount_wads, Decimal::from(1_000u64));
+        assert_eq!(
+            liquidity.fixed_rate.unwrap().reserve_borrowed_amount_wads,
+            Decimal::from(2_000u64)
+        );
+
+        // a year at 10% compounded every second
+        liquidity
+            .accrue_fixed_interest(SECONDS_PER_YEAR as i64)
+            .unwrap();
+        assert_eq!(
+            liquidity.borrowed_amount_wads.try_round_u64().unwrap(),
+            1_105
+        );
+        assert_eq!(
+            liquidity.fixed_rate.unwrap().last_accrual_timestamp,
+            SECONDS_PER_YEAR as i64
+        );
+
+        // accruing again at the same timestamp is a no-op
+        let borrowed_amount_wads = liquidity.borrowed_amount_wads;
+        liquidity
+            .accrue_fixed_interest(SECONDS_PER_YEAR as i64)
+            .unwrap();
+        assert_eq!(liquidity.borrowed_amount_wads, borrowed_amount_wads);
+
+        // variable borrows ignore fixed interest
+        let mut liquidity = ObligationLiquidity {
+            borrowed_amount_wads: Decimal::from(1_000u64),
+            ..ObligationLiquidity::default()
+        };
+        liquidity.accrue_fixed_interest(1_000).unwrap();
+        assert_eq!(liquidity.borrowed_amount_wads, Decimal::from(1_000u64));
+    }
+
+    #[test]
+    fn fixed_rate_borrow_settles_reserve_share() {
+        let mut liquidity = fixed_rate_liquidity(1_200, 1_100);
+        assert_eq!(
+            liquidity
+                .reserve_settle_amount(Decimal::from(600u64))
+                .unwrap(),
+            Decimal::from(550u64)
+        );
+        assert_eq!(
+            liquidity
+                .reserve_settle_amount(Decimal::from(1_200u64))
+                .unwrap(),
+            Decimal::from(1_100u64)
+        );
+
+        liquidity.repay(Decimal::from(600u64)).unwrap();
+        assert_eq!(liquidity.borrowed_amount_wads, Decimal::from(600u64));
+        assert_eq!(
+            liquidity.fixed_rate.unwrap().reserve_borrowed_amount_wads,
+            Decimal::from(550u64)
+        );
+
+        let liquidity = ObligationLiquidity {
+            borrowed_amount_wads: Decimal::from(1_000u64),
+            ..ObligationLiquidity::default()
+        };
+        assert_eq!(
+            liquidity
+                .reserve_settle_amount(Decimal::from(400u64))
+                .unwrap(),
+            Decimal::from(400u64)
+        );
+    }
+
+    #[test]
+    fn matured_fixed_rate_borrow_liquidation() {
+        let liquidity = ObligationLiquidity {
+            market_value: Decimal::from(100u64),
+            ..fixed_rate_liquidity(1_000, 1_000)
+        };
+        let obligation = Obligation {
+            borrowed_value: Decimal::from(100u64),
+            borrows: vec![liquidity.clone()],
+            ..Obligation::default()
+        };
+        assert!(!liquidity.is_matured(999));
+        assert_eq!(
+            obligation.max_liquidation_amount(&liquidity, 999).unwrap(),
+            Decimal::from(500u64)
+        );
+        assert!(liquidity.is_matured(1_000));
+        assert_eq!(
+            obligation
+                .max_liquidation_amount(&liquidity, 1_000)
+                .unwrap(),
+            Decimal::from(1_000u64)
+        );
+    }
+
+    #[test]
+    fn obligation_versions() {
+        assert!(Obligation::is_outdated(OBLIGATION_VERSION_FIXED));
+        assert!(Obligation::is_outdated(OBLIGATION_VERSION_RESIZABLE));
+        assert!(Obligation::is_outdated(OBLIGATION_VERSION_REFERRER));
+        assert!(Obligation::is_outdated(
+            OBLIGATION_VERSION_FIXED_RATE_BORROWS
+        ));
+        assert!(!Obligation::is_outdated(OBLIGATION_VERSION_REWARDS));
+
+        let mut data = vec![0; Obligation::LEN];
+        Obligation::pack(obligation_with_reserves(1, 1), &mut data).unwrap();
+        data[0] = OBLIGATION_VERSION_REWARDS + 1;
+        assert_eq!(
+            Obligation::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    fn obligation_with_version(version: u8) -> Obligation {
+        let mut obligation = obligation_with_reserves(2, 2);
+        if version == OBLIGATION_VERSION_FIXED {
+            return obligation;
+        }
+        obligation.resize(3, 3).unwrap();
+        if version >= OBLIGATION_VERSION_REFERRER {
+            obligation.referrer = Pubkey::new_unique();
+        }
+        if version >= OBLIGATION_VERSION_FIXED_RATE_BORROWS {
+            obligation.borrows[1].fixed_rate = Some(FixedRateBorrow {
+                borrow_rate: Rate::from_percent(12),
+                maturity_timestamp: 1_700_000_000,
+                last_accrual_timestamp: 1_699_000_000,
+                reserve_borrowed_amount_wads: Decimal::from(2u64),
+            });
+        }
+        if version >= OBLIGATION_VERSION_REWARDS {
+            obligation.deposits[0].reward_index = Decimal::from(3u64);
+            obligation.deposits[0].unclaimed_rewards_wads = Decimal::from_scaled_val(5);
+            obligation.borrows[1].reward_index = Decimal::from(4u64);
+            obligation.borrows[1].unclaimed_rewards_wads = Decimal::from(6u64);
+        }
+        obligation.version = version;
+        obligation
+    }
+
+    #[test]
+    fn migrate_obligation_versions() {
+        for version in [
+            OBLIGATION_VERSION_FIXED,
+            OBLIGATION_VERSION_RESIZABLE,
+            OBLIGATION_VERSION_REFERRER,
+            OBLIGATION_VERSION_FIXED_RATE_BORROWS,
+            OBLIGATION_VERSION_REWARDS,
+        ] {
+            let obligation = obligation_with_version(version);
+            let mut data = vec![0; obligation.packed_len()];
+            Obligation::pack(obligation.clone(), &mut data).unwrap();
+            let mut unpacked = Obligation::unpack(&data).unwrap();
+            assert_eq!(unpacked, obligation);
+
+            let len = unpacked.migrate();
+            assert_eq!(unpacked.version, OBLIGATION_VERSION_REWARDS);
+            assert!(!Obligation::is_outdated(unpacked.version));
+            if version == OBLIGATION_VERSION_FIXED {
+                assert_eq!(len, Obligation::resizable_len(10, 10));
+            } else {
+                assert_eq!(len, Obligation::resizable_len(3, 3));
+            }
+
+            let mut data = vec![0; len];
+            Obligation::pack(unpacked.clone(), &mut data).unwrap();
+            let migrated = Obligation::unpack(&data).unwrap();
+            assert_eq!(migrated, unpacked);
+            assert_eq!(migrated.deposits, obligation.deposits);
+            assert_eq!(migrated.borrows, obligation.borrows);
+            assert_eq!(migrated.referrer, obligation.referrer);
+        }
+    }
+
+    #[test]
+    fn resize_obligation_limits() {
+        let mut obligation = obligation_with_reserves(2, 3);
+        assert_eq!(
+            obligation.resize(1, 3),
+            Err(LendingError::ObligationReserveLimit.into())
+        );
+        assert_eq!(
+            obligation.resize(2, 2),
+            Err(LendingError::ObligationReserveLimit.into())
+        );
+        assert_eq!(
+            obligation.resize(33, 3),
+            Err(LendingError::ObligationReserveLimit.into())
+        );
+        assert_eq!(obligation.version, OBLIGATION_VERSION_FIXED);
+
+        obligation.resize(2, 4).unwrap();
+        assert_eq!(
+            obligation
+                .find_or_add_collateral_to_deposits(Pubkey::new_unique(), Decimal::one())
+                .unwrap_err(),
+            LendingError::ObligationReserveLimit.into()
+        );
+        assert!(obligation
+            .find_or_add_liquidity_to_borrows(Pubkey::new_unique(), Decimal::one(), Decimal::one(),)
+            .is_ok());
+        assert_eq!(
+            obligation
+                .find_or_add_liquidity_to_borrows(
+                    Pubkey::new_unique(),
+                    Decimal::one(),
+                    Decimal::one(),
+                )
+                .unwrap_err(),
+            LendingError::ObligationReserveLimit.into()
+        );
+    }
+
+    #[test]
+    fn obligation_accrue_rewards() {
+        let mut collateral = ObligationCollateral {
+            deposited_amount: 100,
+            reward_index: Decimal::one(),
+            ..ObligationCollateral::default()
+        };
+        collateral.accrue_rewards(Decimal::from(3u64)).unwrap();
+        assert_eq!(collateral.unclaimed_rewards_wads, Decimal::from(200u64));
+        assert_eq!(collateral.reward_index, Decimal::from(3u64));
+
+        // the reward index of a reserve never decreases
+        assert_eq!(
+            collateral.accrue_rewards(Decimal::one()),
+            Err(LendingError::MathOverflow.into())
+        );
+
+        // collateral deposited before obligations tracked rewards starts
+        // earning at its first settlement
+        let mut untracked = ObligationCollateral {
+            deposited_amount: 100,
+            ..ObligationCollateral::default()
+        };
+        untracked.accrue_rewards(Decimal::from(3u64)).unwrap();
+        assert_eq!(untracked.unclaimed_rewards_wads, Decimal::zero());
+        assert_eq!(untracked.reward_index, Decimal::from(3u64));
+
+        // fixed rate borrows earn on the reserve liquidity they account for
+        let mut liquidity = fixed_rate_liquidity(1_200, 1_000);
+        liquidity.reward_index = Decimal::one();
+        liquidity.accrue_rewards(Decimal::from(2u64)).unwrap();
+        assert_eq!(liquidity.unclaimed_rewards_wads, Decimal::from(1_000u64));
+    }
+
+    #[test]
+    fn obligation_claim_rewards() {
+        let mut obligation = obligation_with_reserves(2, 1);
+        let reserve = obligation.deposits[0].deposit_reserve;
+        obligation.borrows[0].borrow_reserve = reserve;
+        obligation.deposits[0].unclaimed_rewards_wads =
+            Decimal::from_scaled_val(3 * WAD as u128 + 1);
+        obligation.borrows[0].unclaimed_rewards_wads = Decimal::from(5u64);
+        obligation.deposits[1].unclaimed_rewards_wads = Decimal::from(7u64);
+
+        // emptied deposits and borrows are kept until their rewards are
+        // claimed
+        let deposited_amount = obligation.deposits[0].deposited_amount;
+        obligation.withdraw(deposited_amount, 0).unwrap();
+        let borrowed_amount_wads = obligation.borrows[0].borrowed_amount_wads;
+        obligation.repay(borrowed_amount_wads, 0).unwrap();
+        assert_eq!(obligation.deposits.len(), 2);
+        assert_eq!(obligation.deposits[0].deposited_amount, 0);
+        assert_eq!(obligation.borrows.len(), 1);
+        assert_eq!(obligation.borrows[0].borrowed_amount_wads, Decimal::zero());
+
+        // claims are capped by the reward supply, and pay whole reward tokens
+        // leaving dust which is dropped with the emptied deposit
+        assert_eq!(obligation.claim_rewards(reserve, 6).unwrap(), 6);
+        assert_eq!(obligation.deposits.len(), 1);
+        assert_eq!(
+            obligation.borrows[0].unclaimed_rewards_wads,
+            Decimal::from(2u64)
+        );
+
+        assert_eq!(obligation.claim_rewards(reserve, u64::MAX).unwrap(), 2);
+        assert!(obligation.borrows.is_empty());
+        assert_eq!(
+            obligation.deposits[0].unclaimed_rewards_wads,
+            Decimal::from(7u64)
+        );
+
+        // deposits without rewards are removed once fully withdrawn
+        let deposited_amount = obligation.deposits[0].deposited_amount;
+        obligation.deposits[0].unclaimed_rewards_wads = Decimal::zero();
+        obligation.withdraw(deposited_amount, 0).unwrap();
+        assert!(obligation.deposits.is_empty());
+    }
+
+    #[test]
+    fn obligation_forfeit_rewards() {
+        let mut obligation = obligation_with_reserves(2, 2);
+        let reserve = obligation.deposits[0].deposit_reserve;
+        obligation.borrows[0].borrow_reserve = reserve;
+        obligation.deposits[0].unclaimed_rewards_wads = Decimal::from(3u64);
+        obligation.borrows[0].unclaimed_rewards_wads = Decimal::from(5u64);
+        obligation.deposits[1].unclaimed_rewards_wads = Decimal::from(7u64);
+
+        // rewards of deposits and borrows which are not emptied are kept
+        assert_eq!(
+            obligation.forfeit_rewards(reserve),
+            Err(LendingError::InvalidAccountInput.into())
+        );
+
+        let deposited_amount = obligation.deposits[0].deposited_amount;
+        obligation.withdraw(deposited_amount, 0).unwrap();
+        let borrowed_amount_wads = obligation.borrows[0].borrowed_amount_wads;
+        obligation.repay(borrowed_amount_wads, 0).unwrap();
+        assert_eq!(
+            obligation.forfeit_rewards(reserve).unwrap(),
+            Decimal::from(8u64)
+        );
+        assert_eq!(obligation.deposits.len(), 1);
+        assert_eq!(obligation.borrows.len(), 1);
+        assert_ne!(obligation.deposits[0].deposit_reserve, reserve);
+        assert_ne!(obligation.borrows[0].borrow_reserve, reserve);
+        assert_eq!(
+            obligation.deposits[0].unclaimed_rewards_wads,
+            Decimal::from(7u64)
+        );
+    }
+
+    #[test]
+    fn obligation_health_factor() {
+        let mut obligation = Obligation {
+            unhealthy_borrow_value: Decimal::from(150u64),
+            ..Obligation::default()
+        };
+        assert_eq!(
+            obligation.health_factor(),
+            Ok(Decimal::from_scaled_val(u128::MAX))
+        );
+
+        obligation.borrowed_value = Decimal::from(100u64);
+        assert_eq!(
+            obligation.health_factor(),
+            Ok(Decimal::from_scaled_val(WAD as u128 * 3 / 2))
+        );
+    }
+
+    #[test]
+    fn obligation_header_in_place() {
+        let mut data = vec![0; Obligation::LEN];
+        assert_eq!(
+            ObligationHeader::load(&data).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+
+        let fixed = obligation_with_reserves(4, 6);
+        let mut resized = obligation_with_reserves(12, 3);
+        let len = resized.resize(16, 4).unwrap();
+        for (obligation, len) in [(fixed, Obligation::LEN), (resized, len)] {
+            let mut data = vec![0; len];
+            Obligation::pack(obligation.clone(), &mut data).unwrap();
+
+            let header = ObligationHeader::load(&data).unwrap();
+            assert_eq!(header.version(), obligation.version);
+            assert_eq!(header.last_update_slot(), obligation.last_update.slot);
+            assert_eq!(header.stale(), obligation.last_update.stale);
+            assert_eq!(header.lending_market(), obligation.lending_market);
+            assert_eq!(header.owner(), obligation.owner);
+            assert_eq!(header.deposited_value(), obligation.deposited_value);
+            assert_eq!(header.borrowed_value(), obligation.borrowed_value);
+            assert_eq!(
+                header.allowed_borrow_value(),
+                obligation.allowed_borrow_value
+            );
+            assert_eq!(
+                header.unhealthy_borrow_value(),
+                obligation.unhealthy_borrow_value
+            );
+            assert_eq!(header.deposits_len() as usize, obligation.deposits.len());
+            assert_eq!(header.borrows_len() as usize, obligation.borrows.len());
+
+            // writes in place are read back by unpack
+            let owner = Pubkey::new_unique();
+            ObligationHeader::load_mut(&mut data)
+                .unwrap()
+                .set_owner(&owner);
+            assert_eq!(
+                Obligation::unpack(&data).unwrap(),
+                Obligation {
+                    owner,
+                    ..obligation
+                }
+            );
+
+            data.push(0);
+            assert_eq!(
+                ObligationHeader::load(&data).err(),
+                Some(ProgramError::InvalidAccountData)
+            );
+        }
+
+        data[0] = OBLIGATION_VERSION_REWARDS + 1;
+        assert_eq!(
+            ObligationHeader::load(&data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
 }
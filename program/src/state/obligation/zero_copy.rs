@@ -0,0 +1,146 @@
+use {
+    super::{
+        pack::OBLIGATION_HEADER_LEN, Obligation, OBLIGATION_VERSION_FIXED,
+        OBLIGATION_VERSION_FIXED_RATE_BORROWS, OBLIGATION_VERSION_REFERRER,
+        OBLIGATION_VERSION_RESIZABLE, OBLIGATION_VERSION_REWARDS,
+    },
+    crate::{math::Decimal, state::UNINITIALIZED_VERSION},
+    bytemuck::{Pod, Zeroable},
+    solana_program::{
+        msg,
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+    std::mem::size_of,
+};
+
+/// Obligation header read and written in place, laid out field by field as
+/// `Obligation::pack` writes the fields shared by every obligation layout.
+/// Fields are stored as little endian bytes, as the packed layout is not
+/// aligned.
+///
+/// Processors only touching the header of an obligation can borrow it with
+/// `ObligationHeader::load` or `ObligationHeader::load_mut` instead of
+/// unpacking and packing every deposit and borrow.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct ObligationHeader {
+    version: [u8; 1],
+    last_update_slot: [u8; 8],
+    last_update_stale: [u8; 1],
+    lending_market: [u8; PUBKEY_BYTES],
+    owner: [u8; PUBKEY_BYTES],
+    deposited_value: [u8; 16],
+    borrowed_value: [u8; 16],
+    allowed_borrow_value: [u8; 16],
+    unhealthy_borrow_value: [u8; 16],
+    deposits_len: [u8; 1],
+    borrows_len: [u8; 1],
+}
+
+// only byte arrays, so the layout has no padding and an alignment of one
+unsafe impl Zeroable for ObligationHeader {}
+unsafe impl Pod for ObligationHeader {}
+
+const _: () = assert!(size_of::<ObligationHeader>() == OBLIGATION_HEADER_LEN);
+
+impl ObligationHeader {
+    /// Borrow the header of initialized obligation account data
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        Self::check_layout(data)?;
+        Ok(bytemuck::from_bytes(&data[..OBLIGATION_HEADER_LEN]))
+    }
+
+    /// Mutably borrow the header of initialized obligation account data
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        Self::check_layout(data)?;
+        Ok(bytemuck::from_bytes_mut(&mut data[..OBLIGATION_HEADER_LEN]))
+    }
+
+    fn check_layout(data: &[u8]) -> Result<(), ProgramError> {
+        let expected_len = match data.first() {
+            Some(&UNINITIALIZED_VERSION) => return Err(ProgramError::UninitializedAccount),
+            Some(&OBLIGATION_VERSION_FIXED) => Obligation::LEN,
+            Some(
+                &version @ (OBLIGATION_VERSION_REWARDS
+                | OBLIGATION_VERSION_FIXED_RATE_BORROWS
+                | OBLIGATION_VERSION_REFERRER
+                | OBLIGATION_VERSION_RESIZABLE),
+            ) if data.len() > OBLIGATION_HEADER_LEN + 1 => Obligation::layout_len(
+                version,
+                data[OBLIGATION_HEADER_LEN],
+                data[OBLIGATION_HEADER_LEN + 1],
+            ),
+            _ => {
+                msg!("Obligation version does not match lending program version");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        };
+        if data.len() != expected_len {
+            msg!("Obligation account data length does not match the obligation layout");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    /// Version of the struct
+    pub fn version(&self) -> u8 {
+        self.version[0]
+    }
+
+    /// Last slot when the obligation was updated
+    pub fn last_update_slot(&self) -> u64 {
+        u64::from_le_bytes(self.last_update_slot)
+    }
+
+    /// Whether the obligation is stale
+    pub fn stale(&self) -> bool {
+        self.last_update_stale[0] != 0
+    }
+
+    /// Lending market address
+    pub fn lending_market(&self) -> Pubkey {
+        Pubkey::new_from_array(self.lending_market)
+    }
+
+    /// Owner authority which can borrow liquidity
+    pub fn owner(&self) -> Pubkey {
+        Pubkey::new_from_array(self.owner)
+    }
+
+    /// Set the owner authority which can borrow liquidity
+    pub fn set_owner(&mut self, owner: &Pubkey) {
+        self.owner = owner.to_bytes();
+    }
+
+    /// Market value of deposits
+    pub fn deposited_value(&self) -> Decimal {
+        Decimal::from_scaled_val(u128::from_le_bytes(self.deposited_value))
+    }
+
+    /// Market value of borrows
+    pub fn borrowed_value(&self) -> Decimal {
+        Decimal::from_scaled_val(u128::from_le_bytes(self.borrowed_value))
+    }
+
+    /// The maximum borrow value at the weighted average loan to value ratio
+    pub fn allowed_borrow_value(&self) -> Decimal {
+        Decimal::from_scaled_val(u128::from_le_bytes(self.allowed_borrow_value))
+    }
+
+    /// The dangerous borrow value at the weighted average liquidation threshold
+    pub fn unhealthy_borrow_value(&self) -> Decimal {
+        Decimal::from_scaled_val(u128::from_le_bytes(self.unhealthy_borrow_value))
+    }
+
+    /// Number of deposits
+    pub fn deposits_len(&self) -> u8 {
+        self.deposits_len[0]
+    }
+
+    /// Number of borrows
+    pub fn borrows_len(&self) -> u8 {
+        self.borrows_len[0]
+    }
+}
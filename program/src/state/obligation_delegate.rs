@@ -0,0 +1,186 @@
+use {
+    super::{Migrate, PROGRAM_VERSION, UNINITIALIZED_VERSION},
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    solana_program::{
+        msg,
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+};
+
+/// Actions an obligation delegate can take on behalf of the obligation owner
+///
+/// Repaying needs no permission since anyone can repay an obligation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DelegatePermissions(pub u8);
+
+impl DelegatePermissions {
+    /// Deposit collateral to the obligation
+    pub const DEPOSIT: Self = Self(1);
+    /// Withdraw collateral to token accounts of the obligation owner
+    pub const WITHDRAW: Self = Self(1 << 1);
+    /// Borrow to token accounts of the obligation owner while the obligation
+    /// borrowed value stays within the delegate limit
+    pub const BORROW: Self = Self(1 << 2);
+    /// Every permission
+    pub const ALL: Self = Self(Self::DEPOSIT.0 | Self::WITHDRAW.0 | Self::BORROW.0);
+
+    /// Whether every permission of `other` is granted
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether only known permissions are set
+    pub fn is_valid(self) -> bool {
+        Self::ALL.contains(self)
+    }
+}
+
+/// Initialize an obligation delegate
+pub struct InitObligationDelegateParams {
+    /// Obligation the delegate acts on
+    pub obligation: Pubkey,
+    /// Obligation owner registering the delegate
+    pub owner: Pubkey,
+    /// Delegate authority
+    pub delegate: Pubkey,
+    /// Actions the delegate can take
+    pub permissions: DelegatePermissions,
+    /// Max borrowed value of the obligation after a borrow by the delegate
+    pub max_borrow_value: u64,
+}
+
+/// Key allowed to manage an obligation on behalf of its owner
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObligationDelegate {
+    /// Version of the struct
+    pub version: u8,
+    /// Obligation the delegate acts on
+    pub obligation: Pubkey,
+    /// Obligation owner which registered the delegate, the delegate can no
+    /// longer act once the obligation owner changes
+    pub owner: Pubkey,
+    /// Delegate authority
+    pub delegate: Pubkey,
+    /// Actions the delegate can take
+    pub permissions: DelegatePermissions,
+    /// Max borrowed value of the obligation after a borrow by the delegate,
+    /// in the lending market quote currency
+    pub max_borrow_value: u64,
+}
+
+impl Sealed for ObligationDelegate {}
+impl IsInitialized for ObligationDelegate {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl ObligationDelegate {
+    /// Create a new obligation delegate
+    pub fn new(params: InitObligationDelegateParams) -> Self {
+        let mut obligation_delegate = Self::default();
+        Self::init(&mut obligation_delegate, params);
+        obligation_delegate
+    }
+
+    /// Initialize an obligation delegate
+    pub fn init(&mut self, params: InitObligationDelegateParams) {
+        self.version = PROGRAM_VERSION;
+        self.obligation = params.obligation;
+        self.owner = params.owner;
+        self.delegate = params.delegate;
+        self.permissions = params.permissions;
+        self.max_borrow_value = params.max_borrow_value;
+    }
+}
+
+const OBLIGATION_DELEGATE_LEN: usize = 170; // 1 + 32 + 32 + 32 + 1 + 8 + 64
+impl Migrate for ObligationDelegate {
+    /// Obligation delegates have a single layout
+    fn is_outdated(_version: u8) -> bool {
+        false
+    }
+}
+
+impl Pack for ObligationDelegate {
+    const LEN: usize = OBLIGATION_DELEGATE_LEN;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, OBLIGATION_DELEGATE_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (version, obligation, owner, delegate, permissions, max_borrow_value, _padding) = mut_array_refs![
+            output,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            1,
+            8,
+            64
+        ];
+        *version = self.version.to_le_bytes();
+        obligation.copy_from_slice(self.obligation.as_ref());
+        owner.copy_from_slice(self.owner.as_ref());
+        delegate.copy_from_slice(self.delegate.as_ref());
+        *permissions = self.permissions.0.to_le_bytes();
+        *max_borrow_value = self.max_borrow_value.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, OBLIGATION_DELEGATE_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (version, obligation, owner, delegate, permissions, max_borrow_value, _padding) =
+            array_refs![input, 1, PUBKEY_BYTES, PUBKEY_BYTES, PUBKEY_BYTES, 1, 8, 64];
+        let version = u8::from_le_bytes(*version);
+        match version {
+            UNINITIALIZED_VERSION | PROGRAM_VERSION => {}
+            _ => {
+                msg!("Obligation delegate version does not match lending program version");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        Ok(Self {
+            version,
+            obligation: Pubkey::new_from_array(*obligation),
+            owner: Pubkey::new_from_array(*owner),
+            delegate: Pubkey::new_from_array(*delegate),
+            permissions: DelegatePermissions(u8::from_le_bytes(*permissions)),
+            max_borrow_value: u64::from_le_bytes(*max_borrow_value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn delegate_permissions() {
+        let permissions =
+            DelegatePermissions(DelegatePermissions::DEPOSIT.0 | DelegatePermissions::WITHDRAW.0);
+        assert!(permissions.contains(DelegatePermissions::DEPOSIT));
+        assert!(permissions.contains(DelegatePermissions::WITHDRAW));
+        assert!(!permissions.contains(DelegatePermissions::BORROW));
+        assert!(permissions.is_valid());
+        assert!(!DelegatePermissions(1 << 7).is_valid());
+    }
+
+    #[test]
+    fn pack_obligation_delegate() {
+        let obligation_delegate = ObligationDelegate::new(InitObligationDelegateParams {
+            obligation: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            delegate: Pubkey::new_unique(),
+            permissions: DelegatePermissions::ALL,
+            max_borrow_value: 1_000,
+        });
+        let mut data = [0; ObligationDelegate::LEN];
+        ObligationDelegate::pack(obligation_delegate.clone(), &mut data).unwrap();
+        assert_eq!(
+            ObligationDelegate::unpack(&data).unwrap(),
+            obligation_delegate
+        );
+    }
+}
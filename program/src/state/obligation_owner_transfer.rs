@@ -0,0 +1,123 @@
+use {
+    super::{Migrate, PROGRAM_VERSION, UNINITIALIZED_VERSION},
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    solana_program::{
+        msg,
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+};
+
+/// Initialize an obligation owner transfer
+pub struct InitObligationOwnerTransferParams {
+    /// Obligation being transferred
+    pub obligation: Pubkey,
+    /// Obligation owner proposing the transfer
+    pub owner: Pubkey,
+    /// Owner which must accept the obligation
+    pub new_owner: Pubkey,
+}
+
+/// Obligation ownership transfer waiting for the new owner to accept it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObligationOwnerTransfer {
+    /// Version of the struct
+    pub version: u8,
+    /// Obligation being transferred
+    pub obligation: Pubkey,
+    /// Obligation owner which proposed the transfer, the transfer can no
+    /// longer be accepted once the obligation owner changes
+    pub owner: Pubkey,
+    /// Owner which must accept the obligation
+    pub new_owner: Pubkey,
+}
+
+impl Sealed for ObligationOwnerTransfer {}
+impl IsInitialized for ObligationOwnerTransfer {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl ObligationOwnerTransfer {
+    /// Create a new obligation owner transfer
+    pub fn new(params: InitObligationOwnerTransferParams) -> Self {
+        let mut obligation_owner_transfer = Self::default();
+        Self::init(&mut obligation_owner_transfer, params);
+        obligation_owner_transfer
+    }
+
+    /// Initialize an obligation owner transfer
+    pub fn init(&mut self, params: InitObligationOwnerTransferParams) {
+        self.version = PROGRAM_VERSION;
+        self.obligation = params.obligation;
+        self.owner = params.owner;
+        self.new_owner = params.new_owner;
+    }
+}
+
+const OBLIGATION_OWNER_TRANSFER_LEN: usize = 161; // 1 + 32 + 32 + 32 + 64
+impl Migrate for ObligationOwnerTransfer {
+    /// Obligation owner transfers have a single layout
+    fn is_outdated(_version: u8) -> bool {
+        false
+    }
+}
+
+impl Pack for ObligationOwnerTransfer {
+    const LEN: usize = OBLIGATION_OWNER_TRANSFER_LEN;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, OBLIGATION_OWNER_TRANSFER_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (version, obligation, owner, new_owner, _padding) =
+            mut_array_refs![output, 1, PUBKEY_BYTES, PUBKEY_BYTES, PUBKEY_BYTES, 64];
+        *version = self.version.to_le_bytes();
+        obligation.copy_from_slice(self.obligation.as_ref());
+        owner.copy_from_slice(self.owner.as_ref());
+        new_owner.copy_from_slice(self.new_owner.as_ref());
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, OBLIGATION_OWNER_TRANSFER_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (version, obligation, owner, new_owner, _padding) =
+            array_refs![input, 1, PUBKEY_BYTES, PUBKEY_BYTES, PUBKEY_BYTES, 64];
+        let version = u8::from_le_bytes(*version);
+        match version {
+            UNINITIALIZED_VERSION | PROGRAM_VERSION => {}
+            _ => {
+                msg!("Obligation owner transfer version does not match lending program version");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        Ok(Self {
+            version,
+            obligation: Pubkey::new_from_array(*obligation),
+            owner: Pubkey::new_from_array(*owner),
+            new_owner: Pubkey::new_from_array(*new_owner),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pack_obligation_owner_transfer() {
+        let obligation_owner_transfer =
+            ObligationOwnerTransfer::new(InitObligationOwnerTransferParams {
+                obligation: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                new_owner: Pubkey::new_unique(),
+            });
+        let mut data = [0; ObligationOwnerTransfer::LEN];
+        ObligationOwnerTransfer::pack(obligation_owner_transfer.clone(), &mut data).unwrap();
+        assert_eq!(
+            ObligationOwnerTransfer::unpack(&data).unwrap(),
+            obligation_owner_transfer
+        );
+    }
+}
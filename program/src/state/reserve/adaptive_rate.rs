@@ -0,0 +1,92 @@
+use {
+    crate::{
+        error::LendingError,
+        math::{Decimal, Rate, TryAdd, TryDiv, TryMul, TrySub},
+    },
+    solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError},
+};
+
+/// Lowest borrow rate multiplier the adaptive model can reach, as a percentage
+pub const ADAPTIVE_RATE_MIN_MULTIPLIER: u8 = 25;
+
+/// Highest borrow rate multiplier the adaptive model can reach
+pub const ADAPTIVE_RATE_MAX_MULTIPLIER: u64 = 4;
+
+/// Adaptive interest rate model configuration
+///
+/// When enabled, the borrow rate curve is scaled by a multiplier stored on
+/// the reserve. The multiplier grows while utilization stays above target and
+/// shrinks while it stays below, so the longer the market is out of balance
+/// the stronger the rate adjustment.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AdaptiveRateConfig {
+    /// Utilization rate the model steers towards, in basis points
+    /// 0 if the adaptive model is disabled
+    pub target_utilization_rate_bps: u16,
+    /// Max change of the borrow rate multiplier per day, in basis points,
+    /// reached when utilization is at 0 or 10_000 bps
+    pub adjustment_speed_bps: u16,
+}
+
+impl AdaptiveRateConfig {
+    /// Configuration with the adaptive model turned off
+    pub const DISABLED: Self = Self {
+        target_utilization_rate_bps: 0,
+        adjustment_speed_bps: 0,
+    };
+
+    /// Whether the adaptive model is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.target_utilization_rate_bps > 0
+    }
+
+    /// Check the target utilization rate leaves room on both sides
+    pub fn validate(&self) -> ProgramResult {
+        if self.target_utilization_rate_bps >= 10_000 {
+            msg!("Adaptive target utilization rate must be in range [0, 10_000) bps");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        Ok(())
+    }
+
+    /// Calculate the multiplier after utilization stayed at a rate for a
+    /// number of elapsed periods, either seconds or slots
+    pub fn adjust_multiplier(
+        &self,
+        multiplier: Decimal,
+        utilization_rate: Rate,
+        periods_elapsed: u64,
+        periods_per_year: u64,
+    ) -> Result<Decimal, ProgramError> {
+        let target_utilization_rate = Rate::from_bps(self.target_utilization_rate_bps.into());
+
+        //* Above target: deviation = (utilization_rate - target) / (1 - target)
+        //* Below target: deviation = (target - utilization_rate) / target
+        let above_target = utilization_rate >= target_utilization_rate;
+        let deviation = if above_target {
+            utilization_rate
+                .try_sub(target_utilization_rate)?
+                .try_div(Rate::one().try_sub(target_utilization_rate)?)?
+        } else {
+            target_utilization_rate
+                .try_sub(utilization_rate)?
+                .try_div(target_utilization_rate)?
+        };
+
+        //* factor = 1 + adjustment_speed * deviation * periods_elapsed / periods_per_day
+        let factor =
+            Decimal::from(Rate::from_bps(self.adjustment_speed_bps.into()).try_mul(deviation)?)
+                .try_mul(periods_elapsed)?
+                .try_div(periods_per_year / 365)?
+                .try_add(Decimal::one())?;
+        let multiplier = if above_target {
+            multiplier.try_mul(factor)?
+        } else {
+            multiplier.try_div(factor)?
+        };
+
+        Ok(multiplier
+            .max(Rate::from_percent(ADAPTIVE_RATE_MIN_MULTIPLIER).into())
+            .min(ADAPTIVE_RATE_MAX_MULTIPLIER.into()))
+    }
+}
@@ -0,0 +1,61 @@
+use {
+    crate::{
+        error::LendingError,
+        math::{Rate, TryAdd},
+    },
+    solana_program::{clock::UnixTimestamp, msg, program_error::ProgramError},
+};
+
+/// Fixed rate borrow configuration
+///
+/// Fixed rate borrows lock the current borrow rate of the reserve plus a
+/// premium for a term chosen by the borrower. Once the term ends the borrow
+/// keeps accruing at its fixed rate and can be liquidated until repaid.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FixedRateConfig {
+    /// Premium added to the current borrow rate, in basis points
+    pub premium_bps: u32,
+    /// Longest term of a fixed rate borrow, in seconds
+    /// 0 if fixed rate borrows are disabled
+    pub max_term: u32,
+}
+
+impl FixedRateConfig {
+    /// Configuration with fixed rate borrows turned off
+    pub const DISABLED: Self = Self {
+        premium_bps: 0,
+        max_term: 0,
+    };
+
+    /// Whether fixed rate borrows are enabled
+    pub fn is_enabled(&self) -> bool {
+        self.max_term > 0
+    }
+
+    /// Calculate the rate locked by a fixed rate borrow
+    pub fn borrow_rate(&self, current_borrow_rate: Rate) -> Result<Rate, ProgramError> {
+        current_borrow_rate.try_add(Rate::from_bps(self.premium_bps))
+    }
+
+    /// Calculate when a fixed rate borrow of the given term matures
+    pub fn maturity_timestamp(
+        &self,
+        term: u32,
+        current_unix_timestamp: UnixTimestamp,
+    ) -> Result<UnixTimestamp, ProgramError> {
+        if !self.is_enabled() {
+            msg!("Reserve does not allow fixed rate borrows");
+            return Err(LendingError::InvalidFixedRateBorrow.into());
+        }
+        if term == 0 || term > self.max_term {
+            msg!(
+                "Fixed rate borrow term must be in range (0, {}] seconds",
+                self.max_term
+            );
+            return Err(LendingError::InvalidFixedRateBorrow.into());
+        }
+        current_unix_timestamp
+            .checked_add(term.into())
+            .ok_or_else(|| LendingError::MathOverflow.into())
+    }
+}
@@ -1,13 +1,19 @@
 use {
+    super::{
+        AdaptiveRateConfig, FixedRateConfig, RateCurve, MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS,
+    },
     crate::{
         error::LendingError,
-        math::{Decimal, Rate, TryAdd, TryDiv, TryMul, WAD},
+        math::{Decimal, Rate, TryAdd, TryDiv, TryMul, TrySub, WAD},
+    },
+    num_derive::FromPrimitive,
+    solana_program::{
+        clock::UnixTimestamp, entrypoint::ProgramResult, msg, program_error::ProgramError,
     },
-    solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError},
 };
 
 /// Calculate borrow result
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct CalculateBorrowResult {
     /// Total amount of borrow including fees
     pub borrow_amount: Decimal,
@@ -29,7 +35,7 @@ pub struct CalculateRepayResult {
 }
 
 /// Calculate liquidation result
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct CalculateLiquidationResult {
     /// Amount of liquidity that is settled from the obligation. It includes
     /// the amount of loan that was defaulted if collateral is depleted.
@@ -38,57 +44,71 @@ pub struct CalculateLiquidationResult {
     pub repay_amount: u64,
     /// Amount of collateral to withdraw in exchange for repay amount
     pub withdraw_amount: u64,
+    /// Portion of the withdraw amount sent to the reserve collateral fee
+    /// receiver instead of the liquidator
+    pub protocol_fee: u64,
 }
 
 /// Reserve configuration values
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct ReserveConfig {
-    /// Optimal utilization rate, as a percentage
-    pub optimal_utilization_rate: u8,
-    /// Target ratio of the value of borrows to deposits, as a percentage
+    /// Optimal utilization rate, in basis points
+    pub optimal_utilization_rate_bps: u16,
+    /// Target ratio of the value of borrows to deposits, in basis points
     /// 0 if use as collateral is disabled
-    pub loan_to_value_ratio: u8,
+    pub loan_to_value_ratio_bps: u16,
     /// Bonus a liquidator gets when repaying part of an unhealthy obligation,
-    /// as a percentage
-    pub liquidation_bonus: u8,
-    /// Loan to value ratio at which an obligation can be liquidated, as a
-    /// percentage
-    pub liquidation_threshold: u8,
-    /// Min borrow APY
-    pub min_borrow_rate: u8,
-    /// Optimal (utilization) borrow APY
-    pub optimal_borrow_rate: u8,
-    /// Max borrow APY
-    pub max_borrow_rate: u8,
+    /// in basis points
+    pub liquidation_bonus_bps: u16,
+    /// Loan to value ratio at which an obligation can be liquidated, in basis
+    /// points
+    pub liquidation_threshold_bps: u16,
+    /// Min borrow APY, in basis points
+    pub min_borrow_rate_bps: u32,
+    /// Optimal (utilization) borrow APY, in basis points
+    pub optimal_borrow_rate_bps: u32,
+    /// Max borrow APY, in basis points
+    pub max_borrow_rate_bps: u32,
     /// Program owner fees assessed, separate from gains due to interest accrual
     pub fees: ReserveFees,
+    /// Borrow rate curve replacing the min, optimal and max borrow rates, if
+    /// not empty
+    pub rate_curve: RateCurve,
+    /// Adaptive interest rate model scaling the borrow rate curve
+    pub adaptive_rate: AdaptiveRateConfig,
+    /// Max total liquidity supply of the reserve, 0 if unlimited
+    pub deposit_limit: u64,
+    /// Max borrowed liquidity of the reserve, 0 if unlimited
+    pub borrow_limit: u64,
+    /// Fixed rate borrows of the reserve liquidity
+    pub fixed_rate: FixedRateConfig,
 }
 
 impl ReserveConfig {
     pub fn validate(&self) -> ProgramResult {
-        if self.optimal_utilization_rate > 100 {
-            msg!("Optimal utilization rate must be in range [0, 100]");
+        if self.optimal_utilization_rate_bps > 10_000 {
+            msg!("Optimal utilization rate must be in range [0, 10_000] bps");
             return Err(LendingError::InvalidConfig.into());
         }
-        if self.loan_to_value_ratio >= 100 {
-            msg!("Loan to value ratio must be in range [0, 100)");
+        if self.loan_to_value_ratio_bps >= 10_000 {
+            msg!("Loan to value ratio must be in range [0, 10_000) bps");
             return Err(LendingError::InvalidConfig.into());
         }
-        if self.liquidation_bonus > 100 {
-            msg!("Liquidation bonus must be in range [0, 100]");
+        if self.liquidation_bonus_bps > 10_000 {
+            msg!("Liquidation bonus must be in range [0, 10_000] bps");
             return Err(LendingError::InvalidConfig.into());
         }
-        if self.liquidation_threshold <= self.loan_to_value_ratio
-            || self.liquidation_threshold > 100
+        if self.liquidation_threshold_bps <= self.loan_to_value_ratio_bps
+            || self.liquidation_threshold_bps > 10_000
         {
-            msg!("Liquidation threshold must be in range (LTV, 100]");
+            msg!("Liquidation threshold must be in range (LTV, 10_000] bps");
             return Err(LendingError::InvalidConfig.into());
         }
-        if self.optimal_borrow_rate < self.min_borrow_rate {
+        if self.optimal_borrow_rate_bps < self.min_borrow_rate_bps {
             msg!("Optimal borrow rate must be >= min borrow rate");
             return Err(LendingError::InvalidConfig.into());
         }
-        if self.optimal_borrow_rate > self.max_borrow_rate {
+        if self.optimal_borrow_rate_bps > self.max_borrow_rate_bps {
             msg!("Optimal borrow rate must be <= max borrow rate");
             return Err(LendingError::InvalidConfig.into());
         }
@@ -100,13 +120,144 @@ impl ReserveConfig {
             msg!("Flash loan fee must be in range [0, 1_000_000_000_000_000_000)");
             return Err(LendingError::InvalidConfig.into());
         }
-        if self.fees.host_fee_percentage > 100 {
-            msg!("Host fee percentage must be in range [0, 100]");
+        if self.fees.host_fee_bps > 10_000 {
+            msg!("Host fee must be in range [0, 10_000] bps");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        if self.fees.protocol_liquidation_fee_bps > 10_000 {
+            msg!("Protocol liquidation fee must be in range [0, 10_000] bps");
             return Err(LendingError::InvalidConfig.into());
         }
+        self.rate_curve.validate()?;
+        self.adaptive_rate.validate()?;
 
         Ok(())
     }
+
+    /// Check a change to a new config cannot make existing obligations
+    /// liquidatable all at once
+    pub fn validate_transition(&self, new_config: &ReserveConfig) -> ProgramResult {
+        let liquidation_threshold_decrease = self
+            .liquidation_threshold_bps
+            .saturating_sub(new_config.liquidation_threshold_bps);
+        if liquidation_threshold_decrease > MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS {
+            msg!(
+                "Liquidation threshold can be lowered by at most {} bps per change",
+                MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS
+            );
+            return Err(LendingError::LiquidationThresholdDecreaseTooLarge.into());
+        }
+        Ok(())
+    }
+
+    /// Check a new config only lowers the loan to value ratio and limits,
+    /// leaving every other value unchanged
+    pub fn validate_risk_reduction(&self, new_config: &ReserveConfig) -> ProgramResult {
+        if let Some(reason) = self.risk_increase(new_config) {
+            msg!(reason);
+            return Err(LendingError::RoleNotPermitted.into());
+        }
+        Ok(())
+    }
+
+    /// Whether a new config only lowers the loan to value ratio and limits
+    pub fn is_risk_reduction(&self, new_config: &ReserveConfig) -> bool {
+        self.risk_increase(new_config).is_none()
+    }
+
+    fn risk_increase(&self, new_config: &ReserveConfig) -> Option<&'static str> {
+        if new_config.loan_to_value_ratio_bps > self.loan_to_value_ratio_bps {
+            return Some("Loan to value ratio can only be lowered");
+        }
+        if effective_limit(new_config.deposit_limit) > effective_limit(self.deposit_limit) {
+            return Some("Deposit limit can only be lowered");
+        }
+        if effective_limit(new_config.borrow_limit) > effective_limit(self.borrow_limit) {
+            return Some("Borrow limit can only be lowered");
+        }
+        let unchanged = ReserveConfig {
+            loan_to_value_ratio_bps: new_config.loan_to_value_ratio_bps,
+            deposit_limit: new_config.deposit_limit,
+            borrow_limit: new_config.borrow_limit,
+            ..*self
+        };
+        if unchanged != *new_config {
+            return Some("Only the loan to value ratio and limits can be changed");
+        }
+        None
+    }
+}
+
+/// Reserve config change waiting for its timelock to expire
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PendingReserveConfig {
+    /// Config replacing the current one
+    pub config: ReserveConfig,
+    /// Unix timestamp from which the config can be applied
+    pub activation_timestamp: UnixTimestamp,
+}
+
+/// Linear move of the loan to value ratio and liquidation threshold of a
+/// reserve from their initial values to target values, which are queued as
+/// the pending config of the reserve
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CollateralRamp {
+    /// Loan to value ratio at the start of the ramp, in basis points
+    pub initial_loan_to_value_ratio_bps: u16,
+    /// Liquidation threshold at the start of the ramp, in basis points
+    pub initial_liquidation_threshold_bps: u16,
+    /// Loan to value ratio at the end of the ramp, in basis points
+    pub target_loan_to_value_ratio_bps: u16,
+    /// Liquidation threshold at the end of the ramp, in basis points
+    pub target_liquidation_threshold_bps: u16,
+    /// Unix timestamp when the ramp starts
+    pub start_timestamp: UnixTimestamp,
+    /// Unix timestamp when the target values are reached
+    pub end_timestamp: UnixTimestamp,
+}
+
+impl CollateralRamp {
+    /// Value moving linearly from the initial to the target value over the
+    /// ramp
+    pub(super) fn interpolate(
+        &self,
+        initial_value: u16,
+        target_value: u16,
+        unix_timestamp: UnixTimestamp,
+    ) -> u16 {
+        if unix_timestamp >= self.end_timestamp {
+            return target_value;
+        }
+        if unix_timestamp <= self.start_timestamp {
+            return initial_value;
+        }
+        let elapsed = i128::from(unix_timestamp) - i128::from(self.start_timestamp);
+        let duration = i128::from(self.end_timestamp) - i128::from(self.start_timestamp);
+        let change = (i128::from(target_value) - i128::from(initial_value)) * elapsed / duration;
+        // the value lies between the initial and target values
+        (i128::from(initial_value) + change) as u16
+    }
+}
+
+/// Limit where 0 means unlimited
+fn effective_limit(limit: u64) -> u64 {
+    if limit == 0 {
+        u64::MAX
+    } else {
+        limit
+    }
+}
+
+/// Operations allowed on a reserve, ordered from least to most restrictive
+#[derive(Clone, Copy, Debug, Default, Eq, FromPrimitive, Ord, PartialEq, PartialOrd)]
+pub enum ReserveStatus {
+    /// All operations are allowed
+    #[default]
+    Active,
+    /// Deposits and borrows are rejected, positions can only be reduced
+    ReduceOnly,
+    /// Only repayments and liquidations are allowed
+    Paused,
 }
 
 /// Calculate fees exclusive or inclusive of an amount
@@ -131,11 +282,17 @@ pub struct ReserveFees {
     /// 0.01% (1 basis point) = 100_000_000_000_000
     /// 0.00001% (Aave borrow fee) = 100_000_000_000
     pub borrow_fee_wad: u64,
-    /// Fee for flash loan, expressed as a Wad.
+    /// Fee for flash loan, expressed as a Wad. Unused while flash loans are
+    /// not processed.
     /// 0.3% (Aave flash loan fee) = 3_000_000_000_000_000
     pub flash_loan_fee_wad: u64,
-    /// Amount of fee going to host account, if provided in liquidate and repay
-    pub host_fee_percentage: u8,
+    /// Share of the fees going to the host account, if provided in borrow
+    /// and liquidate, in basis points
+    pub host_fee_bps: u16,
+    /// Share of the liquidation bonus kept by the protocol, in basis points.
+    /// It is withheld from the seized collateral and sent to the reserve
+    /// collateral fee receiver.
+    pub protocol_liquidation_fee_bps: u16,
 }
 
 impl ReserveFees {
@@ -159,6 +316,30 @@ impl ReserveFees {
         )
     }
 
+    /// Calculate the protocol share of the liquidation bonus contained in a
+    /// collateral withdraw amount
+    pub fn calculate_protocol_liquidation_fee(
+        &self,
+        withdraw_amount: u64,
+        bonus_rate: Rate,
+    ) -> Result<u64, ProgramError> {
+        let protocol_fee_rate = Rate::from_bps(self.protocol_liquidation_fee_bps.into());
+        if protocol_fee_rate == Rate::zero() || withdraw_amount == 0 {
+            return Ok(0);
+        }
+        // bonus = withdraw_amount - withdraw_amount / (1 + liquidation_bonus)
+        let withdraw_amount = Decimal::from(withdraw_amount);
+        let bonus_amount = withdraw_amount.try_sub(withdraw_amount.try_div(bonus_rate)?)?;
+        bonus_amount.try_mul(protocol_fee_rate)?.try_floor_u64()
+    }
+
+    /// Calculate the host share of a protocol liquidation fee
+    pub fn calculate_liquidation_host_fee(&self, protocol_fee: u64) -> Result<u64, ProgramError> {
+        Decimal::from(protocol_fee)
+            .try_mul(Rate::from_bps(self.host_fee_bps.into()))?
+            .try_floor_u64()
+    }
+
     pub fn calculate_fees(
         &self,
         amount: Decimal,
@@ -166,7 +347,7 @@ impl ReserveFees {
         fee_calculation: FeeCalculation,
     ) -> Result<(u64, u64), ProgramError> {
         let borrow_fee_rate = Rate::from_scaled_val(fee_wad);
-        let host_fee_rate = Rate::from_percent(self.host_fee_percentage);
+        let host_fee_rate = Rate::from_bps(self.host_fee_bps.into());
         if borrow_fee_rate > Rate::zero() && amount > Decimal::zero() {
             let need_to_assess_host_fee = host_fee_rate > Rate::zero();
             let minimum_fee: u64 = if need_to_assess_host_fee {
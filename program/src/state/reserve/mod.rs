@@ -1,13 +1,17 @@
+mod adaptive_rate;
+mod fixed_rate;
 mod misc;
 mod pack;
+mod rate_curve;
 mod reserve_collateral;
 mod reserve_liquidity;
 mod test;
+mod zero_copy;
 
 use {
     super::{
         last_update::LastUpdate, Obligation, ObligationCollateral, ObligationLiquidity,
-        PROGRAM_VERSION,
+        ReserveRewardEmissions, PROGRAM_VERSION, SECONDS_PER_YEAR, SLOTS_PER_YEAR,
     },
     crate::{
         debug_msg,
@@ -16,11 +20,18 @@ use {
         utils::get_pow,
     },
     solana_program::{
-        clock::Slot, entrypoint::ProgramResult, msg, program_error::ProgramError, pubkey::Pubkey,
+        clock::{Slot, UnixTimestamp, SECONDS_PER_DAY},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
     },
     std::cmp::Ordering,
 };
-pub use {misc::*, reserve_collateral::*, reserve_liquidity::*};
+pub use {
+    adaptive_rate::*, fixed_rate::*, misc::*, rate_curve::*, reserve_collateral::*,
+    reserve_liquidity::*, zero_copy::*,
+};
 
 /// Percentage of an obligation that can be repaid during each liquidation call
 pub const LIQUIDATION_CLOSE_FACTOR: u8 = 50;
@@ -28,6 +39,87 @@ pub const LIQUIDATION_CLOSE_FACTOR: u8 = 50;
 /// Obligation borrow amount that is small enough to close out
 pub const LIQUIDATION_CLOSE_AMOUNT: u64 = 2;
 
+/// Reserve version storing rates of the config as percentages
+pub const RESERVE_VERSION_PERCENT_CONFIG: u8 = PROGRAM_VERSION;
+
+/// Reserve version storing rates of the config in basis points
+pub const RESERVE_VERSION_BPS_CONFIG: u8 = 2;
+
+/// Reserve version storing every value of the config in basis points, in a
+/// larger account
+pub const RESERVE_VERSION_EXTENDED_CONFIG: u8 = 3;
+
+/// Delay before a reserve config change queued by the lending market owner
+/// can be applied, in seconds
+pub const RESERVE_CONFIG_TIMELOCK: UnixTimestamp = SECONDS_PER_DAY as UnixTimestamp;
+
+/// Max decrease of the liquidation threshold of a reserve in a single config
+/// change, in basis points
+pub const MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS: u16 = 500;
+
+/// Seed of the reserve of a liquidity mint in a lending market
+pub const RESERVE_SEED: &[u8] = b"reserve";
+
+/// Seed of the reserve liquidity supply token account
+pub const RESERVE_LIQUIDITY_SUPPLY_SEED: &[u8] = b"liquidity_supply";
+
+/// Seed of the reserve liquidity fee receiver token account
+pub const RESERVE_LIQUIDITY_FEE_RECEIVER_SEED: &[u8] = b"liquidity_fee_receiver";
+
+/// Seed of the reserve collateral mint
+pub const RESERVE_COLLATERAL_MINT_SEED: &[u8] = b"collateral_mint";
+
+/// Seed of the reserve collateral supply token account
+pub const RESERVE_COLLATERAL_SUPPLY_SEED: &[u8] = b"collateral_supply";
+
+/// Seed of the reserve collateral fee receiver token account
+pub const RESERVE_COLLATERAL_FEE_RECEIVER_SEED: &[u8] = b"collateral_fee_receiver";
+
+/// Find the reserve of a liquidity mint in a lending market, so each market
+/// holds at most one reserve per mint
+pub fn find_reserve_address(
+    program_id: &Pubkey,
+    lending_market: &Pubkey,
+    liquidity_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    find_reserve_account_address(program_id, RESERVE_SEED, lending_market, liquidity_mint)
+}
+
+/// Find an account created with the reserve of a liquidity mint in a lending
+/// market, from one of the reserve account seeds
+pub fn find_reserve_account_address(
+    program_id: &Pubkey,
+    seed: &[u8],
+    lending_market: &Pubkey,
+    liquidity_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[seed, lending_market.as_ref(), liquidity_mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Seed of the token accounts collecting the borrow fees of a reserve for a
+/// referrer
+pub const REFERRER_FEE_RECEIVER_SEED: &[u8] = b"referrer_fee_receiver";
+
+/// Find the token account collecting the borrow fees of a reserve for a
+/// referrer
+pub fn find_referrer_fee_receiver_address(
+    program_id: &Pubkey,
+    reserve: &Pubkey,
+    referrer: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            REFERRER_FEE_RECEIVER_SEED,
+            reserve.as_ref(),
+            referrer.as_ref(),
+        ],
+        program_id,
+    )
+}
+
 /// Lending market reserve state
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Reserve {
@@ -43,6 +135,18 @@ pub struct Reserve {
     pub collateral: ReserveCollateral,
     /// Reserve configuration values
     pub config: ReserveConfig,
+    /// Multiplier applied to the borrow rate by the adaptive rate model
+    /// Zero on reserves created before the model existed, treated as one
+    pub adaptive_rate_multiplier: Decimal,
+    /// Operations allowed on the reserve
+    pub status: ReserveStatus,
+    /// Config change queued by the lending market owner
+    pub pending_config: Option<PendingReserveConfig>,
+    /// Gradual move of the loan to value ratio and liquidation threshold
+    /// towards their config values
+    pub collateral_ramp: Option<CollateralRamp>,
+    /// Reward tokens emitted to the obligation positions of the reserve
+    pub reward_emissions: Option<ReserveRewardEmissions>,
 }
 
 impl Reserve {
@@ -54,23 +158,203 @@ impl Reserve {
     }
     /// Initialize a reserve
     pub fn init(&mut self, params: InitReserveParams) {
-        self.version = PROGRAM_VERSION;
-        self.last_update = LastUpdate::new(params.current_slot);
+        self.version = RESERVE_VERSION_EXTENDED_CONFIG;
+        self.last_update = LastUpdate {
+            unix_timestamp: params.current_unix_timestamp,
+            ..LastUpdate::new(params.current_slot)
+        };
         self.lending_market = params.lending_market;
         self.liquidity = params.liquidity;
         self.collateral = params.collateral;
         self.config = params.config;
+        self.adaptive_rate_multiplier = Decimal::one();
+        self.status = ReserveStatus::Active;
     }
     /// Record deposited liquidity and return amount of collateral tokens to
     /// mint
     pub fn deposit_liquidity(&mut self, liquidity_amount: u64) -> Result<u64, ProgramError> {
-        let collateral_amount = self
-            .collateral_exchange_rate()?
-            .liquidity_to_collateral(liquidity_amount)?;
-        self.liquidity.deposit(liquidity_amount)?;
-        self.collateral.mint(collateral_amount)?;
-        Ok(collateral_amount)
+        deposit_liquidity(
+            &mut self.liquidity,
+            &mut self.collateral,
+            self.config.deposit_limit,
+            liquidity_amount,
+        )
+    }
+    /// Queue a config change, replacing any pending one, which can be applied
+    /// once the timelock expires
+    pub fn queue_config(
+        &mut self,
+        config: ReserveConfig,
+        current_unix_timestamp: UnixTimestamp,
+    ) -> Result<UnixTimestamp, ProgramError> {
+        let activation_timestamp = current_unix_timestamp
+            .checked_add(RESERVE_CONFIG_TIMELOCK)
+            .ok_or(LendingError::MathOverflow)?;
+        self.pending_config = Some(PendingReserveConfig {
+            config,
+            activation_timestamp,
+        });
+        Ok(activation_timestamp)
+    }
+
+    /// Replace the config with the pending one if its timelock expired
+    pub fn apply_pending_config(&mut self, current_unix_timestamp: UnixTimestamp) -> ProgramResult {
+        let pending_config = self.pending_config.ok_or_else(|| {
+            msg!("Reserve has no pending config");
+            LendingError::NoPendingReserveConfig
+        })?;
+        if current_unix_timestamp < pending_config.activation_timestamp {
+            msg!(
+                "Pending reserve config cannot be applied before {}",
+                pending_config.activation_timestamp
+            );
+            return Err(LendingError::ReserveConfigTimelocked.into());
+        }
+        self.set_config(pending_config.config);
+        self.pending_config = None;
+        Ok(())
+    }
+
+    /// Replace the config, cancelling the collateral ramp unless the config
+    /// holds the loan to value ratio and liquidation threshold it moves
+    /// towards
+    pub fn set_config(&mut self, config: ReserveConfig) {
+        if let Some(ramp) = &self.collateral_ramp {
+            if config.loan_to_value_ratio_bps != ramp.target_loan_to_value_ratio_bps
+                || config.liquidation_threshold_bps != ramp.target_liquidation_threshold_bps
+            {
+                self.collateral_ramp = None;
+            }
+        }
+        self.config = config;
     }
+
+    /// Replace the config with one only lowering the loan to value ratio and
+    /// limits. A collateral ramp keeps going, with its loan to value ratios
+    /// capped by the new one, as is the pending config it moves towards. Any
+    /// other pending config is dropped if requested.
+    pub fn reduce_risk(&mut self, config: ReserveConfig, drop_pending_config: bool) {
+        let ramp_target = self.collateral_ramp.as_ref().and_then(|ramp| {
+            self.pending_config
+                .as_ref()
+                .filter(|pending_config| {
+                    pending_config.config.loan_to_value_ratio_bps
+                        == ramp.target_loan_to_value_ratio_bps
+                        && pending_config.config.liquidation_threshold_bps
+                            == ramp.target_liquidation_threshold_bps
+                })
+                .map(|pending_config| pending_config.config)
+        });
+        if let Some(ramp) = &mut self.collateral_ramp {
+            ramp.initial_loan_to_value_ratio_bps = ramp
+                .initial_loan_to_value_ratio_bps
+                .min(config.loan_to_value_ratio_bps);
+            ramp.target_loan_to_value_ratio_bps = ramp
+                .target_loan_to_value_ratio_bps
+                .min(config.loan_to_value_ratio_bps);
+        }
+        match (ramp_target, &mut self.pending_config) {
+            (Some(ramp_target), Some(pending_config)) => {
+                pending_config.config = ReserveConfig {
+                    loan_to_value_ratio_bps: ramp_target
+                        .loan_to_value_ratio_bps
+                        .min(config.loan_to_value_ratio_bps),
+                    liquidation_threshold_bps: ramp_target.liquidation_threshold_bps,
+                    ..config
+                };
+            }
+            _ if drop_pending_config => self.pending_config = None,
+            _ => {}
+        }
+        self.config = config;
+    }
+
+    /// Move the loan to value ratio and liquidation threshold linearly from
+    /// their current values to new values between two timestamps. The new
+    /// values are queued as the pending config, replacing any pending one, and
+    /// the ramp cannot start before the timelock of the pending config expires.
+    /// Return the activation timestamp of the pending config.
+    pub fn start_collateral_ramp(
+        &mut self,
+        loan_to_value_ratio_bps: u16,
+        liquidation_threshold_bps: u16,
+        start_timestamp: UnixTimestamp,
+        end_timestamp: UnixTimestamp,
+        current_unix_timestamp: UnixTimestamp,
+    ) -> Result<UnixTimestamp, ProgramError> {
+        let min_start_timestamp = current_unix_timestamp
+            .checked_add(RESERVE_CONFIG_TIMELOCK)
+            .ok_or(LendingError::MathOverflow)?;
+        if start_timestamp < min_start_timestamp {
+            msg!(
+                "Collateral ramp cannot start before the config timelock expires at {}",
+                min_start_timestamp
+            );
+            return Err(LendingError::InvalidCollateralRamp.into());
+        }
+        let min_end_timestamp = start_timestamp
+            .checked_add(RESERVE_CONFIG_TIMELOCK)
+            .ok_or(LendingError::MathOverflow)?;
+        if end_timestamp < min_end_timestamp {
+            msg!(
+                "Collateral ramp must last at least {} seconds",
+                RESERVE_CONFIG_TIMELOCK
+            );
+            return Err(LendingError::InvalidCollateralRamp.into());
+        }
+        let config = ReserveConfig {
+            loan_to_value_ratio_bps,
+            liquidation_threshold_bps,
+            ..self.config
+        };
+        config.validate()?;
+
+        let initial_loan_to_value_ratio_bps = self.loan_to_value_ratio_bps(current_unix_timestamp);
+        let initial_liquidation_threshold_bps =
+            self.liquidation_threshold_bps(current_unix_timestamp);
+        ReserveConfig {
+            loan_to_value_ratio_bps: initial_loan_to_value_ratio_bps,
+            liquidation_threshold_bps: initial_liquidation_threshold_bps,
+            ..self.config
+        }
+        .validate_transition(&config)?;
+
+        let activation_timestamp = self.queue_config(config, current_unix_timestamp)?;
+        self.collateral_ramp = Some(CollateralRamp {
+            initial_loan_to_value_ratio_bps,
+            initial_liquidation_threshold_bps,
+            target_loan_to_value_ratio_bps: loan_to_value_ratio_bps,
+            target_liquidation_threshold_bps: liquidation_threshold_bps,
+            start_timestamp,
+            end_timestamp,
+        });
+        Ok(activation_timestamp)
+    }
+
+    /// Loan to value ratio in effect at the given time, in basis points
+    pub fn loan_to_value_ratio_bps(&self, unix_timestamp: UnixTimestamp) -> u16 {
+        match &self.collateral_ramp {
+            Some(ramp) => ramp.interpolate(
+                ramp.initial_loan_to_value_ratio_bps,
+                ramp.target_loan_to_value_ratio_bps,
+                unix_timestamp,
+            ),
+            None => self.config.loan_to_value_ratio_bps,
+        }
+    }
+
+    /// Liquidation threshold in effect at the given time, in basis points
+    pub fn liquidation_threshold_bps(&self, unix_timestamp: UnixTimestamp) -> u16 {
+        match &self.collateral_ramp {
+            Some(ramp) => ramp.interpolate(
+                ramp.initial_liquidation_threshold_bps,
+                ramp.target_liquidation_threshold_bps,
+                unix_timestamp,
+            ),
+            None => self.config.liquidation_threshold_bps,
+        }
+    }
+
     /// Record redeemed collateral and return amount of liquidity to withdraw
     pub fn redeem_collateral(&mut self, collateral_amount: u64) -> Result<u64, ProgramError> {
         let collateral_exchange_rate = self.collateral_exchange_rate()?;
@@ -82,60 +366,32 @@ impl Reserve {
         Ok(liquididy_amount)
     }
 
-    //* Calculate the current borrow rate
-    //*  This design uses Piecewise Function to encourage:
-    //* 1. When utilization is low: Gentle rate increases to encourage borrowing
-    //* 2. When utilization is high: Sharp rate increases to discourage borrowing
-    //*    and protect the liquidity pool
-    //*
+    /// Calculate the current borrow rate
     pub fn current_borrow_rate(&self) -> Result<Rate, ProgramError> {
-        let utilization_rate = self.liquidity.utilization_rate()?;
-        let optimal_utilization_rate = Rate::from_percent(self.config.optimal_utilization_rate);
-
-        //low utilization is when the utilization rate is less than the optimal
-        // utilization rate.
-
-        let low_utilization = utilization_rate < optimal_utilization_rate;
+        current_borrow_rate(&self.liquidity, &self.config, self.adaptive_rate_multiplier)
+    }
 
-        // When it's low utilization:
-        //* `borrow_rate = normalized_rate * rate_range + min_rate`.
-        //* where:
-        //* normalized_rate = utilization_rate / optimal_utilization_rate
-
-        if low_utilization || self.config.optimal_utilization_rate == 100 {
-            //normalized rate is how much the utilization rate is close to the optimal
-            // utilization rate.
-            let normalized_rate = utilization_rate.try_div(optimal_utilization_rate)?;
-            let min_rate = Rate::from_percent(self.config.min_borrow_rate);
-            let rate_range = Rate::from_percent(
-                self.config
-                    .optimal_borrow_rate
-                    .checked_sub(self.config.min_borrow_rate)
-                    .ok_or(LendingError::MathOverflow)?,
-            );
+    /// Multiplier applied to the borrow rate by the adaptive rate model
+    pub fn adaptive_rate_multiplier(&self) -> Decimal {
+        adaptive_rate_multiplier(self.adaptive_rate_multiplier)
+    }
 
-            Ok(normalized_rate.try_mul(rate_range)?.try_add(min_rate)?)
-        } else {
-            //* When is high utilization: borrow_rate = normalized_rate * rate_range + min_rate.
-            //* where:
-            //* normalized_rate = (utilization_rate - optimal_utilization_rate) / (100 - optimal_utilization_rate)
-            let normalized_rate = utilization_rate
-                .try_sub(optimal_utilization_rate)?
-                .try_div(Rate::from_percent(
-                    100u8
-                        .checked_sub(self.config.optimal_utilization_rate)
-                        .ok_or(LendingError::MathOverflow)?,
-                ))?;
-            let min_rate = Rate::from_percent(self.config.optimal_borrow_rate);
-            let rate_range = Rate::from_percent(
-                self.config
-                    .max_borrow_rate
-                    .checked_sub(self.config.optimal_borrow_rate)
-                    .ok_or(LendingError::MathOverflow)?,
-            );
+    /// Reward index new deposits of reserve collateral start earning from,
+    /// the initial index when the reserve emits no rewards yet
+    pub fn supply_reward_index(&self) -> Decimal {
+        self.reward_emissions
+            .map_or(Decimal::one(), |reward_emissions| {
+                reward_emissions.supply_index
+            })
+    }
 
-            Ok(normalized_rate.try_mul(rate_range)?.try_add(min_rate)?)
-        }
+    /// Reward index new borrows of reserve liquidity start earning from, the
+    /// initial index when the reserve emits no rewards yet
+    pub fn borrow_reward_index(&self) -> Decimal {
+        self.reward_emissions
+            .map_or(Decimal::one(), |reward_emissions| {
+                reward_emissions.borrow_index
+            })
     }
 
     pub fn collateral_exchange_rate(&self) -> Result<CollateralExchangeRate, ProgramError> {
@@ -143,22 +399,25 @@ impl Reserve {
         self.collateral.exchange_rate(total_liquidity)
     }
     /// Update borrow rate and accrue interest
-    pub fn accrue_interest(&mut self, current_slot: Slot) -> ProgramResult {
-        let slots_elapsed = self.last_update.slots_elapsed(current_slot)?;
-        if slots_elapsed > 0 {
-            let current_borrow_rate = self.current_borrow_rate()?;
-            self.liquidity
-                .compound_interest(current_borrow_rate, slots_elapsed)
-                .map_err(|e| {
-                    debug_msg!(
-                        "Error in accrue_interest:, current_borrow_rate: {}, slots_elapsed: {}",
-                        current_borrow_rate,
-                        slots_elapsed
-                    );
-                    e
-                })?;
-        }
-        Ok(())
+    ///
+    /// Interest accrues over the seconds elapsed since the last update.
+    /// Reserves which have not recorded a timestamp yet accrue over the
+    /// elapsed slots instead.
+    pub fn accrue_interest(
+        &mut self,
+        current_slot: Slot,
+        current_unix_timestamp: UnixTimestamp,
+    ) -> ProgramResult {
+        accrue_interest(
+            &self.last_update,
+            &mut self.liquidity,
+            self.collateral.mint_total_supply,
+            &self.config,
+            &mut self.adaptive_rate_multiplier,
+            self.reward_emissions.as_mut(),
+            current_slot,
+            current_unix_timestamp,
+        )
     }
 
     /// Borrow liquidity up to a maximum market value
@@ -167,64 +426,19 @@ impl Reserve {
         amount_to_borrow: u64,
         max_borrow_value: Decimal,
     ) -> Result<CalculateBorrowResult, ProgramError> {
-        let decimals = get_pow(self.liquidity.mint_decimals as u32)?;
-        if amount_to_borrow == u64::MAX {
-            let borrow_amount = max_borrow_value
-                .try_mul(decimals)?
-                .try_div(self.liquidity.market_price)?
-                .min(self.liquidity.available_amount.into());
-            let (borrow_fee, host_fee) = self
-                .config
-                .fees
-                .calculate_borrow_fees(borrow_amount, FeeCalculation::Inclusive)?;
-            let receive_amount = borrow_amount
-                .try_floor_u64()?
-                .checked_sub(borrow_fee)
-                .ok_or(LendingError::MathOverflow)?;
-            Ok(CalculateBorrowResult {
-                borrow_amount,
-                receive_amount,
-                borrow_fee,
-                host_fee,
-            })
-        } else {
-            let receive_amount = amount_to_borrow;
-            let borrow_amount = Decimal::from(receive_amount);
-            let (borrow_fee, host_fee) = self
-                .config
-                .fees
-                .calculate_borrow_fees(borrow_amount, FeeCalculation::Exclusive)?;
-            let borrow_amount = borrow_amount.try_add(borrow_fee.into())?;
-            let borrow_value = borrow_amount
-                .try_mul(self.liquidity.market_price)?
-                .try_div(decimals)?;
-            if borrow_value > max_borrow_value {
-                msg!("Borrow value cannot exceed maximum borrow value");
-                return Err(LendingError::BorrowTooLarge.into());
-            }
-            Ok(CalculateBorrowResult {
-                borrow_amount,
-                receive_amount,
-                borrow_fee,
-                host_fee,
-            })
-        }
+        calculate_borrow(
+            &self.liquidity,
+            &self.config.fees,
+            amount_to_borrow,
+            max_borrow_value,
+        )
     }
     pub fn calculate_repay(
         &self,
         amount_to_repay: u64,
         borrowed_amount: Decimal,
     ) -> Result<CalculateRepayResult, ProgramError> {
-        let settle_amount = if amount_to_repay == u64::MAX {
-            borrowed_amount
-        } else {
-            Decimal::from(amount_to_repay).min(borrowed_amount)
-        };
-        let repay_amount = settle_amount.try_ceil_u64()?;
-        Ok(CalculateRepayResult {
-            settle_amount,
-            repay_amount,
-        })
+        calculate_repay(amount_to_repay, borrowed_amount)
     }
     pub fn calculate_liquidation(
         &self,
@@ -232,88 +446,346 @@ impl Reserve {
         obligation: &Obligation,
         liquidity: &ObligationLiquidity,
         collateral: &ObligationCollateral,
+        current_unix_timestamp: UnixTimestamp,
     ) -> Result<CalculateLiquidationResult, ProgramError> {
-        let bonus_rate = Rate::from_percent(self.config.liquidation_bonus).try_add(Rate::one())?;
-        let max_amount = if amount_to_liquidate == u64::MAX {
-            liquidity.borrowed_amount_wads
-        } else {
-            Decimal::from(amount_to_liquidate).min(liquidity.borrowed_amount_wads)
-        };
+        calculate_liquidation(
+            self.config.liquidation_bonus_bps,
+            &self.config.fees,
+            amount_to_liquidate,
+            obligation,
+            liquidity,
+            collateral,
+            current_unix_timestamp,
+        )
+    }
+}
+
+/// Record deposited liquidity within the deposit limit and return amount of
+/// collateral tokens to mint
+fn deposit_liquidity(
+    liquidity: &mut ReserveLiquidity,
+    collateral: &mut ReserveCollateral,
+    deposit_limit: u64,
+    liquidity_amount: u64,
+) -> Result<u64, ProgramError> {
+    if deposit_limit > 0
+        && liquidity.total_supply()?.try_add(liquidity_amount.into())?
+            > Decimal::from(deposit_limit)
+    {
+        msg!("Deposit would exceed the reserve deposit limit");
+        return Err(LendingError::DepositLimitExceeded.into());
+    }
+    let collateral_amount = collateral
+        .exchange_rate(liquidity.total_supply()?)?
+        .liquidity_to_collateral(liquidity_amount)?;
+    liquidity.deposit(liquidity_amount)?;
+    collateral.mint(collateral_amount)?;
+    Ok(collateral_amount)
+}
+
+/// Settle up to the borrowed amount, repaying the settled amount rounded up
+fn calculate_repay(
+    amount_to_repay: u64,
+    borrowed_amount: Decimal,
+) -> Result<CalculateRepayResult, ProgramError> {
+    let settle_amount = if amount_to_repay == u64::MAX {
+        borrowed_amount
+    } else {
+        Decimal::from(amount_to_repay).min(borrowed_amount)
+    };
+    let repay_amount = settle_amount.try_ceil_u64()?;
+    Ok(CalculateRepayResult {
+        settle_amount,
+        repay_amount,
+    })
+}
+
+/// Multiplier applied to the borrow rate by the adaptive rate model, where a
+/// zero multiplier stored by reserves created before the model is one
+fn adaptive_rate_multiplier(adaptive_rate_multiplier: Decimal) -> Decimal {
+    if adaptive_rate_multiplier == Decimal::zero() {
+        Decimal::one()
+    } else {
+        adaptive_rate_multiplier
+    }
+}
+
+/// Calculate the current borrow rate, scaled by the adaptive rate multiplier
+fn current_borrow_rate(
+    liquidity: &ReserveLiquidity,
+    config: &ReserveConfig,
+    adaptive_rate_multiplier: Decimal,
+) -> Result<Rate, ProgramError> {
+    let borrow_rate = base_borrow_rate(liquidity, config)?;
+    if !config.adaptive_rate.is_enabled() {
+        return Ok(borrow_rate);
+    }
+    borrow_rate.try_mul(Rate::try_from(self::adaptive_rate_multiplier(
+        adaptive_rate_multiplier,
+    ))?)
+}
+
+//* Calculate the current borrow rate
+//*  This design uses Piecewise Function to encourage:
+//* 1. When utilization is low: Gentle rate increases to encourage borrowing
+//* 2. When utilization is high: Sharp rate increases to discourage borrowing
+//*    and protect the liquidity pool
+//*
+fn base_borrow_rate(
+    liquidity: &ReserveLiquidity,
+    config: &ReserveConfig,
+) -> Result<Rate, ProgramError> {
+    let utilization_rate = liquidity.utilization_rate()?;
+    if !config.rate_curve.is_empty() {
+        return config.rate_curve.borrow_rate(utilization_rate);
+    }
+    let optimal_utilization_rate = Rate::from_bps(config.optimal_utilization_rate_bps.into());
+
+    //low utilization is when the utilization rate is less than the optimal
+    // utilization rate.
+
+    let low_utilization = utilization_rate < optimal_utilization_rate;
 
-        let settle_amount;
-        let repay_amount;
-        let withdraw_amount;
-
-        // Close out obligations that are too small to liquidate normally
-        if liquidity.borrowed_amount_wads < LIQUIDATION_CLOSE_AMOUNT.into() {
-            // settle_amount is fixed, calculate withdraw_amount and repay_amount
-            settle_amount = liquidity.borrowed_amount_wads;
-
-            let liquidation_value = liquidity.market_value.try_mul(bonus_rate)?;
-            match liquidation_value.cmp(&collateral.market_value) {
-                Ordering::Greater => {
-                    let repay_pct = collateral.market_value.try_div(liquidation_value)?;
-                    repay_amount = max_amount.try_mul(repay_pct)?.try_ceil_u64()?;
-                    withdraw_amount = collateral.deposited_amount;
-                }
-                Ordering::Equal => {
-                    repay_amount = max_amount.try_ceil_u64()?;
-                    withdraw_amount = collateral.deposited_amount;
-                }
-                Ordering::Less => {
-                    let withdraw_pct = liquidation_value.try_div(collateral.market_value)?;
-                    repay_amount = max_amount.try_floor_u64()?;
-                    withdraw_amount = Decimal::from(collateral.deposited_amount)
-                        .try_mul(withdraw_pct)?
-                        .try_floor_u64()?;
-                }
+    // When it's low utilization:
+    //* `borrow_rate = normalized_rate * rate_range + min_rate`.
+    //* where:
+    //* normalized_rate = utilization_rate / optimal_utilization_rate
+
+    if low_utilization || config.optimal_utilization_rate_bps == 10_000 {
+        //normalized rate is how much the utilization rate is close to the optimal
+        // utilization rate.
+        let normalized_rate = utilization_rate.try_div(optimal_utilization_rate)?;
+        let min_rate = Rate::from_bps(config.min_borrow_rate_bps);
+        let rate_range = Rate::from_bps(
+            config
+                .optimal_borrow_rate_bps
+                .checked_sub(config.min_borrow_rate_bps)
+                .ok_or(LendingError::MathOverflow)?,
+        );
+
+        Ok(normalized_rate.try_mul(rate_range)?.try_add(min_rate)?)
+    } else {
+        //* When is high utilization: borrow_rate = normalized_rate * rate_range + min_rate.
+        //* where:
+        //* normalized_rate = (utilization_rate - optimal_utilization_rate) / (100 - optimal_utilization_rate)
+        let normalized_rate = utilization_rate
+            .try_sub(optimal_utilization_rate)?
+            .try_div(Rate::from_bps(
+                10_000u16
+                    .checked_sub(config.optimal_utilization_rate_bps)
+                    .ok_or(LendingError::MathOverflow)?
+                    .into(),
+            ))?;
+        let min_rate = Rate::from_bps(config.optimal_borrow_rate_bps);
+        let rate_range = Rate::from_bps(
+            config
+                .max_borrow_rate_bps
+                .checked_sub(config.optimal_borrow_rate_bps)
+                .ok_or(LendingError::MathOverflow)?,
+        );
+
+        Ok(normalized_rate.try_mul(rate_range)?.try_add(min_rate)?)
+    }
+}
+
+/// Emit rewards, compound interest and adjust the adaptive rate multiplier
+/// over the time elapsed since the last update
+#[allow(clippy::too_many_arguments)]
+fn accrue_interest(
+    last_update: &LastUpdate,
+    liquidity: &mut ReserveLiquidity,
+    collateral_mint_total_supply: u64,
+    config: &ReserveConfig,
+    adaptive_rate_multiplier: &mut Decimal,
+    reward_emissions: Option<&mut ReserveRewardEmissions>,
+    current_slot: Slot,
+    current_unix_timestamp: UnixTimestamp,
+) -> ProgramResult {
+    let seconds_elapsed = last_update.seconds_elapsed(current_unix_timestamp);
+    let (periods_elapsed, periods_per_year) = match seconds_elapsed {
+        Some(seconds_elapsed) => (seconds_elapsed, SECONDS_PER_YEAR),
+        None => (last_update.slots_elapsed(current_slot)?, SLOTS_PER_YEAR),
+    };
+    if periods_elapsed > 0 {
+        // rewards are emitted to the liquidity borrowed before interest
+        // compounds
+        if let (Some(reward_emissions), Some(seconds_elapsed)) = (reward_emissions, seconds_elapsed)
+        {
+            reward_emissions.accrue(
+                collateral_mint_total_supply,
+                liquidity.borrowed_amount_wads,
+                seconds_elapsed,
+            )?;
+        }
+        let utilization_rate = liquidity.utilization_rate()?;
+        let current_borrow_rate =
+            current_borrow_rate(liquidity, config, *adaptive_rate_multiplier)?;
+        liquidity
+            .compound_interest(current_borrow_rate, periods_elapsed, periods_per_year)
+            .map_err(|e| {
+                debug_msg!(
+                    "Error in accrue_interest:, current_borrow_rate: {}, periods_elapsed: {}",
+                    current_borrow_rate,
+                    periods_elapsed
+                );
+                e
+            })?;
+        if config.adaptive_rate.is_enabled() {
+            *adaptive_rate_multiplier = config.adaptive_rate.adjust_multiplier(
+                self::adaptive_rate_multiplier(*adaptive_rate_multiplier),
+                utilization_rate,
+                periods_elapsed,
+                periods_per_year,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Borrow liquidity up to a maximum market value
+fn calculate_borrow(
+    liquidity: &ReserveLiquidity,
+    fees: &ReserveFees,
+    amount_to_borrow: u64,
+    max_borrow_value: Decimal,
+) -> Result<CalculateBorrowResult, ProgramError> {
+    let decimals = get_pow(liquidity.mint_decimals as u32)?;
+    if amount_to_borrow == u64::MAX {
+        let borrow_amount = max_borrow_value
+            .try_mul(decimals)?
+            .try_div(liquidity.market_price)?
+            .min(liquidity.available_amount.into());
+        let (borrow_fee, host_fee) =
+            fees.calculate_borrow_fees(borrow_amount, FeeCalculation::Inclusive)?;
+        let receive_amount = borrow_amount
+            .try_floor_u64()?
+            .checked_sub(borrow_fee)
+            .ok_or(LendingError::MathOverflow)?;
+        Ok(CalculateBorrowResult {
+            borrow_amount,
+            receive_amount,
+            borrow_fee,
+            host_fee,
+        })
+    } else {
+        let receive_amount = amount_to_borrow;
+        let borrow_amount = Decimal::from(receive_amount);
+        let (borrow_fee, host_fee) =
+            fees.calculate_borrow_fees(borrow_amount, FeeCalculation::Exclusive)?;
+        let borrow_amount = borrow_amount.try_add(borrow_fee.into())?;
+        let borrow_value = borrow_amount
+            .try_mul(liquidity.market_price)?
+            .try_div(decimals)?;
+        if borrow_value > max_borrow_value {
+            msg!("Borrow value cannot exceed maximum borrow value");
+            return Err(LendingError::BorrowTooLarge.into());
+        }
+        Ok(CalculateBorrowResult {
+            borrow_amount,
+            receive_amount,
+            borrow_fee,
+            host_fee,
+        })
+    }
+}
+
+/// Calculate the liquidity to repay and the collateral to withdraw in a
+/// liquidation, with the liquidation bonus and protocol fee of the withdraw
+/// reserve
+fn calculate_liquidation(
+    liquidation_bonus_bps: u16,
+    fees: &ReserveFees,
+    amount_to_liquidate: u64,
+    obligation: &Obligation,
+    liquidity: &ObligationLiquidity,
+    collateral: &ObligationCollateral,
+    current_unix_timestamp: UnixTimestamp,
+) -> Result<CalculateLiquidationResult, ProgramError> {
+    let bonus_rate = Rate::from_bps(liquidation_bonus_bps.into()).try_add(Rate::one())?;
+    let max_amount = if amount_to_liquidate == u64::MAX {
+        liquidity.borrowed_amount_wads
+    } else {
+        Decimal::from(amount_to_liquidate).min(liquidity.borrowed_amount_wads)
+    };
+
+    let settle_amount;
+    let repay_amount;
+    let withdraw_amount;
+
+    // Close out obligations that are too small to liquidate normally
+    if liquidity.borrowed_amount_wads < LIQUIDATION_CLOSE_AMOUNT.into() {
+        // settle_amount is fixed, calculate withdraw_amount and repay_amount
+        settle_amount = liquidity.borrowed_amount_wads;
+
+        let liquidation_value = liquidity.market_value.try_mul(bonus_rate)?;
+        match liquidation_value.cmp(&collateral.market_value) {
+            Ordering::Greater => {
+                let repay_pct = collateral.market_value.try_div(liquidation_value)?;
+                repay_amount = max_amount.try_mul(repay_pct)?.try_ceil_u64()?;
+                withdraw_amount = collateral.deposited_amount;
+            }
+            Ordering::Equal => {
+                repay_amount = max_amount.try_ceil_u64()?;
+                withdraw_amount = collateral.deposited_amount;
             }
-        } else {
-            // calculate settle_amount and withdraw_amount, repay_amount is settle_amount
-            // rounded
-            let liquidation_amount = obligation
-                .max_liquidation_amount(liquidity)?
-                .min(max_amount);
-            let liquidation_pct = liquidation_amount.try_div(liquidity.borrowed_amount_wads)?;
-            let liquidation_value = liquidity
-                .market_value
-                .try_mul(liquidation_pct)?
-                .try_mul(bonus_rate)?;
-
-            match liquidation_value.cmp(&collateral.market_value) {
-                Ordering::Greater => {
-                    let repay_pct = collateral.market_value.try_div(liquidation_value)?;
-                    settle_amount = liquidation_amount.try_mul(repay_pct)?;
-                    repay_amount = settle_amount.try_ceil_u64()?;
-                    withdraw_amount = collateral.deposited_amount;
-                }
-                Ordering::Equal => {
-                    settle_amount = liquidation_amount;
-                    repay_amount = settle_amount.try_ceil_u64()?;
-                    withdraw_amount = collateral.deposited_amount;
-                }
-                Ordering::Less => {
-                    let withdraw_pct = liquidation_value.try_div(collateral.market_value)?;
-                    settle_amount = liquidation_amount;
-                    repay_amount = settle_amount.try_floor_u64()?;
-                    withdraw_amount = Decimal::from(collateral.deposited_amount)
-                        .try_mul(withdraw_pct)?
-                        .try_floor_u64()?;
-                }
+            Ordering::Less => {
+                let withdraw_pct = liquidation_value.try_div(collateral.market_value)?;
+                repay_amount = max_amount.try_floor_u64()?;
+                withdraw_amount = Decimal::from(collateral.deposited_amount)
+                    .try_mul(withdraw_pct)?
+                    .try_floor_u64()?;
             }
         }
+    } else {
+        // calculate settle_amount and withdraw_amount, repay_amount is settle_amount
+        // rounded
+        let liquidation_amount = obligation
+            .max_liquidation_amount(liquidity, current_unix_timestamp)?
+            .min(max_amount);
+        let liquidation_pct = liquidation_amount.try_div(liquidity.borrowed_amount_wads)?;
+        let liquidation_value = liquidity
+            .market_value
+            .try_mul(liquidation_pct)?
+            .try_mul(bonus_rate)?;
 
-        Ok(CalculateLiquidationResult {
-            settle_amount,
-            repay_amount,
-            withdraw_amount,
-        })
+        match liquidation_value.cmp(&collateral.market_value) {
+            Ordering::Greater => {
+                let repay_pct = collateral.market_value.try_div(liquidation_value)?;
+                settle_amount = liquidation_amount.try_mul(repay_pct)?;
+                repay_amount = settle_amount.try_ceil_u64()?;
+                withdraw_amount = collateral.deposited_amount;
+            }
+            Ordering::Equal => {
+                settle_amount = liquidation_amount;
+                repay_amount = settle_amount.try_ceil_u64()?;
+                withdraw_amount = collateral.deposited_amount;
+            }
+            Ordering::Less => {
+                let withdraw_pct = liquidation_value.try_div(collateral.market_value)?;
+                settle_amount = liquidation_amount;
+                repay_amount = settle_amount.try_floor_u64()?;
+                withdraw_amount = Decimal::from(collateral.deposited_amount)
+                    .try_mul(withdraw_pct)?
+                    .try_floor_u64()?;
+            }
+        }
     }
+
+    let protocol_fee = fees.calculate_protocol_liquidation_fee(withdraw_amount, bonus_rate)?;
+
+    Ok(CalculateLiquidationResult {
+        settle_amount,
+        repay_amount,
+        withdraw_amount,
+        protocol_fee,
+    })
 }
 
 pub struct InitReserveParams {
     /// Last slot when supply and rates updated
     pub current_slot: Slot,
+    /// Last unix timestamp when supply and rates updated
+    pub current_unix_timestamp: UnixTimestamp,
     /// Lending market address
     pub lending_market: Pubkey,
     /// Reserve liquidity
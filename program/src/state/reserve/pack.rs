@@ -1,13 +1,20 @@
 use {
     super::{
-        reserve_collateral::ReserveCollateral, reserve_liquidity::ReserveLiquidity, Reserve,
-        ReserveConfig, ReserveFees,
+        reserve_collateral::ReserveCollateral, reserve_liquidity::ReserveLiquidity,
+        AdaptiveRateConfig, CollateralRamp, FixedRateConfig, PendingReserveConfig, RateCurve,
+        RateCurvePoint, Reserve, ReserveConfig, ReserveFees, ReserveStatus, MAX_RATE_CURVE_POINTS,
+        RESERVE_VERSION_BPS_CONFIG, RESERVE_VERSION_EXTENDED_CONFIG,
+        RESERVE_VERSION_PERCENT_CONFIG,
     },
-    crate::state::{
-        last_update::LastUpdate, pack_bool, pack_decimal, unpack_bool, unpack_decimal,
-        PROGRAM_VERSION, UNINITIALIZED_VERSION,
+    crate::{
+        error::LendingError,
+        state::{
+            last_update::LastUpdate, pack_bool, pack_decimal, unpack_bool, unpack_decimal, Migrate,
+            ReserveRewardEmissions, UNINITIALIZED_VERSION,
+        },
     },
     arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    num_traits::FromPrimitive,
     solana_program::{
         msg,
         program_error::ProgramError,
@@ -22,13 +29,98 @@ impl IsInitialized for Reserve {
         self.version != UNINITIALIZED_VERSION
     }
 }
-const RESERVE_LEN: usize = 571; // 1 + 8 + 1 + 32 + 32 + 1 + 32 + 32 + 32 + 8 + 16 + 16 + 16 + 32 + 8 + 32 + 1 +
-                                // 1 + 1 + 1 + 1 + 1 + 1 + 8 + 8 + 1 + 248
+const RATE_CURVE_PERCENT_POINT_LEN: usize = 2; // 1 + 1
+const RATE_CURVE_PERCENT_POINTS_LEN: usize = RATE_CURVE_PERCENT_POINT_LEN * MAX_RATE_CURVE_POINTS;
+const RATE_CURVE_POINT_LEN: usize = 6; // 2 + 4
+const RATE_CURVE_POINTS_LEN: usize = RATE_CURVE_POINT_LEN * MAX_RATE_CURVE_POINTS;
+const RESERVE_CONFIG_LEN: usize = 77; // 2 + 2 + 2 + 2 + 4 + 4 + 4 + 8 + 8 + 1 + 1 + 1 + (2 * 10) + 1 + 1 + 8 + 8
+pub(super) const RESERVE_CONFIG_EXTENSION_LEN: usize = 69; // 2 + 2 + 1 + (6 * 10) + 2 + 2
+pub(super) const RESERVE_EXTENSION_LEN: usize = 202; // 69 + 69 + 1 + 8 + 8 + 16 + 16 + 15
+pub(super) const RESERVE_BASE_LEN: usize = 571; // 1 + 8 + 1 + 32 + 32 + 1 + 32 + 32 + 32 + 8 + 16 + 16 + 16 + 32 + 8 + 32 + 1 +
+                                                // 1 + 1 + 1 + 1 + 1 + 1 + 8 + 8 + 1 + 1 + 32 + 1 + (2 * 10) + 1 + 1 + 16 +
+                                                // 2 + 2 + 2 + 2 + 4 + 4 + 4 + 8 + 1 + 8 + 8 + 1 + 8 + 77 + 1 + 2 +
+                                                // 2 + 8 + 8 + 4 + 4 + 4 + 4 + 2 + 2 + 4
+const RESERVE_LEN: usize = RESERVE_BASE_LEN + RESERVE_EXTENSION_LEN;
+
+impl Reserve {
+    /// Account data length required by the reserve layout version
+    pub fn packed_len(&self) -> usize {
+        Self::layout_len(self.version)
+    }
+
+    /// Account data length of a reserve stored with a layout version
+    pub fn layout_len(version: u8) -> usize {
+        if Self::stores_extended_config(version) {
+            RESERVE_LEN
+        } else {
+            RESERVE_BASE_LEN
+        }
+    }
+
+    /// Whether the layout version stores the fees, rate curve and adaptive
+    /// rate settings of the config in basis points, in an extension following
+    /// the base layout
+    fn stores_extended_config(version: u8) -> bool {
+        version != RESERVE_VERSION_PERCENT_CONFIG && version != RESERVE_VERSION_BPS_CONFIG
+    }
+
+    /// Whether the config values stored as percentages by the base layout
+    /// are whole percentages, and the reserve emits no rewards
+    fn fits_base_layout(&self) -> bool {
+        self.reward_emissions.is_none()
+            && fits_percent_fields(&self.config)
+            && self
+                .pending_config
+                .as_ref()
+                .map_or(true, |pending| fits_percent_fields(&pending.config))
+    }
+}
+
+impl Migrate for Reserve {
+    fn is_outdated(version: u8) -> bool {
+        version == RESERVE_VERSION_PERCENT_CONFIG || version == RESERVE_VERSION_BPS_CONFIG
+    }
+
+    /// Reserves move to the extended layout, storing every config value in
+    /// basis points
+    fn migrate(&mut self) -> usize {
+        self.version = RESERVE_VERSION_EXTENDED_CONFIG;
+        RESERVE_LEN
+    }
+}
+
 impl Pack for Reserve {
+    /// Length of the latest reserve layout, reserves stored with an older
+    /// layout use [Reserve::layout_len]
     const LEN: usize = RESERVE_LEN;
+
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        let expected_len = input
+            .first()
+            .map_or(RESERVE_LEN, |version| Self::layout_len(*version));
+        if input.len() != expected_len {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::unpack_from_slice(input)
+    }
+
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != src.packed_len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !Self::stores_extended_config(src.version) && !src.fits_base_layout() {
+            msg!("Reserve config values in basis points and reward emissions require migrating the reserve");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
     // @TODO: break this up by reserve / liquidity / collateral / config https://git.io/JOCca
     fn pack_into_slice(&self, output: &mut [u8]) {
-        let output = array_mut_ref![output, 0, RESERVE_LEN];
+        let extended = Self::stores_extended_config(self.version);
+        let (output, extension) = output.split_at_mut(RESERVE_BASE_LEN);
+        let output = array_mut_ref![output, 0, RESERVE_BASE_LEN];
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             version,
@@ -57,6 +149,38 @@ impl Pack for Reserve {
             config_fees_borrow_fee_wad,
             config_fees_flash_loan_fee_wad,
             config_fees_host_fee_percentage,
+            config_fees_protocol_liquidation_fee,
+            collateral_fee_receiver,
+            config_rate_curve_len,
+            config_rate_curve_points,
+            config_adaptive_target_utilization_rate,
+            config_adaptive_adjustment_speed,
+            adaptive_rate_multiplier,
+            config_optimal_utilization_rate_bps,
+            config_loan_to_value_ratio_bps,
+            config_liquidation_bonus_bps,
+            config_liquidation_threshold_bps,
+            config_min_borrow_rate_bps,
+            config_optimal_borrow_rate_bps,
+            config_max_borrow_rate_bps,
+            last_update_unix_timestamp,
+            status,
+            config_deposit_limit,
+            config_borrow_limit,
+            pending_config_flag,
+            pending_config_activation_timestamp,
+            pending_config,
+            collateral_ramp_flag,
+            collateral_ramp_initial_loan_to_value_ratio_bps,
+            collateral_ramp_initial_liquidation_threshold_bps,
+            collateral_ramp_start_timestamp,
+            collateral_ramp_end_timestamp,
+            config_fixed_rate_premium_bps,
+            config_fixed_rate_max_term,
+            pending_config_fixed_rate_premium_bps,
+            pending_config_fixed_rate_max_term,
+            collateral_ramp_target_loan_to_value_ratio_bps,
+            collateral_ramp_target_liquidation_threshold_bps,
             _padding,
         ) = mut_array_refs![
             output,
@@ -86,14 +210,48 @@ impl Pack for Reserve {
             8,
             8,
             1,
-            248
+            1,
+            PUBKEY_BYTES,
+            1,
+            RATE_CURVE_PERCENT_POINTS_LEN,
+            1,
+            1,
+            16,
+            2,
+            2,
+            2,
+            2,
+            4,
+            4,
+            4,
+            8,
+            1,
+            8,
+            8,
+            1,
+            8,
+            RESERVE_CONFIG_LEN,
+            1,
+            2,
+            2,
+            8,
+            8,
+            4,
+            4,
+            4,
+            4,
+            2,
+            2,
+            4
         ];
 
         // reserve
         *version = self.version.to_le_bytes();
         *last_update_slot = self.last_update.slot.to_le_bytes();
         pack_bool(self.last_update.stale, last_update_stale);
+        *last_update_unix_timestamp = self.last_update.unix_timestamp.to_le_bytes();
         lending_market.copy_from_slice(self.lending_market.as_ref());
+        *status = (self.status as u8).to_le_bytes();
 
         // liquidity
         liquidity_mint_pubkey.copy_from_slice(self.liquidity.mint_pubkey.as_ref());
@@ -118,20 +276,141 @@ impl Pack for Reserve {
         collateral_supply_pubkey.copy_from_slice(self.collateral.supply_pubkey.as_ref());
 
         // config
-        *config_optimal_utilization_rate = self.config.optimal_utilization_rate.to_le_bytes();
-        *config_loan_to_value_ratio = self.config.loan_to_value_ratio.to_le_bytes();
-        *config_liquidation_bonus = self.config.liquidation_bonus.to_le_bytes();
-        *config_liquidation_threshold = self.config.liquidation_threshold.to_le_bytes();
-        *config_min_borrow_rate = self.config.min_borrow_rate.to_le_bytes();
-        *config_optimal_borrow_rate = self.config.optimal_borrow_rate.to_le_bytes();
-        *config_max_borrow_rate = self.config.max_borrow_rate.to_le_bytes();
         *config_fees_borrow_fee_wad = self.config.fees.borrow_fee_wad.to_le_bytes();
         *config_fees_flash_loan_fee_wad = self.config.fees.flash_loan_fee_wad.to_le_bytes();
-        *config_fees_host_fee_percentage = self.config.fees.host_fee_percentage.to_le_bytes();
+        collateral_fee_receiver.copy_from_slice(self.collateral.fee_receiver.as_ref());
+        pack_percent_fields(
+            (!extended).then_some(&self.config),
+            config_fees_host_fee_percentage,
+            config_fees_protocol_liquidation_fee,
+            config_rate_curve_len,
+            config_rate_curve_points,
+            config_adaptive_target_utilization_rate,
+            config_adaptive_adjustment_speed,
+        );
+        pack_decimal(self.adaptive_rate_multiplier, adaptive_rate_multiplier);
+
+        // rates of the config are only stored in basis points, percentage fields
+        // are cleared so a migrated reserve does not keep stale values
+        *config_optimal_utilization_rate = [0];
+        *config_loan_to_value_ratio = [0];
+        *config_liquidation_bonus = [0];
+        *config_liquidation_threshold = [0];
+        *config_min_borrow_rate = [0];
+        *config_optimal_borrow_rate = [0];
+        *config_max_borrow_rate = [0];
+        *config_optimal_utilization_rate_bps =
+            self.config.optimal_utilization_rate_bps.to_le_bytes();
+        *config_loan_to_value_ratio_bps = self.config.loan_to_value_ratio_bps.to_le_bytes();
+        *config_liquidation_bonus_bps = self.config.liquidation_bonus_bps.to_le_bytes();
+        *config_liquidation_threshold_bps = self.config.liquidation_threshold_bps.to_le_bytes();
+        *config_min_borrow_rate_bps = self.config.min_borrow_rate_bps.to_le_bytes();
+        *config_optimal_borrow_rate_bps = self.config.optimal_borrow_rate_bps.to_le_bytes();
+        *config_max_borrow_rate_bps = self.config.max_borrow_rate_bps.to_le_bytes();
+        *config_deposit_limit = self.config.deposit_limit.to_le_bytes();
+        *config_borrow_limit = self.config.borrow_limit.to_le_bytes();
+        *config_fixed_rate_premium_bps = self.config.fixed_rate.premium_bps.to_le_bytes();
+        *config_fixed_rate_max_term = self.config.fixed_rate.max_term.to_le_bytes();
+
+        // pending config
+        match &self.pending_config {
+            Some(pending) => {
+                pack_bool(true, pending_config_flag);
+                *pending_config_activation_timestamp = pending.activation_timestamp.to_le_bytes();
+                pack_reserve_config(&pending.config, extended, pending_config);
+                *pending_config_fixed_rate_premium_bps =
+                    pending.config.fixed_rate.premium_bps.to_le_bytes();
+                *pending_config_fixed_rate_max_term =
+                    pending.config.fixed_rate.max_term.to_le_bytes();
+            }
+            None => {
+                pack_bool(false, pending_config_flag);
+                *pending_config_activation_timestamp = [0; 8];
+                *pending_config = [0; RESERVE_CONFIG_LEN];
+                *pending_config_fixed_rate_premium_bps = [0; 4];
+                *pending_config_fixed_rate_max_term = [0; 4];
+            }
+        }
+
+        // collateral ramp
+        match &self.collateral_ramp {
+            Some(ramp) => {
+                pack_bool(true, collateral_ramp_flag);
+                *collateral_ramp_initial_loan_to_value_ratio_bps =
+                    ramp.initial_loan_to_value_ratio_bps.to_le_bytes();
+                *collateral_ramp_initial_liquidation_threshold_bps =
+                    ramp.initial_liquidation_threshold_bps.to_le_bytes();
+                *collateral_ramp_start_timestamp = ramp.start_timestamp.to_le_bytes();
+                *collateral_ramp_end_timestamp = ramp.end_timestamp.to_le_bytes();
+                *collateral_ramp_target_loan_to_value_ratio_bps =
+                    ramp.target_loan_to_value_ratio_bps.to_le_bytes();
+                *collateral_ramp_target_liquidation_threshold_bps =
+                    ramp.target_liquidation_threshold_bps.to_le_bytes();
+            }
+            None => {
+                pack_bool(false, collateral_ramp_flag);
+                *collateral_ramp_initial_loan_to_value_ratio_bps = [0; 2];
+                *collateral_ramp_initial_liquidation_threshold_bps = [0; 2];
+                *collateral_ramp_start_timestamp = [0; 8];
+                *collateral_ramp_end_timestamp = [0; 8];
+                *collateral_ramp_target_loan_to_value_ratio_bps = [0; 2];
+                *collateral_ramp_target_liquidation_threshold_bps = [0; 2];
+            }
+        }
+
+        // extension
+        if extended {
+            let extension = array_mut_ref![extension, 0, RESERVE_EXTENSION_LEN];
+            #[allow(clippy::ptr_offset_with_cast)]
+            let (
+                config_extension,
+                pending_config_extension,
+                reward_emissions_flag,
+                reward_supply_emission_per_second,
+                reward_borrow_emission_per_second,
+                reward_supply_index,
+                reward_borrow_index,
+                _padding,
+            ) = mut_array_refs![
+                extension,
+                RESERVE_CONFIG_EXTENSION_LEN,
+                RESERVE_CONFIG_EXTENSION_LEN,
+                1,
+                8,
+                8,
+                16,
+                16,
+                15
+            ];
+            pack_config_extension(&self.config, config_extension);
+            match &self.pending_config {
+                Some(pending) => pack_config_extension(&pending.config, pending_config_extension),
+                None => *pending_config_extension = [0; RESERVE_CONFIG_EXTENSION_LEN],
+            }
+            match &self.reward_emissions {
+                Some(reward_emissions) => {
+                    pack_bool(true, reward_emissions_flag);
+                    *reward_supply_emission_per_second =
+                        reward_emissions.supply_emission_per_second.to_le_bytes();
+                    *reward_borrow_emission_per_second =
+                        reward_emissions.borrow_emission_per_second.to_le_bytes();
+                    pack_decimal(reward_emissions.supply_index, reward_supply_index);
+                    pack_decimal(reward_emissions.borrow_index, reward_borrow_index);
+                }
+                None => {
+                    pack_bool(false, reward_emissions_flag);
+                    *reward_supply_emission_per_second = [0; 8];
+                    *reward_borrow_emission_per_second = [0; 8];
+                    *reward_supply_index = [0; 16];
+                    *reward_borrow_index = [0; 16];
+                }
+            }
+        }
     }
 
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
-        let input = array_ref![input, 0, RESERVE_LEN];
+        let (input, extension) = input.split_at(RESERVE_BASE_LEN);
+        let input = array_ref![input, 0, RESERVE_BASE_LEN];
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             version,
@@ -160,6 +439,38 @@ impl Pack for Reserve {
             config_fees_borrow_fee_wad,
             config_fees_flash_loan_fee_wad,
             config_fees_host_fee_percentage,
+            config_fees_protocol_liquidation_fee,
+            collateral_fee_receiver,
+            config_rate_curve_len,
+            config_rate_curve_points,
+            config_adaptive_target_utilization_rate,
+            config_adaptive_adjustment_speed,
+            adaptive_rate_multiplier,
+            config_optimal_utilization_rate_bps,
+            config_loan_to_value_ratio_bps,
+            config_liquidation_bonus_bps,
+            config_liquidation_threshold_bps,
+            config_min_borrow_rate_bps,
+            config_optimal_borrow_rate_bps,
+            config_max_borrow_rate_bps,
+            last_update_unix_timestamp,
+            status,
+            config_deposit_limit,
+            config_borrow_limit,
+            pending_config_flag,
+            pending_config_activation_timestamp,
+            pending_config,
+            collateral_ramp_flag,
+            collateral_ramp_initial_loan_to_value_ratio_bps,
+            collateral_ramp_initial_liquidation_threshold_bps,
+            collateral_ramp_start_timestamp,
+            collateral_ramp_end_timestamp,
+            config_fixed_rate_premium_bps,
+            config_fixed_rate_max_term,
+            pending_config_fixed_rate_premium_bps,
+            pending_config_fixed_rate_max_term,
+            collateral_ramp_target_loan_to_value_ratio_bps,
+            collateral_ramp_target_liquidation_threshold_bps,
             _padding,
         ) = array_refs![
             input,
@@ -189,18 +500,196 @@ impl Pack for Reserve {
             8,
             8,
             1,
-            248
+            1,
+            PUBKEY_BYTES,
+            1,
+            RATE_CURVE_PERCENT_POINTS_LEN,
+            1,
+            1,
+            16,
+            2,
+            2,
+            2,
+            2,
+            4,
+            4,
+            4,
+            8,
+            1,
+            8,
+            8,
+            1,
+            8,
+            RESERVE_CONFIG_LEN,
+            1,
+            2,
+            2,
+            8,
+            8,
+            4,
+            4,
+            4,
+            4,
+            2,
+            2,
+            4
         ];
-        let version = u8::from_le_bytes(*version);
-        if version > PROGRAM_VERSION {
-            msg!("Reserve version does not match lending program version");
-            return Err(ProgramError::InvalidAccountData);
+        // reserves using the percentage layout are migrated to basis points and
+        // saved with the new layout the next time they are packed
+        let (
+            version,
+            optimal_utilization_rate_bps,
+            loan_to_value_ratio_bps,
+            liquidation_bonus_bps,
+            liquidation_threshold_bps,
+            min_borrow_rate_bps,
+            optimal_borrow_rate_bps,
+            max_borrow_rate_bps,
+        ) = match u8::from_le_bytes(*version) {
+            RESERVE_VERSION_PERCENT_CONFIG => (
+                RESERVE_VERSION_BPS_CONFIG,
+                u16::from(config_optimal_utilization_rate[0]) * 100,
+                u16::from(config_loan_to_value_ratio[0]) * 100,
+                u16::from(config_liquidation_bonus[0]) * 100,
+                u16::from(config_liquidation_threshold[0]) * 100,
+                u32::from(config_min_borrow_rate[0]) * 100,
+                u32::from(config_optimal_borrow_rate[0]) * 100,
+                u32::from(config_max_borrow_rate[0]) * 100,
+            ),
+            version @ (UNINITIALIZED_VERSION
+            | RESERVE_VERSION_BPS_CONFIG
+            | RESERVE_VERSION_EXTENDED_CONFIG) => (
+                version,
+                u16::from_le_bytes(*config_optimal_utilization_rate_bps),
+                u16::from_le_bytes(*config_loan_to_value_ratio_bps),
+                u16::from_le_bytes(*config_liquidation_bonus_bps),
+                u16::from_le_bytes(*config_liquidation_threshold_bps),
+                u32::from_le_bytes(*config_min_borrow_rate_bps),
+                u32::from_le_bytes(*config_optimal_borrow_rate_bps),
+                u32::from_le_bytes(*config_max_borrow_rate_bps),
+            ),
+            _ => {
+                msg!("Reserve version does not match lending program version");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        };
+        let extended = Self::stores_extended_config(version);
+        let mut pending_config = if unpack_bool(pending_config_flag)? {
+            Some(PendingReserveConfig {
+                config: ReserveConfig {
+                    fixed_rate: FixedRateConfig {
+                        premium_bps: u32::from_le_bytes(*pending_config_fixed_rate_premium_bps),
+                        max_term: u32::from_le_bytes(*pending_config_fixed_rate_max_term),
+                    },
+                    ..unpack_reserve_config(pending_config, extended)?
+                },
+                activation_timestamp: i64::from_le_bytes(*pending_config_activation_timestamp),
+            })
+        } else {
+            None
+        };
+        let collateral_ramp = if unpack_bool(collateral_ramp_flag)? {
+            Some(CollateralRamp {
+                initial_loan_to_value_ratio_bps: u16::from_le_bytes(
+                    *collateral_ramp_initial_loan_to_value_ratio_bps,
+                ),
+                initial_liquidation_threshold_bps: u16::from_le_bytes(
+                    *collateral_ramp_initial_liquidation_threshold_bps,
+                ),
+                target_loan_to_value_ratio_bps: u16::from_le_bytes(
+                    *collateral_ramp_target_loan_to_value_ratio_bps,
+                ),
+                target_liquidation_threshold_bps: u16::from_le_bytes(
+                    *collateral_ramp_target_liquidation_threshold_bps,
+                ),
+                start_timestamp: i64::from_le_bytes(*collateral_ramp_start_timestamp),
+                end_timestamp: i64::from_le_bytes(*collateral_ramp_end_timestamp),
+            })
+        } else {
+            None
+        };
+        let status = ReserveStatus::from_u8(u8::from_le_bytes(*status)).ok_or_else(|| {
+            msg!("Reserve status is invalid");
+            ProgramError::InvalidAccountData
+        })?;
+        let mut config = ReserveConfig {
+            optimal_utilization_rate_bps,
+            loan_to_value_ratio_bps,
+            liquidation_bonus_bps,
+            liquidation_threshold_bps,
+            min_borrow_rate_bps,
+            optimal_borrow_rate_bps,
+            max_borrow_rate_bps,
+            fees: ReserveFees {
+                borrow_fee_wad: u64::from_le_bytes(*config_fees_borrow_fee_wad),
+                flash_loan_fee_wad: u64::from_le_bytes(*config_fees_flash_loan_fee_wad),
+                ..ReserveFees::default()
+            },
+            deposit_limit: u64::from_le_bytes(*config_deposit_limit),
+            borrow_limit: u64::from_le_bytes(*config_borrow_limit),
+            fixed_rate: FixedRateConfig {
+                premium_bps: u32::from_le_bytes(*config_fixed_rate_premium_bps),
+                max_term: u32::from_le_bytes(*config_fixed_rate_max_term),
+            },
+            ..ReserveConfig::default()
+        };
+        let mut reward_emissions = None;
+        if extended {
+            let extension = array_ref![extension, 0, RESERVE_EXTENSION_LEN];
+            #[allow(clippy::ptr_offset_with_cast)]
+            let (
+                config_extension,
+                pending_config_extension,
+                reward_emissions_flag,
+                reward_supply_emission_per_second,
+                reward_borrow_emission_per_second,
+                reward_supply_index,
+                reward_borrow_index,
+                _padding,
+            ) = array_refs![
+                extension,
+                RESERVE_CONFIG_EXTENSION_LEN,
+                RESERVE_CONFIG_EXTENSION_LEN,
+                1,
+                8,
+                8,
+                16,
+                16,
+                15
+            ];
+            unpack_config_extension(config_extension, &mut config)?;
+            if let Some(pending) = &mut pending_config {
+                unpack_config_extension(pending_config_extension, &mut pending.config)?;
+            }
+            if unpack_bool(reward_emissions_flag)? {
+                reward_emissions = Some(ReserveRewardEmissions {
+                    supply_emission_per_second: u64::from_le_bytes(
+                        *reward_supply_emission_per_second,
+                    ),
+                    borrow_emission_per_second: u64::from_le_bytes(
+                        *reward_borrow_emission_per_second,
+                    ),
+                    supply_index: unpack_decimal(reward_supply_index),
+                    borrow_index: unpack_decimal(reward_borrow_index),
+                });
+            }
+        } else {
+            unpack_percent_fields(
+                config_fees_host_fee_percentage,
+                config_fees_protocol_liquidation_fee,
+                config_rate_curve_len,
+                config_rate_curve_points,
+                config_adaptive_target_utilization_rate,
+                config_adaptive_adjustment_speed,
+                &mut config,
+            )?;
         }
         Ok(Self {
             version,
             last_update: LastUpdate {
                 slot: u64::from_le_bytes(*last_update_slot),
                 stale: unpack_bool(last_update_stale)?,
+                unix_timestamp: i64::from_le_bytes(*last_update_unix_timestamp),
             },
             lending_market: Pubkey::new_from_array(*lending_market),
             liquidity: ReserveLiquidity {
@@ -218,21 +707,307 @@ impl Pack for Reserve {
                 mint_pubkey: Pubkey::new_from_array(*collateral_mint_pubkey),
                 mint_total_supply: u64::from_le_bytes(*collateral_mint_total_supply),
                 supply_pubkey: Pubkey::new_from_array(*collateral_supply_pubkey),
+                fee_receiver: Pubkey::new_from_array(*collateral_fee_receiver),
             },
-            config: ReserveConfig {
-                optimal_utilization_rate: u8::from_le_bytes(*config_optimal_utilization_rate),
-                loan_to_value_ratio: u8::from_le_bytes(*config_loan_to_value_ratio),
-                liquidation_bonus: u8::from_le_bytes(*config_liquidation_bonus),
-                liquidation_threshold: u8::from_le_bytes(*config_liquidation_threshold),
-                min_borrow_rate: u8::from_le_bytes(*config_min_borrow_rate),
-                optimal_borrow_rate: u8::from_le_bytes(*config_optimal_borrow_rate),
-                max_borrow_rate: u8::from_le_bytes(*config_max_borrow_rate),
-                fees: ReserveFees {
-                    borrow_fee_wad: u64::from_le_bytes(*config_fees_borrow_fee_wad),
-                    flash_loan_fee_wad: u64::from_le_bytes(*config_fees_flash_loan_fee_wad),
-                    host_fee_percentage: u8::from_le_bytes(*config_fees_host_fee_percentage),
-                },
-            },
+            config,
+            adaptive_rate_multiplier: unpack_decimal(adaptive_rate_multiplier),
+            status,
+            pending_config,
+            collateral_ramp,
+            reward_emissions,
         })
     }
 }
+
+/// Whether a bps value is a whole percentage fitting in a byte
+fn is_whole_percent(bps: u32) -> bool {
+    bps % 100 == 0 && bps / 100 <= u8::MAX.into()
+}
+
+fn fits_percent_fields(config: &ReserveConfig) -> bool {
+    is_whole_percent(config.fees.host_fee_bps.into())
+        && is_whole_percent(config.fees.protocol_liquidation_fee_bps.into())
+        && is_whole_percent(config.adaptive_rate.target_utilization_rate_bps.into())
+        && is_whole_percent(config.adaptive_rate.adjustment_speed_bps.into())
+        && config.rate_curve.points().iter().all(|point| {
+            is_whole_percent(point.utilization_rate_bps.into())
+                && is_whole_percent(point.borrow_rate_bps)
+        })
+}
+
+// the base layout stores the fees, rate curve and adaptive rate settings of a
+// config as percentages, the extended layout clears them
+fn pack_percent_fields(
+    config: Option<&ReserveConfig>,
+    host_fee_percentage: &mut [u8; 1],
+    protocol_liquidation_fee: &mut [u8; 1],
+    rate_curve_len: &mut [u8; 1],
+    rate_curve_points: &mut [u8; RATE_CURVE_PERCENT_POINTS_LEN],
+    adaptive_target_utilization_rate: &mut [u8; 1],
+    adaptive_adjustment_speed: &mut [u8; 1],
+) {
+    let config = config.copied().unwrap_or_default();
+    let percent = |bps: u32| (bps / 100) as u8;
+    *host_fee_percentage = [percent(config.fees.host_fee_bps.into())];
+    *protocol_liquidation_fee = [percent(config.fees.protocol_liquidation_fee_bps.into())];
+    let points = config.rate_curve.points();
+    *rate_curve_len = (points.len() as u8).to_le_bytes();
+    *rate_curve_points = [0; RATE_CURVE_PERCENT_POINTS_LEN];
+    for (point, dst) in points
+        .iter()
+        .zip(rate_curve_points.chunks_exact_mut(RATE_CURVE_PERCENT_POINT_LEN))
+    {
+        dst[0] = percent(point.utilization_rate_bps.into());
+        dst[1] = percent(point.borrow_rate_bps);
+    }
+    *adaptive_target_utilization_rate = [percent(
+        config.adaptive_rate.target_utilization_rate_bps.into(),
+    )];
+    *adaptive_adjustment_speed = [percent(config.adaptive_rate.adjustment_speed_bps.into())];
+}
+
+pub(super) fn unpack_percent_fields(
+    host_fee_percentage: &[u8; 1],
+    protocol_liquidation_fee: &[u8; 1],
+    rate_curve_len: &[u8; 1],
+    rate_curve_points: &[u8; RATE_CURVE_PERCENT_POINTS_LEN],
+    adaptive_target_utilization_rate: &[u8; 1],
+    adaptive_adjustment_speed: &[u8; 1],
+    config: &mut ReserveConfig,
+) -> Result<(), ProgramError> {
+    let bps = |percent: &[u8; 1]| u16::from(percent[0]) * 100;
+    config.fees.host_fee_bps = bps(host_fee_percentage);
+    config.fees.protocol_liquidation_fee_bps = bps(protocol_liquidation_fee);
+    let len = unpack_rate_curve_len(rate_curve_len)?;
+    let points = rate_curve_points
+        .chunks_exact(RATE_CURVE_PERCENT_POINT_LEN)
+        .take(len)
+        .map(|src| RateCurvePoint {
+            utilization_rate_bps: u16::from(src[0]) * 100,
+            borrow_rate_bps: u32::from(src[1]) * 100,
+        })
+        .collect::<Vec<_>>();
+    config.rate_curve = RateCurve::new(&points)?;
+    config.adaptive_rate = AdaptiveRateConfig {
+        target_utilization_rate_bps: bps(adaptive_target_utilization_rate),
+        adjustment_speed_bps: bps(adaptive_adjustment_speed),
+    };
+    Ok(())
+}
+
+fn pack_config_extension(config: &ReserveConfig, output: &mut [u8; RESERVE_CONFIG_EXTENSION_LEN]) {
+    #[allow(clippy::ptr_offset_with_cast)]
+    let (
+        host_fee_bps,
+        protocol_liquidation_fee_bps,
+        rate_curve_len,
+        rate_curve_points,
+        adaptive_target_utilization_rate_bps,
+        adaptive_adjustment_speed_bps,
+    ) = mut_array_refs![output, 2, 2, 1, RATE_CURVE_POINTS_LEN, 2, 2];
+    *host_fee_bps = config.fees.host_fee_bps.to_le_bytes();
+    *protocol_liquidation_fee_bps = config.fees.protocol_liquidation_fee_bps.to_le_bytes();
+    let points = config.rate_curve.points();
+    *rate_curve_len = (points.len() as u8).to_le_bytes();
+    *rate_curve_points = [0; RATE_CURVE_POINTS_LEN];
+    for (point, dst) in points
+        .iter()
+        .zip(rate_curve_points.chunks_exact_mut(RATE_CURVE_POINT_LEN))
+    {
+        dst[..2].copy_from_slice(&point.utilization_rate_bps.to_le_bytes());
+        dst[2..].copy_from_slice(&point.borrow_rate_bps.to_le_bytes());
+    }
+    *adaptive_target_utilization_rate_bps = config
+        .adaptive_rate
+        .target_utilization_rate_bps
+        .to_le_bytes();
+    *adaptive_adjustment_speed_bps = config.adaptive_rate.adjustment_speed_bps.to_le_bytes();
+}
+
+pub(super) fn unpack_config_extension(
+    input: &[u8; RESERVE_CONFIG_EXTENSION_LEN],
+    config: &mut ReserveConfig,
+) -> Result<(), ProgramError> {
+    #[allow(clippy::ptr_offset_with_cast)]
+    let (
+        host_fee_bps,
+        protocol_liquidation_fee_bps,
+        rate_curve_len,
+        rate_curve_points,
+        adaptive_target_utilization_rate_bps,
+        adaptive_adjustment_speed_bps,
+    ) = array_refs![input, 2, 2, 1, RATE_CURVE_POINTS_LEN, 2, 2];
+    config.fees.host_fee_bps = u16::from_le_bytes(*host_fee_bps);
+    config.fees.protocol_liquidation_fee_bps = u16::from_le_bytes(*protocol_liquidation_fee_bps);
+    let len = unpack_rate_curve_len(rate_curve_len)?;
+    let points = rate_curve_points
+        .chunks_exact(RATE_CURVE_POINT_LEN)
+        .take(len)
+        .map(|src| RateCurvePoint {
+            utilization_rate_bps: u16::from_le_bytes([src[0], src[1]]),
+            borrow_rate_bps: u32::from_le_bytes([src[2], src[3], src[4], src[5]]),
+        })
+        .collect::<Vec<_>>();
+    config.rate_curve = RateCurve::new(&points)?;
+    config.adaptive_rate = AdaptiveRateConfig {
+        target_utilization_rate_bps: u16::from_le_bytes(*adaptive_target_utilization_rate_bps),
+        adjustment_speed_bps: u16::from_le_bytes(*adaptive_adjustment_speed_bps),
+    };
+    Ok(())
+}
+
+fn unpack_rate_curve_len(input: &[u8; 1]) -> Result<usize, ProgramError> {
+    let len = u8::from_le_bytes(*input) as usize;
+    if len > MAX_RATE_CURVE_POINTS {
+        msg!(
+            "Reserve rate curve cannot have more than {} points",
+            MAX_RATE_CURVE_POINTS
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(len)
+}
+
+// pending configs only exist in the basis points layouts, so unlike the
+// current config they are stored contiguously, except for the fixed rate
+// config and the extension added after the layout
+fn pack_reserve_config(
+    config: &ReserveConfig,
+    extended: bool,
+    output: &mut [u8; RESERVE_CONFIG_LEN],
+) {
+    #[allow(clippy::ptr_offset_with_cast)]
+    let (
+        optimal_utilization_rate_bps,
+        loan_to_value_ratio_bps,
+        liquidation_bonus_bps,
+        liquidation_threshold_bps,
+        min_borrow_rate_bps,
+        optimal_borrow_rate_bps,
+        max_borrow_rate_bps,
+        fees_borrow_fee_wad,
+        fees_flash_loan_fee_wad,
+        fees_host_fee_percentage,
+        fees_protocol_liquidation_fee,
+        rate_curve_len,
+        rate_curve_points,
+        adaptive_target_utilization_rate,
+        adaptive_adjustment_speed,
+        deposit_limit,
+        borrow_limit,
+    ) = mut_array_refs![
+        output,
+        2,
+        2,
+        2,
+        2,
+        4,
+        4,
+        4,
+        8,
+        8,
+        1,
+        1,
+        1,
+        RATE_CURVE_PERCENT_POINTS_LEN,
+        1,
+        1,
+        8,
+        8
+    ];
+    *optimal_utilization_rate_bps = config.optimal_utilization_rate_bps.to_le_bytes();
+    *loan_to_value_ratio_bps = config.loan_to_value_ratio_bps.to_le_bytes();
+    *liquidation_bonus_bps = config.liquidation_bonus_bps.to_le_bytes();
+    *liquidation_threshold_bps = config.liquidation_threshold_bps.to_le_bytes();
+    *min_borrow_rate_bps = config.min_borrow_rate_bps.to_le_bytes();
+    *optimal_borrow_rate_bps = config.optimal_borrow_rate_bps.to_le_bytes();
+    *max_borrow_rate_bps = config.max_borrow_rate_bps.to_le_bytes();
+    *fees_borrow_fee_wad = config.fees.borrow_fee_wad.to_le_bytes();
+    *fees_flash_loan_fee_wad = config.fees.flash_loan_fee_wad.to_le_bytes();
+    pack_percent_fields(
+        (!extended).then_some(config),
+        fees_host_fee_percentage,
+        fees_protocol_liquidation_fee,
+        rate_curve_len,
+        rate_curve_points,
+        adaptive_target_utilization_rate,
+        adaptive_adjustment_speed,
+    );
+    *deposit_limit = config.deposit_limit.to_le_bytes();
+    *borrow_limit = config.borrow_limit.to_le_bytes();
+}
+
+fn unpack_reserve_config(
+    input: &[u8; RESERVE_CONFIG_LEN],
+    extended: bool,
+) -> Result<ReserveConfig, ProgramError> {
+    #[allow(clippy::ptr_offset_with_cast)]
+    let (
+        optimal_utilization_rate_bps,
+        loan_to_value_ratio_bps,
+        liquidation_bonus_bps,
+        liquidation_threshold_bps,
+        min_borrow_rate_bps,
+        optimal_borrow_rate_bps,
+        max_borrow_rate_bps,
+        fees_borrow_fee_wad,
+        fees_flash_loan_fee_wad,
+        fees_host_fee_percentage,
+        fees_protocol_liquidation_fee,
+        rate_curve_len,
+        rate_curve_points,
+        adaptive_target_utilization_rate,
+        adaptive_adjustment_speed,
+        deposit_limit,
+        borrow_limit,
+    ) = array_refs![
+        input,
+        2,
+        2,
+        2,
+        2,
+        4,
+        4,
+        4,
+        8,
+        8,
+        1,
+        1,
+        1,
+        RATE_CURVE_PERCENT_POINTS_LEN,
+        1,
+        1,
+        8,
+        8
+    ];
+    let mut config = ReserveConfig {
+        optimal_utilization_rate_bps: u16::from_le_bytes(*optimal_utilization_rate_bps),
+        loan_to_value_ratio_bps: u16::from_le_bytes(*loan_to_value_ratio_bps),
+        liquidation_bonus_bps: u16::from_le_bytes(*liquidation_bonus_bps),
+        liquidation_threshold_bps: u16::from_le_bytes(*liquidation_threshold_bps),
+        min_borrow_rate_bps: u32::from_le_bytes(*min_borrow_rate_bps),
+        optimal_borrow_rate_bps: u32::from_le_bytes(*optimal_borrow_rate_bps),
+        max_borrow_rate_bps: u32::from_le_bytes(*max_borrow_rate_bps),
+        fees: ReserveFees {
+            borrow_fee_wad: u64::from_le_bytes(*fees_borrow_fee_wad),
+            flash_loan_fee_wad: u64::from_le_bytes(*fees_flash_loan_fee_wad),
+            ..ReserveFees::default()
+        },
+        deposit_limit: u64::from_le_bytes(*deposit_limit),
+        borrow_limit: u64::from_le_bytes(*borrow_limit),
+        fixed_rate: FixedRateConfig::DISABLED,
+        ..ReserveConfig::default()
+    };
+    if !extended {
+        unpack_percent_fields(
+            fees_host_fee_percentage,
+            fees_protocol_liquidation_fee,
+            rate_curve_len,
+            rate_curve_points,
+            adaptive_target_utilization_rate,
+            adaptive_adjustment_speed,
+            &mut config,
+        )?;
+    }
+    Ok(config)
+}
@@ -0,0 +1,131 @@
+use {
+    crate::{
+        error::LendingError,
+        math::{Rate, TryAdd, TryDiv, TryMul, TrySub},
+    },
+    solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError},
+};
+
+/// Max number of points on a reserve borrow rate curve
+pub const MAX_RATE_CURVE_POINTS: usize = 10;
+
+/// Point on a reserve borrow rate curve
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RateCurvePoint {
+    /// Utilization rate, in basis points
+    pub utilization_rate_bps: u16,
+    /// Borrow APY at this utilization rate, in basis points
+    pub borrow_rate_bps: u32,
+}
+
+/// Piecewise linear borrow rate curve
+///
+/// When the curve has points, it replaces the single kink model defined by the
+/// min, optimal and max borrow rates of the reserve config. Borrow rates
+/// between two points are linearly interpolated.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RateCurve {
+    /// Number of points used
+    len: u8,
+    /// Points ordered by utilization rate, only the first `len` are used
+    points: [RateCurvePoint; MAX_RATE_CURVE_POINTS],
+}
+
+impl RateCurve {
+    /// Curve without points, falling back to the single kink model
+    pub const EMPTY: Self = Self {
+        len: 0,
+        points: [RateCurvePoint {
+            utilization_rate_bps: 0,
+            borrow_rate_bps: 0,
+        }; MAX_RATE_CURVE_POINTS],
+    };
+
+    /// Create a curve from a list of points
+    pub fn new(points: &[RateCurvePoint]) -> Result<Self, ProgramError> {
+        if points.len() > MAX_RATE_CURVE_POINTS {
+            msg!(
+                "Rate curve cannot have more than {} points",
+                MAX_RATE_CURVE_POINTS
+            );
+            return Err(LendingError::InvalidConfig.into());
+        }
+        let mut curve = Self::EMPTY;
+        curve.len = points.len() as u8;
+        curve.points[..points.len()].copy_from_slice(points);
+        Ok(curve)
+    }
+
+    /// Points used by the curve
+    pub fn points(&self) -> &[RateCurvePoint] {
+        &self.points[..self.len as usize]
+    }
+
+    /// Whether the curve has no points
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check the curve spans utilization rates from 0 to 10_000 bps with strictly
+    /// increasing utilization rates and non-decreasing borrow rates
+    pub fn validate(&self) -> ProgramResult {
+        let points = self.points();
+        if points.is_empty() {
+            return Ok(());
+        }
+        if points.len() < 2 {
+            msg!("Rate curve must have at least 2 points");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        if points[0].utilization_rate_bps != 0
+            || points[points.len() - 1].utilization_rate_bps != 10_000
+        {
+            msg!("Rate curve must start at 0 and end at 10_000 bps utilization");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        for window in points.windows(2) {
+            if window[1].utilization_rate_bps <= window[0].utilization_rate_bps {
+                msg!("Rate curve utilization rates must be strictly increasing");
+                return Err(LendingError::InvalidConfig.into());
+            }
+            if window[1].borrow_rate_bps < window[0].borrow_rate_bps {
+                msg!("Rate curve borrow rates must be non-decreasing");
+                return Err(LendingError::InvalidConfig.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Interpolate the borrow rate at a utilization rate
+    pub fn borrow_rate(&self, utilization_rate: Rate) -> Result<Rate, ProgramError> {
+        let points = self.points();
+        let last = points.last().ok_or(LendingError::InvalidConfig)?;
+        if utilization_rate >= Rate::from_bps(last.utilization_rate_bps.into()) {
+            return Ok(Rate::from_bps(last.borrow_rate_bps));
+        }
+        for window in points.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let end_utilization_rate = Rate::from_bps(end.utilization_rate_bps.into());
+            if utilization_rate >= end_utilization_rate {
+                continue;
+            }
+
+            //* borrow_rate = start_rate + normalized_rate * rate_range
+            //* where:
+            //* normalized_rate = (utilization_rate - start_utilization) / (end_utilization - start_utilization)
+            let start_utilization_rate = Rate::from_bps(start.utilization_rate_bps.into());
+            let normalized_rate = utilization_rate
+                .try_sub(start_utilization_rate)?
+                .try_div(end_utilization_rate.try_sub(start_utilization_rate)?)?;
+            let rate_range = Rate::from_bps(
+                end.borrow_rate_bps
+                    .checked_sub(start.borrow_rate_bps)
+                    .ok_or(LendingError::MathOverflow)?,
+            );
+            return normalized_rate
+                .try_mul(rate_range)?
+                .try_add(Rate::from_bps(start.borrow_rate_bps));
+        }
+        Ok(Rate::from_bps(last.borrow_rate_bps))
+    }
+}
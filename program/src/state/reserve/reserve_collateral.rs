@@ -17,6 +17,8 @@ pub struct ReserveCollateral {
     pub mint_total_supply: u64,
     /// Reserve collateral supply address
     pub supply_pubkey: Pubkey,
+    /// Reserve collateral fee receiver address
+    pub fee_receiver: Pubkey,
 }
 impl ReserveCollateral {
     pub fn new(params: NewReserveCollateralParams) -> Self {
@@ -24,6 +26,7 @@ impl ReserveCollateral {
             mint_pubkey: params.mint_pubkey,
             mint_total_supply: 0,
             supply_pubkey: params.supply_pubkey,
+            fee_receiver: params.fee_receiver,
         }
     }
     /// Add collateral to total supply
@@ -64,4 +67,6 @@ pub struct NewReserveCollateralParams {
     pub mint_pubkey: Pubkey,
     /// Reserve collateral supply address
     pub supply_pubkey: Pubkey,
+    /// Reserve collateral fee receiver address
+    pub fee_receiver: Pubkey,
 }
@@ -2,7 +2,6 @@ use {
     crate::{
         error::LendingError,
         math::{Decimal, Rate, TryAdd, TryDiv, TryMul, TrySub},
-        state::SLOTS_PER_YEAR,
     },
     solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError, pubkey::Pubkey},
 };
@@ -93,6 +92,15 @@ impl ReserveLiquidity {
 
         Ok(())
     }
+    /// Subtract forgiven debt from total borrows without any repayment, which
+    /// lowers the total supply backing the collateral
+    pub fn forgive_debt(&mut self, settle_amount: Decimal) -> ProgramResult {
+        self.borrowed_amount_wads = self
+            .borrowed_amount_wads
+            .try_sub(settle_amount.min(self.borrowed_amount_wads))?;
+
+        Ok(())
+    }
     /// Calculate the liquidity utilization rate of the reserve
     pub fn utilization_rate(&self) -> Result<Rate, ProgramError> {
         let total_supply = self.total_supply()?;
@@ -101,16 +109,18 @@ impl ReserveLiquidity {
         }
         self.borrowed_amount_wads.try_div(total_supply)?.try_into()
     }
-    /// Compound current borrow rate over elapsed slots
+    /// Compound current borrow rate over elapsed periods, either seconds or
+    /// slots
     pub(super) fn compound_interest(
         &mut self,
         current_borrow_rate: Rate,
-        slots_elapsed: u64,
+        periods_elapsed: u64,
+        periods_per_year: u64,
     ) -> ProgramResult {
-        let slot_interest_rate = current_borrow_rate.try_div(SLOTS_PER_YEAR)?;
+        let period_interest_rate = current_borrow_rate.try_div(periods_per_year)?;
         let compounded_interest_rate = Rate::one()
-            .try_add(slot_interest_rate)?
-            .try_pow(slots_elapsed)?;
+            .try_add(period_interest_rate)?
+            .try_pow(periods_elapsed)?;
         self.cumulative_borrow_rate_wads = self
             .cumulative_borrow_rate_wads
             .try_mul(compounded_interest_rate)?;
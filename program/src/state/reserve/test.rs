@@ -3,28 +3,39 @@ mod tests {
     use {
         crate::{
             error::LendingError,
-            math::{Decimal, Rate, TryDiv, TryMul, PERCENT_SCALER, WAD},
+            math::{Decimal, Rate, TryAdd, TryDiv, TryMul, PERCENT_SCALER, WAD},
             state::{
+                last_update::LastUpdate,
                 reserve::{
                     reserve_collateral::ReserveCollateral, reserve_liquidity::ReserveLiquidity,
-                    CollateralExchangeRate, FeeCalculation, ReserveConfig, ReserveFees,
+                    AdaptiveRateConfig, CollateralExchangeRate, CollateralRamp, FeeCalculation,
+                    FixedRateConfig, PendingReserveConfig, RateCurve, RateCurvePoint,
+                    ReserveConfig, ReserveData, ReserveExtensionData, ReserveFees, ReserveStatus,
+                    RESERVE_VERSION_BPS_CONFIG, RESERVE_VERSION_EXTENDED_CONFIG,
+                    RESERVE_VERSION_PERCENT_CONFIG,
                 },
-                Reserve, SLOTS_PER_YEAR,
+                Migrate, Obligation, ObligationCollateral, ObligationLiquidity, Reserve,
+                ReserveRewardEmissions, MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS,
+                RESERVE_CONFIG_TIMELOCK, SECONDS_PER_YEAR, SLOTS_PER_YEAR,
             },
         },
         proptest::prelude::*,
+        solana_program::{
+            clock::SECONDS_PER_DAY, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+        },
         std::cmp::Ordering,
     };
 
     const MAX_LIQUIDITY: u64 = u64::MAX / 5;
+    const MAX_BORROW_RATE_BPS: u32 = 25_500;
 
     prop_compose! {
-         // Creates rates (min, opt, max) where 0 <= min <= opt <= max <= MAX
-        fn borrow_rates()(optimal_rate in 0..=u8::MAX)(
+         // Creates rates in bps (min, opt, max) where 0 <= min <= opt <= max <= MAX
+        fn borrow_rates()(optimal_rate in 0..=MAX_BORROW_RATE_BPS)(
             min_rate in 0..=optimal_rate,
             optimal_rate in Just(optimal_rate),
-            max_rate in optimal_rate..=u8::MAX,
-        ) -> (u8, u8, u8) {
+            max_rate in optimal_rate..=MAX_BORROW_RATE_BPS,
+        ) -> (u32, u32, u32) {
             (min_rate, optimal_rate, max_rate)
         }
     }
@@ -65,8 +76,8 @@ mod tests {
         fn current_borrow_rate(
             total_liquidity in 0..=MAX_LIQUIDITY,
             borrowed_percent in 0..=WAD,
-            optimal_utilization_rate in 0..=100u8,
-            (min_borrow_rate, optimal_borrow_rate, max_borrow_rate) in borrow_rates(),
+            optimal_utilization_rate_bps in 0..=10_000u16,
+            (min_borrow_rate_bps, optimal_borrow_rate_bps, max_borrow_rate_bps) in borrow_rates(),
         ) {
             let borrowed_amount_wads = Decimal::from(total_liquidity).try_mul(Rate::from_scaled_val(borrowed_percent))?;
             let reserve = Reserve {
@@ -75,19 +86,19 @@ mod tests {
                     available_amount: total_liquidity - borrowed_amount_wads.try_round_u64()?,
                     ..ReserveLiquidity::default()
                 },
-                config: ReserveConfig { optimal_utilization_rate, min_borrow_rate, optimal_borrow_rate, max_borrow_rate, ..ReserveConfig::default() },
+                config: ReserveConfig { optimal_utilization_rate_bps, min_borrow_rate_bps, optimal_borrow_rate_bps, max_borrow_rate_bps, ..ReserveConfig::default() },
                 ..Reserve::default()
             };
 
             let current_borrow_rate = reserve.current_borrow_rate()?;
-            assert!(current_borrow_rate >= Rate::from_percent(min_borrow_rate));
-            assert!(current_borrow_rate <= Rate::from_percent(max_borrow_rate));
+            assert!(current_borrow_rate >= Rate::from_bps(min_borrow_rate_bps));
+            assert!(current_borrow_rate <= Rate::from_bps(max_borrow_rate_bps));
 
-            let optimal_borrow_rate = Rate::from_percent(optimal_borrow_rate);
+            let optimal_borrow_rate = Rate::from_bps(optimal_borrow_rate_bps);
             let current_rate = reserve.liquidity.utilization_rate()?;
-            match current_rate.cmp(&Rate::from_percent(optimal_utilization_rate)) {
+            match current_rate.cmp(&Rate::from_bps(optimal_utilization_rate_bps.into())) {
                 Ordering::Less => {
-                    if min_borrow_rate == reserve.config.optimal_borrow_rate {
+                    if min_borrow_rate_bps == reserve.config.optimal_borrow_rate_bps {
                         assert_eq!(current_borrow_rate, optimal_borrow_rate);
                     } else {
                         assert!(current_borrow_rate < optimal_borrow_rate);
@@ -95,7 +106,7 @@ mod tests {
                 }
                 Ordering::Equal => assert!(current_borrow_rate == optimal_borrow_rate),
                 Ordering::Greater => {
-                    if max_borrow_rate == reserve.config.optimal_borrow_rate {
+                    if max_borrow_rate_bps == reserve.config.optimal_borrow_rate_bps {
                         assert_eq!(current_borrow_rate, optimal_borrow_rate);
                     } else {
                         assert!(current_borrow_rate > optimal_borrow_rate);
@@ -125,7 +136,7 @@ mod tests {
             total_liquidity in 0..=MAX_LIQUIDITY,
             borrowed_percent in 0..=WAD,
             collateral_multiplier in 0..=(5*WAD),
-            borrow_rate in 0..=u8::MAX,
+            borrow_rate_bps in 0..=MAX_BORROW_RATE_BPS,
         ) {
             let borrowed_liquidity_wads = Decimal::from(total_liquidity).try_mul(Rate::from_scaled_val(borrowed_percent))?;
             let available_liquidity = total_liquidity - borrowed_liquidity_wads.try_round_u64()?;
@@ -142,9 +153,9 @@ mod tests {
                     ..ReserveLiquidity::default()
                 },
                 config: ReserveConfig {
-                    min_borrow_rate: borrow_rate,
-                    optimal_borrow_rate: borrow_rate,
-                    optimal_utilization_rate: 100,
+                    min_borrow_rate_bps: borrow_rate_bps,
+                    optimal_borrow_rate_bps: borrow_rate_bps,
+                    optimal_utilization_rate_bps: 10_000,
                     ..ReserveConfig::default()
                 },
                 ..Reserve::default()
@@ -154,10 +165,10 @@ mod tests {
             assert!(exchange_rate.0.to_scaled_val() <= 5u128 * WAD as u128);
 
             // After interest accrual, total liquidity increases and collateral are worth more
-            reserve.accrue_interest(1)?;
+            reserve.accrue_interest(1, 0)?;
 
             let new_exchange_rate = reserve.collateral_exchange_rate()?;
-            if borrow_rate > 0 && total_liquidity > 0 && borrowed_percent > 0 {
+            if borrow_rate_bps > 0 && total_liquidity > 0 && borrowed_percent > 0 {
                 assert!(new_exchange_rate.0 < exchange_rate.0);
             } else {
                 assert_eq!(new_exchange_rate.0, exchange_rate.0);
@@ -175,7 +186,7 @@ mod tests {
             // Simulate running for max 1000 years, assuming that interest is
             // compounded at least once a year
             for _ in 0..1000 {
-                reserve.liquidity.compound_interest(borrow_rate, slots_elapsed)?;
+                reserve.liquidity.compound_interest(borrow_rate, slots_elapsed, SLOTS_PER_YEAR)?;
                 reserve.liquidity.cumulative_borrow_rate_wads.to_scaled_val()?;
             }
         }
@@ -184,7 +195,7 @@ mod tests {
         fn reserve_accrue_interest(
             slots_elapsed in 0..=SLOTS_PER_YEAR,
             borrowed_liquidity in 0..=u64::MAX,
-            borrow_rate in 0..=u8::MAX,
+            borrow_rate_bps in 0..=MAX_BORROW_RATE_BPS,
         ) {
             let borrowed_amount_wads = Decimal::from(borrowed_liquidity);
             let mut reserve = Reserve {
@@ -193,15 +204,15 @@ mod tests {
                     ..ReserveLiquidity::default()
                 },
                 config: ReserveConfig {
-                    max_borrow_rate: borrow_rate,
+                    max_borrow_rate_bps: borrow_rate_bps,
                     ..ReserveConfig::default()
                 },
                 ..Reserve::default()
             };
 
-            reserve.accrue_interest(slots_elapsed)?;
+            reserve.accrue_interest(slots_elapsed, 0)?;
 
-            if borrow_rate > 0 && slots_elapsed > 0 {
+            if borrow_rate_bps > 0 && slots_elapsed > 0 {
                 assert!(reserve.liquidity.borrowed_amount_wads > borrowed_amount_wads);
             } else {
                 assert!(reserve.liquidity.borrowed_amount_wads == borrowed_amount_wads);
@@ -212,7 +223,7 @@ mod tests {
         fn borrow_fee_calculation(
             borrow_fee_wad in 0..WAD, // at WAD, fee == borrow amount, which fails
             flash_loan_fee_wad in 0..WAD, // at WAD, fee == borrow amount, which fails
-            host_fee_percentage in 0..=100u8,
+            host_fee_bps in 0..=10_000u16,
             borrow_amount in 3..=u64::MAX, // start at 3 to ensure calculation success
                                            // 0, 1, and 2 are covered in the minimum tests
                                            // @FIXME: ^ no longer true
@@ -220,7 +231,8 @@ mod tests {
             let fees = ReserveFees {
                 borrow_fee_wad,
                 flash_loan_fee_wad,
-                host_fee_percentage,
+                host_fee_bps,
+                protocol_liquidation_fee_bps: 0,
             };
             let (total_fee, host_fee) = fees.calculate_borrow_fees(Decimal::from(borrow_amount), FeeCalculation::Exclusive)?;
 
@@ -238,13 +250,13 @@ mod tests {
                 assert!(total_fee > 0);
             }
 
-            if host_fee_percentage == 100 {
-                // if the host fee percentage is maxed at 100%, it should get all the fee
+            if host_fee_bps == 10_000 {
+                // if the host fee is maxed at 100%, it should get all the fee
                 assert_eq!(host_fee, total_fee);
             }
 
             // if there's a host fee and some borrow fee, host fee must be greater than 0
-            if host_fee_percentage > 0 && borrow_fee_wad > 0 {
+            if host_fee_bps > 0 && borrow_fee_wad > 0 {
                 assert!(host_fee > 0);
             } else {
                 assert_eq!(host_fee, 0);
@@ -255,7 +267,7 @@ mod tests {
         fn flash_loan_fee_calculation(
             borrow_fee_wad in 0..WAD, // at WAD, fee == borrow amount, which fails
             flash_loan_fee_wad in 0..WAD, // at WAD, fee == borrow amount, which fails
-            host_fee_percentage in 0..=100u8,
+            host_fee_bps in 0..=10_000u16,
             borrow_amount in 3..=u64::MAX, // start at 3 to ensure calculation success
                                            // 0, 1, and 2 are covered in the minimum tests
                                            // @FIXME: ^ no longer true
@@ -263,7 +275,8 @@ mod tests {
             let fees = ReserveFees {
                 borrow_fee_wad,
                 flash_loan_fee_wad,
-                host_fee_percentage,
+                host_fee_bps,
+                protocol_liquidation_fee_bps: 0,
             };
             let (total_fee, host_fee) = fees.calculate_flash_loan_fees(Decimal::from(borrow_amount))?;
 
@@ -281,13 +294,13 @@ mod tests {
                 assert!(total_fee > 0);
             }
 
-            if host_fee_percentage == 100 {
-                // if the host fee percentage is maxed at 100%, it should get all the fee
+            if host_fee_bps == 10_000 {
+                // if the host fee is maxed at 100%, it should get all the fee
                 assert_eq!(host_fee, total_fee);
             }
 
             // if there's a host fee and some borrow fee, host fee must be greater than 0
-            if host_fee_percentage > 0 && borrow_fee_wad > 0 {
+            if host_fee_bps > 0 && borrow_fee_wad > 0 {
                 assert!(host_fee > 0);
             } else {
                 assert_eq!(host_fee, 0);
@@ -300,7 +313,8 @@ mod tests {
         let fees = ReserveFees {
             borrow_fee_wad: 10_000_000_000_000_000, // 1%
             flash_loan_fee_wad: 0,
-            host_fee_percentage: 20,
+            host_fee_bps: 2_000,
+            protocol_liquidation_fee_bps: 0,
         };
         // only 2 tokens borrowed, get error
         let err = fees
@@ -325,7 +339,8 @@ mod tests {
         let fees = ReserveFees {
             borrow_fee_wad: 10_000_000_000_000_000, // 1%
             flash_loan_fee_wad: 0,
-            host_fee_percentage: 0,
+            host_fee_bps: 0,
+            protocol_liquidation_fee_bps: 0,
         };
         let (total_fee, host_fee) = fees
             .calculate_borrow_fees(Decimal::from(2u64), FeeCalculation::Exclusive)
@@ -349,7 +364,8 @@ mod tests {
         let fees = ReserveFees {
             borrow_fee_wad: 10_000_000_000_000_000, // 1%
             flash_loan_fee_wad: 0,
-            host_fee_percentage: 20,
+            host_fee_bps: 2_000,
+            protocol_liquidation_fee_bps: 0,
         };
 
         let (total_fee, host_fee) = fees
@@ -364,7 +380,8 @@ mod tests {
         let fees = ReserveFees {
             borrow_fee_wad: 10_000_000_000_000_000, // 1%
             flash_loan_fee_wad: 0,
-            host_fee_percentage: 0,
+            host_fee_bps: 0,
+            protocol_liquidation_fee_bps: 0,
         };
 
         let (total_fee, host_fee) = fees
@@ -374,4 +391,1367 @@ mod tests {
         assert_eq!(total_fee, 10); // 1% of 1000
         assert_eq!(host_fee, 0); // 0 host fee
     }
+
+    #[test]
+    fn protocol_liquidation_fee_calculation() {
+        let bonus_rate = Rate::from_percent(10).try_add(Rate::one()).unwrap();
+        let fees = ReserveFees {
+            protocol_liquidation_fee_bps: 5_000,
+            ..ReserveFees::default()
+        };
+
+        // 110 collateral at a 10% bonus contains 10 of bonus, half of it is kept
+        assert_eq!(
+            fees.calculate_protocol_liquidation_fee(110, bonus_rate)
+                .unwrap(),
+            5
+        );
+        assert_eq!(
+            fees.calculate_protocol_liquidation_fee(0, bonus_rate)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            ReserveFees::default()
+                .calculate_protocol_liquidation_fee(110, bonus_rate)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn liquidation_host_fee_calculation() {
+        let fees = ReserveFees {
+            host_fee_bps: 2_000,
+            ..ReserveFees::default()
+        };
+        assert_eq!(fees.calculate_liquidation_host_fee(5).unwrap(), 1);
+        assert_eq!(fees.calculate_liquidation_host_fee(4).unwrap(), 0);
+        assert_eq!(
+            ReserveFees::default()
+                .calculate_liquidation_host_fee(5)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn calculate_liquidation_protocol_fee() {
+        let reserve = Reserve {
+            config: ReserveConfig {
+                liquidation_bonus_bps: 1_000,
+                fees: ReserveFees {
+                    protocol_liquidation_fee_bps: 2_000,
+                    ..ReserveFees::default()
+                },
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+        let liquidity = ObligationLiquidity {
+            borrowed_amount_wads: Decimal::from(100u64),
+            market_value: Decimal::from(100u64),
+            ..ObligationLiquidity::default()
+        };
+        let collateral = ObligationCollateral {
+            deposited_amount: 1000,
+            market_value: Decimal::from(1000u64),
+            ..ObligationCollateral::default()
+        };
+        let obligation = Obligation {
+            borrowed_value: Decimal::from(100u64),
+            ..Obligation::default()
+        };
+
+        let result = reserve
+            .calculate_liquidation(u64::MAX, &obligation, &liquidity, &collateral, 0)
+            .unwrap();
+        // 50 repaid for 55 collateral, 20% of the 5 collateral bonus goes to the
+        // protocol
+        assert_eq!(result.repay_amount, 50);
+        assert_eq!(result.withdraw_amount, 55);
+        assert_eq!(result.protocol_fee, 1);
+    }
+
+    #[test]
+    fn forgive_debt_socializes_loss() {
+        let mut reserve = Reserve {
+            collateral: ReserveCollateral {
+                mint_total_supply: 1000,
+                ..ReserveCollateral::default()
+            },
+            liquidity: ReserveLiquidity {
+                available_amount: 600,
+                borrowed_amount_wads: Decimal::from(400u64),
+                ..ReserveLiquidity::default()
+            },
+            ..Reserve::default()
+        };
+        let exchange_rate = reserve.collateral_exchange_rate().unwrap();
+
+        reserve
+            .liquidity
+            .forgive_debt(Decimal::from(100u64))
+            .unwrap();
+        assert_eq!(reserve.liquidity.available_amount, 600);
+        assert_eq!(
+            reserve.liquidity.borrowed_amount_wads,
+            Decimal::from(300u64)
+        );
+        assert_eq!(
+            reserve.liquidity.total_supply().unwrap(),
+            Decimal::from(900u64)
+        );
+
+        // Collateral is now redeemable for less liquidity
+        let new_exchange_rate = reserve.collateral_exchange_rate().unwrap();
+        assert!(new_exchange_rate.0 > exchange_rate.0);
+
+        // Forgiving more than the reserve has borrowed clears its borrows
+        reserve
+            .liquidity
+            .forgive_debt(Decimal::from(500u64))
+            .unwrap();
+        assert_eq!(reserve.liquidity.borrowed_amount_wads, Decimal::zero());
+    }
+
+    fn rate_curve_points(points: &[(u16, u32)]) -> Vec<RateCurvePoint> {
+        points
+            .iter()
+            .map(|&(utilization_rate_bps, borrow_rate_bps)| RateCurvePoint {
+                utilization_rate_bps,
+                borrow_rate_bps,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rate_curve_validation() {
+        let valid = [
+            (0, 0),
+            (5_000, 200),
+            (8_000, 600),
+            (9_000, 2_000),
+            (10_000, 15_000),
+        ];
+        assert!(RateCurve::new(&rate_curve_points(&valid))
+            .unwrap()
+            .validate()
+            .is_ok());
+        assert!(RateCurve::EMPTY.validate().is_ok());
+
+        let invalid_curves: [&[(u16, u32)]; 5] = [
+            // single point
+            &[(0, 500)],
+            // does not start at 0
+            &[(1_000, 0), (10_000, 1_000)],
+            // does not end at 100
+            &[(0, 0), (9_000, 1_000)],
+            // utilization not increasing
+            &[(0, 0), (5_000, 200), (5_000, 400), (10_000, 1_000)],
+            // borrow rate decreasing
+            &[(0, 0), (5_000, 800), (8_000, 400), (10_000, 1_000)],
+        ];
+        for points in invalid_curves {
+            assert_eq!(
+                RateCurve::new(&rate_curve_points(points))
+                    .unwrap()
+                    .validate(),
+                Err(LendingError::InvalidConfig.into())
+            );
+        }
+
+        assert_eq!(
+            RateCurve::new(&rate_curve_points(&[(0, 0); 11])),
+            Err(LendingError::InvalidConfig.into())
+        );
+    }
+
+    #[test]
+    fn rate_curve_borrow_rate() {
+        let rate_curve = RateCurve::new(&rate_curve_points(&[
+            (0, 0),
+            (5_000, 200),
+            (8_000, 600),
+            (9_000, 2_000),
+            (10_000, 15_000),
+        ]))
+        .unwrap();
+
+        // on points
+        assert_eq!(rate_curve.borrow_rate(Rate::zero()).unwrap(), Rate::zero());
+        assert_eq!(
+            rate_curve.borrow_rate(Rate::from_percent(80)).unwrap(),
+            Rate::from_percent(6)
+        );
+        assert_eq!(
+            rate_curve.borrow_rate(Rate::one()).unwrap(),
+            Rate::from_percent(150)
+        );
+
+        // between points
+        assert_eq!(
+            rate_curve.borrow_rate(Rate::from_percent(25)).unwrap(),
+            Rate::from_percent(1)
+        );
+        assert_eq!(
+            rate_curve.borrow_rate(Rate::from_percent(85)).unwrap(),
+            Rate::from_percent(13)
+        );
+        assert_eq!(
+            rate_curve.borrow_rate(Rate::from_percent(95)).unwrap(),
+            Rate::from_percent(85)
+        );
+    }
+
+    #[test]
+    fn current_borrow_rate_with_rate_curve() {
+        let rate_curve = RateCurve::new(&rate_curve_points(&[
+            (0, 100),
+            (5_000, 300),
+            (10_000, 5_000),
+        ]))
+        .unwrap();
+        let reserve = Reserve {
+            liquidity: ReserveLiquidity {
+                available_amount: 75,
+                borrowed_amount_wads: Decimal::from(25u64),
+                ..ReserveLiquidity::default()
+            },
+            config: ReserveConfig {
+                min_borrow_rate_bps: 1_000,
+                optimal_borrow_rate_bps: 2_000,
+                max_borrow_rate_bps: 3_000,
+                optimal_utilization_rate_bps: 8_000,
+                rate_curve,
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+        assert_eq!(
+            reserve.current_borrow_rate().unwrap(),
+            Rate::from_percent(2)
+        );
+
+        let mut data = [0; Reserve::LEN];
+        Reserve::pack(
+            Reserve {
+                version: RESERVE_VERSION_EXTENDED_CONFIG,
+                ..reserve.clone()
+            },
+            &mut data,
+        )
+        .unwrap();
+        assert_eq!(
+            Reserve::unpack(&data).unwrap().config.rate_curve,
+            rate_curve
+        );
+    }
+
+    #[test]
+    fn adaptive_rate_adjust_multiplier() {
+        let adaptive_rate = AdaptiveRateConfig {
+            target_utilization_rate_bps: 8_000,
+            adjustment_speed_bps: 10_000,
+        };
+        assert!(adaptive_rate.validate().is_ok());
+        assert!(AdaptiveRateConfig::DISABLED.validate().is_ok());
+        assert_eq!(
+            AdaptiveRateConfig {
+                target_utilization_rate_bps: 10_000,
+                adjustment_speed_bps: 10_000,
+            }
+            .validate(),
+            Err(LendingError::InvalidConfig.into())
+        );
+
+        // full utilization for a day doubles the curve
+        assert_eq!(
+            adaptive_rate
+                .adjust_multiplier(
+                    Decimal::one(),
+                    Rate::one(),
+                    SECONDS_PER_DAY,
+                    SECONDS_PER_YEAR
+                )
+                .unwrap(),
+            Decimal::from(2u64)
+        );
+        // halfway between target and full utilization for a day
+        assert_eq!(
+            adaptive_rate
+                .adjust_multiplier(
+                    Decimal::one(),
+                    Rate::from_percent(90),
+                    SECONDS_PER_DAY,
+                    SECONDS_PER_YEAR
+                )
+                .unwrap(),
+            Decimal::from_percent(150)
+        );
+        // no utilization for a day halves the curve
+        assert_eq!(
+            adaptive_rate
+                .adjust_multiplier(
+                    Decimal::one(),
+                    Rate::zero(),
+                    SECONDS_PER_DAY,
+                    SECONDS_PER_YEAR
+                )
+                .unwrap(),
+            Decimal::from_percent(50)
+        );
+        // utilization at target leaves the curve unchanged
+        assert_eq!(
+            adaptive_rate
+                .adjust_multiplier(
+                    Decimal::one(),
+                    Rate::from_percent(80),
+                    SECONDS_PER_DAY,
+                    SECONDS_PER_YEAR
+                )
+                .unwrap(),
+            Decimal::one()
+        );
+        // multiplier stays within bounds
+        assert_eq!(
+            adaptive_rate
+                .adjust_multiplier(
+                    Decimal::one(),
+                    Rate::one(),
+                    10 * SECONDS_PER_DAY,
+                    SECONDS_PER_YEAR
+                )
+                .unwrap(),
+            Decimal::from(4u64)
+        );
+        assert_eq!(
+            adaptive_rate
+                .adjust_multiplier(
+                    Decimal::one(),
+                    Rate::zero(),
+                    10 * SECONDS_PER_DAY,
+                    SECONDS_PER_YEAR
+                )
+                .unwrap(),
+            Decimal::from_percent(25)
+        );
+    }
+
+    #[test]
+    fn accrue_interest_with_adaptive_rate() {
+        let mut reserve = Reserve {
+            last_update: LastUpdate {
+                unix_timestamp: 1,
+                ..LastUpdate::default()
+            },
+            liquidity: ReserveLiquidity {
+                available_amount: 10_000_000,
+                borrowed_amount_wads: Decimal::from(90_000_000u64),
+                cumulative_borrow_rate_wads: Decimal::one(),
+                ..ReserveLiquidity::default()
+            },
+            config: ReserveConfig {
+                min_borrow_rate_bps: 0,
+                optimal_borrow_rate_bps: 1_000,
+                max_borrow_rate_bps: 5_000,
+                optimal_utilization_rate_bps: 8_000,
+                adaptive_rate: AdaptiveRateConfig {
+                    target_utilization_rate_bps: 8_000,
+                    adjustment_speed_bps: 10_000,
+                },
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+
+        // reserves created before the adaptive model use the curve as is
+        assert_eq!(reserve.adaptive_rate_multiplier(), Decimal::one());
+        assert_eq!(
+            reserve.current_borrow_rate().unwrap(),
+            Rate::from_percent(30)
+        );
+
+        reserve
+            .accrue_interest(0, 1 + SECONDS_PER_DAY as i64)
+            .unwrap();
+        assert_eq!(reserve.adaptive_rate_multiplier, Decimal::from_percent(150));
+        let base_borrow_rate = Reserve {
+            config: ReserveConfig {
+                adaptive_rate: AdaptiveRateConfig::DISABLED,
+                ..reserve.config
+            },
+            ..reserve.clone()
+        }
+        .current_borrow_rate()
+        .unwrap();
+        assert_eq!(
+            reserve.current_borrow_rate().unwrap(),
+            base_borrow_rate.try_mul(Rate::from_percent(150)).unwrap()
+        );
+
+        let mut data = [0; Reserve::LEN];
+        Reserve::pack(
+            Reserve {
+                version: RESERVE_VERSION_EXTENDED_CONFIG,
+                ..reserve.clone()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let unpacked = Reserve::unpack(&data).unwrap();
+        assert_eq!(unpacked.config.adaptive_rate, reserve.config.adaptive_rate);
+        assert_eq!(
+            unpacked.adaptive_rate_multiplier,
+            reserve.adaptive_rate_multiplier
+        );
+    }
+
+    #[test]
+    fn deposit_limit() {
+        let mut reserve = Reserve {
+            liquidity: ReserveLiquidity {
+                available_amount: 600,
+                borrowed_amount_wads: Decimal::from(300u64),
+                ..ReserveLiquidity::default()
+            },
+            collateral: ReserveCollateral {
+                mint_total_supply: 900,
+                ..ReserveCollateral::default()
+            },
+            config: ReserveConfig {
+                deposit_limit: 1_000,
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+
+        assert_eq!(
+            reserve.deposit_liquidity(101),
+            Err(LendingError::DepositLimitExceeded.into())
+        );
+        assert_eq!(reserve.deposit_liquidity(100), Ok(100));
+
+        reserve.config.deposit_limit = 0;
+        assert_eq!(reserve.deposit_liquidity(1_000), Ok(1_000));
+    }
+
+    #[test]
+    fn validate_risk_reduction() {
+        let config = ReserveConfig {
+            loan_to_value_ratio_bps: 5_000,
+            liquidation_threshold_bps: 5_500,
+            deposit_limit: 1_000,
+            ..ReserveConfig::default()
+        };
+
+        let lowered = ReserveConfig {
+            loan_to_value_ratio_bps: 4_000,
+            deposit_limit: 500,
+            borrow_limit: 100,
+            ..config
+        };
+        assert_eq!(config.validate_risk_reduction(&lowered), Ok(()));
+        assert_eq!(config.validate_risk_reduction(&config), Ok(()));
+
+        let raised_ltv = ReserveConfig {
+            loan_to_value_ratio_bps: 5_100,
+            ..config
+        };
+        assert_eq!(
+            config.validate_risk_reduction(&raised_ltv),
+            Err(LendingError::RoleNotPermitted.into())
+        );
+
+        // a limit of 0 removes the limit
+        let unlimited_deposits = ReserveConfig {
+            deposit_limit: 0,
+            ..config
+        };
+        assert_eq!(
+            config.validate_risk_reduction(&unlimited_deposits),
+            Err(LendingError::RoleNotPermitted.into())
+        );
+
+        let other_change = ReserveConfig {
+            liquidation_threshold_bps: 5_000,
+            ..lowered
+        };
+        assert_eq!(
+            config.validate_risk_reduction(&other_change),
+            Err(LendingError::RoleNotPermitted.into())
+        );
+    }
+
+    #[test]
+    fn validate_transition() {
+        let config = ReserveConfig {
+            loan_to_value_ratio_bps: 5_000,
+            liquidation_threshold_bps: 8_000,
+            ..ReserveConfig::default()
+        };
+
+        let stepped_down = ReserveConfig {
+            liquidation_threshold_bps: 8_000 - MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS,
+            ..config
+        };
+        assert_eq!(config.validate_transition(&stepped_down), Ok(()));
+
+        let raised = ReserveConfig {
+            liquidation_threshold_bps: 9_500,
+            ..config
+        };
+        assert_eq!(config.validate_transition(&raised), Ok(()));
+
+        let dropped = ReserveConfig {
+            liquidation_threshold_bps: 8_000 - MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS - 1,
+            ..config
+        };
+        assert_eq!(
+            config.validate_transition(&dropped),
+            Err(LendingError::LiquidationThresholdDecreaseTooLarge.into())
+        );
+    }
+
+    #[test]
+    fn validate_transition_limits() {
+        let config = ReserveConfig {
+            deposit_limit: 1_000,
+            borrow_limit: 500,
+            ..ReserveConfig::default()
+        };
+
+        let lowered = ReserveConfig {
+            deposit_limit: 900,
+            borrow_limit: 400,
+            ..config
+        };
+        assert_eq!(config.validate_transition(&lowered), Ok(()));
+        assert!(config.is_risk_reduction(&lowered));
+
+        // raised or removed limits are queued behind the timelock
+        let raised = ReserveConfig {
+            deposit_limit: 2_000,
+            borrow_limit: 1_000,
+            ..config
+        };
+        assert_eq!(config.validate_transition(&raised), Ok(()));
+        assert!(!config.is_risk_reduction(&raised));
+
+        let removed = ReserveConfig {
+            deposit_limit: 0,
+            borrow_limit: 0,
+            ..config
+        };
+        assert_eq!(config.validate_transition(&removed), Ok(()));
+        assert!(!config.is_risk_reduction(&removed));
+    }
+
+    #[test]
+    fn collateral_ramp() {
+        let mut reserve = Reserve {
+            config: ReserveConfig {
+                loan_to_value_ratio_bps: 5_000,
+                liquidation_threshold_bps: 5_500,
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+        let current = 1_000;
+        let start = current + RESERVE_CONFIG_TIMELOCK;
+        let end = start + 2 * RESERVE_CONFIG_TIMELOCK;
+
+        assert_eq!(
+            reserve.start_collateral_ramp(4_000, 5_000, start - 1, end, current),
+            Err(LendingError::InvalidCollateralRamp.into())
+        );
+        assert_eq!(
+            reserve.start_collateral_ramp(
+                4_000,
+                5_000,
+                start,
+                start + RESERVE_CONFIG_TIMELOCK - 1,
+                current
+            ),
+            Err(LendingError::InvalidCollateralRamp.into())
+        );
+        assert_eq!(
+            reserve.start_collateral_ramp(5_100, 5_000, start, end, current),
+            Err(LendingError::InvalidConfig.into())
+        );
+        assert_eq!(
+            reserve.start_collateral_ramp(4_000, 4_999, start, end, current),
+            Err(LendingError::LiquidationThresholdDecreaseTooLarge.into())
+        );
+        assert_eq!(reserve.pending_config, None);
+        assert_eq!(reserve.collateral_ramp, None);
+
+        // the ramp target is queued behind the timelock, leaving the config
+        assert_eq!(
+            reserve.start_collateral_ramp(4_000, 5_000, start, end, current),
+            Ok(start)
+        );
+        assert_eq!(reserve.config.loan_to_value_ratio_bps, 5_000);
+        assert_eq!(reserve.config.liquidation_threshold_bps, 5_500);
+        assert_eq!(
+            reserve.pending_config,
+            Some(PendingReserveConfig {
+                config: ReserveConfig {
+                    loan_to_value_ratio_bps: 4_000,
+                    liquidation_threshold_bps: 5_000,
+                    ..reserve.config
+                },
+                activation_timestamp: start,
+            })
+        );
+
+        assert_eq!(reserve.loan_to_value_ratio_bps(current), 5_000);
+        assert_eq!(reserve.liquidation_threshold_bps(start), 5_500);
+        let midpoint = start + RESERVE_CONFIG_TIMELOCK;
+        assert_eq!(reserve.loan_to_value_ratio_bps(midpoint), 4_500);
+        assert_eq!(reserve.liquidation_threshold_bps(midpoint), 5_250);
+        assert_eq!(reserve.loan_to_value_ratio_bps(end), 4_000);
+        assert_eq!(reserve.liquidation_threshold_bps(end + 1), 5_000);
+
+        // applying the ramp target keeps the ramp going
+        reserve.apply_pending_config(start).unwrap();
+        assert_eq!(reserve.config.loan_to_value_ratio_bps, 4_000);
+        assert_eq!(reserve.config.liquidation_threshold_bps, 5_000);
+        assert_eq!(reserve.loan_to_value_ratio_bps(midpoint), 4_500);
+        assert_eq!(reserve.liquidation_threshold_bps(midpoint), 5_250);
+
+        // a new ramp starts from the values in effect
+        assert_eq!(
+            reserve.start_collateral_ramp(
+                3_500,
+                4_900,
+                midpoint + RESERVE_CONFIG_TIMELOCK,
+                midpoint + end,
+                midpoint
+            ),
+            Ok(midpoint + RESERVE_CONFIG_TIMELOCK)
+        );
+        assert_eq!(reserve.loan_to_value_ratio_bps(midpoint), 4_500);
+        assert_eq!(reserve.liquidation_threshold_bps(midpoint), 5_250);
+
+        // changing the ramped values of the config cancels the ramp
+        let config = reserve.config;
+        reserve.set_config(ReserveConfig {
+            loan_to_value_ratio_bps: 3_500,
+            liquidation_threshold_bps: 4_900,
+            deposit_limit: 100,
+            ..config
+        });
+        assert!(reserve.collateral_ramp.is_some());
+        reserve.set_config(ReserveConfig {
+            loan_to_value_ratio_bps: 3_000,
+            ..config
+        });
+        assert_eq!(reserve.collateral_ramp, None);
+        assert_eq!(reserve.loan_to_value_ratio_bps(midpoint), 3_000);
+    }
+
+    #[test]
+    fn reduce_risk() {
+        let config = ReserveConfig {
+            loan_to_value_ratio_bps: 5_000,
+            liquidation_threshold_bps: 5_500,
+            deposit_limit: 1_000,
+            ..ReserveConfig::default()
+        };
+        let mut reserve = Reserve {
+            config,
+            ..Reserve::default()
+        };
+        let current = 1_000;
+        let start = current + RESERVE_CONFIG_TIMELOCK;
+        let end = start + 2 * RESERVE_CONFIG_TIMELOCK;
+        let midpoint = start + RESERVE_CONFIG_TIMELOCK;
+
+        // the ramp keeps going with its loan to value ratios capped, as does
+        // the pending config it moves towards
+        reserve
+            .start_collateral_ramp(4_000, 5_000, start, end, current)
+            .unwrap();
+        let lowered = ReserveConfig {
+            loan_to_value_ratio_bps: 4_200,
+            deposit_limit: 500,
+            ..config
+        };
+        reserve.reduce_risk(lowered, true);
+        assert_eq!(reserve.config, lowered);
+        assert_eq!(reserve.loan_to_value_ratio_bps(current), 4_200);
+        assert_eq!(reserve.loan_to_value_ratio_bps(midpoint), 4_100);
+        assert_eq!(reserve.loan_to_value_ratio_bps(end), 4_000);
+        assert_eq!(reserve.liquidation_threshold_bps(midpoint), 5_250);
+        assert_eq!(
+            reserve.pending_config.unwrap().config,
+            ReserveConfig {
+                loan_to_value_ratio_bps: 4_000,
+                liquidation_threshold_bps: 5_000,
+                ..lowered
+            }
+        );
+        reserve.apply_pending_config(start).unwrap();
+        assert!(reserve.collateral_ramp.is_some());
+        assert_eq!(reserve.config.deposit_limit, 500);
+
+        let lowered = ReserveConfig {
+            loan_to_value_ratio_bps: 3_000,
+            ..reserve.config
+        };
+        reserve.reduce_risk(lowered, true);
+        assert_eq!(reserve.loan_to_value_ratio_bps(midpoint), 3_000);
+        assert_eq!(reserve.liquidation_threshold_bps(midpoint), 5_250);
+
+        // other pending configs are only dropped when requested
+        let mut reserve = Reserve {
+            config,
+            ..Reserve::default()
+        };
+        let raised = ReserveConfig {
+            deposit_limit: 2_000,
+            ..config
+        };
+        reserve.queue_config(raised, current).unwrap();
+        let lowered = ReserveConfig {
+            loan_to_value_ratio_bps: 4_000,
+            ..config
+        };
+        reserve.reduce_risk(lowered, false);
+        assert_eq!(reserve.config, lowered);
+        assert_eq!(reserve.pending_config.unwrap().config, raised);
+        reserve.reduce_risk(
+            ReserveConfig {
+                loan_to_value_ratio_bps: 3_500,
+                ..config
+            },
+            true,
+        );
+        assert_eq!(reserve.pending_config, None);
+    }
+
+    #[test]
+    fn pending_config_timelock() {
+        let mut reserve = Reserve {
+            config: ReserveConfig {
+                loan_to_value_ratio_bps: 5_000,
+                liquidation_threshold_bps: 5_500,
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+        assert_eq!(
+            reserve.apply_pending_config(0),
+            Err(LendingError::NoPendingReserveConfig.into())
+        );
+
+        let new_config = ReserveConfig {
+            loan_to_value_ratio_bps: 6_000,
+            liquidation_threshold_bps: 6_500,
+            ..ReserveConfig::default()
+        };
+        assert!(!reserve.config.is_risk_reduction(&new_config));
+        let activation_timestamp = reserve.queue_config(new_config, 1_000).unwrap();
+        assert_eq!(activation_timestamp, 1_000 + RESERVE_CONFIG_TIMELOCK);
+
+        assert_eq!(
+            reserve.apply_pending_config(activation_timestamp - 1),
+            Err(LendingError::ReserveConfigTimelocked.into())
+        );
+        assert_eq!(reserve.config.loan_to_value_ratio_bps, 5_000);
+
+        reserve.apply_pending_config(activation_timestamp).unwrap();
+        assert_eq!(reserve.config, new_config);
+        assert_eq!(reserve.pending_config, None);
+    }
+
+    #[test]
+    fn reserve_versions() {
+        assert!(Reserve::is_outdated(RESERVE_VERSION_PERCENT_CONFIG));
+        assert!(Reserve::is_outdated(RESERVE_VERSION_BPS_CONFIG));
+        assert!(!Reserve::is_outdated(RESERVE_VERSION_EXTENDED_CONFIG));
+
+        let reserve = Reserve {
+            version: RESERVE_VERSION_EXTENDED_CONFIG,
+            last_update: LastUpdate {
+                slot: 42,
+                stale: false,
+                unix_timestamp: 1_700_000_000,
+            },
+            lending_market: Pubkey::new_unique(),
+            liquidity: ReserveLiquidity {
+                mint_pubkey: Pubkey::new_unique(),
+                mint_decimals: 6,
+                available_amount: 1_000,
+                borrowed_amount_wads: Decimal::from(500u64),
+                cumulative_borrow_rate_wads: Decimal::one(),
+                ..ReserveLiquidity::default()
+            },
+            collateral: ReserveCollateral {
+                mint_pubkey: Pubkey::new_unique(),
+                mint_total_supply: 1_500,
+                fee_receiver: Pubkey::new_unique(),
+                ..ReserveCollateral::default()
+            },
+            config: ReserveConfig {
+                optimal_utilization_rate_bps: 8_050,
+                loan_to_value_ratio_bps: 7_250,
+                liquidation_bonus_bps: 525,
+                liquidation_threshold_bps: 8_000,
+                min_borrow_rate_bps: 50,
+                optimal_borrow_rate_bps: 800,
+                max_borrow_rate_bps: 50_000,
+                rate_curve: RateCurve::new(&rate_curve_points(&[(0, 100), (10_000, 5_000)]))
+                    .unwrap(),
+                adaptive_rate: AdaptiveRateConfig {
+                    target_utilization_rate_bps: 9_000,
+                    adjustment_speed_bps: 1_000,
+                },
+                deposit_limit: 10_000,
+                borrow_limit: 5_000,
+                fixed_rate: FixedRateConfig {
+                    premium_bps: 200,
+                    max_term: 90 * SECONDS_PER_DAY as u32,
+                },
+                ..ReserveConfig::default()
+            },
+            adaptive_rate_multiplier: Decimal::from_percent(120),
+            status: ReserveStatus::ReduceOnly,
+            pending_config: Some(PendingReserveConfig {
+                config: ReserveConfig {
+                    optimal_utilization_rate_bps: 7_000,
+                    loan_to_value_ratio_bps: 7_500,
+                    liquidation_threshold_bps: 8_200,
+                    max_borrow_rate_bps: 40_000,
+                    fees: ReserveFees {
+                        borrow_fee_wad: 7,
+                        flash_loan_fee_wad: 9,
+                        host_fee_bps: 2_050,
+                        protocol_liquidation_fee_bps: 1_025,
+                    },
+                    rate_curve: RateCurve::new(&rate_curve_points(&[
+                        (0, 200),
+                        (8_050, 725),
+                        (10_000, 60_000),
+                    ]))
+                    .unwrap(),
+                    adaptive_rate: AdaptiveRateConfig {
+                        target_utilization_rate_bps: 8_500,
+                        adjustment_speed_bps: 500,
+                    },
+                    deposit_limit: 20_000,
+                    borrow_limit: 15_000,
+                    fixed_rate: FixedRateConfig {
+                        premium_bps: 300,
+                        max_term: 30 * SECONDS_PER_DAY as u32,
+                    },
+                    ..ReserveConfig::default()
+                },
+                activation_timestamp: 1_700_086_400,
+            }),
+            collateral_ramp: Some(CollateralRamp {
+                initial_loan_to_value_ratio_bps: 6_000,
+                initial_liquidation_threshold_bps: 6_500,
+                target_loan_to_value_ratio_bps: 7_500,
+                target_liquidation_threshold_bps: 8_200,
+                start_timestamp: 1_700_000_000,
+                end_timestamp: 1_700_604_800,
+            }),
+            reward_emissions: Some(ReserveRewardEmissions {
+                supply_emission_per_second: 100,
+                borrow_emission_per_second: 50,
+                supply_index: Decimal::from_scaled_val(1_234_567),
+                borrow_index: Decimal::from(2u64),
+            }),
+        };
+        let mut data = [0; Reserve::LEN];
+        Reserve::pack(reserve.clone(), &mut data).unwrap();
+        assert_eq!(Reserve::unpack(&data).unwrap(), reserve);
+
+        // the base layout only stores whole percentages of the fees, rate
+        // curve and adaptive rate settings, and no reward emissions
+        let mut base_data = [0; Reserve::LEN];
+        let base_reserve = Reserve {
+            version: RESERVE_VERSION_BPS_CONFIG,
+            ..reserve.clone()
+        };
+        assert_eq!(
+            Reserve::pack(base_reserve.clone(), &mut base_data),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut base_data = vec![0; Reserve::layout_len(RESERVE_VERSION_BPS_CONFIG)];
+        assert_eq!(
+            Reserve::pack(base_reserve.clone(), &mut base_data),
+            Err(LendingError::InvalidConfig.into())
+        );
+        let base_reserve = Reserve {
+            pending_config: None,
+            ..base_reserve
+        };
+        assert_eq!(
+            Reserve::pack(base_reserve.clone(), &mut base_data),
+            Err(LendingError::InvalidConfig.into())
+        );
+        let mut base_reserve = Reserve {
+            reward_emissions: None,
+            ..base_reserve
+        };
+        Reserve::pack(base_reserve.clone(), &mut base_data).unwrap();
+        assert_eq!(Reserve::unpack(&base_data).unwrap(), base_reserve);
+
+        // migrating moves the reserve to the extended layout
+        assert_eq!(base_reserve.migrate(), Reserve::LEN);
+        assert_eq!(base_reserve.version, RESERVE_VERSION_EXTENDED_CONFIG);
+        Reserve::pack(base_reserve.clone(), &mut data).unwrap();
+        assert_eq!(Reserve::unpack(&data).unwrap(), base_reserve);
+
+        data[0] = RESERVE_VERSION_EXTENDED_CONFIG + 1;
+        assert_eq!(
+            Reserve::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn migrate_percent_config_reserve() {
+        // offsets of the percentage config rates and host fee in the base
+        // layout
+        const PERCENT_CONFIG_OFFSET: usize = 299;
+        const HOST_FEE_PERCENTAGE_OFFSET: usize = 322;
+        let mut data = vec![0; Reserve::layout_len(RESERVE_VERSION_PERCENT_CONFIG)];
+        Reserve::pack(
+            Reserve {
+                version: RESERVE_VERSION_BPS_CONFIG,
+                ..Reserve::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        data[0] = RESERVE_VERSION_PERCENT_CONFIG;
+        data[PERCENT_CONFIG_OFFSET..PERCENT_CONFIG_OFFSET + 7]
+            .copy_from_slice(&[80, 50, 5, 55, 1, 4, 255]);
+        data[HOST_FEE_PERCENTAGE_OFFSET] = 20;
+
+        let mut reserve = Reserve::unpack(&data).unwrap();
+        assert_eq!(reserve.version, RESERVE_VERSION_BPS_CONFIG);
+        assert_eq!(
+            reserve.config,
+            ReserveConfig {
+                optimal_utilization_rate_bps: 8_000,
+                loan_to_value_ratio_bps: 5_000,
+                liquidation_bonus_bps: 500,
+                liquidation_threshold_bps: 5_500,
+                min_borrow_rate_bps: 100,
+                optimal_borrow_rate_bps: 400,
+                max_borrow_rate_bps: 25_500,
+                fees: ReserveFees {
+                    host_fee_bps: 2_000,
+                    ..ReserveFees::default()
+                },
+                ..ReserveConfig::default()
+            }
+        );
+
+        let mut packed = vec![0; Reserve::layout_len(RESERVE_VERSION_BPS_CONFIG)];
+        Reserve::pack(reserve.clone(), &mut packed).unwrap();
+        assert_eq!(
+            packed[PERCENT_CONFIG_OFFSET..PERCENT_CONFIG_OFFSET + 7],
+            [0; 7]
+        );
+        assert_eq!(packed[HOST_FEE_PERCENTAGE_OFFSET], 20);
+        assert_eq!(Reserve::unpack(&packed).unwrap(), reserve);
+
+        assert_eq!(reserve.migrate(), Reserve::LEN);
+        let mut migrated = [0; Reserve::LEN];
+        Reserve::pack(reserve.clone(), &mut migrated).unwrap();
+        assert_eq!(migrated[HOST_FEE_PERCENTAGE_OFFSET], 0);
+        assert_eq!(Reserve::unpack(&migrated).unwrap(), reserve);
+    }
+
+    #[test]
+    fn accrue_interest_by_unix_timestamp() {
+        let reserve = Reserve {
+            last_update: LastUpdate {
+                slot: 0,
+                stale: false,
+                unix_timestamp: 1_000,
+            },
+            liquidity: ReserveLiquidity {
+                borrowed_amount_wads: Decimal::from(1_000_000u64),
+                cumulative_borrow_rate_wads: Decimal::one(),
+                ..ReserveLiquidity::default()
+            },
+            config: ReserveConfig {
+                max_borrow_rate_bps: 1_000,
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+        let compounded_rate = |periods_per_year: u64| -> Decimal {
+            Rate::one()
+                .try_add(Rate::from_bps(1_000).try_div(periods_per_year).unwrap())
+                .unwrap()
+                .try_pow(periods_per_year)
+                .unwrap()
+                .into()
+        };
+
+        // a year of seconds accrues a year of interest regardless of slots
+        let mut timestamped = reserve.clone();
+        timestamped
+            .accrue_interest(1, 1_000 + SECONDS_PER_YEAR as i64)
+            .unwrap();
+        assert_eq!(
+            timestamped.liquidity.cumulative_borrow_rate_wads,
+            compounded_rate(SECONDS_PER_YEAR)
+        );
+
+        // slots elapsed without time passing accrue nothing
+        let mut same_second = reserve.clone();
+        same_second.accrue_interest(SLOTS_PER_YEAR, 1_000).unwrap();
+        assert_eq!(same_second.liquidity, reserve.liquidity);
+
+        // a clock going backwards accrues nothing
+        let mut backwards = reserve.clone();
+        backwards.accrue_interest(SLOTS_PER_YEAR, 999).unwrap();
+        assert_eq!(backwards.liquidity, reserve.liquidity);
+
+        // reserves without a timestamp fall back to elapsed slots
+        let mut legacy = Reserve {
+            last_update: LastUpdate::new(0),
+            ..reserve.clone()
+        };
+        legacy.accrue_interest(SLOTS_PER_YEAR, 1_000).unwrap();
+        assert_eq!(
+            legacy.liquidity.cumulative_borrow_rate_wads,
+            compounded_rate(SLOTS_PER_YEAR)
+        );
+    }
+
+    #[test]
+    fn accrue_reward_emissions() {
+        let reserve = Reserve {
+            last_update: LastUpdate {
+                slot: 0,
+                stale: false,
+                unix_timestamp: 1_000,
+            },
+            liquidity: ReserveLiquidity {
+                available_amount: 500,
+                borrowed_amount_wads: Decimal::from(500u64),
+                cumulative_borrow_rate_wads: Decimal::one(),
+                ..ReserveLiquidity::default()
+            },
+            collateral: ReserveCollateral {
+                mint_total_supply: 1_000,
+                ..ReserveCollateral::default()
+            },
+            config: ReserveConfig {
+                max_borrow_rate_bps: 1_000,
+                ..ReserveConfig::default()
+            },
+            reward_emissions: Some(ReserveRewardEmissions::new(100, 50)),
+            ..Reserve::default()
+        };
+        assert_eq!(reserve.supply_reward_index(), Decimal::one());
+
+        // rewards are split by the collateral supply and the liquidity
+        // borrowed before interest compounds
+        let mut accrued = reserve.clone();
+        accrued.accrue_interest(1, 1_010).unwrap();
+        assert_eq!(accrued.supply_reward_index(), Decimal::from(2u64));
+        assert_eq!(accrued.borrow_reward_index(), Decimal::from(2u64));
+        assert!(accrued.liquidity.borrowed_amount_wads > Decimal::from(500u64));
+
+        // reserves without a timestamp emit no rewards for elapsed slots
+        let mut legacy = Reserve {
+            last_update: LastUpdate::new(0),
+            ..reserve.clone()
+        };
+        legacy.accrue_interest(SLOTS_PER_YEAR, 1_010).unwrap();
+        assert_eq!(legacy.reward_emissions, reserve.reward_emissions);
+
+        // reserves without rewards start positions at the initial index
+        let no_rewards = Reserve {
+            reward_emissions: None,
+            ..reserve
+        };
+        assert_eq!(no_rewards.borrow_reward_index(), Decimal::one());
+    }
+
+    #[test]
+    fn fixed_rate_config() {
+        let config = FixedRateConfig {
+            premium_bps: 250,
+            max_term: 1_000,
+        };
+        assert_eq!(
+            config.borrow_rate(Rate::from_percent(4)).unwrap(),
+            Rate::from_bps(650)
+        );
+        assert_eq!(config.maturity_timestamp(1_000, 5_000).unwrap(), 6_000);
+        assert_eq!(
+            config.maturity_timestamp(0, 5_000),
+            Err(LendingError::InvalidFixedRateBorrow.into())
+        );
+        assert_eq!(
+            config.maturity_timestamp(1_001, 5_000),
+            Err(LendingError::InvalidFixedRateBorrow.into())
+        );
+        assert_eq!(
+            FixedRateConfig::DISABLED.maturity_timestamp(1, 5_000),
+            Err(LendingError::InvalidFixedRateBorrow.into())
+        );
+    }
+
+    #[test]
+    fn reserve_data_in_place() {
+        let reserve = Reserve {
+            version: RESERVE_VERSION_EXTENDED_CONFIG,
+            last_update: LastUpdate {
+                slot: 42,
+                stale: false,
+                unix_timestamp: 1_700_000_000,
+            },
+            lending_market: Pubkey::new_unique(),
+            liquidity: ReserveLiquidity {
+                mint_pubkey: Pubkey::new_unique(),
+                available_amount: 1_000,
+                borrowed_amount_wads: Decimal::from(500u64),
+                market_price: Decimal::from(3u64),
+                ..ReserveLiquidity::default()
+            },
+            collateral: ReserveCollateral {
+                mint_pubkey: Pubkey::new_unique(),
+                mint_total_supply: 1_500,
+                ..ReserveCollateral::default()
+            },
+            status: ReserveStatus::Active,
+            ..Reserve::default()
+        };
+        let mut data = [0; Reserve::LEN];
+        assert_eq!(
+            ReserveData::load(&data).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+        Reserve::pack(reserve.clone(), &mut data).unwrap();
+
+        let reserve_data = ReserveData::load(&data).unwrap();
+        assert_eq!(reserve_data.version(), reserve.version);
+        assert_eq!(reserve_data.last_update_slot(), reserve.last_update.slot);
+        assert_eq!(reserve_data.lending_market(), reserve.lending_market);
+        assert_eq!(reserve_data.status(), Ok(reserve.status));
+        assert_eq!(
+            reserve_data.liquidity_mint_pubkey(),
+            reserve.liquidity.mint_pubkey
+        );
+        assert_eq!(
+            reserve_data.liquidity_available_amount(),
+            reserve.liquidity.available_amount
+        );
+        assert_eq!(
+            reserve_data.liquidity_borrowed_amount_wads(),
+            reserve.liquidity.borrowed_amount_wads
+        );
+        assert_eq!(
+            reserve_data.liquidity_market_price(),
+            reserve.liquidity.market_price
+        );
+        assert_eq!(
+            reserve_data.collateral_mint_pubkey(),
+            reserve.collateral.mint_pubkey
+        );
+        assert_eq!(
+            reserve_data.collateral_mint_total_supply(),
+            reserve.collateral.mint_total_supply
+        );
+
+        // writes in place are read back by unpack
+        ReserveData::load_mut(&mut data)
+            .unwrap()
+            .set_status(ReserveStatus::Paused);
+        assert_eq!(
+            Reserve::unpack(&data).unwrap(),
+            Reserve {
+                status: ReserveStatus::Paused,
+                ..reserve.clone()
+            }
+        );
+
+        // the base layout is read in place too
+        let mut data = vec![0; Reserve::layout_len(RESERVE_VERSION_BPS_CONFIG)];
+        Reserve::pack(
+            Reserve {
+                version: RESERVE_VERSION_BPS_CONFIG,
+                status: ReserveStatus::Paused,
+                ..reserve.clone()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let reserve_data = ReserveData::load(&data).unwrap();
+        assert_eq!(reserve_data.version(), RESERVE_VERSION_BPS_CONFIG);
+        assert_eq!(
+            reserve_data.liquidity_available_amount(),
+            reserve.liquidity.available_amount
+        );
+
+        // percentage layouts must be migrated before they are read in place
+        data[0] = RESERVE_VERSION_PERCENT_CONFIG;
+        assert_eq!(
+            ReserveData::load(&data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            ReserveData::load_mut(&mut data).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(data[0], RESERVE_VERSION_PERCENT_CONFIG);
+    }
+
+    #[test]
+    fn reserve_data_refresh() {
+        let reserve = Reserve {
+            version: RESERVE_VERSION_EXTENDED_CONFIG,
+            last_update: LastUpdate {
+                slot: 1,
+                stale: false,
+                unix_timestamp: 1_000,
+            },
+            lending_market: Pubkey::new_unique(),
+            liquidity: ReserveLiquidity {
+                mint_pubkey: Pubkey::new_unique(),
+                mint_decimals: 6,
+                supply_pubkey: Pubkey::new_unique(),
+                fee_receiver: Pubkey::new_unique(),
+                oracle_pubkey: Pubkey::new_unique(),
+                available_amount: 500,
+                borrowed_amount_wads: Decimal::from(500u64),
+                cumulative_borrow_rate_wads: Decimal::one(),
+                market_price: Decimal::from(3u64),
+            },
+            collateral: ReserveCollateral {
+                mint_pubkey: Pubkey::new_unique(),
+                mint_total_supply: 1_000,
+                supply_pubkey: Pubkey::new_unique(),
+                fee_receiver: Pubkey::new_unique(),
+            },
+            config: ReserveConfig {
+                loan_to_value_ratio_bps: 5_000,
+                liquidation_threshold_bps: 6_000,
+                max_borrow_rate_bps: 1_000,
+                adaptive_rate: AdaptiveRateConfig {
+                    target_utilization_rate_bps: 8_000,
+                    adjustment_speed_bps: 100,
+                },
+                deposit_limit: 1_500,
+                ..ReserveConfig::default()
+            },
+            collateral_ramp: Some(CollateralRamp {
+                initial_loan_to_value_ratio_bps: 5_000,
+                initial_liquidation_threshold_bps: 6_000,
+                target_loan_to_value_ratio_bps: 3_000,
+                target_liquidation_threshold_bps: 4_000,
+                start_timestamp: 1_000,
+                end_timestamp: 2_000,
+            }),
+            reward_emissions: Some(ReserveRewardEmissions::new(100, 50)),
+            status: ReserveStatus::Active,
+            ..Reserve::default()
+        };
+        let mut data = [0; Reserve::LEN];
+        Reserve::pack(reserve.clone(), &mut data).unwrap();
+
+        let reserve_data = ReserveData::load(&data).unwrap();
+        assert_eq!(reserve_data.last_update(), Ok(reserve.last_update.clone()));
+        assert_eq!(reserve_data.liquidity(), reserve.liquidity);
+        assert_eq!(reserve_data.collateral(), reserve.collateral);
+        assert_eq!(
+            reserve_data.collateral_exchange_rate().unwrap().0,
+            reserve.collateral_exchange_rate().unwrap().0
+        );
+        assert_eq!(reserve_data.collateral_ramp(), Ok(reserve.collateral_ramp));
+        for unix_timestamp in [0, 1_500, 3_000] {
+            assert_eq!(
+                reserve_data.loan_to_value_ratio_bps(unix_timestamp),
+                Ok(reserve.loan_to_value_ratio_bps(unix_timestamp))
+            );
+            assert_eq!(
+                reserve_data.liquidation_threshold_bps(unix_timestamp),
+                Ok(reserve.liquidation_threshold_bps(unix_timestamp))
+            );
+        }
+
+        assert_eq!(
+            reserve_data.config(ReserveExtensionData::load(&data).unwrap()),
+            Ok(reserve.config)
+        );
+
+        // a refresh written in place is read back as a full pack
+        let mut refreshed = reserve.clone();
+        refreshed.liquidity.market_price = Decimal::from(4u64);
+        refreshed.accrue_interest(2, 1_010).unwrap();
+        refreshed.last_update.update(2, 1_010);
+        assert_ne!(
+            refreshed.adaptive_rate_multiplier,
+            reserve.adaptive_rate_multiplier
+        );
+        let (reserve_data, extension) = ReserveData::load_mut_with_extension(&mut data).unwrap();
+        let mut liquidity = reserve_data.liquidity();
+        liquidity.market_price = Decimal::from(4u64);
+        reserve_data.set_liquidity(&liquidity);
+        reserve_data
+            .accrue_interest(extension, &reserve.config, 2, 1_010)
+            .unwrap();
+        reserve_data.set_last_update(&refreshed.last_update);
+        let mut packed = [0; Reserve::LEN];
+        Reserve::pack(refreshed.clone(), &mut packed).unwrap();
+        assert_eq!(data, packed);
+
+        let extension = ReserveExtensionData::load(&data).unwrap().unwrap();
+        assert_eq!(
+            extension.supply_reward_index(),
+            Ok(refreshed.supply_reward_index())
+        );
+        assert_eq!(
+            extension.borrow_reward_index(),
+            Ok(refreshed.borrow_reward_index())
+        );
+
+        // deposits and staleness are written in place too
+        let reserve_data = ReserveData::load_mut(&mut data).unwrap();
+        assert_eq!(
+            reserve_data.deposit_liquidity(1_000),
+            Err(LendingError::DepositLimitExceeded.into())
+        );
+        let collateral_amount = reserve_data.deposit_liquidity(100).unwrap();
+        reserve_data.mark_stale();
+        assert_eq!(refreshed.deposit_liquidity(100).unwrap(), collateral_amount);
+        refreshed.last_update.mark_stale();
+        assert_eq!(Reserve::unpack(&data).unwrap(), refreshed);
+
+        // reserves stored with the base layout have no extension
+        let mut data = vec![0; Reserve::layout_len(RESERVE_VERSION_BPS_CONFIG)];
+        Reserve::pack(
+            Reserve {
+                version: RESERVE_VERSION_BPS_CONFIG,
+                reward_emissions: None,
+                ..reserve.clone()
+            },
+            &mut data,
+        )
+        .unwrap();
+        assert!(ReserveExtensionData::load(&data).unwrap().is_none());
+        let reserve_data = ReserveData::load(&data).unwrap();
+        assert_eq!(reserve_data.config(None), Ok(reserve.config));
+        assert_eq!(
+            reserve_data.current_borrow_rate(&reserve.config),
+            reserve.current_borrow_rate()
+        );
+        assert_eq!(
+            reserve_data.calculate_borrow(&reserve.config, 100, Decimal::from(1_000u64)),
+            reserve.calculate_borrow(100, Decimal::from(1_000u64))
+        );
+    }
 }
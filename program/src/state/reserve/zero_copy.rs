@@ -0,0 +1,624 @@
+use {
+    super::{
+        accrue_interest, calculate_borrow, calculate_liquidation, calculate_repay,
+        current_borrow_rate, deposit_liquidity,
+        pack::{
+            unpack_config_extension, unpack_percent_fields, RESERVE_BASE_LEN,
+            RESERVE_CONFIG_EXTENSION_LEN, RESERVE_EXTENSION_LEN,
+        },
+        CalculateBorrowResult, CalculateLiquidationResult, CalculateRepayResult,
+        CollateralExchangeRate, CollateralRamp, FixedRateConfig, Reserve, ReserveCollateral,
+        ReserveConfig, ReserveFees, ReserveLiquidity, ReserveStatus, MAX_RATE_CURVE_POINTS,
+        RESERVE_VERSION_BPS_CONFIG, RESERVE_VERSION_EXTENDED_CONFIG,
+        RESERVE_VERSION_PERCENT_CONFIG,
+    },
+    crate::{
+        math::{Decimal, Rate},
+        state::{
+            last_update::LastUpdate, pack_bool, pack_decimal, unpack_bool, unpack_decimal,
+            Obligation, ObligationCollateral, ObligationLiquidity, ReserveRewardEmissions,
+            UNINITIALIZED_VERSION,
+        },
+    },
+    bytemuck::{Pod, Zeroable},
+    num_traits::FromPrimitive,
+    solana_program::{
+        clock::{Slot, UnixTimestamp},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+    std::mem::size_of,
+};
+
+/// Reserve account data read and written in place, laid out field by field as
+/// `Reserve::pack` writes the base layout shared by the basis point reserve
+/// versions. Fields are stored as little endian bytes, as the packed layout is
+/// not aligned.
+///
+/// Processors only touching a few fields of a reserve can borrow it with
+/// `ReserveData::load` or `ReserveData::load_mut` instead of unpacking and
+/// packing the whole reserve.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct ReserveData {
+    version: [u8; 1],
+    last_update_slot: [u8; 8],
+    last_update_stale: [u8; 1],
+    lending_market: [u8; PUBKEY_BYTES],
+    liquidity_mint_pubkey: [u8; PUBKEY_BYTES],
+    liquidity_mint_decimals: [u8; 1],
+    liquidity_supply_pubkey: [u8; PUBKEY_BYTES],
+    liquidity_fee_receiver: [u8; PUBKEY_BYTES],
+    liquidity_oracle_pubkey: [u8; PUBKEY_BYTES],
+    liquidity_available_amount: [u8; 8],
+    liquidity_borrowed_amount_wads: [u8; 16],
+    liquidity_cumulative_borrow_rate_wads: [u8; 16],
+    liquidity_market_price: [u8; 16],
+    collateral_mint_pubkey: [u8; PUBKEY_BYTES],
+    collateral_mint_total_supply: [u8; 8],
+    collateral_supply_pubkey: [u8; PUBKEY_BYTES],
+    config_optimal_utilization_rate: [u8; 1],
+    config_loan_to_value_ratio: [u8; 1],
+    config_liquidation_bonus: [u8; 1],
+    config_liquidation_threshold: [u8; 1],
+    config_min_borrow_rate: [u8; 1],
+    config_optimal_borrow_rate: [u8; 1],
+    config_max_borrow_rate: [u8; 1],
+    config_fees_borrow_fee_wad: [u8; 8],
+    config_fees_flash_loan_fee_wad: [u8; 8],
+    config_fees_host_fee_percentage: [u8; 1],
+    config_fees_protocol_liquidation_fee: [u8; 1],
+    collateral_fee_receiver: [u8; PUBKEY_BYTES],
+    config_rate_curve_len: [u8; 1],
+    config_rate_curve_points: [u8; 2 * MAX_RATE_CURVE_POINTS],
+    config_adaptive_target_utilization_rate: [u8; 1],
+    config_adaptive_adjustment_speed: [u8; 1],
+    adaptive_rate_multiplier: [u8; 16],
+    config_optimal_utilization_rate_bps: [u8; 2],
+    config_loan_to_value_ratio_bps: [u8; 2],
+    config_liquidation_bonus_bps: [u8; 2],
+    config_liquidation_threshold_bps: [u8; 2],
+    config_min_borrow_rate_bps: [u8; 4],
+    config_optimal_borrow_rate_bps: [u8; 4],
+    config_max_borrow_rate_bps: [u8; 4],
+    last_update_unix_timestamp: [u8; 8],
+    status: [u8; 1],
+    config_deposit_limit: [u8; 8],
+    config_borrow_limit: [u8; 8],
+    pending_config_flag: [u8; 1],
+    pending_config_activation_timestamp: [u8; 8],
+    pending_config: [u8; 77],
+    collateral_ramp_flag: [u8; 1],
+    collateral_ramp_initial_loan_to_value_ratio_bps: [u8; 2],
+    collateral_ramp_initial_liquidation_threshold_bps: [u8; 2],
+    collateral_ramp_start_timestamp: [u8; 8],
+    collateral_ramp_end_timestamp: [u8; 8],
+    config_fixed_rate_premium_bps: [u8; 4],
+    config_fixed_rate_max_term: [u8; 4],
+    pending_config_fixed_rate_premium_bps: [u8; 4],
+    pending_config_fixed_rate_max_term: [u8; 4],
+    collateral_ramp_target_loan_to_value_ratio_bps: [u8; 2],
+    collateral_ramp_target_liquidation_threshold_bps: [u8; 2],
+    _padding: [u8; 4],
+}
+
+// only byte arrays, so the layout has no padding and an alignment of one
+unsafe impl Zeroable for ReserveData {}
+unsafe impl Pod for ReserveData {}
+
+const _: () = assert!(size_of::<ReserveData>() == RESERVE_BASE_LEN);
+
+impl ReserveData {
+    /// Borrow reserve account data stored with a basis point layout
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        Self::check_layout(data)?;
+        Ok(bytemuck::from_bytes(&data[..RESERVE_BASE_LEN]))
+    }
+
+    /// Mutably borrow reserve account data stored with a basis point layout
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        Self::check_layout(data)?;
+        Ok(bytemuck::from_bytes_mut(&mut data[..RESERVE_BASE_LEN]))
+    }
+
+    /// Mutably borrow reserve account data together with its extension, or
+    /// `None` for the extension when the reserve is stored with the base
+    /// layout
+    pub fn load_mut_with_extension(
+        data: &mut [u8],
+    ) -> Result<(&mut Self, Option<&mut ReserveExtensionData>), ProgramError> {
+        Self::check_layout(data)?;
+        let (base, extension) = data.split_at_mut(RESERVE_BASE_LEN);
+        Ok((
+            bytemuck::from_bytes_mut(base),
+            (!extension.is_empty()).then(|| bytemuck::from_bytes_mut(extension)),
+        ))
+    }
+
+    fn check_layout(data: &[u8]) -> Result<(), ProgramError> {
+        let version = *data.first().ok_or(ProgramError::InvalidAccountData)?;
+        if data.len() != Reserve::layout_len(version) {
+            msg!("Reserve account data length does not match the reserve layout");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        match version {
+            RESERVE_VERSION_BPS_CONFIG | RESERVE_VERSION_EXTENDED_CONFIG => Ok(()),
+            UNINITIALIZED_VERSION => Err(ProgramError::UninitializedAccount),
+            RESERVE_VERSION_PERCENT_CONFIG => {
+                msg!("Reserve uses an outdated layout and must be migrated with MigrateAccount");
+                Err(ProgramError::InvalidAccountData)
+            }
+            _ => {
+                msg!("Reserve version does not match a basis point reserve layout");
+                Err(ProgramError::InvalidAccountData)
+            }
+        }
+    }
+
+    /// Version of the struct
+    pub fn version(&self) -> u8 {
+        self.version[0]
+    }
+
+    /// Last slot when the reserve was updated
+    pub fn last_update_slot(&self) -> u64 {
+        u64::from_le_bytes(self.last_update_slot)
+    }
+
+    /// Lending market address
+    pub fn lending_market(&self) -> Pubkey {
+        Pubkey::new_from_array(self.lending_market)
+    }
+
+    /// Operations allowed on the reserve
+    pub fn status(&self) -> Result<ReserveStatus, ProgramError> {
+        ReserveStatus::from_u8(self.status[0]).ok_or_else(|| {
+            msg!("Reserve status is invalid");
+            ProgramError::InvalidAccountData
+        })
+    }
+
+    /// Set the operations allowed on the reserve
+    pub fn set_status(&mut self, status: ReserveStatus) {
+        self.status = [status as u8];
+    }
+
+    /// Reserve liquidity mint address
+    pub fn liquidity_mint_pubkey(&self) -> Pubkey {
+        Pubkey::new_from_array(self.liquidity_mint_pubkey)
+    }
+
+    /// Reserve liquidity available
+    pub fn liquidity_available_amount(&self) -> u64 {
+        u64::from_le_bytes(self.liquidity_available_amount)
+    }
+
+    /// Reserve liquidity borrowed
+    pub fn liquidity_borrowed_amount_wads(&self) -> Decimal {
+        Decimal::from_scaled_val(u128::from_le_bytes(self.liquidity_borrowed_amount_wads))
+    }
+
+    /// Reserve liquidity market price in quote currency
+    pub fn liquidity_market_price(&self) -> Decimal {
+        Decimal::from_scaled_val(u128::from_le_bytes(self.liquidity_market_price))
+    }
+
+    /// Reserve collateral mint address
+    pub fn collateral_mint_pubkey(&self) -> Pubkey {
+        Pubkey::new_from_array(self.collateral_mint_pubkey)
+    }
+
+    /// Reserve collateral mint supply, used for exchange rate
+    pub fn collateral_mint_total_supply(&self) -> u64 {
+        u64::from_le_bytes(self.collateral_mint_total_supply)
+    }
+
+    /// Last update to collateral, liquidity, or their market values
+    pub fn last_update(&self) -> Result<LastUpdate, ProgramError> {
+        Ok(LastUpdate {
+            slot: self.last_update_slot(),
+            stale: unpack_bool(&self.last_update_stale)?,
+            unix_timestamp: i64::from_le_bytes(self.last_update_unix_timestamp),
+        })
+    }
+
+    /// Set the last update to collateral, liquidity, or their market values
+    pub fn set_last_update(&mut self, last_update: &LastUpdate) {
+        self.last_update_slot = last_update.slot.to_le_bytes();
+        pack_bool(last_update.stale, &mut self.last_update_stale);
+        self.last_update_unix_timestamp = last_update.unix_timestamp.to_le_bytes();
+    }
+
+    /// Mark the reserve stale, so it must be refreshed before its values are
+    /// used again
+    pub fn mark_stale(&mut self) {
+        pack_bool(true, &mut self.last_update_stale);
+    }
+
+    /// Reserve liquidity mint decimals
+    pub fn liquidity_mint_decimals(&self) -> u8 {
+        self.liquidity_mint_decimals[0]
+    }
+
+    /// Reserve liquidity supply address
+    pub fn liquidity_supply_pubkey(&self) -> Pubkey {
+        Pubkey::new_from_array(self.liquidity_supply_pubkey)
+    }
+
+    /// Reserve liquidity fee receiver address
+    pub fn liquidity_fee_receiver(&self) -> Pubkey {
+        Pubkey::new_from_array(self.liquidity_fee_receiver)
+    }
+
+    /// Reserve liquidity oracle account
+    pub fn liquidity_oracle_pubkey(&self) -> Pubkey {
+        Pubkey::new_from_array(self.liquidity_oracle_pubkey)
+    }
+
+    /// Reserve liquidity cumulative borrow rate
+    pub fn liquidity_cumulative_borrow_rate_wads(&self) -> Decimal {
+        Decimal::from_scaled_val(u128::from_le_bytes(
+            self.liquidity_cumulative_borrow_rate_wads,
+        ))
+    }
+
+    /// Reserve liquidity
+    pub fn liquidity(&self) -> ReserveLiquidity {
+        ReserveLiquidity {
+            mint_pubkey: self.liquidity_mint_pubkey(),
+            mint_decimals: self.liquidity_mint_decimals(),
+            supply_pubkey: self.liquidity_supply_pubkey(),
+            fee_receiver: self.liquidity_fee_receiver(),
+            oracle_pubkey: self.liquidity_oracle_pubkey(),
+            available_amount: self.liquidity_available_amount(),
+            borrowed_amount_wads: self.liquidity_borrowed_amount_wads(),
+            cumulative_borrow_rate_wads: self.liquidity_cumulative_borrow_rate_wads(),
+            market_price: self.liquidity_market_price(),
+        }
+    }
+
+    /// Set the amounts, cumulative borrow rate and market price of the
+    /// reserve liquidity
+    pub fn set_liquidity(&mut self, liquidity: &ReserveLiquidity) {
+        self.liquidity_available_amount = liquidity.available_amount.to_le_bytes();
+        pack_decimal(
+            liquidity.borrowed_amount_wads,
+            &mut self.liquidity_borrowed_amount_wads,
+        );
+        pack_decimal(
+            liquidity.cumulative_borrow_rate_wads,
+            &mut self.liquidity_cumulative_borrow_rate_wads,
+        );
+        pack_decimal(liquidity.market_price, &mut self.liquidity_market_price);
+    }
+
+    /// Reserve collateral supply address
+    pub fn collateral_supply_pubkey(&self) -> Pubkey {
+        Pubkey::new_from_array(self.collateral_supply_pubkey)
+    }
+
+    /// Reserve collateral fee receiver address
+    pub fn collateral_fee_receiver(&self) -> Pubkey {
+        Pubkey::new_from_array(self.collateral_fee_receiver)
+    }
+
+    /// Reserve collateral
+    pub fn collateral(&self) -> ReserveCollateral {
+        ReserveCollateral {
+            mint_pubkey: self.collateral_mint_pubkey(),
+            mint_total_supply: self.collateral_mint_total_supply(),
+            supply_pubkey: self.collateral_supply_pubkey(),
+            fee_receiver: self.collateral_fee_receiver(),
+        }
+    }
+
+    /// Set the reserve collateral mint supply
+    pub fn set_collateral(&mut self, collateral: &ReserveCollateral) {
+        self.collateral_mint_total_supply = collateral.mint_total_supply.to_le_bytes();
+    }
+
+    /// Collateral exchange rate
+    pub fn collateral_exchange_rate(&self) -> Result<CollateralExchangeRate, ProgramError> {
+        let total_liquidity = self.liquidity().total_supply()?;
+        self.collateral().exchange_rate(total_liquidity)
+    }
+
+    /// Configured loan to value ratio, in basis points
+    pub fn config_loan_to_value_ratio_bps(&self) -> u16 {
+        u16::from_le_bytes(self.config_loan_to_value_ratio_bps)
+    }
+
+    /// Configured liquidation threshold, in basis points
+    pub fn config_liquidation_threshold_bps(&self) -> u16 {
+        u16::from_le_bytes(self.config_liquidation_threshold_bps)
+    }
+
+    /// Collateral ramp moving the loan to value ratio and liquidation
+    /// threshold towards their targets
+    pub fn collateral_ramp(&self) -> Result<Option<CollateralRamp>, ProgramError> {
+        if !unpack_bool(&self.collateral_ramp_flag)? {
+            return Ok(None);
+        }
+        Ok(Some(CollateralRamp {
+            initial_loan_to_value_ratio_bps: u16::from_le_bytes(
+                self.collateral_ramp_initial_loan_to_value_ratio_bps,
+            ),
+            initial_liquidation_threshold_bps: u16::from_le_bytes(
+                self.collateral_ramp_initial_liquidation_threshold_bps,
+            ),
+            target_loan_to_value_ratio_bps: u16::from_le_bytes(
+                self.collateral_ramp_target_loan_to_value_ratio_bps,
+            ),
+            target_liquidation_threshold_bps: u16::from_le_bytes(
+                self.collateral_ramp_target_liquidation_threshold_bps,
+            ),
+            start_timestamp: i64::from_le_bytes(self.collateral_ramp_start_timestamp),
+            end_timestamp: i64::from_le_bytes(self.collateral_ramp_end_timestamp),
+        }))
+    }
+
+    /// Loan to value ratio in effect at the given time, in basis points
+    pub fn loan_to_value_ratio_bps(
+        &self,
+        unix_timestamp: UnixTimestamp,
+    ) -> Result<u16, ProgramError> {
+        Ok(match self.collateral_ramp()? {
+            Some(ramp) => ramp.interpolate(
+                ramp.initial_loan_to_value_ratio_bps,
+                ramp.target_loan_to_value_ratio_bps,
+                unix_timestamp,
+            ),
+            None => self.config_loan_to_value_ratio_bps(),
+        })
+    }
+
+    /// Liquidation threshold in effect at the given time, in basis points
+    pub fn liquidation_threshold_bps(
+        &self,
+        unix_timestamp: UnixTimestamp,
+    ) -> Result<u16, ProgramError> {
+        Ok(match self.collateral_ramp()? {
+            Some(ramp) => ramp.interpolate(
+                ramp.initial_liquidation_threshold_bps,
+                ramp.target_liquidation_threshold_bps,
+                unix_timestamp,
+            ),
+            None => self.config_liquidation_threshold_bps(),
+        })
+    }
+
+    /// Configured limit on the total liquidity supply, where 0 means unlimited
+    pub fn config_deposit_limit(&self) -> u64 {
+        u64::from_le_bytes(self.config_deposit_limit)
+    }
+
+    /// Current reserve config, decoded from the fields stored as percentages
+    /// by the base layout or from the extension of the extended layout
+    pub fn config(
+        &self,
+        extension: Option<&ReserveExtensionData>,
+    ) -> Result<ReserveConfig, ProgramError> {
+        let mut config = ReserveConfig {
+            optimal_utilization_rate_bps: u16::from_le_bytes(
+                self.config_optimal_utilization_rate_bps,
+            ),
+            loan_to_value_ratio_bps: self.config_loan_to_value_ratio_bps(),
+            liquidation_bonus_bps: u16::from_le_bytes(self.config_liquidation_bonus_bps),
+            liquidation_threshold_bps: self.config_liquidation_threshold_bps(),
+            min_borrow_rate_bps: u32::from_le_bytes(self.config_min_borrow_rate_bps),
+            optimal_borrow_rate_bps: u32::from_le_bytes(self.config_optimal_borrow_rate_bps),
+            max_borrow_rate_bps: u32::from_le_bytes(self.config_max_borrow_rate_bps),
+            fees: ReserveFees {
+                borrow_fee_wad: u64::from_le_bytes(self.config_fees_borrow_fee_wad),
+                flash_loan_fee_wad: u64::from_le_bytes(self.config_fees_flash_loan_fee_wad),
+                ..ReserveFees::default()
+            },
+            deposit_limit: self.config_deposit_limit(),
+            borrow_limit: u64::from_le_bytes(self.config_borrow_limit),
+            fixed_rate: FixedRateConfig {
+                premium_bps: u32::from_le_bytes(self.config_fixed_rate_premium_bps),
+                max_term: u32::from_le_bytes(self.config_fixed_rate_max_term),
+            },
+            ..ReserveConfig::default()
+        };
+        match extension {
+            Some(extension) => unpack_config_extension(&extension.config_extension, &mut config)?,
+            None => unpack_percent_fields(
+                &self.config_fees_host_fee_percentage,
+                &self.config_fees_protocol_liquidation_fee,
+                &self.config_rate_curve_len,
+                &self.config_rate_curve_points,
+                &self.config_adaptive_target_utilization_rate,
+                &self.config_adaptive_adjustment_speed,
+                &mut config,
+            )?,
+        }
+        Ok(config)
+    }
+
+    /// Calculate the current borrow rate
+    pub fn current_borrow_rate(&self, config: &ReserveConfig) -> Result<Rate, ProgramError> {
+        current_borrow_rate(
+            &self.liquidity(),
+            config,
+            unpack_decimal(&self.adaptive_rate_multiplier),
+        )
+    }
+
+    /// Update borrow rate and accrue interest, writing the reward indices to
+    /// the extension
+    pub fn accrue_interest(
+        &mut self,
+        extension: Option<&mut ReserveExtensionData>,
+        config: &ReserveConfig,
+        current_slot: Slot,
+        current_unix_timestamp: UnixTimestamp,
+    ) -> ProgramResult {
+        let mut liquidity = self.liquidity();
+        let mut adaptive_rate_multiplier = unpack_decimal(&self.adaptive_rate_multiplier);
+        let mut reward_emissions = match &extension {
+            Some(extension) => extension.reward_emissions()?,
+            None => None,
+        };
+        accrue_interest(
+            &self.last_update()?,
+            &mut liquidity,
+            self.collateral_mint_total_supply(),
+            config,
+            &mut adaptive_rate_multiplier,
+            reward_emissions.as_mut(),
+            current_slot,
+            current_unix_timestamp,
+        )?;
+        self.set_liquidity(&liquidity);
+        pack_decimal(adaptive_rate_multiplier, &mut self.adaptive_rate_multiplier);
+        if let (Some(extension), Some(reward_emissions)) = (extension, &reward_emissions) {
+            extension.set_reward_indices(reward_emissions);
+        }
+        Ok(())
+    }
+
+    /// Borrow liquidity up to a maximum market value
+    pub fn calculate_borrow(
+        &self,
+        config: &ReserveConfig,
+        amount_to_borrow: u64,
+        max_borrow_value: Decimal,
+    ) -> Result<CalculateBorrowResult, ProgramError> {
+        calculate_borrow(
+            &self.liquidity(),
+            &config.fees,
+            amount_to_borrow,
+            max_borrow_value,
+        )
+    }
+
+    /// Calculate the liquidity to repay and the collateral to withdraw in a
+    /// liquidation of collateral of the reserve
+    pub fn calculate_liquidation(
+        &self,
+        config: &ReserveConfig,
+        amount_to_liquidate: u64,
+        obligation: &Obligation,
+        liquidity: &ObligationLiquidity,
+        collateral: &ObligationCollateral,
+        current_unix_timestamp: UnixTimestamp,
+    ) -> Result<CalculateLiquidationResult, ProgramError> {
+        calculate_liquidation(
+            config.liquidation_bonus_bps,
+            &config.fees,
+            amount_to_liquidate,
+            obligation,
+            liquidity,
+            collateral,
+            current_unix_timestamp,
+        )
+    }
+
+    /// Record deposited liquidity and return amount of collateral tokens to
+    /// mint
+    pub fn deposit_liquidity(&mut self, liquidity_amount: u64) -> Result<u64, ProgramError> {
+        let mut liquidity = self.liquidity();
+        let mut collateral = self.collateral();
+        let collateral_amount = deposit_liquidity(
+            &mut liquidity,
+            &mut collateral,
+            self.config_deposit_limit(),
+            liquidity_amount,
+        )?;
+        self.set_liquidity(&liquidity);
+        self.set_collateral(&collateral);
+        Ok(collateral_amount)
+    }
+
+    /// Calculate the liquidity to repay and the borrow to settle
+    pub fn calculate_repay(
+        &self,
+        amount_to_repay: u64,
+        borrowed_amount: Decimal,
+    ) -> Result<CalculateRepayResult, ProgramError> {
+        calculate_repay(amount_to_repay, borrowed_amount)
+    }
+}
+
+/// Extension following the base layout of reserve account data stored with
+/// the extended config layout, read and written in place
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct ReserveExtensionData {
+    config_extension: [u8; RESERVE_CONFIG_EXTENSION_LEN],
+    pending_config_extension: [u8; RESERVE_CONFIG_EXTENSION_LEN],
+    reward_emissions_flag: [u8; 1],
+    reward_supply_emission_per_second: [u8; 8],
+    reward_borrow_emission_per_second: [u8; 8],
+    reward_supply_index: [u8; 16],
+    reward_borrow_index: [u8; 16],
+    _padding: [u8; 15],
+}
+
+// only byte arrays, so the layout has no padding and an alignment of one
+unsafe impl Zeroable for ReserveExtensionData {}
+unsafe impl Pod for ReserveExtensionData {}
+
+const _: () = assert!(size_of::<ReserveExtensionData>() == RESERVE_EXTENSION_LEN);
+
+impl ReserveExtensionData {
+    /// Borrow the extension of reserve account data, or `None` when the
+    /// reserve is stored with the base layout
+    pub fn load(data: &[u8]) -> Result<Option<&Self>, ProgramError> {
+        ReserveData::check_layout(data)?;
+        Ok(data
+            .get(RESERVE_BASE_LEN..)
+            .filter(|extension| !extension.is_empty())
+            .map(bytemuck::from_bytes))
+    }
+
+    /// Mutably borrow the extension of reserve account data, or `None` when
+    /// the reserve is stored with the base layout
+    pub fn load_mut(data: &mut [u8]) -> Result<Option<&mut Self>, ProgramError> {
+        ReserveData::check_layout(data)?;
+        Ok(data
+            .get_mut(RESERVE_BASE_LEN..)
+            .filter(|extension| !extension.is_empty())
+            .map(bytemuck::from_bytes_mut))
+    }
+
+    /// Reward tokens emitted to the obligation positions of the reserve
+    pub fn reward_emissions(&self) -> Result<Option<ReserveRewardEmissions>, ProgramError> {
+        if !unpack_bool(&self.reward_emissions_flag)? {
+            return Ok(None);
+        }
+        Ok(Some(ReserveRewardEmissions {
+            supply_emission_per_second: u64::from_le_bytes(self.reward_supply_emission_per_second),
+            borrow_emission_per_second: u64::from_le_bytes(self.reward_borrow_emission_per_second),
+            supply_index: unpack_decimal(&self.reward_supply_index),
+            borrow_index: unpack_decimal(&self.reward_borrow_index),
+        }))
+    }
+
+    /// Set the reward indices accrued by the reserve reward emissions
+    pub fn set_reward_indices(&mut self, reward_emissions: &ReserveRewardEmissions) {
+        pack_decimal(reward_emissions.supply_index, &mut self.reward_supply_index);
+        pack_decimal(reward_emissions.borrow_index, &mut self.reward_borrow_index);
+    }
+
+    /// Reward index new deposits of reserve collateral start earning from,
+    /// the initial index when the reserve emits no rewards yet
+    pub fn supply_reward_index(&self) -> Result<Decimal, ProgramError> {
+        if !unpack_bool(&self.reward_emissions_flag)? {
+            return Ok(Decimal::one());
+        }
+        Ok(unpack_decimal(&self.reward_supply_index))
+    }
+
+    /// Reward index new borrows of reserve liquidity start earning from, the
+    /// initial index when the reserve emits no rewards yet
+    pub fn borrow_reward_index(&self) -> Result<Decimal, ProgramError> {
+        if !unpack_bool(&self.reward_emissions_flag)? {
+            return Ok(Decimal::one());
+        }
+        Ok(unpack_decimal(&self.reward_borrow_index))
+    }
+}
@@ -0,0 +1,219 @@
+use {
+    super::{Migrate, PROGRAM_VERSION, UNINITIALIZED_VERSION},
+    crate::math::{Decimal, TryAdd, TryDiv, TryMul},
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    solana_program::{
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+};
+
+/// Initialize reserve rewards
+pub struct InitReserveRewardsParams {
+    /// Lending market address
+    pub lending_market: Pubkey,
+    /// Reserve the rewards are emitted for
+    pub reserve: Pubkey,
+    /// Mint of the reward tokens
+    pub reward_mint: Pubkey,
+    /// Token account holding the reward tokens to emit
+    pub reward_supply: Pubkey,
+}
+
+/// Reward tokens emitted to the depositors and borrowers of a reserve
+///
+/// Holds the reward mint and supply, the emissions are tracked by the reserve
+/// in [ReserveRewardEmissions].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReserveRewards {
+    /// Version of the struct
+    pub version: u8,
+    /// Lending market address
+    pub lending_market: Pubkey,
+    /// Reserve the rewards are emitted for
+    pub reserve: Pubkey,
+    /// Mint of the reward tokens
+    pub reward_mint: Pubkey,
+    /// Token account holding the reward tokens to emit, owned by the lending
+    /// market authority
+    pub reward_supply: Pubkey,
+}
+
+impl Sealed for ReserveRewards {}
+impl IsInitialized for ReserveRewards {
+    fn is_initialized(&self) -> bool {
+        self.version != UNINITIALIZED_VERSION
+    }
+}
+
+impl ReserveRewards {
+    /// Create new reserve rewards
+    pub fn new(params: InitReserveRewardsParams) -> Self {
+        let mut reserve_rewards = Self::default();
+        Self::init(&mut reserve_rewards, params);
+        reserve_rewards
+    }
+
+    /// Initialize reserve rewards
+    pub fn init(&mut self, params: InitReserveRewardsParams) {
+        self.version = PROGRAM_VERSION;
+        self.lending_market = params.lending_market;
+        self.reserve = params.reserve;
+        self.reward_mint = params.reward_mint;
+        self.reward_supply = params.reward_supply;
+    }
+}
+
+/// Reward tokens emitted per second by a reserve, split between obligation
+/// positions by index like interest is with the cumulative borrow rate
+///
+/// The supply index is the amount of reward tokens emitted per collateral
+/// token, and the borrow index the amount emitted per borrowed liquidity
+/// token. Indices start at one, positions settle the rewards earned since
+/// the index they last recorded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReserveRewardEmissions {
+    /// Reward tokens emitted per second to reserve collateral deposited in
+    /// obligations
+    pub supply_emission_per_second: u64,
+    /// Reward tokens emitted per second to reserve liquidity borrowed by
+    /// obligations
+    pub borrow_emission_per_second: u64,
+    /// Reward tokens emitted per collateral token, plus one
+    pub supply_index: Decimal,
+    /// Reward tokens emitted per borrowed liquidity token, plus one
+    pub borrow_index: Decimal,
+}
+
+impl ReserveRewardEmissions {
+    /// Start emitting rewards from the initial indices
+    pub fn new(supply_emission_per_second: u64, borrow_emission_per_second: u64) -> Self {
+        Self {
+            supply_emission_per_second,
+            borrow_emission_per_second,
+            supply_index: Decimal::one(),
+            borrow_index: Decimal::one(),
+        }
+    }
+
+    /// Emit rewards over the seconds elapsed since the last reserve update
+    ///
+    /// Rewards are split by the reserve collateral supply and borrows at the
+    /// time of the update, and are not emitted while either side is empty.
+    pub fn accrue(
+        &mut self,
+        collateral_supply: u64,
+        borrowed_amount_wads: Decimal,
+        seconds_elapsed: u64,
+    ) -> ProgramResult {
+        if collateral_supply > 0 {
+            self.supply_index = self.supply_index.try_add(
+                Decimal::from(self.supply_emission_per_second)
+                    .try_mul(seconds_elapsed)?
+                    .try_div(collateral_supply)?,
+            )?;
+        }
+        if borrowed_amount_wads > Decimal::zero() {
+            self.borrow_index = self.borrow_index.try_add(
+                Decimal::from(self.borrow_emission_per_second)
+                    .try_mul(seconds_elapsed)?
+                    .try_div(borrowed_amount_wads)?,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+const RESERVE_REWARDS_LEN: usize = 193; // 1 + 32 + 32 + 32 + 32 + 64
+impl Migrate for ReserveRewards {
+    /// Reserve rewards have a single layout
+    fn is_outdated(_version: u8) -> bool {
+        false
+    }
+}
+
+impl Pack for ReserveRewards {
+    const LEN: usize = RESERVE_REWARDS_LEN;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, RESERVE_REWARDS_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (version, lending_market, reserve, reward_mint, reward_supply, _padding) = mut_array_refs![
+            output,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            64
+        ];
+        *version = self.version.to_le_bytes();
+        lending_market.copy_from_slice(self.lending_market.as_ref());
+        reserve.copy_from_slice(self.reserve.as_ref());
+        reward_mint.copy_from_slice(self.reward_mint.as_ref());
+        reward_supply.copy_from_slice(self.reward_supply.as_ref());
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, RESERVE_REWARDS_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (version, lending_market, reserve, reward_mint, reward_supply, _padding) = array_refs![
+            input,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            64
+        ];
+        let version = u8::from_le_bytes(*version);
+        match version {
+            UNINITIALIZED_VERSION | PROGRAM_VERSION => {}
+            _ => {
+                msg!("Reserve rewards version does not match lending program version");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        Ok(Self {
+            version,
+            lending_market: Pubkey::new_from_array(*lending_market),
+            reserve: Pubkey::new_from_array(*reserve),
+            reward_mint: Pubkey::new_from_array(*reward_mint),
+            reward_supply: Pubkey::new_from_array(*reward_supply),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pack_reserve_rewards() {
+        let reserve_rewards = ReserveRewards::new(InitReserveRewardsParams {
+            lending_market: Pubkey::new_unique(),
+            reserve: Pubkey::new_unique(),
+            reward_mint: Pubkey::new_unique(),
+            reward_supply: Pubkey::new_unique(),
+        });
+        let mut data = [0; ReserveRewards::LEN];
+        ReserveRewards::pack(reserve_rewards.clone(), &mut data).unwrap();
+        assert_eq!(ReserveRewards::unpack(&data).unwrap(), reserve_rewards);
+    }
+
+    #[test]
+    fn reserve_reward_emissions_accrue() {
+        let mut emissions = ReserveRewardEmissions::new(100, 50);
+        emissions.accrue(1_000, Decimal::from(500u64), 10).unwrap();
+        assert_eq!(emissions.supply_index, Decimal::from(2u64));
+        assert_eq!(emissions.borrow_index, Decimal::from(2u64));
+
+        // nothing is emitted to an empty side
+        emissions.accrue(0, Decimal::zero(), 10).unwrap();
+        assert_eq!(emissions.supply_index, Decimal::from(2u64));
+        assert_eq!(emissions.borrow_index, Decimal::from(2u64));
+    }
+}
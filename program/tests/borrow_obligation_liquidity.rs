@@ -18,7 +18,8 @@ use {
         instruction::builder::{borrow_obligation_liquidity, refresh_obligation},
         math::Decimal,
         processor::process_instruction,
-        state::{FeeCalculation, INITIAL_COLLATERAL_RATIO},
+        return_data::LendingReturnData,
+        state::{CalculateBorrowResult, FeeCalculation, INITIAL_COLLATERAL_RATIO},
     },
 };
 
@@ -45,7 +46,7 @@ async fn test_borrow_usdc_fixed_amount() {
     let lending_market: helpers::TestLendingMarket = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
@@ -115,7 +116,11 @@ async fn test_borrow_usdc_fixed_amount() {
     );
 
     transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
-    assert!(banks_client.process_transaction(transaction).await.is_ok());
+    let result = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
 
     let usdc_reserve = usdc_test_reserve.get_state(&banks_client).await;
     let obligation = test_obligation.get_state(&banks_client).await;
@@ -131,6 +136,18 @@ async fn test_borrow_usdc_fixed_amount() {
     assert_eq!(total_fee, FEE_AMOUNT);
     assert_eq!(host_fee, HOST_FEE_AMOUNT);
 
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, spl_token_lending::id());
+    assert_eq!(
+        CalculateBorrowResult::unpack(&return_data.data).unwrap(),
+        CalculateBorrowResult {
+            borrow_amount: USDC_TOTAL_BORROW_FRACTIONAL.into(),
+            receive_amount: USDC_BORROW_AMOUNT_FRACTIONAL,
+            borrow_fee: FEE_AMOUNT,
+            host_fee: HOST_FEE_AMOUNT,
+        }
+    );
+
     let borrow_amount =
         get_token_balance(&banks_client, usdc_test_reserve.user_liquidity_pubkey).await;
     assert_eq!(borrow_amount, USDC_BORROW_AMOUNT_FRACTIONAL);
@@ -183,7 +200,7 @@ async fn test_borrow_sol_max_amount() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
@@ -312,7 +329,7 @@ async fn test_borrow_too_large() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
@@ -416,7 +433,7 @@ async fn test_borrow_max_receive_minimum() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let usdc_mint = add_usdc_mint(&mut test);
     let usdc_oracle = add_usdc_oracle(&mut test);
@@ -558,7 +575,7 @@ async fn test_borrow_max_receive_less_than_slippage() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let usdc_mint = add_usdc_mint(&mut test);
     let usdc_oracle = add_usdc_oracle(&mut test);
@@ -664,7 +681,7 @@ async fn test_borrow_less_than_max_with_slippage() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
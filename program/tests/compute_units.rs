@@ -0,0 +1,396 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        hash::Hash,
+        instruction::Instruction,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    spl_token::instruction::approve,
+    spl_token_lending::{
+        instruction::builder::{
+            borrow_obligation_liquidity, deposit_obligation_collateral, deposit_reserve_liquidity,
+            liquidate_obligation, refresh_obligation, refresh_reserve, repay_obligation_liquidity,
+        },
+        processor::process_instruction,
+        state::INITIAL_COLLATERAL_RATIO,
+    },
+};
+
+// budgets of the benchmarked instructions, matching the compute unit limits
+// their own tests run with
+const REFRESH_RESERVE: (&str, u64) = ("refresh_reserve", 45_000);
+const REFRESH_OBLIGATION: (&str, u64) = ("refresh_obligation", 45_000);
+const DEPOSIT_RESERVE_LIQUIDITY: (&str, u64) = ("deposit_reserve_liquidity", 50_000);
+const DEPOSIT_OBLIGATION_COLLATERAL: (&str, u64) = ("deposit_obligation_collateral", 38_000);
+const BORROW_OBLIGATION_LIQUIDITY: (&str, u64) = ("borrow_obligation_liquidity", 60_000);
+const REPAY_OBLIGATION_LIQUIDITY: (&str, u64) = ("repay_obligation_liquidity", 36_000);
+const LIQUIDATE_OBLIGATION: (&str, u64) = ("liquidate_obligation", 68_000);
+
+/// Process a transaction holding a single benchmarked instruction, report
+/// the compute units it consumed and check they stay within its budget
+async fn process_metered(
+    banks_client: &BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    (name, max_compute_units): (&str, u64),
+    instruction: Instruction,
+    signers: &[&Keypair],
+) {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&[payer], signers].concat(), recent_blockhash);
+    let result = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result.result, Ok(()), "{} failed", name);
+    let compute_units = result.metadata.unwrap().compute_units_consumed;
+    println!("{}: {} compute units", name, compute_units);
+    assert!(
+        compute_units <= max_compute_units,
+        "{} consumed {} compute units, more than its budget of {}",
+        name,
+        compute_units,
+        max_compute_units
+    );
+}
+
+async fn process(
+    banks_client: &BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    transaction.sign(&[&[payer], signers].concat(), recent_blockhash);
+    let result = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result.result, Ok(()));
+}
+
+/// Compute units of the hot path instructions, each processed alone in a
+/// transaction, each within its budget. The units are only metered when the
+/// program is loaded from the SBF build, so compare runs of `cargo test-sbf
+/// --test compute_units -- --nocapture` before and after a change.
+#[tokio::test]
+async fn test_hot_path_compute_units() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    // 100 SOL collateral per obligation
+    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+    // 100 SOL * 50% LTV -> 50 SOL * 20 USDC -> 1000 USDC allowed borrow
+    const USDC_HEALTHY_BORROW_FRACTIONAL: u64 = 100 * FRACTIONAL_TO_USDC;
+    // 100 SOL * 80% liquidation threshold -> 1600 USDC unhealthy borrow
+    const USDC_UNHEALTHY_BORROW_FRACTIONAL: u64 = 1_600 * FRACTIONAL_TO_USDC;
+    const USDC_AMOUNT_FRACTIONAL: u64 = 10 * FRACTIONAL_TO_USDC;
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let mut reserve_config = TEST_RESERVE_CONFIG;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
+    reserve_config.liquidation_threshold_bps = 8_000;
+    reserve_config.liquidation_bonus_bps = 1_000;
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: 4 * SOL_DEPOSIT_AMOUNT_LAMPORTS,
+            liquidity_amount: 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: reserve_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            borrow_amount: USDC_HEALTHY_BORROW_FRACTIONAL + USDC_UNHEALTHY_BORROW_FRACTIONAL,
+            user_liquidity_amount: USDC_UNHEALTHY_BORROW_FRACTIONAL,
+            liquidity_amount: 2 * USDC_UNHEALTHY_BORROW_FRACTIONAL,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            config: reserve_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let healthy_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&sol_test_reserve, SOL_DEPOSIT_AMOUNT_LAMPORTS)],
+            borrows: &[(&usdc_test_reserve, USDC_HEALTHY_BORROW_FRACTIONAL)],
+            ..AddObligationArgs::default()
+        },
+    );
+    let unhealthy_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&sol_test_reserve, SOL_DEPOSIT_AMOUNT_LAMPORTS)],
+            borrows: &[(&usdc_test_reserve, USDC_UNHEALTHY_BORROW_FRACTIONAL)],
+            ..AddObligationArgs::default()
+        },
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let owner = &[&user_accounts_owner];
+    let authority = &[&user_transfer_authority];
+
+    // every transaction lands in the same slot, so transactions repeating an
+    // instruction are told apart by the instructions next to it
+    process_metered(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        REFRESH_RESERVE,
+        refresh_reserve(
+            spl_token_lending::id(),
+            usdc_test_reserve.pubkey,
+            usdc_oracle.price_pubkey,
+        ),
+        &[],
+    )
+    .await;
+    process(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        &[
+            refresh_reserve(
+                spl_token_lending::id(),
+                sol_test_reserve.pubkey,
+                sol_oracle.price_pubkey,
+            ),
+            approve(
+                &spl_token::id(),
+                &usdc_test_reserve.user_liquidity_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                USDC_UNHEALTHY_BORROW_FRACTIONAL,
+            )
+            .unwrap(),
+            approve(
+                &spl_token::id(),
+                &sol_test_reserve.user_collateral_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                SOL_DEPOSIT_AMOUNT_LAMPORTS,
+            )
+            .unwrap(),
+        ],
+        owner,
+    )
+    .await;
+
+    process_metered(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        REFRESH_OBLIGATION,
+        refresh_obligation(
+            spl_token_lending::id(),
+            healthy_obligation.pubkey,
+            vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+        ),
+        &[],
+    )
+    .await;
+
+    process_metered(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        DEPOSIT_RESERVE_LIQUIDITY,
+        deposit_reserve_liquidity(
+            spl_token_lending::id(),
+            USDC_AMOUNT_FRACTIONAL,
+            usdc_test_reserve.user_liquidity_pubkey,
+            usdc_test_reserve.user_collateral_pubkey,
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.liquidity_supply_pubkey,
+            usdc_test_reserve.collateral_mint_pubkey,
+            lending_market.pubkey,
+            user_transfer_authority.pubkey(),
+        ),
+        authority,
+    )
+    .await;
+
+    process_metered(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        DEPOSIT_OBLIGATION_COLLATERAL,
+        deposit_obligation_collateral(
+            spl_token_lending::id(),
+            SOL_DEPOSIT_AMOUNT_LAMPORTS,
+            sol_test_reserve.user_collateral_pubkey,
+            sol_test_reserve.collateral_supply_pubkey,
+            sol_test_reserve.pubkey,
+            healthy_obligation.pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+            user_transfer_authority.pubkey(),
+        ),
+        &[&user_accounts_owner, &user_transfer_authority],
+    )
+    .await;
+
+    process(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        &[
+            refresh_reserve(
+                spl_token_lending::id(),
+                usdc_test_reserve.pubkey,
+                usdc_oracle.price_pubkey,
+            ),
+            refresh_obligation(
+                spl_token_lending::id(),
+                healthy_obligation.pubkey,
+                vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+        ],
+        &[],
+    )
+    .await;
+    process_metered(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        BORROW_OBLIGATION_LIQUIDITY,
+        borrow_obligation_liquidity(
+            spl_token_lending::id(),
+            USDC_AMOUNT_FRACTIONAL,
+            None,
+            usdc_test_reserve.liquidity_supply_pubkey,
+            usdc_test_reserve.user_liquidity_pubkey,
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.liquidity_fee_receiver_pubkey,
+            healthy_obligation.pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+            None,
+        ),
+        owner,
+    )
+    .await;
+
+    process(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        &[
+            refresh_reserve(
+                spl_token_lending::id(),
+                sol_test_reserve.pubkey,
+                sol_oracle.price_pubkey,
+            ),
+            refresh_reserve(
+                spl_token_lending::id(),
+                usdc_test_reserve.pubkey,
+                usdc_oracle.price_pubkey,
+            ),
+            refresh_obligation(
+                spl_token_lending::id(),
+                healthy_obligation.pubkey,
+                vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+        ],
+        &[],
+    )
+    .await;
+    process_metered(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        REPAY_OBLIGATION_LIQUIDITY,
+        repay_obligation_liquidity(
+            spl_token_lending::id(),
+            USDC_AMOUNT_FRACTIONAL,
+            usdc_test_reserve.user_liquidity_pubkey,
+            usdc_test_reserve.liquidity_supply_pubkey,
+            usdc_test_reserve.pubkey,
+            healthy_obligation.pubkey,
+            lending_market.pubkey,
+            user_transfer_authority.pubkey(),
+        ),
+        authority,
+    )
+    .await;
+
+    process(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        &[
+            refresh_reserve(
+                spl_token_lending::id(),
+                usdc_test_reserve.pubkey,
+                usdc_oracle.price_pubkey,
+            ),
+            refresh_obligation(
+                spl_token_lending::id(),
+                unhealthy_obligation.pubkey,
+                vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+        ],
+        &[],
+    )
+    .await;
+    process_metered(
+        &banks_client,
+        &payer,
+        recent_blockhash,
+        LIQUIDATE_OBLIGATION,
+        liquidate_obligation(
+            spl_token_lending::id(),
+            USDC_AMOUNT_FRACTIONAL,
+            usdc_test_reserve.user_liquidity_pubkey,
+            sol_test_reserve.user_collateral_pubkey,
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.liquidity_supply_pubkey,
+            sol_test_reserve.pubkey,
+            sol_test_reserve.collateral_supply_pubkey,
+            sol_test_reserve.collateral_fee_receiver_pubkey,
+            unhealthy_obligation.pubkey,
+            lending_market.pubkey,
+            user_transfer_authority.pubkey(),
+            None,
+        ),
+        authority,
+    )
+    .await;
+}
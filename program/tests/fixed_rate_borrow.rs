@@ -0,0 +1,375 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        account::Account,
+        clock::Clock,
+        instruction::{Instruction, InstructionError},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{
+            borrow_obligation_liquidity_fixed_rate, liquidate_obligation, refresh_obligation,
+            refresh_reserve, resize_obligation,
+        },
+        math::{Decimal, Rate},
+        processor::process_instruction,
+        state::{
+            FixedRateConfig, InitObligationParams, Obligation, ObligationCollateral, ReserveConfig,
+            INITIAL_COLLATERAL_RATIO, OBLIGATION_VERSION_REWARDS,
+        },
+    },
+};
+
+const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
+const USDC_RESERVE_LIQUIDITY_FRACTIONAL: u64 = 2_000 * FRACTIONAL_TO_USDC;
+const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = 100 * FRACTIONAL_TO_USDC;
+const FIXED_RATE_PREMIUM_BPS: u32 = 1_000;
+const FIXED_RATE_TERM: u32 = 24 * 60 * 60;
+
+struct TestSetup {
+    test: ProgramTest,
+    user_accounts_owner: Keypair,
+    lending_market: TestLendingMarket,
+    sol_test_reserve: TestReserve,
+    usdc_test_reserve: TestReserve,
+    obligation_pubkey: Pubkey,
+}
+
+fn setup(fixed_rate: FixedRateConfig, resizable: bool) -> TestSetup {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: SOL_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: USDC_RESERVE_LIQUIDITY_FRACTIONAL,
+            user_liquidity_amount: 2 * USDC_BORROW_AMOUNT_FRACTIONAL,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            config: ReserveConfig {
+                fixed_rate,
+                ..TEST_RESERVE_CONFIG
+            },
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let mut collateral = ObligationCollateral::new(sol_test_reserve.pubkey);
+    collateral.deposited_amount = SOL_DEPOSIT_AMOUNT_LAMPORTS;
+    let mut obligation = Obligation::new(InitObligationParams {
+        current_slot: 1,
+        lending_market: lending_market.pubkey,
+        owner: user_accounts_owner.pubkey(),
+        deposits: vec![collateral],
+        borrows: vec![],
+    });
+    if resizable {
+        obligation.resize(1, 1).unwrap();
+    }
+    let obligation_pubkey = Pubkey::new_unique();
+    let mut account = Account::new(
+        u32::MAX as u64,
+        obligation.packed_len(),
+        &spl_token_lending::id(),
+    );
+    Obligation::pack(obligation, &mut account.data).unwrap();
+    test.add_account(obligation_pubkey, account);
+
+    TestSetup {
+        test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        obligation_pubkey,
+    }
+}
+
+fn refresh(
+    sol_test_reserve: &TestReserve,
+    usdc_test_reserve: &TestReserve,
+    obligation_pubkey: Pubkey,
+    obligation_reserves: Vec<Pubkey>,
+) -> Vec<Instruction> {
+    vec![
+        refresh_reserve(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+            sol_test_reserve.liquidity_oracle_pubkey,
+        ),
+        refresh_reserve(
+            spl_token_lending::id(),
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.liquidity_oracle_pubkey,
+        ),
+        refresh_obligation(
+            spl_token_lending::id(),
+            obligation_pubkey,
+            obligation_reserves,
+        ),
+    ]
+}
+
+fn borrow_fixed_rate(setup: &TestSetup, max_borrow_rate_bps: u32) -> Vec<Instruction> {
+    let mut instructions = refresh(
+        &setup.sol_test_reserve,
+        &setup.usdc_test_reserve,
+        setup.obligation_pubkey,
+        vec![setup.sol_test_reserve.pubkey],
+    );
+    instructions.push(borrow_obligation_liquidity_fixed_rate(
+        spl_token_lending::id(),
+        USDC_BORROW_AMOUNT_FRACTIONAL,
+        None,
+        FIXED_RATE_TERM,
+        max_borrow_rate_bps,
+        setup.usdc_test_reserve.liquidity_supply_pubkey,
+        setup.usdc_test_reserve.user_liquidity_pubkey,
+        setup.usdc_test_reserve.pubkey,
+        setup.usdc_test_reserve.liquidity_fee_receiver_pubkey,
+        setup.obligation_pubkey,
+        setup.lending_market.pubkey,
+        setup.user_accounts_owner.pubkey(),
+        None,
+    ));
+    instructions
+}
+
+#[tokio::test]
+async fn test_success() {
+    let setup = setup(
+        FixedRateConfig {
+            premium_bps: FIXED_RATE_PREMIUM_BPS,
+            max_term: FIXED_RATE_TERM,
+        },
+        true,
+    );
+    let borrow = borrow_fixed_rate(&setup, FIXED_RATE_PREMIUM_BPS);
+    let TestSetup {
+        test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        obligation_pubkey,
+    } = setup;
+
+    let mut context = test.start_with_context().await;
+    let banks_client = context.banks_client.clone();
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+    let mut clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(&borrow, Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    // the unused reserve borrows at its min rate plus the premium
+    let obligation = get_state::<Obligation>(obligation_pubkey, &banks_client)
+        .await
+        .unwrap();
+    let liquidity = &obligation.borrows[0];
+    let fixed_rate = liquidity.fixed_rate.unwrap();
+    assert_eq!(
+        fixed_rate.borrow_rate,
+        Rate::from_bps(FIXED_RATE_PREMIUM_BPS)
+    );
+    assert_eq!(
+        fixed_rate.maturity_timestamp,
+        clock.unix_timestamp + FIXED_RATE_TERM as i64
+    );
+    assert_eq!(
+        fixed_rate.reserve_borrowed_amount_wads,
+        liquidity.borrowed_amount_wads
+    );
+
+    let liquidate = |liquidity_amount: u64| {
+        let mut instructions = refresh(
+            &sol_test_reserve,
+            &usdc_test_reserve,
+            obligation_pubkey,
+            vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+        );
+        instructions.push(liquidate_obligation(
+            spl_token_lending::id(),
+            liquidity_amount,
+            usdc_test_reserve.user_liquidity_pubkey,
+            sol_test_reserve.user_collateral_pubkey,
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.liquidity_supply_pubkey,
+            sol_test_reserve.pubkey,
+            sol_test_reserve.collateral_supply_pubkey,
+            sol_test_reserve.collateral_fee_receiver_pubkey,
+            obligation_pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+            None,
+        ));
+        instructions
+    };
+
+    // healthy obligations cannot be liquidated before maturity
+    let mut transaction = Transaction::new_with_payer(&liquidate(u64::MAX), Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            3,
+            InstructionError::Custom(LendingError::ObligationHealthy as u32)
+        )
+    );
+
+    // interest keeps accruing at the fixed rate past maturity
+    clock.unix_timestamp += 2 * FIXED_RATE_TERM as i64;
+    context.set_sysvar(&clock);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &refresh(
+            &sol_test_reserve,
+            &usdc_test_reserve,
+            obligation_pubkey,
+            vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+        ),
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = get_state::<Obligation>(obligation_pubkey, &banks_client)
+        .await
+        .unwrap();
+    let liquidity = &obligation.borrows[0];
+    assert!(
+        liquidity.borrowed_amount_wads > liquidity.fixed_rate.unwrap().reserve_borrowed_amount_wads
+    );
+    assert!(obligation.borrowed_value < obligation.unhealthy_borrow_value);
+
+    // matured borrows can be liquidated in full while the obligation is healthy
+    let mut transaction = Transaction::new_with_payer(&liquidate(u64::MAX), Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = get_state::<Obligation>(obligation_pubkey, &banks_client)
+        .await
+        .unwrap();
+    assert!(obligation.borrows.is_empty());
+    let usdc_reserve = usdc_test_reserve.get_state(&banks_client).await;
+    assert!(usdc_reserve.liquidity.borrowed_amount_wads < Decimal::one());
+}
+
+#[tokio::test]
+async fn test_invalid_fixed_rate_borrow() {
+    let fixed_rate = FixedRateConfig {
+        premium_bps: FIXED_RATE_PREMIUM_BPS,
+        max_term: FIXED_RATE_TERM,
+    };
+    for (setup, max_borrow_rate_bps, error) in [
+        (
+            setup(FixedRateConfig::DISABLED, true),
+            FIXED_RATE_PREMIUM_BPS,
+            LendingError::InvalidFixedRateBorrow,
+        ),
+        (
+            setup(fixed_rate, false),
+            FIXED_RATE_PREMIUM_BPS,
+            LendingError::InvalidFixedRateBorrow,
+        ),
+        (
+            setup(fixed_rate, true),
+            FIXED_RATE_PREMIUM_BPS - 1,
+            LendingError::ExceededSlippage,
+        ),
+    ] {
+        let borrow = borrow_fixed_rate(&setup, max_borrow_rate_bps);
+        let (banks_client, payer, recent_blockhash) = setup.test.start().await;
+        let mut transaction = Transaction::new_with_payer(&borrow, Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &setup.user_accounts_owner], recent_blockhash);
+        assert_eq!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(3, InstructionError::Custom(error as u32))
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_resized_legacy_obligation() {
+    let setup = setup(
+        FixedRateConfig {
+            premium_bps: FIXED_RATE_PREMIUM_BPS,
+            max_term: FIXED_RATE_TERM,
+        },
+        false,
+    );
+
+    // fixed size obligations move to the latest layout before borrowing at a
+    // fixed rate
+    let mut instructions = vec![resize_obligation(
+        spl_token_lending::id(),
+        1,
+        1,
+        setup.obligation_pubkey,
+        setup.lending_market.pubkey,
+        setup.user_accounts_owner.pubkey(),
+    )];
+    instructions.extend(borrow_fixed_rate(&setup, FIXED_RATE_PREMIUM_BPS));
+
+    let (banks_client, payer, recent_blockhash) = setup.test.start().await;
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &setup.user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = get_state::<Obligation>(setup.obligation_pubkey, &banks_client)
+        .await
+        .unwrap();
+    assert_eq!(obligation.version, OBLIGATION_VERSION_REWARDS);
+    assert_eq!(
+        obligation.borrows[0].fixed_rate.unwrap().borrow_rate,
+        Rate::from_bps(FIXED_RATE_PREMIUM_BPS)
+    );
+}
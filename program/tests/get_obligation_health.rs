@@ -0,0 +1,170 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    spl_token_lending::{
+        instruction::builder::{get_obligation_health, refresh_obligation, refresh_reserve},
+        math::{Decimal, TryDiv},
+        processor::process_instruction,
+        return_data::{LendingReturnData, ObligationHealthResult},
+        state::INITIAL_COLLATERAL_RATIO,
+    },
+};
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    // limit to track compute unit increase
+    test.set_compute_max_units(45_000);
+
+    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+    const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = 1_000 * FRACTIONAL_TO_USDC;
+    const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
+    const USDC_RESERVE_LIQUIDITY_FRACTIONAL: u64 = 2 * USDC_BORROW_AMOUNT_FRACTIONAL;
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: SOL_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: TEST_RESERVE_CONFIG,
+            slots_elapsed: 1, // elapsed from 1; clock.slot = 2
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            borrow_amount: USDC_BORROW_AMOUNT_FRACTIONAL,
+            liquidity_amount: USDC_RESERVE_LIQUIDITY_FRACTIONAL,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            config: TEST_RESERVE_CONFIG,
+            slots_elapsed: 1, // elapsed from 1; clock.slot = 2
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&sol_test_reserve, SOL_DEPOSIT_AMOUNT_LAMPORTS)],
+            borrows: &[(&usdc_test_reserve, USDC_BORROW_AMOUNT_FRACTIONAL)],
+            slots_elapsed: 1, // elapsed from 1; clock.slot = 2
+            ..AddObligationArgs::default()
+        },
+    );
+
+    let mut test_context = test.start_with_context().await;
+    test_context.warp_to_slot(3).unwrap(); // clock.slot = 3
+
+    let ProgramTestContext {
+        banks_client,
+        payer,
+        last_blockhash: recent_blockhash,
+        ..
+    } = test_context;
+
+    let refresh_reserves = [
+        refresh_reserve(
+            spl_token_lending::id(),
+            usdc_test_reserve.pubkey,
+            usdc_oracle.price_pubkey,
+        ),
+        refresh_reserve(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+            sol_oracle.price_pubkey,
+        ),
+    ];
+    let reserve_pubkeys = vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey];
+
+    // the health query leaves the obligation untouched
+    let obligation_before = test_obligation.get_state(&banks_client).await;
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_reserves[0].clone(),
+            refresh_reserves[1].clone(),
+            get_obligation_health(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                reserve_pubkeys.clone(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    let result = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, spl_token_lending::id());
+    let health = ObligationHealthResult::unpack(&return_data.data).unwrap();
+    assert_eq!(
+        test_obligation.get_state(&banks_client).await,
+        obligation_before
+    );
+
+    // and values it as a refresh in the same slot would
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_reserves[0].clone(),
+            refresh_reserves[1].clone(),
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                reserve_pubkeys,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert!(obligation.borrowed_value > Decimal::zero());
+    assert_eq!(
+        health,
+        ObligationHealthResult {
+            deposited_value: obligation.deposited_value,
+            borrowed_value: obligation.borrowed_value,
+            allowed_borrow_value: obligation.allowed_borrow_value,
+            unhealthy_borrow_value: obligation.unhealthy_borrow_value,
+            health_factor: obligation
+                .unhealthy_borrow_value
+                .try_div(obligation.borrowed_value)
+                .unwrap(),
+        }
+    );
+}
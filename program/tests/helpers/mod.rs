@@ -10,7 +10,7 @@ use {
         pubkey::Pubkey,
         signature::{read_keypair_file, Keypair},
         signer::Signer,
-        system_instruction::create_account,
+        system_instruction::{create_account, transfer},
         transaction::{Transaction, TransactionError},
     },
     spl_token::{
@@ -19,15 +19,20 @@ use {
     },
     spl_token_lending::{
         instruction::builder::{
-            deposit_reserve_liquidity, init_lending_market, init_obligation, init_reserve,
+            deposit_reserve_liquidity, find_reserve_address, init_lending_market, init_obligation,
+            init_reserve,
         },
         math::{Decimal, Rate, TryAdd, TryMul},
         pyth,
         state::{
+            find_reserve_account_address, AdaptiveRateConfig, FixedRateConfig,
             InitLendingMarketParams, InitObligationParams, InitReserveParams, LendingMarket,
             NewReserveCollateralParams, NewReserveLiquidityParams, Obligation,
-            ObligationCollateral, ObligationLiquidity, Reserve, ReserveCollateral, ReserveConfig,
-            ReserveFees, ReserveLiquidity, INITIAL_COLLATERAL_RATIO, PROGRAM_VERSION,
+            ObligationCollateral, ObligationLiquidity, RateCurve, Reserve, ReserveCollateral,
+            ReserveConfig, ReserveFees, ReserveLiquidity, INITIAL_COLLATERAL_RATIO,
+            PROGRAM_VERSION, RESERVE_COLLATERAL_FEE_RECEIVER_SEED, RESERVE_COLLATERAL_MINT_SEED,
+            RESERVE_COLLATERAL_SUPPLY_SEED, RESERVE_LIQUIDITY_FEE_RECEIVER_SEED,
+            RESERVE_LIQUIDITY_SUPPLY_SEED, RESERVE_VERSION_EXTENDED_CONFIG,
         },
     },
     std::str::FromStr,
@@ -48,32 +53,38 @@ pub const LAMPORTS_TO_SOL: u64 = 1_000_000_000;
 pub const FRACTIONAL_TO_USDC: u64 = 1_000_000;
 
 pub const TEST_RESERVE_CONFIG: ReserveConfig = ReserveConfig {
-    optimal_utilization_rate: 80,
-    loan_to_value_ratio: 50,
-    liquidation_bonus: 5,
-    liquidation_threshold: 55,
-    min_borrow_rate: 0,
-    optimal_borrow_rate: 4,
-    max_borrow_rate: 30,
+    optimal_utilization_rate_bps: 8_000,
+    loan_to_value_ratio_bps: 5_000,
+    liquidation_bonus_bps: 500,
+    liquidation_threshold_bps: 5_500,
+    min_borrow_rate_bps: 0,
+    optimal_borrow_rate_bps: 400,
+    max_borrow_rate_bps: 3_000,
     fees: ReserveFees {
         // 0.00001% (Aave borrow fee)
         borrow_fee_wad: 100_000_000_000,
         // 0.3% (Aave flash loan fee)
         flash_loan_fee_wad: 3_000_000_000_000_000,
-        host_fee_percentage: 20,
+        host_fee_bps: 2_000,
+        protocol_liquidation_fee_bps: 0,
     },
+    rate_curve: RateCurve::EMPTY,
+    adaptive_rate: AdaptiveRateConfig::DISABLED,
+    deposit_limit: 0,
+    borrow_limit: 0,
+    fixed_rate: FixedRateConfig::DISABLED,
 };
 
 /**
  * create new reserve config with given loan to value ratio and liquidation threshold
  */
 pub fn create_test_reserve_config(
-    loan_to_value_ratio: u8,
-    liquidation_threshold: u8,
+    loan_to_value_ratio_bps: u16,
+    liquidation_threshold_bps: u16,
 ) -> ReserveConfig {
     ReserveConfig {
-        loan_to_value_ratio,
-        liquidation_threshold,
+        loan_to_value_ratio_bps,
+        liquidation_threshold_bps,
         ..TEST_RESERVE_CONFIG
     }
 }
@@ -179,7 +190,7 @@ impl TestLendingMarket {
                 .pubkey()
         });
         let quote_currency = quote_currency.unwrap_or(QUOTE_CURRENCY);
-        let lending_market_keypair = lending_market_keypair.unwrap_or_else(|| Keypair::new());
+        let lending_market_keypair = lending_market_keypair.unwrap_or_else(Keypair::new);
         let lending_market_pubkey = lending_market_keypair.pubkey();
         let (lending_market_authority, _bump_seed) = Pubkey::find_program_address(
             &[&lending_market_pubkey.to_bytes()[..32]],
@@ -404,6 +415,7 @@ pub struct TestReserve {
     pub liquidity_oracle_pubkey: Pubkey,
     pub collateral_mint_pubkey: Pubkey,
     pub collateral_supply_pubkey: Pubkey,
+    pub collateral_fee_receiver_pubkey: Pubkey,
     pub user_liquidity_pubkey: Pubkey,
     pub user_collateral_pubkey: Pubkey,
     pub market_price: Decimal,
@@ -423,12 +435,21 @@ impl TestReserve {
         payer: &Keypair,
         user_accounts_owner: &Keypair,
     ) -> Result<Self, TransactionError> {
-        let reserve_keypair = Keypair::new();
-        let reserve_pubkey = reserve_keypair.pubkey();
-        let collateral_mint_keypair = Keypair::new();
-        let collateral_supply_keypair = Keypair::new();
-        let liquidity_supply_keypair = Keypair::new();
-        let liquidity_fee_receiver_keypair = Keypair::new();
+        let reserve_pubkey = find_reserve_address(
+            &spl_token_lending::id(),
+            &lending_market.pubkey,
+            &liquidity_mint_pubkey,
+        )
+        .0;
+        let reserve_account = |seed| {
+            find_reserve_account_address(
+                &spl_token_lending::id(),
+                seed,
+                &lending_market.pubkey,
+                &liquidity_mint_pubkey,
+            )
+            .0
+        };
         let liquidity_host_keypair = Keypair::new();
         let user_collateral_token_keypair = Keypair::new();
         let user_transfer_authority_keypair = Keypair::new();
@@ -453,34 +474,6 @@ impl TestReserve {
                     liquidity_amount,
                 )
                 .unwrap(),
-                create_account(
-                    &payer.pubkey(),
-                    &collateral_mint_keypair.pubkey(),
-                    rent.minimum_balance(Mint::LEN),
-                    Mint::LEN as u64,
-                    &spl_token::id(),
-                ),
-                create_account(
-                    &payer.pubkey(),
-                    &collateral_supply_keypair.pubkey(),
-                    rent.minimum_balance(Token::LEN),
-                    Token::LEN as u64,
-                    &spl_token::id(),
-                ),
-                create_account(
-                    &payer.pubkey(),
-                    &liquidity_supply_keypair.pubkey(),
-                    rent.minimum_balance(Token::LEN),
-                    Token::LEN as u64,
-                    &spl_token::id(),
-                ),
-                create_account(
-                    &payer.pubkey(),
-                    &liquidity_fee_receiver_keypair.pubkey(),
-                    rent.minimum_balance(Token::LEN),
-                    Token::LEN as u64,
-                    &spl_token::id(),
-                ),
                 create_account(
                     &payer.pubkey(),
                     &liquidity_host_keypair.pubkey(),
@@ -495,12 +488,13 @@ impl TestReserve {
                     Token::LEN as u64,
                     &spl_token::id(),
                 ),
-                create_account(
+                // the lending market owner funds the reserve accounts
+                transfer(
                     &payer.pubkey(),
-                    &reserve_pubkey,
-                    rent.minimum_balance(Reserve::LEN),
-                    Reserve::LEN as u64,
-                    &spl_token_lending::id(),
+                    &lending_market.owner.pubkey(),
+                    rent.minimum_balance(Reserve::LEN)
+                        + rent.minimum_balance(Mint::LEN)
+                        + 4 * rent.minimum_balance(Token::LEN),
                 ),
                 init_reserve(
                     spl_token_lending::id(),
@@ -508,12 +502,7 @@ impl TestReserve {
                     config,
                     user_liquidity_pubkey,
                     user_collateral_token_keypair.pubkey(),
-                    reserve_pubkey,
                     liquidity_mint_pubkey,
-                    liquidity_supply_keypair.pubkey(),
-                    liquidity_fee_receiver_keypair.pubkey(),
-                    collateral_mint_keypair.pubkey(),
-                    collateral_supply_keypair.pubkey(),
                     oracle.product_pubkey,
                     oracle.price_pubkey,
                     lending_market.pubkey,
@@ -529,12 +518,7 @@ impl TestReserve {
             &vec![
                 payer,
                 user_accounts_owner,
-                &reserve_keypair,
                 &lending_market.owner,
-                &collateral_mint_keypair,
-                &collateral_supply_keypair,
-                &liquidity_supply_keypair,
-                &liquidity_fee_receiver_keypair,
                 &liquidity_host_keypair,
                 &user_collateral_token_keypair,
                 &user_transfer_authority_keypair,
@@ -552,12 +536,15 @@ impl TestReserve {
                 config,
                 liquidity_mint_pubkey,
                 liquidity_mint_decimals: liquidity_mint.decimals,
-                liquidity_supply_pubkey: liquidity_supply_keypair.pubkey(),
-                liquidity_fee_receiver_pubkey: liquidity_fee_receiver_keypair.pubkey(),
+                liquidity_supply_pubkey: reserve_account(RESERVE_LIQUIDITY_SUPPLY_SEED),
+                liquidity_fee_receiver_pubkey: reserve_account(RESERVE_LIQUIDITY_FEE_RECEIVER_SEED),
                 liquidity_host_pubkey: liquidity_host_keypair.pubkey(),
                 liquidity_oracle_pubkey: oracle.price_pubkey,
-                collateral_mint_pubkey: collateral_mint_keypair.pubkey(),
-                collateral_supply_pubkey: collateral_supply_keypair.pubkey(),
+                collateral_mint_pubkey: reserve_account(RESERVE_COLLATERAL_MINT_SEED),
+                collateral_supply_pubkey: reserve_account(RESERVE_COLLATERAL_SUPPLY_SEED),
+                collateral_fee_receiver_pubkey: reserve_account(
+                    RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+                ),
                 user_liquidity_pubkey,
                 user_collateral_pubkey: user_collateral_token_keypair.pubkey(),
                 market_price: oracle.price,
@@ -572,7 +559,7 @@ impl TestReserve {
     pub async fn validate_state(&self, banks_client: &BanksClient) {
         let reserve = self.get_state(banks_client).await;
         assert!(reserve.last_update.slot > 0);
-        assert_eq!(PROGRAM_VERSION, reserve.version);
+        assert_eq!(RESERVE_VERSION_EXTENDED_CONFIG, reserve.version);
         assert_eq!(self.lending_market_pubkey, reserve.lending_market);
         assert_eq!(self.liquidity_mint_pubkey, reserve.liquidity.mint_pubkey);
         assert_eq!(
@@ -584,6 +571,10 @@ impl TestReserve {
             self.collateral_supply_pubkey,
             reserve.collateral.supply_pubkey
         );
+        assert_eq!(
+            self.collateral_fee_receiver_pubkey,
+            reserve.collateral.fee_receiver
+        );
         assert_eq!(self.config, reserve.config);
 
         assert_eq!(
@@ -657,6 +648,20 @@ pub fn add_reserve(
         &spl_token::id(),
     );
 
+    let collateral_fee_receiver_pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        collateral_fee_receiver_pubkey,
+        u32::MAX as u64,
+        &Token {
+            mint: collateral_mint_pubkey,
+            owner: lending_market.authority,
+            amount: 0,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
     let amount = if let COption::Some(rent_reserve) = is_native {
         liquidity_amount + rent_reserve
     } else {
@@ -710,6 +715,7 @@ pub fn add_reserve(
     let reserve_pubkey = reserve_keypair.pubkey();
     let mut reserve = Reserve::new(InitReserveParams {
         current_slot,
+        current_unix_timestamp: 0,
         lending_market: lending_market.pubkey,
         liquidity: ReserveLiquidity::new(NewReserveLiquidityParams {
             mint_pubkey: liquidity_mint_pubkey,
@@ -722,6 +728,7 @@ pub fn add_reserve(
         collateral: ReserveCollateral::new(NewReserveCollateralParams {
             mint_pubkey: collateral_mint_pubkey,
             supply_pubkey: collateral_supply_pubkey,
+            fee_receiver: collateral_fee_receiver_pubkey,
         }),
         config,
     });
@@ -791,6 +798,7 @@ pub fn add_reserve(
         liquidity_oracle_pubkey: oracle.price_pubkey,
         collateral_mint_pubkey,
         collateral_supply_pubkey,
+        collateral_fee_receiver_pubkey,
         user_liquidity_pubkey,
         user_collateral_pubkey,
         market_price: oracle.price,
@@ -1000,6 +1008,7 @@ impl TestObligation {
                     obligation.pubkey,
                     lending_market.pubkey,
                     user_accounts_owner.pubkey(),
+                    None,
                 ),
             ],
             Some(&payer.pubkey()),
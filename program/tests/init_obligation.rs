@@ -3,19 +3,36 @@
 
 mod helpers;
 use {
-    helpers::{add_lending_market, add_obligation, AddObligationArgs, TestObligation},
+    helpers::{
+        add_lending_market, add_obligation, add_reserve, add_sol_oracle, get_state,
+        AddObligationArgs, AddReserveArgs, TestObligation, LAMPORTS_TO_SOL, TEST_RESERVE_CONFIG,
+    },
     solana_program_test::*,
     solana_sdk::{
+        account::Account,
         instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::Pack,
+        pubkey::Pubkey,
         signature::Keypair,
         signer::Signer,
+        system_instruction, system_program,
         transaction::{Transaction, TransactionError},
     },
     spl_token_lending::{
-        error::LendingError, instruction::builder::init_obligation, processor::process_instruction,
+        error::LendingError,
+        instruction::builder::{
+            deposit_obligation_collateral, find_obligation_address, init_obligation,
+            init_obligation_with_seed, resize_obligation,
+        },
+        processor::process_instruction,
+        state::{Obligation, INITIAL_COLLATERAL_RATIO, OBLIGATION_VERSION_REWARDS},
     },
 };
 
+const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 10 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
+
 #[tokio::test]
 async fn test_success() {
     let mut test = ProgramTest::new(
@@ -62,6 +79,7 @@ async fn test_already_initialized() {
             usdc_obligation.pubkey,
             lending_market.pubkey,
             user_accounts_owner.pubkey(),
+            None,
         )],
         Some(&payer.pubkey()),
     );
@@ -79,3 +97,215 @@ async fn test_already_initialized() {
         )
     );
 }
+
+#[tokio::test]
+async fn test_init_with_seed() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let user_accounts_owner = Keypair::new();
+    test.add_account(
+        user_accounts_owner.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &system_program::id()),
+    );
+    let lending_market = add_lending_market(&mut test);
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    // one owner can hold several obligations in the same lending market
+    let referrer = Pubkey::new_unique();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            init_obligation_with_seed(
+                spl_token_lending::id(),
+                0,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                None,
+            ),
+            init_obligation_with_seed(
+                spl_token_lending::id(),
+                1,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                Some(referrer),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    for seed_id in 0..2 {
+        let obligation_pubkey = find_obligation_address(
+            &spl_token_lending::id(),
+            &lending_market.pubkey,
+            &user_accounts_owner.pubkey(),
+            seed_id,
+        )
+        .0;
+        let account = banks_client
+            .get_account(obligation_pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.owner, spl_token_lending::id());
+        assert_eq!(account.data.len(), Obligation::resizable_len(0, 0));
+        let obligation = Obligation::unpack(&account.data).unwrap();
+        assert_eq!(obligation.version, OBLIGATION_VERSION_REWARDS);
+        assert_eq!(obligation.lending_market, lending_market.pubkey);
+        assert_eq!(obligation.owner, user_accounts_owner.pubkey());
+        assert_eq!(obligation.referrer(), (seed_id == 1).then_some(referrer));
+    }
+
+    // obligations are only created at the address derived for the seed id
+    let mut instruction = init_obligation_with_seed(
+        spl_token_lending::id(),
+        2,
+        lending_market.pubkey,
+        user_accounts_owner.pubkey(),
+        None,
+    );
+    instruction.accounts[0].pubkey = find_obligation_address(
+        &spl_token_lending::id(),
+        &lending_market.pubkey,
+        &payer.pubkey(),
+        2,
+    )
+    .0;
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidAccountInput as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_init_with_seed_resize_and_deposit() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let user_accounts_owner = Keypair::new();
+    test.add_account(
+        user_accounts_owner.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &system_program::id()),
+    );
+    let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            user_liquidity_amount: SOL_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_amount: SOL_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: TEST_RESERVE_CONFIG,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    // lamports sent to the derived address ahead of time do not block the
+    // obligation creation
+    let obligation_pubkey = find_obligation_address(
+        &spl_token_lending::id(),
+        &lending_market.pubkey,
+        &user_accounts_owner.pubkey(),
+        0,
+    )
+    .0;
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &obligation_pubkey, 1),
+            init_obligation_with_seed(
+                spl_token_lending::id(),
+                0,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                None,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let rent = banks_client.get_rent().await.unwrap();
+    let len = Obligation::resizable_len(0, 0);
+    let account = banks_client
+        .get_account(obligation_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, spl_token_lending::id());
+    assert_eq!(account.data.len(), len);
+    assert_eq!(account.lamports, rent.minimum_balance(len));
+
+    // the obligation is created without room for deposits or borrows
+    let deposit = deposit_obligation_collateral(
+        spl_token_lending::id(),
+        SOL_DEPOSIT_AMOUNT_LAMPORTS,
+        sol_test_reserve.user_collateral_pubkey,
+        sol_test_reserve.collateral_supply_pubkey,
+        sol_test_reserve.pubkey,
+        obligation_pubkey,
+        lending_market.pubkey,
+        user_accounts_owner.pubkey(),
+        user_accounts_owner.pubkey(),
+    );
+    let mut transaction = Transaction::new_with_payer(&[deposit.clone()], Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::ObligationReserveLimit as u32)
+        )
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            resize_obligation(
+                spl_token_lending::id(),
+                1,
+                1,
+                obligation_pubkey,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+            ),
+            deposit,
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = get_state::<Obligation>(obligation_pubkey, &banks_client)
+        .await
+        .unwrap();
+    assert_eq!(obligation.deposits_limit, 1);
+    assert_eq!(obligation.deposits.len(), 1);
+    assert_eq!(
+        obligation.deposits[0].deposited_amount,
+        SOL_DEPOSIT_AMOUNT_LAMPORTS
+    );
+}
@@ -1,19 +1,17 @@
 mod helpers;
 use {
     helpers::{
-        add_lending_market, add_reserve, add_sol_oracle, add_usdc_mint, add_usdc_oracle,
-        create_and_mint_to_token_account, AddReserveArgs, TestReserve, TEST_RESERVE_CONFIG,
+        add_lending_market, add_sol_oracle, create_and_mint_to_token_account, TestReserve,
+        TEST_RESERVE_CONFIG,
     },
     solana_program_test::*,
     solana_sdk::{
-        instruction::InstructionError,
-        signature::Keypair,
-        signer::Signer,
-        transaction::{Transaction, TransactionError},
+        account::Account, instruction::InstructionError, native_token::LAMPORTS_PER_SOL,
+        signature::Keypair, signer::Signer, system_program, transaction::TransactionError,
     },
     spl_token_lending::{
-        error::LendingError, instruction::builder::init_reserve, processor::process_instruction,
-        state::ReserveFees,
+        error::LendingError, instruction::builder::find_reserve_address,
+        processor::process_instruction, state::ReserveFees,
     },
 };
 
@@ -65,67 +63,102 @@ async fn test_already_initialized() {
         spl_token_lending::id(),
         processor!(process_instruction),
     );
-
     let user_accounts_owner = Keypair::new();
-    let user_transfer_authority = Keypair::new();
     let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+    const RESERVE_AMOUNT: u64 = 42;
+    let sol_user_liquidity_account = create_and_mint_to_token_account(
+        &banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        2 * RESERVE_AMOUNT,
+    )
+    .await;
 
-    let usdc_mint = add_usdc_mint(&mut test);
-    let usdc_oracle = add_usdc_oracle(&mut test);
-    let usdc_test_reserve = add_reserve(
-        &mut test,
-        &lending_market,
-        &usdc_oracle,
-        &user_accounts_owner,
-        AddReserveArgs {
-            liquidity_amount: 42,
-            liquidity_mint_decimals: usdc_mint.decimals,
-            liquidity_mint_pubkey: usdc_mint.pubkey,
-            config: TEST_RESERVE_CONFIG,
-            ..AddReserveArgs::default()
-        },
-    );
-
-    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let init = || {
+        TestReserve::init(
+            "sol".to_owned(),
+            &banks_client,
+            &lending_market,
+            &sol_oracle,
+            RESERVE_AMOUNT,
+            TEST_RESERVE_CONFIG,
+            spl_token::native_mint::id(),
+            sol_user_liquidity_account,
+            &payer,
+            &user_accounts_owner,
+        )
+    };
+    init().await.unwrap();
 
-    let mut transaction = Transaction::new_with_payer(
-        &[init_reserve(
-            spl_token_lending::id(),
-            42,
-            usdc_test_reserve.config,
-            usdc_test_reserve.user_liquidity_pubkey,
-            usdc_test_reserve.user_collateral_pubkey,
-            usdc_test_reserve.pubkey,
-            usdc_test_reserve.liquidity_mint_pubkey,
-            usdc_test_reserve.liquidity_supply_pubkey,
-            usdc_test_reserve.liquidity_fee_receiver_pubkey,
-            usdc_test_reserve.collateral_mint_pubkey,
-            usdc_test_reserve.collateral_supply_pubkey,
-            usdc_oracle.product_pubkey,
-            usdc_oracle.price_pubkey,
-            lending_market.pubkey,
-            lending_market.owner.pubkey(),
-            user_transfer_authority.pubkey(),
-        )],
-        Some(&payer.pubkey()),
-    );
-    transaction.sign(
-        &[&payer, &lending_market.owner, &user_transfer_authority],
-        recent_blockhash,
-    );
+    // the reserve address is derived from the lending market and liquidity
+    // mint, so the mint cannot be listed twice
     assert_eq!(
-        banks_client
-            .process_transaction(transaction)
-            .await
-            .unwrap_err()
-            .unwrap(),
+        init().await.unwrap_err(),
         TransactionError::InstructionError(
-            0,
+            4,
             InstructionError::Custom(LendingError::AlreadyInitialized as u32)
         )
     );
 }
 
+#[tokio::test]
+async fn test_prefunded_reserve_address() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+
+    // lamports sent to the derived addresses ahead of time do not block the
+    // reserve creation
+    let reserve_pubkey = find_reserve_address(
+        &spl_token_lending::id(),
+        &lending_market.pubkey,
+        &spl_token::native_mint::id(),
+    )
+    .0;
+    test.add_account(
+        reserve_pubkey,
+        Account::new(LAMPORTS_PER_SOL / 1_000, 0, &system_program::id()),
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+    const RESERVE_AMOUNT: u64 = 42;
+    let sol_user_liquidity_account = create_and_mint_to_token_account(
+        &banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        RESERVE_AMOUNT,
+    )
+    .await;
+
+    let sol_reserve = TestReserve::init(
+        "sol".to_owned(),
+        &banks_client,
+        &lending_market,
+        &sol_oracle,
+        RESERVE_AMOUNT,
+        TEST_RESERVE_CONFIG,
+        spl_token::native_mint::id(),
+        sol_user_liquidity_account,
+        &payer,
+        &user_accounts_owner,
+    )
+    .await
+    .unwrap();
+    assert_eq!(sol_reserve.pubkey, reserve_pubkey);
+    sol_reserve.validate_state(&banks_client).await;
+}
+
 #[tokio::test]
 async fn test_invalid_fees() {
     let mut test = ProgramTest::new(
@@ -155,7 +188,8 @@ async fn test_invalid_fees() {
         config.fees = ReserveFees {
             borrow_fee_wad: 1_000_000_000_000_000_001,
             flash_loan_fee_wad: 1_000_000_000_000_000_001,
-            host_fee_percentage: 0,
+            host_fee_bps: 0,
+            protocol_liquidation_fee_bps: 0,
         };
 
         assert_eq!(
@@ -174,7 +208,7 @@ async fn test_invalid_fees() {
             .await
             .unwrap_err(),
             TransactionError::InstructionError(
-                8,
+                4,
                 InstructionError::Custom(LendingError::InvalidConfig as u32)
             )
         );
@@ -185,7 +219,39 @@ async fn test_invalid_fees() {
         config.fees = ReserveFees {
             borrow_fee_wad: 10_000_000_000_000_000,
             flash_loan_fee_wad: 10_000_000_000_000_000,
-            host_fee_percentage: 101,
+            host_fee_bps: 10_100,
+            protocol_liquidation_fee_bps: 0,
+        };
+
+        assert_eq!(
+            TestReserve::init(
+                "sol".to_owned(),
+                &banks_client,
+                &lending_market,
+                &sol_oracle,
+                RESERVE_AMOUNT,
+                config,
+                spl_token::native_mint::id(),
+                sol_user_liquidity_account,
+                &payer,
+                &user_accounts_owner,
+            )
+            .await
+            .unwrap_err(),
+            TransactionError::InstructionError(
+                4,
+                InstructionError::Custom(LendingError::InvalidConfig as u32)
+            )
+        );
+    }
+    // protocol liquidation fee above 100%
+    {
+        let mut config = TEST_RESERVE_CONFIG;
+        config.fees = ReserveFees {
+            borrow_fee_wad: 10_000_000_000_000_000,
+            flash_loan_fee_wad: 10_000_000_000_000_000,
+            host_fee_bps: 0,
+            protocol_liquidation_fee_bps: 10_100,
         };
 
         assert_eq!(
@@ -204,7 +270,7 @@ async fn test_invalid_fees() {
             .await
             .unwrap_err(),
             TransactionError::InstructionError(
-                8,
+                4,
                 InstructionError::Custom(LendingError::InvalidConfig as u32)
             )
         );
@@ -94,7 +94,7 @@ async fn alice_can_borrow_sol_and_repay() {
 async fn alice_can_borrow_sol_but_got_liquidited_from_bob() {
     let mut test = IntegrationTest::new().await;
     test.create_market(None).await;
-    let sol_reserve_config = Some(create_test_reserve_config(90, 99));
+    let sol_reserve_config = Some(create_test_reserve_config(9_000, 9_900));
     test.create_reserves(Some(CreateReserveParams {
         sol_reserve_config,
         ..Default::default()
@@ -120,7 +120,7 @@ async fn alice_can_borrow_sol_but_got_liquidited_from_bob() {
     test.refresh_obligation("alice").await;
 
     test.alice_borrow_sol_with_usdc_collateral().await;
-    let new_usdc_reseve_config = create_test_reserve_config(5, 6);
+    let new_usdc_reseve_config = create_test_reserve_config(4_500, 5_000);
     test.modify_reserve_config("usdc", new_usdc_reseve_config)
         .await
         .unwrap();
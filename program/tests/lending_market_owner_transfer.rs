@@ -0,0 +1,176 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::add_lending_market,
+    solana_program_test::*,
+    solana_sdk::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{
+            accept_lending_market_owner, cancel_lending_market_owner, propose_lending_market_owner,
+        },
+        processor::process_instruction,
+    },
+};
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let lending_market = add_lending_market(&mut test);
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let new_owner = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[propose_lending_market_owner(
+            spl_token_lending::id(),
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+            new_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    // ownership is only transferred once accepted
+    let lending_market_info = lending_market.get_state(&banks_client).await;
+    assert_eq!(lending_market_info.owner, lending_market.owner.pubkey());
+    assert_eq!(
+        lending_market_info.pending_owner(),
+        Some(new_owner.pubkey())
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[accept_lending_market_owner(
+            spl_token_lending::id(),
+            lending_market.pubkey,
+            new_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &new_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let lending_market_info = lending_market.get_state(&banks_client).await;
+    assert_eq!(lending_market_info.owner, new_owner.pubkey());
+    assert_eq!(lending_market_info.pending_owner(), None);
+}
+
+#[tokio::test]
+async fn test_invalid_pending_owner() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let lending_market = add_lending_market(&mut test);
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let invalid_owner = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            propose_lending_market_owner(
+                spl_token_lending::id(),
+                lending_market.pubkey,
+                lending_market.owner.pubkey(),
+                Pubkey::new_unique(),
+            ),
+            accept_lending_market_owner(
+                spl_token_lending::id(),
+                lending_market.pubkey,
+                invalid_owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &lending_market.owner, &invalid_owner],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::InvalidPendingMarketOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_cancel() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let lending_market = add_lending_market(&mut test);
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let new_owner = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            propose_lending_market_owner(
+                spl_token_lending::id(),
+                lending_market.pubkey,
+                lending_market.owner.pubkey(),
+                new_owner.pubkey(),
+            ),
+            cancel_lending_market_owner(
+                spl_token_lending::id(),
+                lending_market.pubkey,
+                lending_market.owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let lending_market_info = lending_market.get_state(&banks_client).await;
+    assert_eq!(lending_market_info.pending_owner(), None);
+
+    // the cancelled proposal can no longer be accepted
+    let mut transaction = Transaction::new_with_payer(
+        &[accept_lending_market_owner(
+            spl_token_lending::id(),
+            lending_market.pubkey,
+            new_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &new_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidPendingMarketOwner as u32)
+        )
+    );
+
+    let lending_market_info = lending_market.get_state(&banks_client).await;
+    assert_eq!(lending_market_info.owner, lending_market.owner.pubkey());
+}
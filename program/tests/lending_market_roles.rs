@@ -0,0 +1,423 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+use {
+    helpers::{
+        add_lending_market, add_obligation, add_reserve, add_usdc_mint, add_usdc_oracle,
+        AddObligationArgs, AddReserveArgs, FRACTIONAL_TO_USDC, TEST_RESERVE_CONFIG,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::Keypair,
+        signer::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::instruction::approve,
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{
+            borrow_obligation_liquidity, deposit_obligation_collateral, deposit_reserve_liquidity,
+            modify_reserve_config, redeem_reserve_collateral, refresh_obligation, refresh_reserve,
+            set_guardian, set_reserve_status, set_risk_manager, withdraw_obligation_collateral,
+        },
+        processor::process_instruction,
+        state::{ReserveConfig, ReserveStatus},
+    },
+};
+
+#[tokio::test]
+async fn test_guardian() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            user_liquidity_amount: 100 * FRACTIONAL_TO_USDC,
+            liquidity_amount: 10_000 * FRACTIONAL_TO_USDC,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let guardian = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            set_guardian(
+                spl_token_lending::id(),
+                lending_market.pubkey,
+                lending_market.owner.pubkey(),
+                guardian.pubkey(),
+            ),
+            set_reserve_status(
+                spl_token_lending::id(),
+                ReserveStatus::ReduceOnly,
+                usdc_test_reserve.pubkey,
+                lending_market.pubkey,
+                guardian.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &lending_market.owner, &guardian],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let lending_market_info = lending_market.get_state(&banks_client).await;
+    assert_eq!(lending_market_info.guardian, guardian.pubkey());
+    let reserve = usdc_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve.status, ReserveStatus::ReduceOnly);
+
+    // deposits are rejected while reduce-only
+    let user_transfer_authority = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &usdc_test_reserve.user_liquidity_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                FRACTIONAL_TO_USDC,
+            )
+            .unwrap(),
+            refresh_reserve(
+                spl_token_lending::id(),
+                usdc_test_reserve.pubkey,
+                usdc_oracle.price_pubkey,
+            ),
+            deposit_reserve_liquidity(
+                spl_token_lending::id(),
+                FRACTIONAL_TO_USDC,
+                usdc_test_reserve.user_liquidity_pubkey,
+                usdc_test_reserve.user_collateral_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                usdc_test_reserve.collateral_mint_pubkey,
+                lending_market.pubkey,
+                user_transfer_authority.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(LendingError::ReserveStatusRestricted as u32)
+        )
+    );
+
+    // the guardian cannot lift restrictions
+    let mut transaction = Transaction::new_with_payer(
+        &[set_reserve_status(
+            spl_token_lending::id(),
+            ReserveStatus::Active,
+            usdc_test_reserve.pubkey,
+            lending_market.pubkey,
+            guardian.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &guardian], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::RoleNotPermitted as u32)
+        )
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_reserve_status(
+            spl_token_lending::id(),
+            ReserveStatus::Active,
+            usdc_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let reserve = usdc_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve.status, ReserveStatus::Active);
+}
+
+#[tokio::test]
+async fn test_paused_reserve() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            user_liquidity_amount: 100 * FRACTIONAL_TO_USDC,
+            liquidity_amount: 10_000 * FRACTIONAL_TO_USDC,
+            collateral_amount: 1_000 * FRACTIONAL_TO_USDC,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&usdc_test_reserve, 1_000 * FRACTIONAL_TO_USDC)],
+            ..AddObligationArgs::default()
+        },
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_reserve_status(
+            spl_token_lending::id(),
+            ReserveStatus::Paused,
+            usdc_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let refresh = refresh_reserve(
+        spl_token_lending::id(),
+        usdc_test_reserve.pubkey,
+        usdc_oracle.price_pubkey,
+    );
+    let refresh_with_obligation = [
+        refresh.clone(),
+        refresh_obligation(
+            spl_token_lending::id(),
+            test_obligation.pubkey,
+            vec![usdc_test_reserve.pubkey],
+        ),
+    ];
+    let blocked = [
+        deposit_reserve_liquidity(
+            spl_token_lending::id(),
+            FRACTIONAL_TO_USDC,
+            usdc_test_reserve.user_liquidity_pubkey,
+            usdc_test_reserve.user_collateral_pubkey,
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.liquidity_supply_pubkey,
+            usdc_test_reserve.collateral_mint_pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+        ),
+        redeem_reserve_collateral(
+            spl_token_lending::id(),
+            FRACTIONAL_TO_USDC,
+            usdc_test_reserve.user_collateral_pubkey,
+            usdc_test_reserve.user_liquidity_pubkey,
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.collateral_mint_pubkey,
+            usdc_test_reserve.liquidity_supply_pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+        ),
+        deposit_obligation_collateral(
+            spl_token_lending::id(),
+            FRACTIONAL_TO_USDC,
+            usdc_test_reserve.user_collateral_pubkey,
+            usdc_test_reserve.collateral_supply_pubkey,
+            usdc_test_reserve.pubkey,
+            test_obligation.pubkey,
+            lending_market.pubkey,
+            test_obligation.owner,
+            user_accounts_owner.pubkey(),
+        ),
+        withdraw_obligation_collateral(
+            spl_token_lending::id(),
+            FRACTIONAL_TO_USDC,
+            usdc_test_reserve.collateral_supply_pubkey,
+            usdc_test_reserve.user_collateral_pubkey,
+            usdc_test_reserve.pubkey,
+            test_obligation.pubkey,
+            lending_market.pubkey,
+            test_obligation.owner,
+        ),
+        borrow_obligation_liquidity(
+            spl_token_lending::id(),
+            FRACTIONAL_TO_USDC,
+            None,
+            usdc_test_reserve.liquidity_supply_pubkey,
+            usdc_test_reserve.user_liquidity_pubkey,
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.liquidity_fee_receiver_pubkey,
+            test_obligation.pubkey,
+            lending_market.pubkey,
+            test_obligation.owner,
+            Some(usdc_test_reserve.liquidity_host_pubkey),
+        ),
+    ];
+
+    // every instruction that moves funds in or out of a paused reserve,
+    // other than repayments and liquidations, is rejected
+    for instruction in blocked {
+        let mut instructions = refresh_with_obligation.to_vec();
+        instructions.push(instruction);
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+        assert_eq!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                2,
+                InstructionError::Custom(LendingError::ReserveStatusRestricted as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_risk_manager() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: 10_000 * FRACTIONAL_TO_USDC,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let risk_manager = Keypair::new();
+
+    let lowered_config = ReserveConfig {
+        loan_to_value_ratio_bps: TEST_RESERVE_CONFIG.loan_to_value_ratio_bps - 1_000,
+        deposit_limit: 20_000 * FRACTIONAL_TO_USDC,
+        ..TEST_RESERVE_CONFIG
+    };
+    let queued_config = ReserveConfig {
+        optimal_borrow_rate_bps: TEST_RESERVE_CONFIG.optimal_borrow_rate_bps + 1,
+        ..TEST_RESERVE_CONFIG
+    };
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            set_risk_manager(
+                spl_token_lending::id(),
+                lending_market.pubkey,
+                lending_market.owner.pubkey(),
+                risk_manager.pubkey(),
+            ),
+            modify_reserve_config(
+                spl_token_lending::id(),
+                queued_config,
+                usdc_test_reserve.pubkey,
+                lending_market.pubkey,
+                lending_market.owner.pubkey(),
+            ),
+            modify_reserve_config(
+                spl_token_lending::id(),
+                lowered_config,
+                usdc_test_reserve.pubkey,
+                lending_market.pubkey,
+                risk_manager.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &lending_market.owner, &risk_manager],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    // the risk manager cannot cancel the change queued by the owner
+    let reserve = usdc_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve.config, lowered_config);
+    assert_eq!(reserve.pending_config.unwrap().config, queued_config);
+
+    // the risk manager cannot raise the loan to value ratio back
+    let mut transaction = Transaction::new_with_payer(
+        &[modify_reserve_config(
+            spl_token_lending::id(),
+            ReserveConfig {
+                loan_to_value_ratio_bps: TEST_RESERVE_CONFIG.loan_to_value_ratio_bps,
+                ..lowered_config
+            },
+            usdc_test_reserve.pubkey,
+            lending_market.pubkey,
+            risk_manager.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &risk_manager], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::RoleNotPermitted as u32)
+        )
+    );
+}
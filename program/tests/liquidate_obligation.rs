@@ -7,14 +7,26 @@ use {
     helpers::*,
     solana_program_test::*,
     solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
         signature::{Keypair, Signer},
-        transaction::Transaction,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::{
+        instruction::approve,
+        state::{Account as Token, AccountState},
     },
-    spl_token::instruction::approve,
     spl_token_lending::{
-        instruction::builder::{liquidate_obligation, refresh_obligation},
+        error::LendingError,
+        instruction::builder::{liquidate_obligation, migrate_reserve, refresh_obligation},
+        math::{Rate, TryAdd},
         processor::process_instruction,
-        state::INITIAL_COLLATERAL_RATIO,
+        return_data::LendingReturnData,
+        state::{
+            find_reserve_account_address, CalculateLiquidationResult, Reserve,
+            INITIAL_COLLATERAL_RATIO, RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+        },
     },
 };
 #[tokio::test]
@@ -42,9 +54,9 @@ async fn test_success() {
     let user_transfer_authority = Keypair::new();
     let lending_market = add_lending_market(&mut test);
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
-    reserve_config.liquidation_threshold = 80;
-    reserve_config.liquidation_bonus = 10;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
+    reserve_config.liquidation_threshold_bps = 8_000;
+    reserve_config.liquidation_bonus_bps = 1_000;
 
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
@@ -125,9 +137,11 @@ async fn test_success() {
                 usdc_test_reserve.liquidity_supply_pubkey,
                 sol_test_reserve.pubkey,
                 sol_test_reserve.collateral_supply_pubkey,
+                sol_test_reserve.collateral_fee_receiver_pubkey,
                 test_obligation.pubkey,
                 lending_market.pubkey,
                 user_transfer_authority.pubkey(),
+                None,
             ),
         ],
         Some(&payer.pubkey()),
@@ -136,8 +150,24 @@ async fn test_success() {
         &[&payer, &user_accounts_owner, &user_transfer_authority],
         recent_blockhash,
     );
-    let result = banks_client.process_transaction(transaction).await;
-    assert!(result.is_ok());
+    let result = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, spl_token_lending::id());
+    let liquidation = CalculateLiquidationResult::unpack(&return_data.data).unwrap();
+    assert_eq!(liquidation.repay_amount, USDC_LIQUIDATION_AMOUNT_FRACTIONAL);
+    assert_eq!(
+        liquidation.settle_amount,
+        USDC_LIQUIDATION_AMOUNT_FRACTIONAL.into()
+    );
+    assert_eq!(
+        liquidation.withdraw_amount - liquidation.protocol_fee,
+        SOL_LIQUIDATION_AMOUNT_LAMPORTS
+    );
+
     let user_liquidity_balance =
         get_token_balance(&banks_client, usdc_test_reserve.user_liquidity_pubkey).await;
     assert_eq!(
@@ -176,3 +206,341 @@ async fn test_success() {
         (USDC_BORROW_AMOUNT_FRACTIONAL - USDC_LIQUIDATION_AMOUNT_FRACTIONAL).into()
     )
 }
+
+#[tokio::test]
+async fn test_host_fee() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+    const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = 1_600 * FRACTIONAL_TO_USDC;
+    const USDC_LIQUIDATION_AMOUNT_FRACTIONAL: u64 = USDC_BORROW_AMOUNT_FRACTIONAL / 2;
+    const SOL_LIQUIDATION_AMOUNT_LAMPORTS: u64 = 44 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let mut reserve_config = TEST_RESERVE_CONFIG;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
+    reserve_config.liquidation_threshold_bps = 8_000;
+    reserve_config.liquidation_bonus_bps = 1_000;
+    reserve_config.fees.protocol_liquidation_fee_bps = 5_000;
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: reserve_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            borrow_amount: USDC_BORROW_AMOUNT_FRACTIONAL,
+            user_liquidity_amount: USDC_BORROW_AMOUNT_FRACTIONAL,
+            liquidity_amount: 2 * USDC_BORROW_AMOUNT_FRACTIONAL,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            config: reserve_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&sol_test_reserve, SOL_DEPOSIT_AMOUNT_LAMPORTS)],
+            borrows: &[(&usdc_test_reserve, USDC_BORROW_AMOUNT_FRACTIONAL)],
+            ..AddObligationArgs::default()
+        },
+    );
+
+    let host_fee_receiver_pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        host_fee_receiver_pubkey,
+        u32::MAX as u64,
+        &Token {
+            mint: sol_test_reserve.collateral_mint_pubkey,
+            owner: Pubkey::new_unique(),
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let initial_user_collateral_balance =
+        get_token_balance(&banks_client, sol_test_reserve.user_collateral_pubkey).await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &usdc_test_reserve.user_liquidity_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                USDC_LIQUIDATION_AMOUNT_FRACTIONAL,
+            )
+            .unwrap(),
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+            liquidate_obligation(
+                spl_token_lending::id(),
+                USDC_LIQUIDATION_AMOUNT_FRACTIONAL,
+                usdc_test_reserve.user_liquidity_pubkey,
+                sol_test_reserve.user_collateral_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                sol_test_reserve.pubkey,
+                sol_test_reserve.collateral_supply_pubkey,
+                sol_test_reserve.collateral_fee_receiver_pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                user_transfer_authority.pubkey(),
+                Some(host_fee_receiver_pubkey),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let bonus_rate = Rate::from_percent(10).try_add(Rate::one()).unwrap();
+    let protocol_fee = reserve_config
+        .fees
+        .calculate_protocol_liquidation_fee(SOL_LIQUIDATION_AMOUNT_LAMPORTS, bonus_rate)
+        .unwrap();
+    let host_fee = reserve_config
+        .fees
+        .calculate_liquidation_host_fee(protocol_fee)
+        .unwrap();
+    assert!(host_fee > 0);
+
+    let user_collateral_balance =
+        get_token_balance(&banks_client, sol_test_reserve.user_collateral_pubkey).await;
+    assert_eq!(
+        user_collateral_balance,
+        initial_user_collateral_balance + SOL_LIQUIDATION_AMOUNT_LAMPORTS - protocol_fee
+    );
+    let host_fee_balance = get_token_balance(&banks_client, host_fee_receiver_pubkey).await;
+    assert_eq!(host_fee_balance, host_fee);
+    let fee_receiver_balance = get_token_balance(
+        &banks_client,
+        sol_test_reserve.collateral_fee_receiver_pubkey,
+    )
+    .await;
+    assert_eq!(fee_receiver_balance, protocol_fee - host_fee);
+}
+
+#[tokio::test]
+async fn test_reserve_without_collateral_fee_receiver() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+    const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = 1_600 * FRACTIONAL_TO_USDC;
+    const USDC_LIQUIDATION_AMOUNT_FRACTIONAL: u64 = USDC_BORROW_AMOUNT_FRACTIONAL / 2;
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let mut reserve_config = TEST_RESERVE_CONFIG;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
+    reserve_config.liquidation_threshold_bps = 8_000;
+    reserve_config.liquidation_bonus_bps = 1_000;
+    reserve_config.fees.protocol_liquidation_fee_bps = 1_000;
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: reserve_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            borrow_amount: USDC_BORROW_AMOUNT_FRACTIONAL,
+            user_liquidity_amount: USDC_BORROW_AMOUNT_FRACTIONAL,
+            liquidity_amount: 2 * USDC_BORROW_AMOUNT_FRACTIONAL,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            config: reserve_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&sol_test_reserve, SOL_DEPOSIT_AMOUNT_LAMPORTS)],
+            borrows: &[(&usdc_test_reserve, USDC_BORROW_AMOUNT_FRACTIONAL)],
+            ..AddObligationArgs::default()
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    // reserves created before the protocol liquidation fee have no collateral
+    // fee receiver
+    let mut reserve_account = context
+        .banks_client
+        .get_account(sol_test_reserve.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut reserve = Reserve::unpack(&reserve_account.data).unwrap();
+    reserve.collateral.fee_receiver = Pubkey::default();
+    Reserve::pack(reserve, &mut reserve_account.data).unwrap();
+    context.set_account(&sol_test_reserve.pubkey, &reserve_account.into());
+
+    let liquidate_instructions = |fee_receiver_pubkey| {
+        [
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+            liquidate_obligation(
+                spl_token_lending::id(),
+                USDC_LIQUIDATION_AMOUNT_FRACTIONAL,
+                usdc_test_reserve.user_liquidity_pubkey,
+                sol_test_reserve.user_collateral_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                sol_test_reserve.pubkey,
+                sol_test_reserve.collateral_supply_pubkey,
+                fee_receiver_pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                None,
+            ),
+        ]
+    };
+
+    let mut transaction = Transaction::new_with_payer(
+        &liquidate_instructions(sol_test_reserve.collateral_fee_receiver_pubkey),
+        Some(&context.payer.pubkey()),
+    );
+    transaction.sign(
+        &[&context.payer, &user_accounts_owner],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::MissingCollateralFeeReceiver as u32)
+        )
+    );
+
+    // migrating the reserve creates its collateral fee receiver
+    let mut transaction = Transaction::new_with_payer(
+        &[migrate_reserve(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+            spl_token::native_mint::id(),
+            sol_test_reserve.collateral_mint_pubkey,
+            lending_market.pubkey,
+            context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+    );
+    transaction.sign(&[&context.payer], context.last_blockhash);
+    assert!(context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .is_ok());
+
+    let (collateral_fee_receiver_pubkey, _bump_seed) = find_reserve_account_address(
+        &spl_token_lending::id(),
+        RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+        &lending_market.pubkey,
+        &spl_token::native_mint::id(),
+    );
+    let reserve = sol_test_reserve.get_state(&context.banks_client).await;
+    assert_eq!(
+        reserve.collateral.fee_receiver,
+        collateral_fee_receiver_pubkey
+    );
+    let collateral_fee_receiver = Token::unpack(
+        &context
+            .banks_client
+            .get_account(collateral_fee_receiver_pubkey)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    assert_eq!(
+        collateral_fee_receiver.mint,
+        sol_test_reserve.collateral_mint_pubkey
+    );
+    assert_eq!(collateral_fee_receiver.owner, lending_market.authority);
+
+    let mut transaction = Transaction::new_with_payer(
+        &liquidate_instructions(collateral_fee_receiver_pubkey),
+        Some(&context.payer.pubkey()),
+    );
+    transaction.sign(
+        &[&context.payer, &user_accounts_owner],
+        context.last_blockhash,
+    );
+    assert!(context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .is_ok());
+    assert!(get_token_balance(&context.banks_client, collateral_fee_receiver_pubkey).await > 0);
+}
@@ -0,0 +1,600 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        account::Account,
+        clock::Clock,
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_program,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::state::Account as Token,
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{
+            claim_rewards, forfeit_rewards, init_reserve_rewards, refresh_obligation,
+            refresh_reserve, set_reserve_reward_emissions, withdraw_obligation_collateral,
+        },
+        math::Decimal,
+        processor::process_instruction,
+        state::{
+            InitObligationParams, Obligation, ObligationCollateral, ReserveRewardEmissions,
+            ReserveRewards, INITIAL_COLLATERAL_RATIO,
+        },
+    },
+};
+
+const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
+const SUPPLY_EMISSION_PER_SECOND: u64 = 1_000;
+const REWARD_SUPPLY_AMOUNT: u64 = 1_000_000;
+
+struct TestSetup {
+    test: ProgramTest,
+    user_accounts_owner: Keypair,
+    lending_market: TestLendingMarket,
+    sol_test_reserve: TestReserve,
+    reward_mint: TestMint,
+    reserve_rewards_pubkey: Pubkey,
+    reward_supply_pubkey: Pubkey,
+    obligation_pubkey: Pubkey,
+}
+
+fn setup() -> TestSetup {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    test.add_account(
+        user_accounts_owner.pubkey(),
+        Account::new(LAMPORTS_PER_SOL, 0, &system_program::id()),
+    );
+    let lending_market = add_lending_market(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: SOL_RESERVE_COLLATERAL_LAMPORTS / INITIAL_COLLATERAL_RATIO,
+            collateral_amount: SOL_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+    let reward_mint = add_usdc_mint(&mut test);
+
+    let reserve_rewards_pubkey = Pubkey::new_unique();
+    test.add_account(
+        reserve_rewards_pubkey,
+        Account::new(
+            u32::MAX as u64,
+            ReserveRewards::LEN,
+            &spl_token_lending::id(),
+        ),
+    );
+    let reward_supply_pubkey = Pubkey::new_unique();
+    test.add_account(
+        reward_supply_pubkey,
+        Account::new(u32::MAX as u64, Token::LEN, &spl_token::id()),
+    );
+
+    // collateral deposited by an obligation tracking rewards before the
+    // reserve emits any starts at the initial reward index
+    let mut collateral = ObligationCollateral::new(sol_test_reserve.pubkey);
+    collateral.deposited_amount = SOL_DEPOSIT_AMOUNT_LAMPORTS;
+    collateral.reward_index = Decimal::one();
+    let mut obligation = Obligation::new(InitObligationParams {
+        current_slot: 1,
+        lending_market: lending_market.pubkey,
+        owner: user_accounts_owner.pubkey(),
+        deposits: vec![collateral],
+        borrows: vec![],
+    });
+    let len = obligation.resize(1, 1).unwrap();
+    let obligation_pubkey = Pubkey::new_unique();
+    let mut account = Account::new(u32::MAX as u64, len, &spl_token_lending::id());
+    Obligation::pack(obligation, &mut account.data).unwrap();
+    test.add_account(obligation_pubkey, account);
+
+    TestSetup {
+        test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        reward_mint,
+        reserve_rewards_pubkey,
+        reward_supply_pubkey,
+        obligation_pubkey,
+    }
+}
+
+#[tokio::test]
+async fn test_success() {
+    let TestSetup {
+        test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        reward_mint,
+        reserve_rewards_pubkey,
+        reward_supply_pubkey,
+        obligation_pubkey,
+    } = setup();
+
+    let mut context = test.start_with_context().await;
+    let banks_client = context.banks_client.clone();
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+    let mut clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+
+    let refresh_sol_reserve = refresh_reserve(
+        spl_token_lending::id(),
+        sol_test_reserve.pubkey,
+        sol_test_reserve.liquidity_oracle_pubkey,
+    );
+    let refresh_sol_obligation = refresh_obligation(
+        spl_token_lending::id(),
+        obligation_pubkey,
+        vec![sol_test_reserve.pubkey],
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_sol_reserve.clone(),
+            init_reserve_rewards(
+                spl_token_lending::id(),
+                SUPPLY_EMISSION_PER_SECOND,
+                0,
+                reserve_rewards_pubkey,
+                reward_supply_pubkey,
+                reward_mint.pubkey,
+                sol_test_reserve.pubkey,
+                lending_market.pubkey,
+                lending_market.owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let reserve_rewards = get_state::<ReserveRewards>(reserve_rewards_pubkey, &banks_client)
+        .await
+        .unwrap();
+    assert_eq!(reserve_rewards.reserve, sol_test_reserve.pubkey);
+    assert_eq!(reserve_rewards.reward_supply, reward_supply_pubkey);
+    let reserve = sol_test_reserve.get_state(&banks_client).await;
+    assert_eq!(
+        reserve.reward_emissions,
+        Some(ReserveRewardEmissions::new(SUPPLY_EMISSION_PER_SECOND, 0))
+    );
+
+    mint_to(
+        &banks_client,
+        reward_mint.pubkey,
+        &payer,
+        reward_supply_pubkey,
+        &reward_mint.authority,
+        REWARD_SUPPLY_AMOUNT,
+    )
+    .await;
+    let destination_pubkey = create_token_account(
+        &banks_client,
+        reward_mint.pubkey,
+        &payer,
+        Some(user_accounts_owner.pubkey()),
+        None,
+    )
+    .await;
+
+    let claim = claim_rewards(
+        spl_token_lending::id(),
+        reserve_rewards_pubkey,
+        reward_supply_pubkey,
+        destination_pubkey,
+        obligation_pubkey,
+        lending_market.pubkey,
+        user_accounts_owner.pubkey(),
+    );
+
+    // the obligation holds half the collateral supply, so earns half the
+    // supply emissions since the rewards started
+    clock.unix_timestamp += 100;
+    context.set_sysvar(&clock);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_sol_reserve.clone(),
+            refresh_sol_obligation.clone(),
+            claim.clone(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+    let earned_amount = 100 * SUPPLY_EMISSION_PER_SECOND / 2;
+    assert_eq!(
+        get_token_balance(&banks_client, destination_pubkey).await,
+        earned_amount
+    );
+
+    // withdrawing the whole collateral keeps the rewards it earned
+    clock.unix_timestamp += 100;
+    context.set_sysvar(&clock);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_sol_reserve.clone(),
+            refresh_sol_obligation.clone(),
+            withdraw_obligation_collateral(
+                spl_token_lending::id(),
+                SOL_DEPOSIT_AMOUNT_LAMPORTS,
+                sol_test_reserve.collateral_supply_pubkey,
+                sol_test_reserve.user_collateral_pubkey,
+                sol_test_reserve.pubkey,
+                obligation_pubkey,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = get_state::<Obligation>(obligation_pubkey, &banks_client)
+        .await
+        .unwrap();
+    assert_eq!(obligation.deposits[0].deposited_amount, 0);
+    assert_eq!(
+        obligation.deposits[0].unclaimed_rewards_wads,
+        Decimal::from(earned_amount)
+    );
+
+    // rewards are only claimed from a refreshed obligation
+    let mut transaction = Transaction::new_with_payer(&[claim.clone()], Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::ObligationStale as u32)
+        )
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_sol_reserve.clone(),
+            set_reserve_reward_emissions(
+                spl_token_lending::id(),
+                0,
+                0,
+                reserve_rewards_pubkey,
+                sol_test_reserve.pubkey,
+                lending_market.pubkey,
+                lending_market.owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    // the emptied deposit is removed once its rewards are claimed
+    clock.unix_timestamp += 100;
+    context.set_sysvar(&clock);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[refresh_sol_reserve, refresh_sol_obligation, claim],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+    assert_eq!(
+        get_token_balance(&banks_client, destination_pubkey).await,
+        2 * earned_amount
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, reward_supply_pubkey).await,
+        REWARD_SUPPLY_AMOUNT - 2 * earned_amount
+    );
+    let obligation = get_state::<Obligation>(obligation_pubkey, &banks_client)
+        .await
+        .unwrap();
+    assert!(obligation.deposits.is_empty());
+}
+
+#[tokio::test]
+async fn test_invalid_reserve_rewards() {
+    let TestSetup {
+        mut test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        reward_mint,
+        reserve_rewards_pubkey,
+        reward_supply_pubkey,
+        obligation_pubkey,
+    } = setup();
+
+    let other_reserve_rewards_pubkey = Pubkey::new_unique();
+    test.add_account(
+        other_reserve_rewards_pubkey,
+        Account::new(
+            u32::MAX as u64,
+            ReserveRewards::LEN,
+            &spl_token_lending::id(),
+        ),
+    );
+    let other_reward_supply_pubkey = Pubkey::new_unique();
+    test.add_account(
+        other_reward_supply_pubkey,
+        Account::new(u32::MAX as u64, Token::LEN, &spl_token::id()),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let refresh_sol_reserve = refresh_reserve(
+        spl_token_lending::id(),
+        sol_test_reserve.pubkey,
+        sol_test_reserve.liquidity_oracle_pubkey,
+    );
+    let init = |reserve_rewards_pubkey, reward_supply_pubkey, lending_market_owner_pubkey| {
+        init_reserve_rewards(
+            spl_token_lending::id(),
+            SUPPLY_EMISSION_PER_SECOND,
+            0,
+            reserve_rewards_pubkey,
+            reward_supply_pubkey,
+            reward_mint.pubkey,
+            sol_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market_owner_pubkey,
+        )
+    };
+
+    // only the lending market owner can emit rewards
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_sol_reserve.clone(),
+            init(
+                reserve_rewards_pubkey,
+                reward_supply_pubkey,
+                user_accounts_owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::InvalidMarketOwner as u32)
+        )
+    );
+
+    // rewards start emitting from a refreshed reserve
+    let mut transaction = Transaction::new_with_payer(
+        &[init(
+            reserve_rewards_pubkey,
+            reward_supply_pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::ReserveStale as u32)
+        )
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_sol_reserve.clone(),
+            init(
+                reserve_rewards_pubkey,
+                reward_supply_pubkey,
+                lending_market.owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    // a reserve emits a single reward
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_sol_reserve,
+            init(
+                other_reserve_rewards_pubkey,
+                other_reward_supply_pubkey,
+                lending_market.owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::InvalidReserveRewards as u32)
+        )
+    );
+
+    // rewards are only paid from the reward supply of the reserve rewards
+    let other_supply_pubkey = create_token_account(
+        &banks_client,
+        reward_mint.pubkey,
+        &payer,
+        Some(lending_market.authority),
+        None,
+    )
+    .await;
+    let mut transaction = Transaction::new_with_payer(
+        &[claim_rewards(
+            spl_token_lending::id(),
+            reserve_rewards_pubkey,
+            other_supply_pubkey,
+            other_supply_pubkey,
+            obligation_pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidReserveRewards as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_forfeit_rewards() {
+    let TestSetup {
+        mut test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        obligation_pubkey,
+        ..
+    } = setup();
+
+    // collateral withdrawn while holding rewards which the reward supply
+    // cannot pay
+    let mut collateral = ObligationCollateral::new(sol_test_reserve.pubkey);
+    collateral.reward_index = Decimal::one();
+    collateral.unclaimed_rewards_wads = Decimal::from(SUPPLY_EMISSION_PER_SECOND);
+    let mut obligation = Obligation::new(InitObligationParams {
+        current_slot: 1,
+        lending_market: lending_market.pubkey,
+        owner: user_accounts_owner.pubkey(),
+        deposits: vec![collateral],
+        borrows: vec![],
+    });
+    let len = obligation.resize(1, 1).unwrap();
+    let emptied_obligation_pubkey = Pubkey::new_unique();
+    let mut account = Account::new(u32::MAX as u64, len, &spl_token_lending::id());
+    Obligation::pack(obligation, &mut account.data).unwrap();
+    test.add_account(emptied_obligation_pubkey, account);
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    // only the obligation owner can forfeit its rewards
+    let other_owner = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[forfeit_rewards(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+            emptied_obligation_pubkey,
+            lending_market.pubkey,
+            other_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &other_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidObligationOwner as u32)
+        )
+    );
+
+    // rewards of collateral still deposited cannot be forfeited
+    let mut transaction = Transaction::new_with_payer(
+        &[forfeit_rewards(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+            obligation_pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidAccountInput as u32)
+        )
+    );
+
+    // forfeiting frees the slot held by the emptied deposit
+    let mut transaction = Transaction::new_with_payer(
+        &[forfeit_rewards(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+            emptied_obligation_pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = get_state::<Obligation>(emptied_obligation_pubkey, &banks_client)
+        .await
+        .unwrap();
+    assert!(obligation.deposits.is_empty());
+    assert!(obligation.last_update.stale);
+}
@@ -0,0 +1,300 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+use {
+    helpers::{add_lending_market, AddPacked},
+    solana_program_test::*,
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        program_option::COption,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signer::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::state::{Account as Token, Mint},
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{migrate_account, migrate_reserve, refresh_reserve},
+        math::Decimal,
+        processor::process_instruction,
+        state::{
+            find_reserve_account_address, AccountType, InitObligationParams, Obligation,
+            ObligationCollateral, ObligationLiquidity, Reserve, ReserveCollateral, ReserveConfig,
+            ReserveLiquidity, MAX_OBLIGATION_RESERVES, OBLIGATION_VERSION_FIXED,
+            OBLIGATION_VERSION_REFERRER, OBLIGATION_VERSION_RESIZABLE, OBLIGATION_VERSION_REWARDS,
+            RESERVE_COLLATERAL_FEE_RECEIVER_SEED, RESERVE_VERSION_BPS_CONFIG,
+            RESERVE_VERSION_EXTENDED_CONFIG, RESERVE_VERSION_PERCENT_CONFIG,
+        },
+    },
+};
+
+// offset of the percentage config rates in the legacy reserve layout
+const PERCENT_CONFIG_OFFSET: usize = 299;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let lending_market = add_lending_market(&mut test);
+
+    let liquidity_mint_pubkey = Pubkey::new_unique();
+    let collateral_mint_pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        collateral_mint_pubkey,
+        u32::MAX as u64,
+        &Mint {
+            is_initialized: true,
+            mint_authority: COption::Some(lending_market.authority),
+            ..Mint::default()
+        },
+        &spl_token::id(),
+    );
+
+    let reserve_pubkey = Pubkey::new_unique();
+    let reserve_len = Reserve::layout_len(RESERVE_VERSION_PERCENT_CONFIG);
+    let mut reserve_account = Account::new(
+        Rent::default().minimum_balance(reserve_len),
+        reserve_len,
+        &spl_token_lending::id(),
+    );
+    Reserve::pack(
+        Reserve {
+            version: RESERVE_VERSION_BPS_CONFIG,
+            lending_market: lending_market.pubkey,
+            liquidity: ReserveLiquidity {
+                mint_pubkey: liquidity_mint_pubkey,
+                ..ReserveLiquidity::default()
+            },
+            collateral: ReserveCollateral {
+                mint_pubkey: collateral_mint_pubkey,
+                ..ReserveCollateral::default()
+            },
+            ..Reserve::default()
+        },
+        &mut reserve_account.data,
+    )
+    .unwrap();
+    reserve_account.data[0] = RESERVE_VERSION_PERCENT_CONFIG;
+    reserve_account.data[PERCENT_CONFIG_OFFSET..PERCENT_CONFIG_OFFSET + 7]
+        .copy_from_slice(&[80, 50, 5, 55, 0, 4, 30]);
+    test.add_account(reserve_pubkey, reserve_account);
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    // outdated layouts are not written in place until migrated
+    let mut transaction = Transaction::new_with_payer(
+        &[refresh_reserve(
+            spl_token_lending::id(),
+            reserve_pubkey,
+            Pubkey::new_unique(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            migrate_reserve(
+                spl_token_lending::id(),
+                reserve_pubkey,
+                liquidity_mint_pubkey,
+                collateral_mint_pubkey,
+                lending_market.pubkey,
+                payer.pubkey(),
+            ),
+            migrate_account(
+                spl_token_lending::id(),
+                AccountType::LendingMarket,
+                lending_market.pubkey,
+                payer.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let reserve_account = banks_client
+        .get_account(reserve_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(reserve_account.data.len(), Reserve::LEN);
+    assert!(reserve_account.lamports >= Rent::default().minimum_balance(Reserve::LEN));
+    assert_eq!(reserve_account.data[0], RESERVE_VERSION_EXTENDED_CONFIG);
+    assert_eq!(
+        reserve_account.data[PERCENT_CONFIG_OFFSET..PERCENT_CONFIG_OFFSET + 7],
+        [0; 7]
+    );
+    let reserve = Reserve::unpack(&reserve_account.data).unwrap();
+    assert_eq!(reserve.lending_market, lending_market.pubkey);
+    // the reserve predates the collateral fee receiver, which is created
+    assert_eq!(
+        reserve.collateral.fee_receiver,
+        find_reserve_account_address(
+            &spl_token_lending::id(),
+            RESERVE_COLLATERAL_FEE_RECEIVER_SEED,
+            &lending_market.pubkey,
+            &liquidity_mint_pubkey,
+        )
+        .0
+    );
+    let collateral_fee_receiver = banks_client
+        .get_account(reserve.collateral.fee_receiver)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(collateral_fee_receiver.owner, spl_token::id());
+    assert_eq!(
+        Token::unpack(&collateral_fee_receiver.data).unwrap().owner,
+        lending_market.authority
+    );
+    assert_eq!(
+        reserve.config,
+        ReserveConfig {
+            optimal_utilization_rate_bps: 8_000,
+            loan_to_value_ratio_bps: 5_000,
+            liquidation_bonus_bps: 500,
+            liquidation_threshold_bps: 5_500,
+            min_borrow_rate_bps: 0,
+            optimal_borrow_rate_bps: 400,
+            max_borrow_rate_bps: 3_000,
+            ..ReserveConfig::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_obligation() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let lending_market = add_lending_market(&mut test);
+
+    let mut obligations = vec![];
+    for version in [
+        OBLIGATION_VERSION_FIXED,
+        OBLIGATION_VERSION_RESIZABLE,
+        OBLIGATION_VERSION_REFERRER,
+    ] {
+        let mut obligation = Obligation::new(InitObligationParams {
+            current_slot: 0,
+            lending_market: lending_market.pubkey,
+            owner: Pubkey::new_unique(),
+            deposits: vec![ObligationCollateral::new(Pubkey::new_unique())],
+            borrows: vec![ObligationLiquidity::new(
+                Pubkey::new_unique(),
+                Decimal::one(),
+            )],
+        });
+        if version != OBLIGATION_VERSION_FIXED {
+            obligation.resize(2, 2).unwrap();
+            obligation.version = version;
+        }
+        if version == OBLIGATION_VERSION_REFERRER {
+            obligation.referrer = Pubkey::new_unique();
+        }
+        let len = obligation.packed_len();
+        let mut account = Account::new(
+            Rent::default().minimum_balance(len),
+            len,
+            &spl_token_lending::id(),
+        );
+        Obligation::pack(obligation.clone(), &mut account.data).unwrap();
+        let pubkey = Pubkey::new_unique();
+        test.add_account(pubkey, account);
+        obligations.push((pubkey, obligation));
+    }
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &obligations
+            .iter()
+            .map(|(pubkey, _)| {
+                migrate_account(
+                    spl_token_lending::id(),
+                    AccountType::Obligation,
+                    *pubkey,
+                    payer.pubkey(),
+                )
+            })
+            .collect::<Vec<_>>(),
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    for (pubkey, obligation) in obligations {
+        let account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+        let expected_len = if obligation.version == OBLIGATION_VERSION_FIXED {
+            Obligation::resizable_len(MAX_OBLIGATION_RESERVES as u8, MAX_OBLIGATION_RESERVES as u8)
+        } else {
+            Obligation::resizable_len(2, 2)
+        };
+        assert_eq!(account.data.len(), expected_len);
+        assert!(account.lamports >= Rent::default().minimum_balance(expected_len));
+
+        let migrated = Obligation::unpack(&account.data).unwrap();
+        assert_eq!(migrated.version, OBLIGATION_VERSION_REWARDS);
+        assert_eq!(migrated.owner, obligation.owner);
+        assert_eq!(migrated.deposits, obligation.deposits);
+        assert_eq!(migrated.borrows, obligation.borrows);
+        assert_eq!(migrated.referrer, obligation.referrer);
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_owner() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let account_pubkey = Pubkey::new_unique();
+    test.add_account(
+        account_pubkey,
+        Account::new(u32::MAX as u64, Reserve::LEN, &spl_token::id()),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[migrate_account(
+            spl_token_lending::id(),
+            AccountType::Reserve,
+            account_pubkey,
+            payer.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidAccountOwner as u32)
+        )
+    );
+}
@@ -6,6 +6,7 @@ use {
     helpers::*,
     solana_program_test::*,
     solana_sdk::{
+        clock::Clock,
         instruction::InstructionError,
         signature::Keypair,
         signer::Signer,
@@ -13,9 +14,12 @@ use {
     },
     spl_token_lending::{
         error::LendingError,
-        instruction::builder::modify_reserve_config,
+        instruction::builder::{apply_pending_reserve_config, modify_reserve_config},
         processor::process_instruction,
-        state::{ReserveConfig, ReserveFees, INITIAL_COLLATERAL_RATIO},
+        state::{
+            AdaptiveRateConfig, FixedRateConfig, RateCurve, ReserveConfig, ReserveFees,
+            INITIAL_COLLATERAL_RATIO, MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS,
+        },
     },
 };
 
@@ -51,22 +55,151 @@ async fn modify_reserve_config_success() {
             ..AddReserveArgs::default()
         },
     );
-    let (banks_client, payer, recent_blockhash) = test.start().await;
-    const OPTIMAL_UTILIZATION_RATE_CHANGE: u8 = 10;
+    let mut context = test.start_with_context().await;
+    let banks_client = context.banks_client.clone();
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+    const OPTIMAL_UTILIZATION_RATE_CHANGE: u16 = 1_000;
     let new_config = ReserveConfig {
-        optimal_utilization_rate: TEST_RESERVE_CONFIG.optimal_utilization_rate
+        optimal_utilization_rate_bps: TEST_RESERVE_CONFIG.optimal_utilization_rate_bps
             - OPTIMAL_UTILIZATION_RATE_CHANGE,
-        loan_to_value_ratio: 50,
-        liquidation_bonus: 5,
-        liquidation_threshold: 55,
-        min_borrow_rate: 0,
-        optimal_borrow_rate: 4,
-        max_borrow_rate: 30,
+        loan_to_value_ratio_bps: 5_000,
+        liquidation_bonus_bps: 500,
+        liquidation_threshold_bps: 5_500,
+        min_borrow_rate_bps: 0,
+        optimal_borrow_rate_bps: 400,
+        max_borrow_rate_bps: 3_000,
         fees: ReserveFees {
             borrow_fee_wad: 100_000_000_000,
             flash_loan_fee_wad: 3_000_000_000_000_000,
-            host_fee_percentage: 20,
+            host_fee_bps: 2_000,
+            protocol_liquidation_fee_bps: 0,
+        },
+        rate_curve: RateCurve::EMPTY,
+        adaptive_rate: AdaptiveRateConfig::DISABLED,
+        deposit_limit: 0,
+        borrow_limit: 0,
+        fixed_rate: FixedRateConfig::DISABLED,
+    };
+    let mut transaction = Transaction::new_with_payer(
+        &[modify_reserve_config(
+            spl_token_lending::id(),
+            new_config,
+            sol_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+    // the change is queued until the timelock expires
+    let reserve_info = sol_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve_info.config, TEST_RESERVE_CONFIG);
+    let pending_config = reserve_info.pending_config.unwrap();
+    assert_eq!(pending_config.config, new_config);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[apply_pending_reserve_config(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::ReserveConfigTimelocked as u32)
+        )
+    );
+
+    let mut clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = pending_config.activation_timestamp;
+    context.set_sysvar(&clock);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    // anyone can apply the pending config
+    let mut transaction = Transaction::new_with_payer(
+        &[apply_pending_reserve_config(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+    let reserve_info = sol_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve_info.config, new_config);
+    assert_eq!(reserve_info.pending_config, None);
+}
+
+#[tokio::test]
+async fn risk_reducing_change_bypasses_timelock() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: 10 * LAMPORTS_TO_SOL,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
         },
+    );
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    // a config which changes nothing is rejected
+    let mut transaction = Transaction::new_with_payer(
+        &[modify_reserve_config(
+            spl_token_lending::id(),
+            TEST_RESERVE_CONFIG,
+            sol_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidConfig as u32)
+        )
+    );
+
+    let new_config = ReserveConfig {
+        loan_to_value_ratio_bps: TEST_RESERVE_CONFIG.loan_to_value_ratio_bps / 2,
+        ..TEST_RESERVE_CONFIG
     };
     let mut transaction = Transaction::new_with_payer(
         &[modify_reserve_config(
@@ -86,6 +219,151 @@ async fn modify_reserve_config_success() {
         .unwrap();
     let reserve_info = sol_test_reserve.get_state(&banks_client).await;
     assert_eq!(reserve_info.config, new_config);
+    assert_eq!(reserve_info.pending_config, None);
+}
+
+#[tokio::test]
+async fn owner_raises_limits_through_timelock() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let capped_config = ReserveConfig {
+        deposit_limit: 100 * LAMPORTS_TO_SOL,
+        borrow_limit: 50 * LAMPORTS_TO_SOL,
+        ..TEST_RESERVE_CONFIG
+    };
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: 10 * LAMPORTS_TO_SOL,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: capped_config,
+            ..AddReserveArgs::default()
+        },
+    );
+    let mut context = test.start_with_context().await;
+    let banks_client = context.banks_client.clone();
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+
+    let raised_config = ReserveConfig {
+        deposit_limit: 2 * capped_config.deposit_limit,
+        borrow_limit: 2 * capped_config.borrow_limit,
+        ..capped_config
+    };
+    let mut transaction = Transaction::new_with_payer(
+        &[modify_reserve_config(
+            spl_token_lending::id(),
+            raised_config,
+            sol_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+    let reserve_info = sol_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve_info.config, capped_config);
+    let pending_config = reserve_info.pending_config.unwrap();
+    assert_eq!(pending_config.config, raised_config);
+
+    let mut clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = pending_config.activation_timestamp;
+    context.set_sysvar(&clock);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[apply_pending_reserve_config(
+            spl_token_lending::id(),
+            sol_test_reserve.pubkey,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+    let reserve_info = sol_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve_info.config, raised_config);
+    assert_eq!(reserve_info.pending_config, None);
+}
+
+#[tokio::test]
+async fn liquidation_threshold_cannot_drop_at_once() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: 10 * LAMPORTS_TO_SOL,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let new_config = ReserveConfig {
+        loan_to_value_ratio_bps: 500,
+        liquidation_threshold_bps: TEST_RESERVE_CONFIG.liquidation_threshold_bps
+            - MAX_LIQUIDATION_THRESHOLD_DECREASE_BPS
+            - 1,
+        ..TEST_RESERVE_CONFIG
+    };
+    let mut transaction = Transaction::new_with_payer(
+        &[modify_reserve_config(
+            spl_token_lending::id(),
+            new_config,
+            sol_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::LiquidationThresholdDecreaseTooLarge as u32)
+        )
+    );
+
+    let reserve_info = sol_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve_info.config, TEST_RESERVE_CONFIG);
+    assert_eq!(reserve_info.pending_config, None);
 }
 
 #[tokio::test]
@@ -124,22 +402,28 @@ async fn wrong_signer_of_lending_market_cannot_change_reserve_config() {
 
     let (banks_client, payer, recent_blockhash) = test.start().await;
 
-    const OPTIMAL_UTILIZATION_RATE_CHANGE: u8 = 10;
+    const OPTIMAL_UTILIZATION_RATE_CHANGE: u16 = 1_000;
 
     let new_config = ReserveConfig {
-        optimal_utilization_rate: TEST_RESERVE_CONFIG.optimal_utilization_rate
+        optimal_utilization_rate_bps: TEST_RESERVE_CONFIG.optimal_utilization_rate_bps
             - OPTIMAL_UTILIZATION_RATE_CHANGE,
-        loan_to_value_ratio: 50,
-        liquidation_bonus: 5,
-        liquidation_threshold: 55,
-        min_borrow_rate: 0,
-        optimal_borrow_rate: 4,
-        max_borrow_rate: 30,
+        loan_to_value_ratio_bps: 5_000,
+        liquidation_bonus_bps: 500,
+        liquidation_threshold_bps: 5_500,
+        min_borrow_rate_bps: 0,
+        optimal_borrow_rate_bps: 400,
+        max_borrow_rate_bps: 3_000,
         fees: ReserveFees {
             borrow_fee_wad: 100_000_000_000,
             flash_loan_fee_wad: 3_000_000_000_000_000,
-            host_fee_percentage: 20,
+            host_fee_bps: 2_000,
+            protocol_liquidation_fee_bps: 0,
         },
+        rate_curve: RateCurve::EMPTY,
+        adaptive_rate: AdaptiveRateConfig::DISABLED,
+        deposit_limit: 0,
+        borrow_limit: 0,
+        fixed_rate: FixedRateConfig::DISABLED,
     };
 
     let mut instruction = modify_reserve_config(
@@ -209,22 +493,28 @@ async fn owner_of_different_lending_market_cannot_change_reserve_config() {
 
     let (banks_client, payer, recent_blockhash) = test.start().await;
     // Test modify reserve config instruction
-    const OPTIMAL_UTILIZATION_RATE_CHANGE: u8 = 10;
+    const OPTIMAL_UTILIZATION_RATE_CHANGE: u16 = 1_000;
 
     let new_config = ReserveConfig {
-        optimal_utilization_rate: TEST_RESERVE_CONFIG.optimal_utilization_rate
+        optimal_utilization_rate_bps: TEST_RESERVE_CONFIG.optimal_utilization_rate_bps
             - OPTIMAL_UTILIZATION_RATE_CHANGE,
-        loan_to_value_ratio: 50,
-        liquidation_bonus: 5,
-        liquidation_threshold: 55,
-        min_borrow_rate: 0,
-        optimal_borrow_rate: 4,
-        max_borrow_rate: 30,
+        loan_to_value_ratio_bps: 5_000,
+        liquidation_bonus_bps: 500,
+        liquidation_threshold_bps: 5_500,
+        min_borrow_rate_bps: 0,
+        optimal_borrow_rate_bps: 400,
+        max_borrow_rate_bps: 3_000,
         fees: ReserveFees {
             borrow_fee_wad: 100_000_000_000,
             flash_loan_fee_wad: 3_000_000_000_000_000,
-            host_fee_percentage: 20,
+            host_fee_bps: 2_000,
+            protocol_liquidation_fee_bps: 0,
         },
+        rate_curve: RateCurve::EMPTY,
+        adaptive_rate: AdaptiveRateConfig::DISABLED,
+        deposit_limit: 0,
+        borrow_limit: 0,
+        fixed_rate: FixedRateConfig::DISABLED,
     };
 
     let mut transaction = Transaction::new_with_payer(
@@ -299,22 +589,28 @@ async fn correct_owner_providing_wrong_lending_market_fails() {
 
     let (banks_client, payer, recent_blockhash) = test.start().await;
 
-    const OPTIMAL_UTILIZATION_RATE_CHANGE: u8 = 10;
+    const OPTIMAL_UTILIZATION_RATE_CHANGE: u16 = 1_000;
 
     let new_config = ReserveConfig {
-        optimal_utilization_rate: TEST_RESERVE_CONFIG.optimal_utilization_rate
+        optimal_utilization_rate_bps: TEST_RESERVE_CONFIG.optimal_utilization_rate_bps
             - OPTIMAL_UTILIZATION_RATE_CHANGE,
-        loan_to_value_ratio: 50,
-        liquidation_bonus: 5,
-        liquidation_threshold: 55,
-        min_borrow_rate: 0,
-        optimal_borrow_rate: 4,
-        max_borrow_rate: 30,
+        loan_to_value_ratio_bps: 5_000,
+        liquidation_bonus_bps: 500,
+        liquidation_threshold_bps: 5_500,
+        min_borrow_rate_bps: 0,
+        optimal_borrow_rate_bps: 400,
+        max_borrow_rate_bps: 3_000,
         fees: ReserveFees {
             borrow_fee_wad: 100_000_000_000,
             flash_loan_fee_wad: 3_000_000_000_000_000,
-            host_fee_percentage: 20,
+            host_fee_bps: 2_000,
+            protocol_liquidation_fee_bps: 0,
         },
+        rate_curve: RateCurve::EMPTY,
+        adaptive_rate: AdaptiveRateConfig::DISABLED,
+        deposit_limit: 0,
+        borrow_limit: 0,
+        fixed_rate: FixedRateConfig::DISABLED,
     };
 
     let mut transaction = Transaction::new_with_payer(
@@ -0,0 +1,462 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction::create_account,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::state::{Account as Token, AccountState},
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{
+            borrow_obligation_liquidity, deposit_obligation_collateral, refresh_obligation,
+            refresh_reserve, set_obligation_delegate, with_obligation_delegate,
+            withdraw_obligation_collateral,
+        },
+        processor::process_instruction,
+        state::{
+            DelegatePermissions, InitObligationDelegateParams, ObligationDelegate,
+            INITIAL_COLLATERAL_RATIO,
+        },
+    },
+};
+
+const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
+const USDC_RESERVE_LIQUIDITY_FRACTIONAL: u64 = 2_000 * FRACTIONAL_TO_USDC;
+const WITHDRAW_AMOUNT: u64 = 10 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+// in quote currency, 100 SOL at $20 allow borrowing $1,000
+const MAX_BORROW_VALUE: u64 = 600;
+
+struct TestSetup {
+    test: ProgramTest,
+    user_accounts_owner: Keypair,
+    lending_market: TestLendingMarket,
+    sol_test_reserve: TestReserve,
+    usdc_test_reserve: TestReserve,
+    test_obligation: TestObligation,
+}
+
+fn setup() -> TestSetup {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: SOL_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: TEST_RESERVE_CONFIG,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: USDC_RESERVE_LIQUIDITY_FRACTIONAL,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            config: TEST_RESERVE_CONFIG,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&sol_test_reserve, SOL_DEPOSIT_AMOUNT_LAMPORTS)],
+            ..AddObligationArgs::default()
+        },
+    );
+
+    TestSetup {
+        test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        test_obligation,
+    }
+}
+
+#[tokio::test]
+async fn test_success() {
+    let TestSetup {
+        test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        test_obligation,
+    } = setup();
+    let delegate = Keypair::new();
+    let obligation_delegate_keypair = Keypair::new();
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let initial_user_collateral_balance =
+        get_token_balance(&banks_client, sol_test_reserve.user_collateral_pubkey).await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_account(
+                &payer.pubkey(),
+                &obligation_delegate_keypair.pubkey(),
+                rent.minimum_balance(ObligationDelegate::LEN),
+                ObligationDelegate::LEN as u64,
+                &spl_token_lending::id(),
+            ),
+            set_obligation_delegate(
+                spl_token_lending::id(),
+                delegate.pubkey(),
+                DelegatePermissions(
+                    DelegatePermissions::WITHDRAW.0 | DelegatePermissions::BORROW.0,
+                ),
+                MAX_BORROW_VALUE,
+                obligation_delegate_keypair.pubkey(),
+                test_obligation.pubkey,
+                test_obligation.owner,
+            ),
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey],
+            ),
+            with_obligation_delegate(
+                borrow_obligation_liquidity(
+                    spl_token_lending::id(),
+                    500 * FRACTIONAL_TO_USDC,
+                    None,
+                    usdc_test_reserve.liquidity_supply_pubkey,
+                    usdc_test_reserve.user_liquidity_pubkey,
+                    usdc_test_reserve.pubkey,
+                    usdc_test_reserve.liquidity_fee_receiver_pubkey,
+                    test_obligation.pubkey,
+                    lending_market.pubkey,
+                    delegate.pubkey(),
+                    None,
+                ),
+                obligation_delegate_keypair.pubkey(),
+            ),
+            refresh_reserve(
+                spl_token_lending::id(),
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_oracle_pubkey,
+            ),
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+            with_obligation_delegate(
+                withdraw_obligation_collateral(
+                    spl_token_lending::id(),
+                    WITHDRAW_AMOUNT,
+                    sol_test_reserve.collateral_supply_pubkey,
+                    sol_test_reserve.user_collateral_pubkey,
+                    sol_test_reserve.pubkey,
+                    test_obligation.pubkey,
+                    lending_market.pubkey,
+                    delegate.pubkey(),
+                ),
+                obligation_delegate_keypair.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[
+            &payer,
+            &obligation_delegate_keypair,
+            &user_accounts_owner,
+            &delegate,
+        ],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation_delegate =
+        get_state::<ObligationDelegate>(obligation_delegate_keypair.pubkey(), &banks_client)
+            .await
+            .unwrap();
+    assert_eq!(obligation_delegate.obligation, test_obligation.pubkey);
+    assert_eq!(obligation_delegate.owner, test_obligation.owner);
+    assert_eq!(obligation_delegate.delegate, delegate.pubkey());
+    assert_eq!(obligation_delegate.max_borrow_value, MAX_BORROW_VALUE);
+
+    let borrow_amount =
+        get_token_balance(&banks_client, usdc_test_reserve.user_liquidity_pubkey).await;
+    assert_eq!(borrow_amount, 500 * FRACTIONAL_TO_USDC);
+    let user_collateral_balance =
+        get_token_balance(&banks_client, sol_test_reserve.user_collateral_pubkey).await;
+    assert_eq!(
+        user_collateral_balance,
+        initial_user_collateral_balance + WITHDRAW_AMOUNT
+    );
+
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert_eq!(
+        obligation.deposits[0].deposited_amount,
+        SOL_DEPOSIT_AMOUNT_LAMPORTS - WITHDRAW_AMOUNT
+    );
+    assert_eq!(obligation.borrows.len(), 1);
+}
+
+#[tokio::test]
+async fn test_delegate_restrictions() {
+    let TestSetup {
+        mut test,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        test_obligation,
+        ..
+    } = setup();
+    let delegate = Keypair::new();
+    let obligation_delegate_pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        obligation_delegate_pubkey,
+        u32::MAX as u64,
+        &ObligationDelegate::new(InitObligationDelegateParams {
+            obligation: test_obligation.pubkey,
+            owner: test_obligation.owner,
+            delegate: delegate.pubkey(),
+            permissions: DelegatePermissions(
+                DelegatePermissions::WITHDRAW.0 | DelegatePermissions::BORROW.0,
+            ),
+            max_borrow_value: MAX_BORROW_VALUE,
+        }),
+        &spl_token_lending::id(),
+    );
+    let delegate_collateral_pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        delegate_collateral_pubkey,
+        u32::MAX as u64,
+        &Token {
+            mint: sol_test_reserve.collateral_mint_pubkey,
+            owner: delegate.pubkey(),
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+    let delegate_liquidity_pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        delegate_liquidity_pubkey,
+        u32::MAX as u64,
+        &Token {
+            mint: usdc_test_reserve.liquidity_mint_pubkey,
+            owner: delegate.pubkey(),
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    // the delegate was not granted the deposit permission
+    let mut transaction = Transaction::new_with_payer(
+        &[with_obligation_delegate(
+            deposit_obligation_collateral(
+                spl_token_lending::id(),
+                WITHDRAW_AMOUNT,
+                delegate_collateral_pubkey,
+                sol_test_reserve.collateral_supply_pubkey,
+                sol_test_reserve.pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                delegate.pubkey(),
+                delegate.pubkey(),
+            ),
+            obligation_delegate_pubkey,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &delegate], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::DelegateNotPermitted as u32)
+        )
+    );
+
+    // withdrawals can only go to token accounts of the obligation owner
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey],
+            ),
+            with_obligation_delegate(
+                withdraw_obligation_collateral(
+                    spl_token_lending::id(),
+                    WITHDRAW_AMOUNT,
+                    sol_test_reserve.collateral_supply_pubkey,
+                    delegate_collateral_pubkey,
+                    sol_test_reserve.pubkey,
+                    test_obligation.pubkey,
+                    lending_market.pubkey,
+                    delegate.pubkey(),
+                ),
+                obligation_delegate_pubkey,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &delegate], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::DelegateNotPermitted as u32)
+        )
+    );
+
+    // borrows can only go to token accounts of the obligation owner
+    let borrow_instruction = |borrow_amount, destination_liquidity_pubkey, delegate_pubkey| {
+        with_obligation_delegate(
+            borrow_obligation_liquidity(
+                spl_token_lending::id(),
+                borrow_amount,
+                None,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                destination_liquidity_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_fee_receiver_pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                delegate_pubkey,
+                None,
+            ),
+            obligation_delegate_pubkey,
+        )
+    };
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey],
+            ),
+            borrow_instruction(
+                100 * FRACTIONAL_TO_USDC,
+                delegate_liquidity_pubkey,
+                delegate.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &delegate], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::DelegateNotPermitted as u32)
+        )
+    );
+
+    // borrows are capped by the delegate limit, below the obligation limit
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey],
+            ),
+            borrow_instruction(
+                700 * FRACTIONAL_TO_USDC,
+                usdc_test_reserve.user_liquidity_pubkey,
+                delegate.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &delegate], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::DelegateBorrowLimitExceeded as u32)
+        )
+    );
+
+    // the delegate record only authorizes its delegate
+    let impostor = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey],
+            ),
+            borrow_instruction(
+                100 * FRACTIONAL_TO_USDC,
+                usdc_test_reserve.user_liquidity_pubkey,
+                impostor.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &impostor], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::InvalidObligationDelegate as u32)
+        )
+    );
+}
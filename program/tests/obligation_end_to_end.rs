@@ -55,7 +55,7 @@ async fn test_success() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
@@ -120,6 +120,7 @@ async fn test_success() {
                 obligation_pubkey,
                 lending_market.pubkey,
                 user_accounts_owner_pubkey,
+                None,
             ),
             // 2
             refresh_reserve(
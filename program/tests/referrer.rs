@@ -0,0 +1,506 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{
+            borrow_obligation_liquidity, claim_referrer_fees, init_obligation,
+            init_referrer_fee_receiver, refresh_obligation, refresh_reserve, resize_obligation,
+        },
+        math::Decimal,
+        processor::process_instruction,
+        state::{
+            find_referrer_fee_receiver_address, FeeCalculation, InitObligationParams, Obligation,
+            ObligationCollateral, INITIAL_COLLATERAL_RATIO, OBLIGATION_VERSION_REWARDS,
+        },
+    },
+};
+
+const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
+const USDC_RESERVE_LIQUIDITY_FRACTIONAL: u64 = 2_000 * FRACTIONAL_TO_USDC;
+const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = 100 * FRACTIONAL_TO_USDC;
+
+struct TestSetup {
+    test: ProgramTest,
+    user_accounts_owner: Keypair,
+    referrer: Keypair,
+    lending_market: TestLendingMarket,
+    sol_test_reserve: TestReserve,
+    usdc_test_reserve: TestReserve,
+    obligation_pubkey: Pubkey,
+}
+
+fn setup() -> TestSetup {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let referrer = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: SOL_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: USDC_RESERVE_LIQUIDITY_FRACTIONAL,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    // referred obligations use the resizable layout
+    let mut collateral = ObligationCollateral::new(sol_test_reserve.pubkey);
+    collateral.deposited_amount = SOL_DEPOSIT_AMOUNT_LAMPORTS;
+    let mut obligation = Obligation::new(InitObligationParams {
+        current_slot: 1,
+        lending_market: lending_market.pubkey,
+        owner: user_accounts_owner.pubkey(),
+        deposits: vec![collateral],
+        borrows: vec![],
+    });
+    obligation.resize(1, 1).unwrap();
+    obligation.referrer = referrer.pubkey();
+    let obligation_pubkey = Pubkey::new_unique();
+    let mut account = Account::new(
+        u32::MAX as u64,
+        obligation.packed_len(),
+        &spl_token_lending::id(),
+    );
+    Obligation::pack(obligation, &mut account.data).unwrap();
+    test.add_account(obligation_pubkey, account);
+
+    TestSetup {
+        test,
+        user_accounts_owner,
+        referrer,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        obligation_pubkey,
+    }
+}
+
+#[tokio::test]
+async fn test_success() {
+    let TestSetup {
+        test,
+        user_accounts_owner,
+        referrer,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        obligation_pubkey,
+    } = setup();
+    let (referrer_fee_receiver_pubkey, _bump_seed) = find_referrer_fee_receiver_address(
+        &spl_token_lending::id(),
+        &usdc_test_reserve.pubkey,
+        &referrer.pubkey(),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let (borrow_fee, host_fee) = TEST_RESERVE_CONFIG
+        .fees
+        .calculate_borrow_fees(
+            Decimal::from(USDC_BORROW_AMOUNT_FRACTIONAL),
+            FeeCalculation::Exclusive,
+        )
+        .unwrap();
+    assert!(host_fee > 0);
+
+    let borrow = |deposit_reserves: Vec<Pubkey>| {
+        vec![
+            refresh_reserve(
+                spl_token_lending::id(),
+                sol_test_reserve.pubkey,
+                sol_test_reserve.liquidity_oracle_pubkey,
+            ),
+            refresh_reserve(
+                spl_token_lending::id(),
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_oracle_pubkey,
+            ),
+            refresh_obligation(spl_token_lending::id(), obligation_pubkey, deposit_reserves),
+            borrow_obligation_liquidity(
+                spl_token_lending::id(),
+                USDC_BORROW_AMOUNT_FRACTIONAL,
+                None,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                usdc_test_reserve.user_liquidity_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_fee_receiver_pubkey,
+                obligation_pubkey,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                Some(referrer_fee_receiver_pubkey),
+            ),
+        ]
+    };
+
+    // the host fee is not diverted to the reserve owner before the referrer
+    // fee receiver exists
+    let mut transaction = Transaction::new_with_payer(
+        &borrow(vec![sol_test_reserve.pubkey]),
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            3,
+            InstructionError::Custom(LendingError::InvalidReferrerFeeReceiver as u32)
+        )
+    );
+
+    // anyone can create the referrer fee receiver, even once lamports were
+    // sent to its address
+    let mut instructions = vec![
+        system_instruction::transfer(&payer.pubkey(), &referrer_fee_receiver_pubkey, 1),
+        init_referrer_fee_receiver(
+            spl_token_lending::id(),
+            payer.pubkey(),
+            usdc_test_reserve.pubkey,
+            usdc_test_reserve.liquidity_mint_pubkey,
+            lending_market.pubkey,
+            referrer.pubkey(),
+        ),
+    ];
+    instructions.extend(borrow(vec![sol_test_reserve.pubkey]));
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let fee_balance = get_token_balance(
+        &banks_client,
+        usdc_test_reserve.liquidity_fee_receiver_pubkey,
+    )
+    .await;
+    assert_eq!(fee_balance, borrow_fee - host_fee);
+    let referrer_fee_balance = get_token_balance(&banks_client, referrer_fee_receiver_pubkey).await;
+    assert_eq!(referrer_fee_balance, host_fee);
+
+    let initial_user_liquidity_balance =
+        get_token_balance(&banks_client, usdc_test_reserve.user_liquidity_pubkey).await;
+    let mut transaction = Transaction::new_with_payer(
+        &[claim_referrer_fees(
+            spl_token_lending::id(),
+            usdc_test_reserve.user_liquidity_pubkey,
+            usdc_test_reserve.pubkey,
+            lending_market.pubkey,
+            referrer.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &referrer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let user_liquidity_balance =
+        get_token_balance(&banks_client, usdc_test_reserve.user_liquidity_pubkey).await;
+    assert_eq!(
+        user_liquidity_balance,
+        initial_user_liquidity_balance + host_fee
+    );
+    let referrer_fee_balance = get_token_balance(&banks_client, referrer_fee_receiver_pubkey).await;
+    assert_eq!(referrer_fee_balance, 0);
+}
+
+#[tokio::test]
+async fn test_host_fee_receiver_left_out() {
+    let TestSetup {
+        test,
+        user_accounts_owner,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        obligation_pubkey,
+        ..
+    } = setup();
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    // the referrer fee receiver cannot be left out to hand the host fee to
+    // the reserve owner
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_reserve(
+                spl_token_lending::id(),
+                sol_test_reserve.pubkey,
+                sol_test_reserve.liquidity_oracle_pubkey,
+            ),
+            refresh_reserve(
+                spl_token_lending::id(),
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_oracle_pubkey,
+            ),
+            refresh_obligation(
+                spl_token_lending::id(),
+                obligation_pubkey,
+                vec![sol_test_reserve.pubkey],
+            ),
+            borrow_obligation_liquidity(
+                spl_token_lending::id(),
+                USDC_BORROW_AMOUNT_FRACTIONAL,
+                None,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                usdc_test_reserve.user_liquidity_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_fee_receiver_pubkey,
+                obligation_pubkey,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                None,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            3,
+            InstructionError::Custom(LendingError::InvalidReferrerFeeReceiver as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_referrer_fee_receiver() {
+    let TestSetup {
+        test,
+        user_accounts_owner,
+        referrer,
+        lending_market,
+        sol_test_reserve,
+        usdc_test_reserve,
+        obligation_pubkey,
+    } = setup();
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_reserve(
+                spl_token_lending::id(),
+                sol_test_reserve.pubkey,
+                sol_test_reserve.liquidity_oracle_pubkey,
+            ),
+            refresh_reserve(
+                spl_token_lending::id(),
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_oracle_pubkey,
+            ),
+            refresh_obligation(
+                spl_token_lending::id(),
+                obligation_pubkey,
+                vec![sol_test_reserve.pubkey],
+            ),
+            borrow_obligation_liquidity(
+                spl_token_lending::id(),
+                USDC_BORROW_AMOUNT_FRACTIONAL,
+                None,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                usdc_test_reserve.user_liquidity_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_fee_receiver_pubkey,
+                obligation_pubkey,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                Some(usdc_test_reserve.user_liquidity_pubkey),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            3,
+            InstructionError::Custom(LendingError::InvalidReferrerFeeReceiver as u32)
+        )
+    );
+
+    // only the referrer can claim its fees
+    let impostor = Keypair::new();
+    let mut instruction = claim_referrer_fees(
+        spl_token_lending::id(),
+        usdc_test_reserve.user_liquidity_pubkey,
+        usdc_test_reserve.pubkey,
+        lending_market.pubkey,
+        impostor.pubkey(),
+    );
+    instruction.accounts[0].pubkey = find_referrer_fee_receiver_address(
+        &spl_token_lending::id(),
+        &usdc_test_reserve.pubkey,
+        &referrer.pubkey(),
+    )
+    .0;
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &impostor], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidReferrerFeeReceiver as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_init_obligation() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let user_accounts_owner = Keypair::new();
+    let referrer = Pubkey::new_unique();
+    let lending_market = add_lending_market(&mut test);
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let obligation_keypair = Keypair::new();
+
+    let len = Obligation::resizable_len(0, 0);
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &obligation_keypair.pubkey(),
+                rent.minimum_balance(len),
+                len as u64,
+                &spl_token_lending::id(),
+            ),
+            init_obligation(
+                spl_token_lending::id(),
+                obligation_keypair.pubkey(),
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                Some(referrer),
+            ),
+            system_instruction::transfer(
+                &payer.pubkey(),
+                &user_accounts_owner.pubkey(),
+                LAMPORTS_PER_SOL,
+            ),
+            resize_obligation(
+                spl_token_lending::id(),
+                4,
+                4,
+                obligation_keypair.pubkey(),
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &obligation_keypair, &user_accounts_owner],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation_account = banks_client
+        .get_account(obligation_keypair.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        obligation_account.data.len(),
+        Obligation::resizable_len(4, 4)
+    );
+    let obligation = Obligation::unpack(&obligation_account.data).unwrap();
+    assert_eq!(obligation.version, OBLIGATION_VERSION_REWARDS);
+    assert_eq!(obligation.owner, user_accounts_owner.pubkey());
+    assert_eq!(obligation.referrer(), Some(referrer));
+
+    // a referrer can only be recorded on a resizable obligation
+    let obligation_keypair = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &obligation_keypair.pubkey(),
+                rent.minimum_balance(Obligation::LEN),
+                Obligation::LEN as u64,
+                &spl_token_lending::id(),
+            ),
+            init_obligation(
+                spl_token_lending::id(),
+                obligation_keypair.pubkey(),
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                Some(referrer),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &obligation_keypair, &user_accounts_owner],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::InvalidAccountInput as u32)
+        )
+    );
+}
@@ -42,13 +42,13 @@ async fn test_success() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     // Configure reserve to a fixed borrow rate of 1%
-    const BORROW_RATE: u8 = 1;
-    reserve_config.min_borrow_rate = BORROW_RATE;
-    reserve_config.optimal_borrow_rate = BORROW_RATE;
-    reserve_config.optimal_utilization_rate = 100;
+    const BORROW_RATE_BPS: u32 = 100;
+    reserve_config.min_borrow_rate_bps = BORROW_RATE_BPS;
+    reserve_config.optimal_borrow_rate_bps = BORROW_RATE_BPS;
+    reserve_config.optimal_utilization_rate_bps = 10_000;
 
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
@@ -141,7 +141,7 @@ async fn test_success() {
 
     let collateral_price = collateral.market_value.try_div(SOL_DEPOSIT_AMOUNT).unwrap();
 
-    let slot_rate = Rate::from_percent(BORROW_RATE)
+    let slot_rate = Rate::from_bps(BORROW_RATE_BPS)
         .try_div(SLOTS_PER_YEAR)
         .unwrap();
     let compound_rate = Rate::one().try_add(slot_rate).unwrap();
@@ -30,13 +30,13 @@ async fn test_success() {
     let user_accounts_owner = Keypair::new();
     let lending_market = add_lending_market(&mut test);
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 80;
+    reserve_config.loan_to_value_ratio_bps = 8_000;
 
     // Configure reserve to a fixed borrow rate of 1%
-    const BORROW_RATE: u8 = 1;
-    reserve_config.min_borrow_rate = BORROW_RATE;
-    reserve_config.optimal_borrow_rate = BORROW_RATE;
-    reserve_config.optimal_utilization_rate = 100;
+    const BORROW_RATE_BPS: u32 = 100;
+    reserve_config.min_borrow_rate_bps = BORROW_RATE_BPS;
+    reserve_config.optimal_borrow_rate_bps = BORROW_RATE_BPS;
+    reserve_config.optimal_utilization_rate_bps = 10_000;
 
     let usdc_mint = add_usdc_mint(&mut test);
     let usdc_oracle = add_usdc_oracle(&mut test);
@@ -98,7 +98,7 @@ async fn test_success() {
     assert!(banks_client.process_transaction(transaction).await.is_ok());
     let sol_reserve = sol_test_reserve.get_state(&banks_client).await;
     let usdc_reserve = usdc_test_reserve.get_state(&banks_client).await;
-    let slot_rate = Rate::from_percent(BORROW_RATE)
+    let slot_rate = Rate::from_bps(BORROW_RATE_BPS)
         .try_div(SLOTS_PER_YEAR)
         .unwrap();
     let compound_rate = Rate::one().try_add(slot_rate).unwrap();
@@ -39,7 +39,7 @@ async fn test_success() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
@@ -0,0 +1,154 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+use {
+    helpers::{add_lending_market, TestObligation},
+    solana_program_test::*,
+    solana_sdk::{
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::Pack,
+        signature::Keypair,
+        signer::Signer,
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::resize_obligation,
+        processor::process_instruction,
+        state::{Obligation, MAX_RESIZED_OBLIGATION_RESERVES, OBLIGATION_VERSION_REWARDS},
+    },
+};
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let test_obligation =
+        TestObligation::init(&banks_client, &lending_market, &user_accounts_owner, &payer)
+            .await
+            .unwrap();
+    let rent = banks_client.get_rent().await.unwrap();
+
+    // grow past the fixed layout, owner pays for the additional rent
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::transfer(
+                &payer.pubkey(),
+                &user_accounts_owner.pubkey(),
+                LAMPORTS_PER_SOL,
+            ),
+            resize_obligation(
+                spl_token_lending::id(),
+                16,
+                8,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation_account = banks_client
+        .get_account(test_obligation.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let len = Obligation::resizable_len(16, 8);
+    assert!(len > Obligation::LEN);
+    assert_eq!(obligation_account.data.len(), len);
+    assert_eq!(obligation_account.lamports, rent.minimum_balance(len));
+
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert_eq!(obligation.version, OBLIGATION_VERSION_REWARDS);
+    assert_eq!(obligation.deposits_limit, 16);
+    assert_eq!(obligation.borrows_limit, 8);
+    assert_eq!(obligation.owner, user_accounts_owner.pubkey());
+
+    // shrink back, owner receives the excess lamports
+    let owner_lamports = banks_client
+        .get_balance(user_accounts_owner.pubkey())
+        .await
+        .unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[resize_obligation(
+            spl_token_lending::id(),
+            2,
+            2,
+            test_obligation.pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let len = Obligation::resizable_len(2, 2);
+    let obligation_account = banks_client
+        .get_account(test_obligation.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(obligation_account.data.len(), len);
+    assert_eq!(obligation_account.lamports, rent.minimum_balance(len));
+    assert_eq!(
+        banks_client
+            .get_balance(user_accounts_owner.pubkey())
+            .await
+            .unwrap(),
+        owner_lamports + rent.minimum_balance(Obligation::resizable_len(16, 8))
+            - rent.minimum_balance(len)
+    );
+}
+
+#[tokio::test]
+async fn test_limit_too_high() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let test_obligation =
+        TestObligation::init(&banks_client, &lending_market, &user_accounts_owner, &payer)
+            .await
+            .unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[resize_obligation(
+            spl_token_lending::id(),
+            MAX_RESIZED_OBLIGATION_RESERVES + 1,
+            1,
+            test_obligation.pubkey,
+            lending_market.pubkey,
+            user_accounts_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::ObligationReserveLimit as u32)
+        )
+    );
+}
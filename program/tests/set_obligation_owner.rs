@@ -0,0 +1,422 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction::create_account,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{
+            accept_obligation_owner, deposit_obligation_collateral, set_obligation_owner,
+            with_obligation_delegate,
+        },
+        processor::process_instruction,
+        state::{
+            DelegatePermissions, InitObligationDelegateParams, ObligationDelegate,
+            ObligationOwnerTransfer,
+        },
+    },
+};
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: 10 * LAMPORTS_TO_SOL,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: TEST_RESERVE_CONFIG,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs::default(),
+    );
+
+    let delegate = Keypair::new();
+    let obligation_delegate_pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        obligation_delegate_pubkey,
+        u32::MAX as u64,
+        &ObligationDelegate::new(InitObligationDelegateParams {
+            obligation: test_obligation.pubkey,
+            owner: test_obligation.owner,
+            delegate: delegate.pubkey(),
+            permissions: DelegatePermissions::ALL,
+            max_borrow_value: 0,
+        }),
+        &spl_token_lending::id(),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let new_owner = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_obligation_owner(
+            spl_token_lending::id(),
+            new_owner.pubkey(),
+            test_obligation.pubkey,
+            test_obligation.owner,
+            None,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert_eq!(obligation.owner, new_owner.pubkey());
+
+    // delegates registered by the previous owner can no longer act
+    let mut transaction = Transaction::new_with_payer(
+        &[with_obligation_delegate(
+            deposit_obligation_collateral(
+                spl_token_lending::id(),
+                1,
+                sol_test_reserve.user_collateral_pubkey,
+                sol_test_reserve.collateral_supply_pubkey,
+                sol_test_reserve.pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                delegate.pubkey(),
+                user_accounts_owner.pubkey(),
+            ),
+            obligation_delegate_pubkey,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &delegate, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidObligationDelegate as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_accept() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs::default(),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let new_owner = Keypair::new();
+    let obligation_owner_transfer_keypair = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_account(
+                &payer.pubkey(),
+                &obligation_owner_transfer_keypair.pubkey(),
+                rent.minimum_balance(ObligationOwnerTransfer::LEN),
+                ObligationOwnerTransfer::LEN as u64,
+                &spl_token_lending::id(),
+            ),
+            set_obligation_owner(
+                spl_token_lending::id(),
+                new_owner.pubkey(),
+                test_obligation.pubkey,
+                test_obligation.owner,
+                Some(obligation_owner_transfer_keypair.pubkey()),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[
+            &payer,
+            &obligation_owner_transfer_keypair,
+            &user_accounts_owner,
+        ],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    // ownership is only transferred once accepted
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert_eq!(obligation.owner, user_accounts_owner.pubkey());
+
+    let impostor = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[accept_obligation_owner(
+            spl_token_lending::id(),
+            obligation_owner_transfer_keypair.pubkey(),
+            test_obligation.pubkey,
+            impostor.pubkey(),
+            user_accounts_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &impostor], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidObligationOwnerTransfer as u32)
+        )
+    );
+
+    // the rent must go back to the owner which proposed the transfer
+    let mut transaction = Transaction::new_with_payer(
+        &[accept_obligation_owner(
+            spl_token_lending::id(),
+            obligation_owner_transfer_keypair.pubkey(),
+            test_obligation.pubkey,
+            new_owner.pubkey(),
+            new_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &new_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidObligationOwner as u32)
+        )
+    );
+
+    let owner_balance = banks_client
+        .get_balance(user_accounts_owner.pubkey())
+        .await
+        .unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[accept_obligation_owner(
+            spl_token_lending::id(),
+            obligation_owner_transfer_keypair.pubkey(),
+            test_obligation.pubkey,
+            new_owner.pubkey(),
+            user_accounts_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &new_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert_eq!(obligation.owner, new_owner.pubkey());
+    assert_eq!(
+        banks_client
+            .get_account(obligation_owner_transfer_keypair.pubkey())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        banks_client
+            .get_balance(user_accounts_owner.pubkey())
+            .await
+            .unwrap(),
+        owner_balance + rent.minimum_balance(ObligationOwnerTransfer::LEN)
+    );
+}
+
+#[tokio::test]
+async fn test_cancel() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs::default(),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let new_owner = Keypair::new();
+    let obligation_owner_transfer_keypair = Keypair::new();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_account(
+                &payer.pubkey(),
+                &obligation_owner_transfer_keypair.pubkey(),
+                rent.minimum_balance(ObligationOwnerTransfer::LEN),
+                ObligationOwnerTransfer::LEN as u64,
+                &spl_token_lending::id(),
+            ),
+            set_obligation_owner(
+                spl_token_lending::id(),
+                new_owner.pubkey(),
+                test_obligation.pubkey,
+                test_obligation.owner,
+                Some(obligation_owner_transfer_keypair.pubkey()),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[
+            &payer,
+            &obligation_owner_transfer_keypair,
+            &user_accounts_owner,
+        ],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    // proposing the current owner closes the transfer account
+    let owner_balance = banks_client
+        .get_balance(user_accounts_owner.pubkey())
+        .await
+        .unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[set_obligation_owner(
+            spl_token_lending::id(),
+            user_accounts_owner.pubkey(),
+            test_obligation.pubkey,
+            test_obligation.owner,
+            Some(obligation_owner_transfer_keypair.pubkey()),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    assert_eq!(
+        banks_client
+            .get_account(obligation_owner_transfer_keypair.pubkey())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        banks_client
+            .get_balance(user_accounts_owner.pubkey())
+            .await
+            .unwrap(),
+        owner_balance + rent.minimum_balance(ObligationOwnerTransfer::LEN)
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[accept_obligation_owner(
+            spl_token_lending::id(),
+            obligation_owner_transfer_keypair.pubkey(),
+            test_obligation.pubkey,
+            new_owner.pubkey(),
+            user_accounts_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &new_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidAccountOwner as u32)
+        )
+    );
+
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert_eq!(obligation.owner, user_accounts_owner.pubkey());
+}
+
+#[tokio::test]
+async fn test_default_new_owner() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs::default(),
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_obligation_owner(
+            spl_token_lending::id(),
+            Pubkey::default(),
+            test_obligation.pubkey,
+            test_obligation.owner,
+            None,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_accounts_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidObligationOwner as u32)
+        )
+    );
+
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert_eq!(obligation.owner, user_accounts_owner.pubkey());
+}
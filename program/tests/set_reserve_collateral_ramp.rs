@@ -0,0 +1,196 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        clock::Clock,
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_lending::{
+        error::LendingError,
+        instruction::builder::{refresh_obligation, refresh_reserve, set_reserve_collateral_ramp},
+        math::{Decimal, Rate, TryMul},
+        processor::process_instruction,
+        state::{
+            PendingReserveConfig, ReserveConfig, INITIAL_COLLATERAL_RATIO, RESERVE_CONFIG_TIMELOCK,
+        },
+    },
+};
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: SOL_DEPOSIT_AMOUNT_LAMPORTS,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&sol_test_reserve, SOL_DEPOSIT_AMOUNT_LAMPORTS)],
+            ..AddObligationArgs::default()
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+    let banks_client = context.banks_client.clone();
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+
+    let mut clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+    let start_timestamp = clock.unix_timestamp + RESERVE_CONFIG_TIMELOCK;
+    let end_timestamp = start_timestamp + 2 * RESERVE_CONFIG_TIMELOCK;
+    let mut transaction = Transaction::new_with_payer(
+        &[set_reserve_collateral_ramp(
+            spl_token_lending::id(),
+            4_000,
+            5_000,
+            start_timestamp,
+            end_timestamp,
+            sol_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    // the ramp target waits for the config timelock
+    let reserve = sol_test_reserve.get_state(&banks_client).await;
+    assert_eq!(reserve.config, TEST_RESERVE_CONFIG);
+    assert_eq!(
+        reserve.pending_config,
+        Some(PendingReserveConfig {
+            config: ReserveConfig {
+                loan_to_value_ratio_bps: 4_000,
+                liquidation_threshold_bps: 5_000,
+                ..TEST_RESERVE_CONFIG
+            },
+            activation_timestamp: start_timestamp,
+        })
+    );
+    assert!(reserve.collateral_ramp.is_some());
+
+    // halfway through the ramp obligations use the interpolated values
+    clock.unix_timestamp = start_timestamp + RESERVE_CONFIG_TIMELOCK;
+    context.set_sysvar(&clock);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_reserve(
+                spl_token_lending::id(),
+                sol_test_reserve.pubkey,
+                sol_oracle.price_pubkey,
+            ),
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![sol_test_reserve.pubkey],
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let obligation = test_obligation.get_state(&banks_client).await;
+    assert!(obligation.deposited_value > Decimal::zero());
+    assert_eq!(
+        obligation.allowed_borrow_value,
+        obligation
+            .deposited_value
+            .try_mul(Rate::from_bps(4_500))
+            .unwrap()
+    );
+    assert_eq!(
+        obligation.unhealthy_borrow_value,
+        obligation
+            .deposited_value
+            .try_mul(Rate::from_bps(5_250))
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_too_short() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: LAMPORTS_TO_SOL,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: TEST_RESERVE_CONFIG,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let (banks_client, payer, recent_blockhash) = test.start().await;
+
+    let clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[set_reserve_collateral_ramp(
+            spl_token_lending::id(),
+            4_000,
+            5_000,
+            clock.unix_timestamp + RESERVE_CONFIG_TIMELOCK,
+            clock.unix_timestamp + RESERVE_CONFIG_TIMELOCK + 1,
+            sol_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidCollateralRamp as u32)
+        )
+    );
+}
@@ -5,7 +5,9 @@ use {
         sign_and_execute,
     },
     solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction},
-    spl_token_lending::instruction::builder::set_lending_market_owner,
+    spl_token_lending::instruction::builder::{
+        accept_lending_market_owner, propose_lending_market_owner,
+    },
 };
 
 impl IntegrationTest {
@@ -32,16 +34,23 @@ impl IntegrationTest {
     pub async fn change_market_owner(&mut self, market_owner: Keypair) {
         let lending_market = self.lending_market.as_mut().unwrap();
         let mut transaction = Transaction::new_with_payer(
-            &[set_lending_market_owner(
-                spl_token_lending::id(),
-                lending_market.pubkey,
-                lending_market.owner.pubkey(),
-                market_owner.pubkey(),
-            )],
+            &[
+                propose_lending_market_owner(
+                    spl_token_lending::id(),
+                    lending_market.pubkey,
+                    lending_market.owner.pubkey(),
+                    market_owner.pubkey(),
+                ),
+                accept_lending_market_owner(
+                    spl_token_lending::id(),
+                    lending_market.pubkey,
+                    market_owner.pubkey(),
+                ),
+            ],
             Some(&self.test_context.payer.pubkey()),
         );
 
-        sign_and_execute!(self, transaction, &lending_market.owner).unwrap();
+        sign_and_execute!(self, transaction, &lending_market.owner, &market_owner).unwrap();
 
         let market = lending_market
             .get_state(&mut self.test_context.banks_client)
@@ -258,9 +258,11 @@ impl IntegrationTest {
                     repay_reserve.liquidity_supply_pubkey,
                     withdraw_reserve.pubkey,
                     withdraw_reserve.collateral_supply_pubkey,
+                    withdraw_reserve.collateral_fee_receiver_pubkey,
                     obligation.pubkey,
                     self.lending_market.as_ref().unwrap().pubkey,
                     liquidator.user_transfer_authority.pubkey(),
+                    None,
                 ),
             ],
             Some(&self.test_context.payer.pubkey()),
@@ -5,12 +5,14 @@ use {
         sign_and_execute, CURRENCY_TYPE,
     },
     solana_program_test::BanksClientError,
-    solana_sdk::{native_token::LAMPORTS_PER_SOL, signer::Signer, transaction::Transaction},
+    solana_sdk::{
+        clock::Clock, native_token::LAMPORTS_PER_SOL, signer::Signer, transaction::Transaction,
+    },
     spl_token::instruction::approve,
     spl_token_lending::{
         instruction::builder::{
-            deposit_reserve_liquidity, modify_reserve_config, redeem_reserve_collateral,
-            refresh_reserve,
+            apply_pending_reserve_config, deposit_reserve_liquidity, modify_reserve_config,
+            redeem_reserve_collateral, refresh_reserve,
         },
         state::ReserveConfig,
     },
@@ -111,7 +113,25 @@ impl IntegrationTest {
             )],
             Some(&self.test_context.payer.pubkey()),
         );
-        sign_and_execute!(self, transaction, &lending_market.owner)
+        sign_and_execute!(self, transaction, &lending_market.owner)?;
+
+        // wait out the timelock of queued changes
+        let reserve_state = reserve.get_state(&self.test_context.banks_client).await;
+        if let Some(pending_config) = reserve_state.pending_config {
+            let mut clock = self.test_context.banks_client.get_sysvar::<Clock>().await?;
+            clock.unix_timestamp = pending_config.activation_timestamp;
+            self.test_context.set_sysvar(&clock);
+
+            let mut transaction = Transaction::new_with_payer(
+                &[apply_pending_reserve_config(
+                    spl_token_lending::id(),
+                    reserve.pubkey,
+                )],
+                Some(&self.test_context.payer.pubkey()),
+            );
+            sign_and_execute!(self, transaction)?;
+        }
+        Ok(())
     }
 
     pub(crate) async fn deposit_reserve_liquidity(
@@ -38,7 +38,7 @@ async fn test_withdraw_fixed_amount() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(
@@ -165,7 +165,7 @@ async fn test_withdraw_max_amount() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let usdc_mint = add_usdc_mint(&mut test);
     let usdc_oracle = add_usdc_oracle(&mut test);
@@ -265,7 +265,7 @@ async fn test_withdraw_too_large() {
     let lending_market = add_lending_market(&mut test);
 
     let mut reserve_config = TEST_RESERVE_CONFIG;
-    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.loan_to_value_ratio_bps = 5_000;
 
     let sol_oracle = add_sol_oracle(&mut test);
     let sol_test_reserve = add_reserve(